@@ -1,6 +1,7 @@
 pub mod completions;
 
 use clap::{Parser, Subcommand};
+use std::{ffi::OsString, path::Path};
 
 /// Command-line interface for peter hook manager
 #[derive(Parser)]
@@ -12,16 +13,79 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub debug: bool,
 
-    /// Enable trace mode (show hook resolution decisions)
+    /// Enable trace mode (show hook resolution decisions; same as -vvv)
     #[arg(long, global = true)]
     pub trace: bool,
 
+    /// Increase trace verbosity (-v phases, -vv decisions, -vvv per-file)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Format for trace/debug events written to stderr
+    #[arg(long, global = true, default_value = "text", value_parser = clap::builder::PossibleValuesParser::new(["text", "json"]))]
+    pub log_format: String,
+
+    /// When to use colors and emoji: auto (on a terminal, honoring `NO_COLOR`
+    /// and `CLICOLOR_FORCE`), always or never
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto", value_parser = clap::builder::PossibleValuesParser::new(["auto", "always", "never"]))]
+    pub color: String,
+
+    /// Use this config file instead of discovering the nearest hooks.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<std::path::PathBuf>,
+
+    /// Apply this `[profiles.<name>]` section of each hooks.toml (default:
+    /// `PETER_HOOK_PROFILE`, else `default`)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Treat this directory as the repository root and search for hooks.toml
+    /// from it
+    #[arg(long, global = true, value_name = "PATH")]
+    pub repo_root: Option<std::path::PathBuf>,
+
     /// Subcommand to execute
     #[command(subcommand)]
     pub command: Commands,
 }
 
+impl Cli {
+    /// Parse the process arguments, dispatching on the name peter-hook was
+    /// invoked as (see [`invocation_args`])
+    #[must_use]
+    pub fn parse_invocation() -> Self {
+        Self::parse_from(invocation_args(std::env::args_os()))
+    }
+}
+
+/// Rewrite `args` (including the program name) for busybox-style dispatch
+///
+/// When the program is invoked under the name of a git hook, e.g. through a
+/// `.git/hooks/pre-commit` symlink, the invocation means `run <event>` with
+/// the remaining arguments passed on as git arguments. Any other name leaves
+/// the arguments unchanged.
+pub fn invocation_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.into_iter().collect();
+    let event = args
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .and_then(|name| name.to_str())
+        .map(|name| {
+            name.strip_suffix(std::env::consts::EXE_SUFFIX)
+                .unwrap_or(name)
+        })
+        .filter(|name| crate::git::SUPPORTED_HOOKS.contains(name))
+        .map(str::to_string);
+
+    if let Some(event) = event {
+        args.splice(1..1, ["run".into(), event.into(), "--".into()]);
+    }
+    args
+}
+
 /// Available subcommands
+// Parsed once per process, so the size of `Run` does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub enum Commands {
     /// Install hooks for the current repository
@@ -43,28 +107,180 @@ pub enum Commands {
     Run {
         /// The git hook event (pre-commit, pre-push, etc.)
         event: String,
-        /// Run on all files instead of only changed files
+        /// Run on every tracked file instead of only changed files
         #[arg(long)]
         all_files: bool,
         /// Show what would run without executing hooks
         #[arg(long)]
         dry_run: bool,
+        /// With --dry-run, expand every hook's templates and fail if any
+        /// references an unknown variable
+        #[arg(long, requires = "dry_run")]
+        check_templates: bool,
+        /// With --dry-run, print the environment each hook would receive and
+        /// where each variable comes from (`pass_env`, `env_file` or `env`)
+        #[arg(long, requires = "dry_run")]
+        show_env: bool,
+        /// Only show output for failing hooks, plus a one-line summary
+        #[arg(long)]
+        quiet: bool,
+        /// Extra output for CI: `github` also prints a GitHub Actions
+        /// `::error` annotation per failing hook (and per file location in
+        /// its output)
+        #[arg(long, default_value = "text", value_parser = clap::builder::PossibleValuesParser::new(["text", "github"]))]
+        format: String,
+        /// Print per-hook durations (slowest first) and the critical path
+        #[arg(long)]
+        timings: bool,
+        /// Run the hooks one at a time and report how long each takes on its
+        /// own (--timings then reports on that same run)
+        #[arg(long, conflicts_with_all = ["dry_run", "watch"])]
+        profile_hooks: bool,
+        /// Run hooks, report files they would modify, then restore the
+        /// working tree (fails if anything would change)
+        #[arg(long)]
+        check: bool,
+        /// Prompt before running each hook that modifies the repository
+        /// (requires a terminal on stdin)
+        #[arg(long)]
+        interactive: bool,
+        /// Also write each hook's stdout, stderr and metadata to
+        /// `<DIR>/<event>/<hook>.{stdout,stderr,meta.json}`
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<std::path::PathBuf>,
+        /// Run without a git repository: `--repo-root` (or the current
+        /// directory) is the root and hooks run on all files
+        #[arg(long, conflicts_with_all = ["check", "stash"])]
+        no_git: bool,
+        /// For staged-file runs (pre-commit), set unstaged changes aside so
+        /// hooks see exactly what is being committed, then restore them
+        #[arg(long)]
+        stash: bool,
+        /// Run up to N config groups (from different hooks.toml files) at
+        /// once, and up to N hooks of a parallel group at once, highest
+        /// `priority` first; by default groups run in order, stopping at the
+        /// first failing group, and a parallel group starts all its hooks
+        #[arg(long, value_name = "N")]
+        jobs: Option<usize>,
+        /// Run only this hook, resolved by name from the nearest config
+        /// that defines it, with the event's change detection and templates
+        #[arg(long, value_name = "NAME")]
+        hook: Option<String>,
+        /// Only run the hooks of configs at or under this directory, on the
+        /// changed files within it
+        #[arg(long, value_name = "DIR")]
+        path: Option<std::path::PathBuf>,
+        /// Fail, listing them, if any changed file is not governed by a
+        /// hooks.toml
+        #[arg(long, conflicts_with = "warn_uncovered")]
+        require_config_coverage: bool,
+        /// Print changed files that are not governed by any hooks.toml
+        #[arg(long)]
+        warn_uncovered: bool,
+        /// Total wall-clock budget for the run: once it is spent, running
+        /// hooks are killed, the rest are not started, and the run fails
+        #[arg(long, value_name = "SECONDS")]
+        max_time: Option<u64>,
+        /// Only check files changed since the last successful run of this
+        /// event (all changed files if no run was recorded)
+        #[arg(long)]
+        incremental: bool,
+        /// Fail, printing why, if any hook of the event was skipped during
+        /// resolution (e.g. by `files` patterns or `requires_files`)
+        #[arg(long)]
+        fail_on_skip: bool,
+        /// After the run, list every hook of the event that did not run and
+        /// why
+        #[arg(long)]
+        report_skips: bool,
+        /// Run configs that resolve to identical hooks once, over the union
+        /// of their files
+        #[arg(long)]
+        merge_identical_configs: bool,
+        /// Run hooks that modify the repository even while a rebase or merge
+        /// is in progress
+        #[arg(long)]
+        force: bool,
+        /// Check the files changed since the most recent tag reachable from
+        /// HEAD (e.g. before tagging a release)
+        #[arg(
+            long,
+            conflicts_with_all = ["all_files", "no_git", "incremental", "files_from", "files_from0"]
+        )]
+        since_tag: bool,
+        /// Use the newline-separated, repo-relative paths in FILE (`-` for
+        /// stdin) as the changed files instead of asking git
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["files_from0", "all_files", "no_git", "incremental"]
+        )]
+        files_from: Option<String>,
+        /// Like --files-from, but the paths are NUL-separated (e.g. from
+        /// `git diff -z --name-only`)
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["all_files", "no_git", "incremental"]
+        )]
+        files_from0: Option<String>,
+        /// Keep running: watch the working tree and re-run the hooks on the
+        /// files that changed (stop with Ctrl-C)
+        #[arg(
+            long,
+            conflicts_with_all = ["dry_run", "interactive", "no_git", "incremental", "files_from", "files_from0"]
+        )]
+        watch: bool,
         /// Additional arguments passed from git (e.g., commit message file,
         /// refs)
         #[arg(trailing_var_arg = true)]
         git_args: Vec<String>,
     },
+    /// Run hooks for every git event configured in any hooks.toml in the
+    /// repository
+    RunAll {
+        /// Run on every tracked file instead of only changed files
+        #[arg(long)]
+        all_files: bool,
+        /// Show what would run without executing hooks
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Validate hook configuration
     Validate {
         /// Trace imports and show merge/override diagnostics
         #[arg(long)]
         trace_imports: bool,
-        /// Output diagnostics as JSON (use with --trace-imports)
-        #[arg(long)]
+        /// Deprecated: print the --trace-imports diagnostics as JSON; use
+        /// --format json for machine-readable validation results
+        #[arg(long, conflicts_with = "format")]
         json: bool,
+        /// Output format for validation results
+        #[arg(long, default_value = "human", value_parser = clap::builder::PossibleValuesParser::new(["human", "json"]))]
+        format: String,
+        /// Validate this config file on its own, even outside a git
+        /// repository
+        #[arg(long, value_name = "FILE")]
+        file: Option<std::path::PathBuf>,
+    },
+    /// Print the effective configuration of the nearest hooks.toml, with
+    /// imports merged and defaults (e.g. timeouts) filled in
+    PrintConfig {
+        /// Output format
+        #[arg(long, default_value = "toml", value_parser = clap::builder::PossibleValuesParser::new(["toml", "json"]))]
+        format: String,
+    },
+    /// Describe what a git event (or group or hook) of the nearest
+    /// hooks.toml runs: descriptions, commands, file patterns and timeouts
+    Info {
+        /// The git hook event (pre-commit, pre-push, etc.), group or hook
+        event: String,
     },
     /// List installed git hooks
     List,
+    /// List the git hook events defined by hooks.toml files in the
+    /// repository, and which configs define each
+    ListEvents,
     /// List worktrees and their hook configuration
     ListWorktrees,
     /// Manage global configuration
@@ -80,6 +296,10 @@ pub enum Commands {
         /// Show what would run without executing hooks
         #[arg(long)]
         dry_run: bool,
+        /// Arguments appended to the hook's command (after `--`; the name
+        /// must resolve to a single hook)
+        #[arg(last = true)]
+        args: Vec<String>,
     },
     /// Show version information
     Version,
@@ -90,6 +310,17 @@ pub enum Commands {
         /// Shell type (bash, zsh, fish, etc.)
         shell: clap_complete::Shell,
     },
+    /// Print a JSON Schema for hooks.toml (point your editor's TOML language
+    /// server at it for completion and validation)
+    Schema,
+    /// List everything `run` and `lint` accept (git events, and the groups
+    /// and hooks of the nearest hooks.toml) with their metadata, for editor
+    /// integrations
+    Targets {
+        /// Output format
+        #[arg(long, default_value = "text", value_parser = clap::builder::PossibleValuesParser::new(["text", "json"]))]
+        format: String,
+    },
     /// Internal helper: list run targets for shell completions
     #[command(name = "_run-targets", hide = true)]
     RunTargets,
@@ -97,7 +328,12 @@ pub enum Commands {
     #[command(name = "_lint-targets", hide = true)]
     LintTargets,
     /// Check health and configuration
-    Doctor,
+    Doctor {
+        /// Only check that every git hook `install` would install is
+        /// installed and managed by peter-hook, failing (for CI) if not
+        #[arg(long)]
+        assert_managed: bool,
+    },
 }
 
 /// Configuration management subcommands
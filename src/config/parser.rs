@@ -2,17 +2,282 @@
 //! Configuration parsing for git hooks
 
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use shellexpand;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::{LazyLock, Mutex, PoisonError},
+    time::SystemTime,
 };
 
 use crate::config::GlobalConfig;
 
+/// Config selection given on the command line: the config file
+/// (`--config`), the repository root (`--repo-root`) and the profile
+/// (`--profile`)
+///
+/// Config loading and hook resolution take it as an argument; the default
+/// overrides nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ConfigOverrides {
+    /// Config file forced by `--config`, bypassing nearest-config discovery
+    config: Option<PathBuf>,
+    /// Directory forced by `--repo-root`, used instead of the enclosing git
+    /// root
+    repo_root: Option<PathBuf>,
+    /// Profile forced by `--profile`, taking precedence over
+    /// `PETER_HOOK_PROFILE`
+    profile: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// Force hook resolution to use a specific config file
+    ///
+    /// Directory-based discovery (walking up to the nearest hooks.toml) is
+    /// disabled. The file does not need to live inside the git repository.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not exist
+    pub fn with_config<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Config file not found: {}", path.display()))?;
+        if !canonical.is_file() {
+            return Err(anyhow::anyhow!(
+                "Config path is not a file: {}",
+                path.display()
+            ));
+        }
+        self.config = Some(canonical);
+        Ok(self)
+    }
+
+    /// Treat a directory as the repository root
+    ///
+    /// Config loading uses it instead of looking for an enclosing `.git`, so
+    /// configs can be loaded outside a git checkout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is not a directory
+    pub fn with_repo_root<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Repository root not found: {}", path.display()))?;
+        if !canonical.is_dir() {
+            return Err(anyhow::anyhow!(
+                "Repository root is not a directory: {}",
+                path.display()
+            ));
+        }
+        self.repo_root = Some(canonical);
+        Ok(self)
+    }
+
+    /// Select the `[profiles.<name>]` section applied to every config loaded
+    #[must_use]
+    pub fn with_profile(mut self, name: &str) -> Self {
+        self.profile = Some(name.to_string());
+        self
+    }
+
+    /// The config file set with `with_config`, if any
+    #[must_use]
+    pub fn config(&self) -> Option<&Path> {
+        self.config.as_deref()
+    }
+
+    /// The directory set with `with_repo_root`, if any
+    #[must_use]
+    pub fn repo_root(&self) -> Option<&Path> {
+        self.repo_root.as_deref()
+    }
+
+    /// Name of the profile applied to configs: `--profile`, else
+    /// `PETER_HOOK_PROFILE`, else `default`
+    #[must_use]
+    pub fn profile(&self) -> String {
+        self.profile.clone().unwrap_or_else(|| {
+            std::env::var("PETER_HOOK_PROFILE")
+                .ok()
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "default".to_string())
+        })
+    }
+
+    /// Check whether a path refers to the overridden config file
+    fn is_config(&self, path: &Path) -> bool {
+        self.config()
+            .is_some_and(|override_path| path.canonicalize().is_ok_and(|p| p == override_path))
+    }
+}
+
+/// Repo-wide default config, read from the repository root
+pub const REPO_DEFAULT_CONFIG: &str = ".peter-hook.toml";
+
+/// Directory beside a config whose `*.toml` fragments are merged into it
+pub const FRAGMENTS_DIR: &str = ".peter-hook.d";
+
+/// The `*.toml` files in the `.peter-hook.d` directory of `dir`, as import
+/// paths relative to `dir`, in file name order
+///
+/// # Errors
+///
+/// Returns an error if the directory exists but cannot be read
+fn fragment_imports(dir: &Path) -> Result<Vec<String>> {
+    let fragments_dir = dir.join(FRAGMENTS_DIR);
+    if !fragments_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut imports = Vec::new();
+    let entries = std::fs::read_dir(&fragments_dir)
+        .with_context(|| format!("Failed to read {}", fragments_dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "toml") {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+            imports.push(format!("{FRAGMENTS_DIR}/{name}"));
+        }
+    }
+    imports.sort();
+    Ok(imports)
+}
+
+/// Where the config governing a path comes from, highest precedence first
+///
+/// Exactly one config governs a path: the first level that has one wins and
+/// levels are never merged (within the hierarchy only `inherit = true` pulls
+/// in a parent). The repo default and user config only apply to paths with
+/// no hooks.toml between them and the repository root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigSource {
+    /// The file given with `--config`
+    Override,
+    /// The nearest hooks.toml above the path
+    Hierarchical,
+    /// `.peter-hook.toml` at the repository root
+    RepoDefault,
+    /// hooks.toml in the user's config directory
+    User,
+}
+
+/// The user-wide hooks config: `$XDG_CONFIG_HOME/peter-hook/hooks.toml`,
+/// falling back to the platform config directory
+#[must_use]
+pub fn user_config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(dirs::config_dir)
+        .map(|dir| dir.join("peter-hook").join("hooks.toml"))
+}
+
+/// Config for a path with no hooks.toml up to the repository root: the repo
+/// default if `repo_root` has one, else the user config if it exists
+#[must_use]
+pub fn fallback_config(repo_root: Option<&Path>) -> Option<(PathBuf, ConfigSource)> {
+    repo_root
+        .map(|root| root.join(REPO_DEFAULT_CONFIG))
+        .filter(|path| path.is_file())
+        .map(|path| (path, ConfigSource::RepoDefault))
+        .or_else(|| {
+            user_config_path()
+                .filter(|path| path.is_file())
+                .map(|path| (path, ConfigSource::User))
+        })
+}
+
+/// Whether `path` is the user config from `user_config_path`
+fn is_user_config(path: &Path) -> bool {
+    user_config_path().is_some_and(|user_path| {
+        user_path
+            .canonicalize()
+            .is_ok_and(|user_path| path.canonicalize().is_ok_and(|p| p == user_path))
+    })
+}
+
+/// Parsed configs keyed by canonical path and overrides, reused while none of
+/// the files they were loaded from changed
+static CONFIG_CACHE: LazyLock<Mutex<HashMap<(PathBuf, ConfigOverrides), CachedConfig>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Number of times `from_file_cached` parsed each canonical path
+#[cfg(test)]
+static PARSE_COUNTS: LazyLock<Mutex<HashMap<PathBuf, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Modification time and length of a file or directory, `None` if it does
+/// not exist
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// The stamp of `path` now
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// A parsed config along with the files it was loaded from
+struct CachedConfig {
+    /// Every file the config was loaded from (itself, its imports, fragments
+    /// and inherited parents) and each of their fragments directories, with
+    /// their stamps when it was parsed
+    stamps: Vec<(PathBuf, FileStamp)>,
+    /// The parsed configuration
+    config: HookConfig,
+}
+
+impl CachedConfig {
+    /// Whether none of the files the config was loaded from changed
+    fn is_fresh(&self) -> bool {
+        self.stamps
+            .iter()
+            .all(|(path, stamp)| file_stamp(path) == *stamp)
+    }
+}
+
+/// A hooks.toml that could not be read, parsed or validated
+///
+/// Wraps config loading errors so callers can tell them apart from other
+/// failures (see [`crate::exit_code::CONFIG_ERROR`]). Displays as the
+/// wrapped error.
+#[derive(Debug)]
+pub struct ConfigError {
+    /// The config file that failed to load
+    pub path: PathBuf,
+    /// Why it failed
+    error: anyhow::Error,
+}
+
+impl ConfigError {
+    /// Wrap `error`, raised while loading the config at `path`
+    fn wrap(path: &Path, error: anyhow::Error) -> anyhow::Error {
+        if error.downcast_ref::<Self>().is_some() {
+            return error;
+        }
+        anyhow::Error::new(Self {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.error)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
 /// Represents a hook configuration file (hooks.toml)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct HookConfig {
     /// Individual hook definitions
     pub hooks: Option<HashMap<String, HookDefinition>>,
@@ -20,10 +285,87 @@ pub struct HookConfig {
     pub groups: Option<HashMap<String, HookGroup>>,
     /// Optional list of files to import and merge
     pub imports: Option<Vec<String>>,
+    /// Merge in the nearest ancestor hooks.toml: its hooks are added unless
+    /// this file defines one with the same name, and a group defined in both
+    /// runs the parent's includes as well as its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherit: Option<bool>,
+    /// Default working directory for hooks in this file (groups can override)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir_mode: Option<WorkdirMode>,
+    /// What `files` globs in this file are relative to (repo root by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_relative_to: Option<FilesRelativeTo>,
+    /// Present changed files relative to each hook's working directory
+    /// (groups can override)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_paths: Option<bool>,
+    /// Command to run after every hook run (see `NotifyConfig`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<NotifyConfig>,
+    /// Diagnostic command to run only when a hook run fails (see
+    /// `OnFailureConfig`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<OnFailureConfig>,
+    /// Rename/copy detection for changed-file diffs (git's default if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rename_detection: Option<RenameDetection>,
+    /// Whether working-directory change detection includes untracked files
+    /// (default: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_untracked: Option<bool>,
+    /// Named sets of overrides; the active one (see
+    /// `ConfigOverrides::profile`) is merged onto the hooks and groups when
+    /// the file is parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<HashMap<String, ConfigProfile>>,
+}
+
+/// Overrides applied when a profile is active (`[profiles.<name>]` section)
+///
+/// Each entry sets fields of the hook or group of the same name, leaving the
+/// others as they are; an entry for a name the base config does not define
+/// adds a new hook or group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ConfigProfile {
+    /// Hook fields to override, by hook name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HashMap<String, serde_json::Map<String, serde_json::Value>>>,
+    /// Group fields to override (e.g. `includes`), by group name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<HashMap<String, serde_json::Map<String, serde_json::Value>>>,
+}
+
+/// Post-run notification settings (`[notify]` section)
+///
+/// The command runs from the config directory after each run, pass or fail,
+/// with the JSON results on stdin and `PETER_HOOK_STATUS` set to `success` or
+/// `failure`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct NotifyConfig {
+    /// Command to execute (either as string or array)
+    pub command: HookCommand,
+    /// Kill the command if it runs longer than this (0 means no limit)
+    #[serde(default = "default_notify_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+/// Failure diagnostics settings (`[on_failure]` section)
+///
+/// The command runs from the config directory only when a run fails, with
+/// the failing hook names in `PETER_HOOK_FAILED_HOOKS` (comma-separated). Its
+/// own exit status never changes the outcome of the run.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct OnFailureConfig {
+    /// Command to execute (either as string or array)
+    pub command: HookCommand,
+    /// Kill the command if it runs longer than this (0 means no limit)
+    #[serde(default = "default_notify_timeout_seconds")]
+    pub timeout_seconds: u64,
 }
 
 /// Definition of an individual hook
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct HookDefinition {
     /// Command to execute (either as string or array)
@@ -32,6 +374,15 @@ pub struct HookDefinition {
     pub workdir: Option<String>,
     /// Environment variables to set
     pub env: Option<HashMap<String, String>>,
+    /// File of `KEY=VALUE` lines (relative to the config's directory) whose
+    /// variables are set for the hook; `env` overrides them
+    pub env_file: Option<String>,
+    /// Parent environment variables to forward to the hook (in addition to
+    /// PATH and HOME); all others are cleared
+    pub pass_env: Option<Vec<String>>,
+    /// Variables whose values are masked when the environment is shown
+    /// (`run --dry-run --show-env`)
+    pub mask_env: Option<Vec<String>>,
     /// Description of what this hook does
     pub description: Option<String>,
     /// Whether this hook modifies the repository contents
@@ -41,6 +392,17 @@ pub struct HookDefinition {
     /// File patterns that trigger this hook (glob patterns)
     /// If specified, hook only runs if changed files match these patterns
     pub files: Option<Vec<String>>,
+    /// File extensions that trigger this hook, matched case-insensitively in
+    /// any directory (`["rs"]` is shorthand for `files = ["**/*.rs"]`);
+    /// combines with `files`
+    pub extensions: Option<Vec<String>>,
+    /// Regexes matched against the first line of each changed file (e.g. a
+    /// shebang); a file matching either these or `files` triggers the hook
+    pub content_patterns: Option<Vec<String>>,
+    /// Glob patterns for files this hook never sees, even if they match
+    /// `files` or `content_patterns` (patterns starting with `/` match the
+    /// canonicalized absolute path)
+    pub exclude_files: Option<Vec<String>>,
     /// Run this hook always, regardless of file changes
     #[serde(default)]
     pub run_always: bool,
@@ -50,8 +412,59 @@ pub struct HookDefinition {
     /// contexts like commit-msg
     #[serde(default)]
     pub requires_files: bool,
+    /// Skip the hook when fewer changed files than this match it
+    pub min_changed_files: Option<usize>,
+    /// Skip the hook when more changed files than this match it (e.g. to
+    /// leave huge changesets to a batch job)
+    pub max_changed_files: Option<usize>,
+    /// Report a non-zero exit without failing the overall run (for checks
+    /// that should inform but never block)
+    #[serde(default)]
+    pub allow_failure: bool,
+    /// Fail the hook, even if it exits 0, when more lines of its output than
+    /// this match `warning_pattern`
+    pub max_warnings: Option<usize>,
+    /// Regex counted against `max_warnings`, one match per output line
+    /// (default: `warning` as a word, any case)
+    pub warning_pattern: Option<String>,
+    /// Regex selecting the lines of the hook's stdout and stderr to keep;
+    /// others are dropped before display and warning counting
+    pub output_filter: Option<String>,
+    /// Regex selecting lines of the hook's stdout and stderr to drop before
+    /// display and warning counting
+    pub output_exclude: Option<String>,
+    /// Refuse to run if files this hook would consider have unstaged
+    /// changes, so a modifying hook cannot clobber uncommitted work
+    #[serde(default)]
+    pub require_clean_worktree: bool,
+    /// Skip the hook, reporting a cached pass, when its command and the
+    /// contents of the files it checks match its last successful run
+    #[serde(default)]
+    pub cacheable: bool,
+    /// Stage the matching files the hook rewrites, re-running it until a
+    /// pass changes nothing (for formatters); requires
+    /// `modifies_repository = true`
+    #[serde(default)]
+    pub autofix: bool,
+    /// Hook in the same config to run after an `autofix` hook settles; the
+    /// autofix hook then fails only if this check fails
+    pub autofix_check: Option<String>,
+    /// Feed the hook the stdin git passed to peter-hook (the pre-push ref
+    /// lines) instead of letting it inherit stdin
+    #[serde(default)]
+    pub stdin_from_git: bool,
+    /// Files (relative to the hook's working directory) that must all exist
+    /// for the hook to run; otherwise it is skipped
+    pub if_exists: Option<Vec<String>>,
     /// Hooks that must complete successfully before this hook runs
     pub depends_on: Option<Vec<String>>,
+    /// Position when hooks run one after another: lower numbers run first,
+    /// hooks without one count as 0 and ties run in name order
+    pub order: Option<i32>,
+    /// Start position when hooks run at the same time under a `--jobs`
+    /// limit: higher numbers start first, hooks without one count as 0 and
+    /// ties start in name order
+    pub priority: Option<i32>,
     /// How to execute this hook with respect to changed files
     #[serde(default)]
     pub execution_type: ExecutionType,
@@ -59,10 +472,83 @@ pub struct HookDefinition {
     /// directory
     #[serde(default)]
     pub run_at_root: bool,
+    /// Create the working directory (e.g. a `workdir` output directory) if
+    /// it does not exist, instead of failing the hook
+    #[serde(default)]
+    pub create_workdir: bool,
     /// Maximum execution time in seconds (default: 300 = 5 minutes)
-    /// If the hook exceeds this timeout, it will be killed
+    /// If the hook exceeds this timeout, it will be killed. 0 means no
+    /// timeout at all, not an immediate kill
     #[serde(default = "default_timeout_seconds")]
     pub timeout_seconds: u64,
+    /// Seconds to wait after sending SIGTERM on timeout before sending
+    /// SIGKILL (default: 2)
+    #[serde(default = "default_kill_grace_seconds")]
+    pub kill_grace_seconds: u64,
+    /// Maximum bytes of stdout and of stderr kept per hook (default: 4 MiB)
+    /// Beyond this, only the head and tail are kept
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: u64,
+    /// Niceness adjustment for the hook process (Unix only, e.g. 10 for
+    /// heavy background work); ignored with a warning elsewhere
+    pub nice: Option<i32>,
+    /// Docker image to run the command in (e.g. `node:20`), with the
+    /// repository mounted at `/work`
+    pub container: Option<String>,
+}
+
+impl HookDefinition {
+    /// Glob patterns from `files` plus, for each of `extensions`, a
+    /// recursive glob matching that extension in any case
+    ///
+    /// Returns `None` when neither is set.
+    #[must_use]
+    pub fn file_globs(&self) -> Option<Vec<String>> {
+        if self.files.is_none() && self.extensions.is_none() {
+            return None;
+        }
+
+        let extension_globs = self.extensions.iter().flatten().map(|extension| {
+            let extension: String = extension
+                .trim_start_matches('.')
+                .chars()
+                .map(|c| {
+                    let (lower, upper) = (c.to_lowercase(), c.to_uppercase());
+                    if lower.clone().eq(upper.clone()) {
+                        glob::Pattern::escape(&c.to_string())
+                    } else {
+                        format!("[{lower}{upper}]")
+                    }
+                })
+                .collect();
+            format!("**/*.{extension}")
+        });
+        Some(
+            self.files
+                .iter()
+                .flatten()
+                .cloned()
+                .chain(extension_globs)
+                .collect(),
+        )
+    }
+
+    /// Why `count` matching changed files falls outside this hook's
+    /// `min_changed_files`/`max_changed_files` range, if it does
+    #[must_use]
+    pub fn changed_file_count_skip_reason(&self, count: usize) -> Option<String> {
+        if let Some(min) = self.min_changed_files.filter(|&min| count < min) {
+            return Some(format!(
+                "{count} matching files changed, fewer than min_changed_files = {min}"
+            ));
+        }
+        if let Some(max) = self.max_changed_files.filter(|&max| count > max) {
+            return Some(format!(
+                "{count} matching files changed, more than max_changed_files = {max}"
+            ));
+        }
+        None
+    }
 }
 
 /// Default timeout value: 5 minutes
@@ -70,8 +556,24 @@ const fn default_timeout_seconds() -> u64 {
     300
 }
 
+/// Default timeout of `[notify]` and `[on_failure]` commands: 30 seconds, so
+/// a hung notifier cannot block the commit
+const fn default_notify_timeout_seconds() -> u64 {
+    30
+}
+
+/// Default grace period between SIGTERM and SIGKILL: 2 seconds
+const fn default_kill_grace_seconds() -> u64 {
+    2
+}
+
+/// Default output cap per stream: 4 MiB
+const fn default_max_output_bytes() -> u64 {
+    4 * 1024 * 1024
+}
+
 /// How to execute hooks with respect to changed files
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default, Copy)]
 #[serde(rename_all = "kebab-case")]
 pub enum ExecutionType {
     /// Pass changed files as individual arguments to the command (default)
@@ -83,18 +585,119 @@ pub enum ExecutionType {
     Other,
 }
 
+/// Default working directory for hooks without `workdir` or `run_at_root`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkdirMode {
+    /// Run in the directory containing the config file (default)
+    #[default]
+    ConfigDir,
+    /// Run at the repository root
+    RepoRoot,
+}
+
+/// Base directory for hook and group `files` globs
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FilesRelativeTo {
+    /// Match repo-root-relative paths (default)
+    #[default]
+    RepoRoot,
+    /// Match paths relative to the config file's directory; files outside
+    /// it never match
+    ConfigDir,
+}
+
+/// Rename and copy detection used when diffing for changed files
+///
+/// Written in hooks.toml as `"off"`, `"on"`, or a similarity percentage
+/// (`rename_detection = 40`), which is passed to git as `-M40%`/`-C40%`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(try_from = "RawRenameDetection", into = "RawRenameDetection")]
+pub enum RenameDetection {
+    /// Never pair deletions with additions
+    Off,
+    /// Detect renames and copies with git's default threshold (50%)
+    On,
+    /// Detect renames and copies at this similarity percentage (0-100)
+    Threshold(u8),
+}
+
+impl RenameDetection {
+    /// Arguments to add to `git diff` for this setting
+    #[must_use]
+    pub fn git_args(self) -> Vec<String> {
+        match self {
+            Self::Off => vec!["--no-renames".to_string()],
+            Self::On => vec!["--find-renames".to_string(), "--find-copies".to_string()],
+            Self::Threshold(percent) => vec![
+                format!("--find-renames={percent}%"),
+                format!("--find-copies={percent}%"),
+            ],
+        }
+    }
+}
+
+/// On-disk form of [`RenameDetection`]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum RawRenameDetection {
+    Mode(String),
+    Percent(i64),
+}
+
+impl TryFrom<RawRenameDetection> for RenameDetection {
+    type Error = String;
+
+    fn try_from(raw: RawRenameDetection) -> Result<Self, Self::Error> {
+        match raw {
+            RawRenameDetection::Mode(mode) => match mode.as_str() {
+                "off" => Ok(Self::Off),
+                "on" => Ok(Self::On),
+                other => Err(format!(
+                    "invalid rename_detection '{other}': expected \"off\", \"on\", or a \
+                     percentage from 0 to 100"
+                )),
+            },
+            RawRenameDetection::Percent(percent) => u8::try_from(percent)
+                .ok()
+                .filter(|percent| *percent <= 100)
+                .map(Self::Threshold)
+                .ok_or_else(|| {
+                    format!("invalid rename_detection {percent}: percentage must be 0 to 100")
+                }),
+        }
+    }
+}
+
+impl From<RenameDetection> for RawRenameDetection {
+    fn from(detection: RenameDetection) -> Self {
+        match detection {
+            RenameDetection::Off => Self::Mode("off".to_string()),
+            RenameDetection::On => Self::Mode("on".to_string()),
+            RenameDetection::Threshold(percent) => Self::Percent(i64::from(percent)),
+        }
+    }
+}
+
 /// Command specification for a hook
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum HookCommand {
     /// Shell command as a string
     Shell(String),
     /// Execve-style command as array
     Args(Vec<String>),
+    /// Shell commands run one after another, stopping at the first failure
+    /// (`command = { steps = ["cargo fmt --check", "cargo clippy"] }`)
+    Steps {
+        /// The shell command of each step, in order
+        steps: Vec<String>,
+    },
 }
 
 /// Execution strategy for hook groups
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default, Copy)]
 #[serde(rename_all = "kebab-case")]
 pub enum ExecutionStrategy {
     /// Run all hooks sequentially (default)
@@ -107,7 +710,7 @@ pub enum ExecutionStrategy {
 }
 
 /// Group of hooks that run together
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct HookGroup {
     /// List of hooks or other groups to include
     pub includes: Vec<String>,
@@ -116,11 +719,35 @@ pub struct HookGroup {
     /// Execution strategy for this group
     #[serde(default)]
     pub execution: ExecutionStrategy,
+    /// Glob patterns gating the whole group: when file filtering is active
+    /// and no changed file matches, none of the group's hooks run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<String>>,
     /// Whether this is a placeholder group for hierarchical resolution
     /// Placeholder groups trigger git hook installation but don't run any hooks
     /// at the root level - they only enable subdirectory hooks to be discovered
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub placeholder: Option<bool>,
+    /// Default working directory for hooks in this group (overrides the
+    /// config-level mode; hooks can still set `workdir` or `run_at_root`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir_mode: Option<WorkdirMode>,
+    /// Present changed files relative to each hook's working directory
+    /// (overrides the config-level setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_paths: Option<bool>,
+    /// Hook run once, to completion, before any of the group's hooks; if it
+    /// fails, the group's hooks do not run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<String>,
+    /// Hook run once after the group's hooks, even when they or `setup`
+    /// failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub teardown: Option<String>,
+    /// Hooks or groups that must finish before any of this group's hooks
+    /// start; a group stands for every hook it includes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<Vec<String>>,
     /// Whether to run hooks in parallel (deprecated - use execution field)
     /// Kept for backward compatibility
     #[serde(skip_serializing)]
@@ -151,9 +778,93 @@ impl HookConfig {
     /// # Errors
     ///
     /// Returns an error if the file cannot be read or parsed
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub fn from_file<P: AsRef<Path>>(path: P, overrides: &ConfigOverrides) -> Result<Self> {
+        let path = path.as_ref();
         let mut visited = HashSet::new();
-        Self::from_file_internal(path.as_ref(), &mut visited, None)
+        Self::from_file_internal(path, &mut visited, None, overrides)
+    }
+
+    /// Parse a hooks.toml file, reusing an earlier parse in this process
+    ///
+    /// Results are cached by canonical path and invalidated when the
+    /// modification time or length of any file they were loaded from
+    /// changes: the config itself, its imports, its `.peter-hook.d`
+    /// fragments and inherited parents. Fragments being added or removed
+    /// also invalidate it. Loads with different `overrides` are cached
+    /// separately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed
+    pub fn from_file_cached<P: AsRef<Path>>(path: P, overrides: &ConfigOverrides) -> Result<Self> {
+        let path = path.as_ref();
+        let Ok(canonical) = path.canonicalize() else {
+            return Self::from_file(path, overrides);
+        };
+        let cache_key = (canonical.clone(), overrides.clone());
+
+        if let Some(cached) = CONFIG_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&cache_key)
+            .filter(|cached| cached.is_fresh())
+        {
+            return Ok(cached.config.clone());
+        }
+
+        // The config is stamped before it is read, so an edit made while it
+        // is parsed invalidates the entry
+        let mut stamps = vec![(canonical.clone(), file_stamp(&canonical))];
+        let mut visited = HashSet::new();
+        let config = Self::from_file_internal(path, &mut visited, None, overrides)?;
+        let mut files: Vec<PathBuf> = visited.into_iter().collect();
+        files.push(canonical.clone());
+        for file in files {
+            let fragments = file
+                .parent()
+                .map(|dir| dir.join(FRAGMENTS_DIR))
+                .unwrap_or_default();
+            if file != canonical {
+                let stamp = file_stamp(&file);
+                stamps.push((file, stamp));
+            }
+            let stamp = file_stamp(&fragments);
+            stamps.push((fragments, stamp));
+        }
+
+        #[cfg(test)]
+        {
+            *PARSE_COUNTS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(canonical)
+                .or_default() += 1;
+        }
+        CONFIG_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                cache_key,
+                CachedConfig {
+                    stamps,
+                    config: config.clone(),
+                },
+            );
+        Ok(config)
+    }
+
+    /// Number of times `from_file_cached` has parsed the given file
+    #[cfg(test)]
+    pub(crate) fn cached_parse_count<P: AsRef<Path>>(path: P) -> usize {
+        let Ok(canonical) = path.as_ref().canonicalize() else {
+            return 0;
+        };
+        PARSE_COUNTS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&canonical)
+            .copied()
+            .unwrap_or(0)
     }
 
     /// Parse a hooks.toml file and collect import diagnostics
@@ -165,10 +876,14 @@ impl HookConfig {
     /// - The TOML content is malformed
     /// - Import cycles are detected
     /// - Required configuration fields are missing
-    pub fn from_file_with_trace<P: AsRef<Path>>(path: P) -> Result<(Self, ImportDiagnostics)> {
+    pub fn from_file_with_trace<P: AsRef<Path>>(
+        path: P,
+        overrides: &ConfigOverrides,
+    ) -> Result<(Self, ImportDiagnostics)> {
+        let path = path.as_ref();
         let mut visited = HashSet::new();
         let mut diag = ImportDiagnostics::default();
-        let cfg = Self::from_file_internal(path.as_ref(), &mut visited, Some(&mut diag))?;
+        let cfg = Self::from_file_internal(path, &mut visited, Some(&mut diag), overrides)?;
         // Compute unused imports: those that were resolved but contributed no names
         let unused: Vec<String> = diag
             .imports
@@ -185,28 +900,37 @@ impl HookConfig {
         path: &Path,
         visited: &mut HashSet<PathBuf>,
         diag: Option<&mut ImportDiagnostics>,
+        overrides: &ConfigOverrides,
     ) -> Result<Self> {
-        Self::from_file_internal_with_options(path, visited, diag, true)
+        // An overridden or user config may live outside the repository
+        let require_git_root = !overrides.is_config(path) && !is_user_config(path);
+        Self::from_file_internal_with_options(
+            path,
+            visited,
+            diag,
+            require_git_root,
+            true,
+            overrides,
+        )
     }
 
-    #[allow(clippy::too_many_lines)]
+    /// Load a config file; `with_fragments` also merges its `.peter-hook.d`
+    /// fragments, which imported files do not get
     fn from_file_internal_with_options(
         path: &Path,
         visited: &mut HashSet<PathBuf>,
-        mut diag: Option<&mut ImportDiagnostics>,
+        diag: Option<&mut ImportDiagnostics>,
         require_git_root: bool,
+        with_fragments: bool,
+        overrides: &ConfigOverrides,
     ) -> Result<Self> {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
-
-        let parsed: Self = Self::parse(&content)?;
         let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
 
         // Determine repository root for import security (relative-only, under repo
         // root) Skip git root requirement for absolute paths (they have their
         // own validation)
         let (_repo_root, repo_root_real) = if require_git_root {
-            let repo_root = find_git_root_for_config(base_dir).with_context(|| {
+            let repo_root = find_git_root_for_config(base_dir, overrides).with_context(|| {
                 format!(
                     "Failed to determine git repository root for {}",
                     base_dir.display()
@@ -223,58 +947,51 @@ impl HookConfig {
             (dummy_root.clone(), dummy_root)
         };
 
-        // Start with merged result from imports (if any)
-        let mut merged_hooks: HashMap<String, HookDefinition> = HashMap::new();
-        let mut merged_groups: HashMap<String, HookGroup> = HashMap::new();
-        // Track sources to produce override diagnostics
-        let mut hook_sources: HashMap<String, String> = HashMap::new();
-        let mut group_sources: HashMap<String, String> = HashMap::new();
+        // A missing repository is not a problem with the config itself
+        Self::load(
+            path,
+            base_dir,
+            &repo_root_real,
+            visited,
+            diag,
+            with_fragments,
+            overrides,
+        )
+        .map_err(|error| ConfigError::wrap(path, error))
+    }
 
-        if let Some(imports) = &parsed.imports {
-            // Load global configuration for absolute path validation
-            let global_config = GlobalConfig::load().unwrap_or_default();
+    /// Read, parse and merge the imports of the config at `path`
+    ///
+    /// With `with_fragments`, the `.peter-hook.d/*.toml` fragments beside it
+    /// are merged like imports listed after its own, in file name order.
+    fn load(
+        path: &Path,
+        base_dir: &Path,
+        repo_root_real: &Path,
+        visited: &mut HashSet<PathBuf>,
+        mut diag: Option<&mut ImportDiagnostics>,
+        with_fragments: bool,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-            for imp in imports {
-                // Expand tilde in the import path
-                let expanded = shellexpand::tilde(imp);
-                let p = Path::new(&*expanded);
-                let (imp_path, is_absolute) = if p.is_absolute() {
-                    // Check if absolute path is allowed via global config
-                    if !global_config.is_absolute_path_allowed(p)? {
-                        return Err(anyhow::anyhow!(
-                            "Absolute import path not allowed: {imp}\nHint: Only imports from \
-                             $HOME/.local/peter-hook are allowed.\nEnable with: peter-hook config \
-                             init --allow-local"
-                        ));
-                    }
-                    (p.to_path_buf(), true)
-                } else {
-                    (base_dir.join(p), false)
-                };
+        let mut parsed: Self = Self::parse_with_profile(&content, &overrides.profile())?;
 
-                let imp_real = imp_path.canonicalize().with_context(|| {
-                    format!("Failed to resolve import path: {}", imp_path.display())
-                })?;
+        // Start with merged result from imports (if any)
+        let mut merged = MergedDefinitions::default();
 
-                // Enforce import stays within repo root (but only for relative imports)
-                if !is_absolute && !imp_real.starts_with(&repo_root_real) {
-                    return Err(anyhow::anyhow!(
-                        "import outside repository root is not allowed: {} (repo root: {})",
-                        imp_real.display(),
-                        repo_root_real.display()
-                    ));
-                }
+        let mut imports = parsed.imports.clone().unwrap_or_default();
+        if with_fragments {
+            imports.extend(fragment_imports(base_dir)?);
+        }
+        if !imports.is_empty() {
+            // Load global configuration for absolute path validation
+            let global_config = GlobalConfig::load().unwrap_or_default();
 
-                // For absolute paths, verify they are still within peter-hook dir after
-                // canonicalization (this protects against symlink attacks)
-                if is_absolute && !global_config.is_absolute_path_allowed(&imp_real)? {
-                    return Err(anyhow::anyhow!(
-                        "Import path resolves outside $HOME/.local/peter-hook (possible symlink): \
-                         {} -> {}",
-                        imp_path.display(),
-                        imp_real.display()
-                    ));
-                }
+            for imp in &imports {
+                let (imp_real, is_absolute) =
+                    resolve_import(imp, base_dir, repo_root_real, &global_config)?;
 
                 // Diagnostics: record import edge
                 if let Some(d) = diag.as_mut() {
@@ -293,128 +1010,152 @@ impl HookConfig {
                 }
                 // For absolute imports, don't require git root since they're in peter-hook
                 // directory
-                let skip_git_for_import = is_absolute;
                 let imported = Self::from_file_internal_with_options(
                     &imp_real,
                     visited,
                     diag.as_deref_mut(),
-                    !skip_git_for_import,
+                    !is_absolute,
+                    false,
+                    overrides,
                 )
                 .with_context(|| format!("Failed to import config: {imp}"))?;
-                if let Some(h) = imported.hooks {
-                    for (k, v) in h {
-                        if let Some(d) = diag.as_mut() {
-                            let prev = hook_sources.get(&k).cloned();
-                            if let Some(prev_src) = prev {
-                                d.overrides.push(OverrideRecord {
-                                    kind: "hook".to_string(),
-                                    name: k.clone(),
-                                    previous: prev_src,
-                                    new: imp_real.display().to_string(),
-                                });
-                            } else {
-                                *d.contributions
-                                    .entry(imp_real.display().to_string())
-                                    .or_default() += 1;
-                            }
-                        }
-                        hook_sources.insert(k.clone(), imp_real.display().to_string());
-                        merged_hooks.insert(k, v);
-                    }
-                }
-                if let Some(g) = imported.groups {
-                    for (k, v) in g {
-                        if let Some(d) = diag.as_mut() {
-                            let prev = group_sources.get(&k).cloned();
-                            if let Some(prev_src) = prev {
-                                d.overrides.push(OverrideRecord {
-                                    kind: "group".to_string(),
-                                    name: k.clone(),
-                                    previous: prev_src,
-                                    new: imp_real.display().to_string(),
-                                });
-                            } else {
-                                *d.contributions
-                                    .entry(imp_real.display().to_string())
-                                    .or_default() += 1;
-                            }
-                        }
-                        group_sources.insert(k.clone(), imp_real.display().to_string());
-                        merged_groups.insert(k, v);
-                    }
-                }
+                merged.overlay(
+                    imported.hooks,
+                    imported.groups,
+                    &imp_real.display().to_string(),
+                    diag.as_deref_mut(),
+                );
             }
         }
 
         // Overlay with local definitions (local overrides imports)
-        if let Some(h) = parsed.hooks {
-            for (k, v) in h {
-                if let Some(d) = diag.as_mut() {
-                    if let Some(prev_src) = hook_sources.get(&k).cloned() {
-                        d.overrides.push(OverrideRecord {
-                            kind: "hook".to_string(),
-                            name: k.clone(),
-                            previous: prev_src,
-                            new: path.display().to_string(),
-                        });
-                    } else {
-                        *d.contributions
-                            .entry(path.display().to_string())
-                            .or_default() += 1;
-                    }
-                }
-                hook_sources.insert(k.clone(), path.display().to_string());
-                merged_hooks.insert(k, v);
-            }
-        }
-        if let Some(g) = parsed.groups {
-            for (k, v) in g {
-                if let Some(d) = diag.as_mut() {
-                    if let Some(prev_src) = group_sources.get(&k).cloned() {
-                        d.overrides.push(OverrideRecord {
-                            kind: "group".to_string(),
-                            name: k.clone(),
-                            previous: prev_src,
-                            new: path.display().to_string(),
-                        });
-                    } else {
-                        *d.contributions
-                            .entry(path.display().to_string())
-                            .or_default() += 1;
-                    }
+        merged.overlay(
+            parsed.hooks.take(),
+            parsed.groups.take(),
+            &path.display().to_string(),
+            diag.as_deref_mut(),
+        );
+
+        // Opt-in inheritance: the parent config sits beneath everything else
+        if parsed.inherit == Some(true) {
+            if let Some(parent_path) = find_parent_config(base_dir, repo_root_real) {
+                if visited.insert(parent_path.clone()) {
+                    let parent = Self::from_file_internal(&parent_path, visited, diag, overrides)
+                        .with_context(|| {
+                        format!("Failed to inherit config: {}", parent_path.display())
+                    })?;
+                    merged.inherit(parent);
                 }
-                group_sources.insert(k.clone(), path.display().to_string());
-                merged_groups.insert(k, v);
             }
         }
 
         Ok(Self {
-            hooks: if merged_hooks.is_empty() {
+            hooks: if merged.hooks.is_empty() {
                 None
             } else {
-                Some(merged_hooks)
+                Some(merged.hooks)
             },
-            groups: if merged_groups.is_empty() {
+            groups: if merged.groups.is_empty() {
                 None
             } else {
-                Some(merged_groups)
+                Some(merged.groups)
             },
             imports: None,
+            inherit: None,
+            workdir_mode: parsed.workdir_mode,
+            files_relative_to: parsed.files_relative_to,
+            relative_paths: parsed.relative_paths,
+            notify: parsed.notify,
+            on_failure: parsed.on_failure,
+            rename_detection: parsed.rename_detection,
+            include_untracked: parsed.include_untracked,
+            profiles: None,
         })
     }
 
+    /// JSON Schema for hooks.toml, derived from these types so it cannot
+    /// drift from what the parser accepts
+    ///
+    /// Draft 7 is used because it is what TOML language servers support best.
+    #[must_use]
+    pub fn json_schema() -> serde_json::Value {
+        let generator = schemars::generate::SchemaSettings::draft07()
+            .for_deserialize()
+            .into_generator();
+        generator.into_root_schema_for::<Self>().to_value()
+    }
+
     /// Parse a hooks.toml configuration from a string
     ///
+    /// The profile selected by `PETER_HOOK_PROFILE` (else `default`) is
+    /// applied.
+    ///
     /// # Errors
     ///
     /// Returns an error if the TOML content cannot be parsed or validation
     /// fails
     pub fn parse(content: &str) -> Result<Self> {
-        let config: Self = toml::from_str(content).context("Failed to parse TOML configuration")?;
+        Self::parse_with_profile(content, &ConfigOverrides::default().profile())
+    }
+
+    /// Parse a hooks.toml configuration from a string, applying profile
+    /// `profile`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML content cannot be parsed or validation
+    /// fails
+    fn parse_with_profile(content: &str, profile: &str) -> Result<Self> {
+        let mut config: Self =
+            toml::from_str(content).context("Failed to parse TOML configuration")?;
+        config.apply_profile(profile)?;
         config.validate()?;
         Ok(config)
     }
 
+    /// Merge the overrides of profile `name`, if this config defines it, and
+    /// drop the `profiles` section
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an override does not produce a valid hook or group
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        /// Set `fields` on `base` (or on an empty table if there is none)
+        fn merge<T: Serialize + serde::de::DeserializeOwned>(
+            base: Option<&T>,
+            fields: serde_json::Map<String, serde_json::Value>,
+        ) -> Result<T> {
+            let mut value = match base {
+                Some(base) => serde_json::to_value(base)?,
+                None => serde_json::Value::Object(serde_json::Map::new()),
+            };
+            if let serde_json::Value::Object(table) = &mut value {
+                table.extend(fields);
+            }
+            Ok(serde_json::from_value(value)?)
+        }
+
+        let Some(profile) = self.profiles.take().and_then(|mut p| p.remove(name)) else {
+            return Ok(());
+        };
+
+        for (hook, fields) in profile.hooks.unwrap_or_default() {
+            let hooks = self.hooks.get_or_insert_with(HashMap::new);
+            let merged = merge(hooks.get(&hook), fields).with_context(|| {
+                format!("Profile '{name}' has an invalid override for hook '{hook}'")
+            })?;
+            hooks.insert(hook, merged);
+        }
+        for (group, fields) in profile.groups.unwrap_or_default() {
+            let groups = self.groups.get_or_insert_with(HashMap::new);
+            let merged = merge(groups.get(&group), fields).with_context(|| {
+                format!("Profile '{name}' has an invalid override for group '{group}'")
+            })?;
+            groups.insert(group, merged);
+        }
+        Ok(())
+    }
+
     /// Validate the configuration for consistency
     ///
     /// # Errors
@@ -424,6 +1165,10 @@ impl HookConfig {
     ///   options)
     /// - A hook uses `execution_type` = "per-file" or "in-place" with template
     ///   variables like `{CHANGED_FILES}`
+    /// - A hook's `content_patterns` contains an invalid regex
+    /// - A hook's `autofix`/`autofix_check` settings are inconsistent
+    /// - A hook's `warning_pattern` is invalid or lacks `max_warnings`
+    #[allow(clippy::too_many_lines)]
     pub fn validate(&self) -> Result<()> {
         if let Some(hooks) = &self.hooks {
             for (name, hook) in hooks {
@@ -435,6 +1180,77 @@ impl HookConfig {
                          true' for unconditional execution."
                     ));
                 }
+                if hook.run_always && hook.extensions.is_some() {
+                    return Err(anyhow::anyhow!(
+                        "Hook '{name}' cannot have both 'extensions' and 'run_always = true'"
+                    ));
+                }
+                if hook
+                    .extensions
+                    .iter()
+                    .flatten()
+                    .any(|extension| extension.trim_start_matches('.').is_empty())
+                {
+                    return Err(anyhow::anyhow!("Hook '{name}' has an empty extension"));
+                }
+                if hook
+                    .container
+                    .as_ref()
+                    .is_some_and(|image| image.trim().is_empty())
+                {
+                    return Err(anyhow::anyhow!(
+                        "Hook '{name}' has an empty container image"
+                    ));
+                }
+
+                if hook.autofix && !hook.modifies_repository {
+                    return Err(anyhow::anyhow!(
+                        "Hook '{name}' has 'autofix = true' but does not set 'modifies_repository \
+                         = true'"
+                    ));
+                }
+                if let Some(check) = &hook.autofix_check {
+                    if !hook.autofix {
+                        return Err(anyhow::anyhow!(
+                            "Hook '{name}' has 'autofix_check' without 'autofix = true'"
+                        ));
+                    }
+                    if !hooks.contains_key(check) || check == name {
+                        return Err(anyhow::anyhow!(
+                            "Hook '{name}' has autofix_check '{check}', which is not another hook \
+                             in this config"
+                        ));
+                    }
+                }
+
+                if let Some(pattern) = &hook.warning_pattern {
+                    if hook.max_warnings.is_none() {
+                        return Err(anyhow::anyhow!(
+                            "Hook '{name}' has 'warning_pattern' without 'max_warnings'"
+                        ));
+                    }
+                    regex::Regex::new(pattern).with_context(|| {
+                        format!("Hook '{name}' has an invalid warning pattern: {pattern}")
+                    })?;
+                }
+
+                for (key, pattern) in [
+                    ("output_filter", &hook.output_filter),
+                    ("output_exclude", &hook.output_exclude),
+                ] {
+                    if let Some(pattern) = pattern {
+                        regex::Regex::new(pattern).with_context(|| {
+                            format!("Hook '{name}' has an invalid {key}: {pattern}")
+                        })?;
+                    }
+                }
+
+                // Check that content patterns are valid regexes
+                for pattern in hook.content_patterns.iter().flatten() {
+                    regex::Regex::new(pattern).with_context(|| {
+                        format!("Hook '{name}' has an invalid content pattern: {pattern}")
+                    })?;
+                }
 
                 // Check for conflicting requires_files and run_always settings
                 if hook.requires_files && hook.run_always {
@@ -446,6 +1262,24 @@ impl HookConfig {
                     ));
                 }
 
+                // Check that changed-file count thresholds make sense
+                let has_count_range =
+                    hook.min_changed_files.is_some() || hook.max_changed_files.is_some();
+                if has_count_range && hook.run_always {
+                    return Err(anyhow::anyhow!(
+                        "Hook '{name}' cannot have both changed-file count thresholds and \
+                         'run_always = true'"
+                    ));
+                }
+                if let (Some(min), Some(max)) = (hook.min_changed_files, hook.max_changed_files) {
+                    if min > max {
+                        return Err(anyhow::anyhow!(
+                            "Hook '{name}' has min_changed_files = {min} greater than \
+                             max_changed_files = {max}"
+                        ));
+                    }
+                }
+
                 // Check for conflicting execution_type and template variable usage
                 if matches!(
                     hook.execution_type,
@@ -500,15 +1334,181 @@ impl HookConfig {
             names.extend(groups.keys().cloned());
         }
 
-        names.sort();
-        names
+        names.sort();
+        names
+    }
+
+    /// Check if a hook or group exists
+    #[must_use]
+    pub fn has_hook(&self, name: &str) -> bool {
+        self.hooks.as_ref().is_some_and(|h| h.contains_key(name))
+            || self.groups.as_ref().is_some_and(|g| g.contains_key(name))
+    }
+
+    /// Find group `includes` entries that name neither a hook nor a group
+    ///
+    /// Returns `(group, include)` pairs sorted by group name. Resolution at
+    /// run time ignores such entries, so this is only used by validation.
+    #[must_use]
+    pub fn unresolved_includes(&self) -> Vec<(String, String)> {
+        let mut unresolved = Vec::new();
+
+        if let Some(groups) = &self.groups {
+            for (group_name, group) in groups {
+                for include in &group.includes {
+                    if !self.has_hook(include) {
+                        unresolved.push((group_name.clone(), include.clone()));
+                    }
+                }
+            }
+        }
+
+        unresolved.sort();
+        unresolved
+    }
+
+    /// Find group `setup`/`teardown` entries that do not name a hook
+    ///
+    /// Returns `(group, name)` pairs sorted by group name. Running such a
+    /// group fails, so validation reports them as errors.
+    #[must_use]
+    pub fn unresolved_group_steps(&self) -> Vec<(String, String)> {
+        let mut unresolved: Vec<_> = self
+            .groups
+            .iter()
+            .flatten()
+            .flat_map(|(group_name, group)| {
+                group
+                    .setup
+                    .iter()
+                    .chain(&group.teardown)
+                    .filter(|name| {
+                        !self
+                            .hooks
+                            .as_ref()
+                            .is_some_and(|hooks| hooks.contains_key(*name))
+                    })
+                    .map(move |name| (group_name.clone(), name.clone()))
+            })
+            .collect();
+        unresolved.sort();
+        unresolved
+    }
+
+    /// Find `files`, `extensions` and `exclude_files` globs that do not
+    /// compile
+    ///
+    /// Returns `(hook, message)` pairs sorted by hook name. Resolving such a
+    /// hook fails, so validation reports them as errors.
+    #[must_use]
+    pub fn invalid_file_patterns(&self) -> Vec<(String, String)> {
+        let mut invalid: Vec<_> = self
+            .hooks
+            .iter()
+            .flatten()
+            .flat_map(|(hook_name, hook)| {
+                hook.file_globs()
+                    .into_iter()
+                    .flatten()
+                    .chain(hook.exclude_files.iter().flatten().cloned())
+                    .filter_map(move |pattern| {
+                        glob::Pattern::new(&pattern).err().map(|error| {
+                            (
+                                hook_name.clone(),
+                                format!(
+                                    "Hook '{hook_name}' has an invalid file pattern '{pattern}': \
+                                     {error}"
+                                ),
+                            )
+                        })
+                    })
+            })
+            .collect();
+        invalid.sort();
+        invalid
+    }
+
+    /// Every hook `name` stands for: itself if it is a hook, or the hooks
+    /// a group includes, directly or through nested groups
+    ///
+    /// Unknown names stand for themselves.
+    #[must_use]
+    pub fn hooks_named_by(&self, name: &str) -> Vec<String> {
+        fn collect(
+            config: &HookConfig,
+            name: &str,
+            visited: &mut HashSet<String>,
+            hooks: &mut Vec<String>,
+        ) {
+            if !visited.insert(name.to_string()) {
+                return;
+            }
+            let group = config.groups.as_ref().and_then(|groups| groups.get(name));
+            match group {
+                Some(group) if !config.hooks.as_ref().is_some_and(|h| h.contains_key(name)) => {
+                    for include in &group.includes {
+                        collect(config, include, visited, hooks);
+                    }
+                }
+                _ => hooks.push(name.to_string()),
+            }
+        }
+
+        let mut hooks = Vec::new();
+        collect(self, name, &mut HashSet::new(), &mut hooks);
+        hooks
     }
 
-    /// Check if a hook or group exists
+    /// Hooks that must finish before `hook` starts
+    ///
+    /// Combines the hook's own `depends_on` with the `depends_on` of every
+    /// group that includes it, with group names expanded to their hooks.
+    /// Returns `None` when the hook depends on nothing.
     #[must_use]
-    pub fn has_hook(&self, name: &str) -> bool {
-        self.hooks.as_ref().is_some_and(|h| h.contains_key(name))
-            || self.groups.as_ref().is_some_and(|g| g.contains_key(name))
+    pub fn effective_depends_on(&self, hook: &str) -> Option<Vec<String>> {
+        let own = self
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.get(hook))
+            .and_then(|definition| definition.depends_on.as_ref());
+        let inherited = self
+            .groups
+            .iter()
+            .flatten()
+            .filter(|(name, group)| {
+                group.depends_on.is_some() && self.hooks_named_by(name).iter().any(|h| h == hook)
+            })
+            .filter_map(|(_, group)| group.depends_on.as_ref());
+
+        let mut dependencies: Vec<String> = Vec::new();
+        for name in own.into_iter().chain(inherited).flatten() {
+            for dependency in self.hooks_named_by(name) {
+                if !dependencies.contains(&dependency) {
+                    dependencies.push(dependency);
+                }
+            }
+        }
+        (!dependencies.is_empty()).then_some(dependencies)
+    }
+
+    /// Describe a cycle among the hooks' `depends_on` (including group
+    /// `depends_on`), if there is one
+    #[must_use]
+    pub fn dependency_cycle(&self) -> Option<String> {
+        let hooks = self.hooks.as_ref()?;
+        let mut resolver = crate::hooks::DependencyResolver::new();
+        for name in hooks.keys() {
+            resolver.add_hook(
+                name.clone(),
+                self.effective_depends_on(name).unwrap_or_default(),
+            );
+        }
+        let mut names: Vec<String> = hooks.keys().cloned().collect();
+        names.sort();
+        resolver
+            .resolve(&names)
+            .err()
+            .map(|error| error.to_string())
     }
 }
 
@@ -528,6 +1528,27 @@ pub struct ImportDiagnostics {
     pub contributions: HashMap<String, usize>,
 }
 
+impl ImportDiagnostics {
+    /// Hook names defined by more than one source in the merged configuration
+    ///
+    /// Each entry lists the defining sources in merge order; the last source
+    /// is the one whose definition takes effect.
+    #[must_use]
+    pub fn duplicate_hooks(&self) -> Vec<(String, Vec<String>)> {
+        let mut duplicates: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for record in self.overrides.iter().filter(|r| r.kind == "hook") {
+            let sources = duplicates.entry(record.name.clone()).or_default();
+            if sources.is_empty() {
+                sources.push(record.previous.clone());
+            }
+            sources.push(record.new.clone());
+        }
+
+        duplicates.into_iter().collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 /// Record of a configuration file import operation
 pub struct ImportRecord {
@@ -550,8 +1571,172 @@ pub struct OverrideRecord {
     pub new: String,
 }
 
+/// Resolve an `imports` entry of the config in `base_dir` to its canonical
+/// path, and whether it was given as an absolute path
+///
+/// Relative imports must stay within the repository; absolute ones must be
+/// allowed by the global config.
+fn resolve_import(
+    imp: &str,
+    base_dir: &Path,
+    repo_root_real: &Path,
+    global_config: &GlobalConfig,
+) -> Result<(PathBuf, bool)> {
+    // Expand tilde in the import path
+    let expanded = shellexpand::tilde(imp);
+    let p = Path::new(&*expanded);
+    let (imp_path, is_absolute) = if p.is_absolute() {
+        // Check if absolute path is allowed via global config
+        if !global_config.is_absolute_path_allowed(p)? {
+            return Err(anyhow::anyhow!(
+                "Absolute import path not allowed: {imp}\nHint: Only imports from \
+                 $HOME/.local/peter-hook are allowed.\nEnable with: peter-hook config init \
+                 --allow-local"
+            ));
+        }
+        (p.to_path_buf(), true)
+    } else {
+        (base_dir.join(p), false)
+    };
+
+    let imp_real = imp_path
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve import path: {}", imp_path.display()))?;
+
+    // Enforce import stays within repo root (but only for relative imports)
+    if !is_absolute && !imp_real.starts_with(repo_root_real) {
+        return Err(anyhow::anyhow!(
+            "import outside repository root is not allowed: {} (repo root: {})",
+            imp_real.display(),
+            repo_root_real.display()
+        ));
+    }
+
+    // For absolute paths, verify they are still within peter-hook dir after
+    // canonicalization (this protects against symlink attacks)
+    if is_absolute && !global_config.is_absolute_path_allowed(&imp_real)? {
+        return Err(anyhow::anyhow!(
+            "Import path resolves outside $HOME/.local/peter-hook (possible symlink): {} -> {}",
+            imp_path.display(),
+            imp_real.display()
+        ));
+    }
+
+    Ok((imp_real, is_absolute))
+}
+
+/// Hooks and groups merged from a config's imports, its own definitions
+/// and its inherited parent, with the source each one came from
+#[derive(Default)]
+struct MergedDefinitions {
+    hooks: HashMap<String, HookDefinition>,
+    groups: HashMap<String, HookGroup>,
+    /// Source of each hook, to produce override diagnostics
+    hook_sources: HashMap<String, String>,
+    /// Source of each group, to produce override diagnostics
+    group_sources: HashMap<String, String>,
+}
+
+impl MergedDefinitions {
+    /// Merge the hooks and groups of `source` over those merged so far
+    fn overlay(
+        &mut self,
+        hooks: Option<HashMap<String, HookDefinition>>,
+        groups: Option<HashMap<String, HookGroup>>,
+        source: &str,
+        mut diag: Option<&mut ImportDiagnostics>,
+    ) {
+        overlay_definitions(
+            "hook",
+            hooks,
+            source,
+            &mut self.hooks,
+            &mut self.hook_sources,
+            diag.as_deref_mut(),
+        );
+        overlay_definitions(
+            "group",
+            groups,
+            source,
+            &mut self.groups,
+            &mut self.group_sources,
+            diag,
+        );
+    }
+
+    /// Merge an inherited `parent` config beneath everything merged so far
+    ///
+    /// Hooks defined on both sides keep the child's definition; groups on
+    /// both sides run the parent's includes first, then the child's additions.
+    fn inherit(&mut self, parent: HookConfig) {
+        for (name, hook) in parent.hooks.unwrap_or_default() {
+            self.hooks.entry(name).or_insert(hook);
+        }
+        for (name, group) in parent.groups.unwrap_or_default() {
+            match self.groups.get_mut(&name) {
+                Some(own) => {
+                    let mut includes = group.includes;
+                    for include in own.includes.drain(..) {
+                        if !includes.contains(&include) {
+                            includes.push(include);
+                        }
+                    }
+                    own.includes = includes;
+                }
+                None => {
+                    self.groups.insert(name, group);
+                }
+            }
+        }
+    }
+}
+
+/// Merge `definitions` from `source` over `merged`, recording in `diag` which
+/// earlier source each one overrides or that `source` contributed it
+fn overlay_definitions<T>(
+    kind: &str,
+    definitions: Option<HashMap<String, T>>,
+    source: &str,
+    merged: &mut HashMap<String, T>,
+    sources: &mut HashMap<String, String>,
+    mut diag: Option<&mut ImportDiagnostics>,
+) {
+    for (name, definition) in definitions.unwrap_or_default() {
+        if let Some(d) = diag.as_mut() {
+            if let Some(previous) = sources.get(&name).cloned() {
+                d.overrides.push(OverrideRecord {
+                    kind: kind.to_string(),
+                    name: name.clone(),
+                    previous,
+                    new: source.to_string(),
+                });
+            } else {
+                *d.contributions.entry(source.to_string()).or_default() += 1;
+            }
+        }
+        sources.insert(name.clone(), source.to_string());
+        merged.insert(name, definition);
+    }
+}
+
+/// The nearest hooks.toml above `base_dir`, not looking past the repository
+/// root
+fn find_parent_config(base_dir: &Path, repo_root_real: &Path) -> Option<PathBuf> {
+    let base_dir = base_dir.canonicalize().ok()?;
+    base_dir
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(repo_root_real))
+        .map(|dir| dir.join("hooks.toml"))
+        .find(|config| config.is_file())
+}
+
 /// Find git repository root by walking up directories for config parsing
-fn find_git_root_for_config(start_dir: &Path) -> Result<PathBuf> {
+fn find_git_root_for_config(start_dir: &Path, overrides: &ConfigOverrides) -> Result<PathBuf> {
+    if let Some(repo_root) = overrides.repo_root() {
+        return Ok(repo_root.to_path_buf());
+    }
+
     let mut current = start_dir;
     loop {
         if current.join(".git").exists() {
@@ -618,6 +1803,28 @@ command = ["cargo", "clippy", "--all-targets", "--", "-D", "warnings"]
         );
     }
 
+    #[test]
+    fn test_parse_steps_command() {
+        let toml = r#"
+[hooks.check]
+command = { steps = ["cargo fmt --check", "cargo clippy"] }
+"#;
+
+        let config = HookConfig::parse(toml).unwrap();
+        let hook = &config.hooks.unwrap()["check"];
+
+        assert_eq!(
+            hook.command,
+            HookCommand::Steps {
+                steps: vec!["cargo fmt --check".to_string(), "cargo clippy".to_string()],
+            }
+        );
+        assert_eq!(
+            hook.command.to_string(),
+            "cargo fmt --check && cargo clippy"
+        );
+    }
+
     #[test]
     fn test_parse_hook_group() {
         let toml = r#"
@@ -657,6 +1864,32 @@ includes = ["test1", "test2"]
         assert_eq!(names, vec!["all-tests", "test1", "test2"]);
     }
 
+    #[test]
+    fn test_rename_detection_values() {
+        let parse = |value: &str| {
+            HookConfig::parse(&format!(
+                "rename_detection = {value}\n[hooks.a]\ncommand = \"true\"\n"
+            ))
+            .map(|config| config.rename_detection)
+        };
+
+        assert_eq!(parse("\"off\"").unwrap(), Some(RenameDetection::Off));
+        assert_eq!(parse("\"on\"").unwrap(), Some(RenameDetection::On));
+        assert_eq!(parse("40").unwrap(), Some(RenameDetection::Threshold(40)));
+        assert!(parse("101").is_err());
+        assert!(parse("\"sometimes\"").is_err());
+        assert_eq!(
+            HookConfig::parse("[hooks.a]\ncommand = \"true\"\n")
+                .unwrap()
+                .rename_detection,
+            None
+        );
+        assert_eq!(
+            RenameDetection::Threshold(40).git_args(),
+            vec!["--find-renames=40%", "--find-copies=40%"]
+        );
+    }
+
     #[test]
     fn test_repository_modifying_hook() {
         let toml = r#"
@@ -674,6 +1907,148 @@ modifies_repository = true
         assert_eq!(hook.description, Some("Format Rust code".to_string()));
     }
 
+    #[test]
+    fn test_profile_overrides_merge_onto_base() {
+        let toml = r#"
+[hooks.lint]
+command = "cargo clippy"
+timeout_seconds = 60
+
+[groups.pre-commit]
+includes = ["lint"]
+description = "Checks"
+
+[profiles.ci.hooks.lint]
+timeout_seconds = 600
+
+[profiles.ci.hooks.audit]
+command = "cargo audit"
+
+[profiles.ci.groups.pre-commit]
+includes = ["lint", "audit"]
+"#;
+        let base: HookConfig = toml::from_str(toml).unwrap();
+
+        let mut local = base.clone();
+        local.apply_profile("local").unwrap();
+        assert!(local.profiles.is_none());
+        assert_eq!(local.hooks.as_ref().unwrap().len(), 1);
+
+        let mut ci = base;
+        ci.apply_profile("ci").unwrap();
+        let hooks = ci.hooks.unwrap();
+        // Overridden field changes, the others stay
+        assert_eq!(hooks["lint"].timeout_seconds, 600);
+        assert_eq!(hooks["lint"].command.to_string(), "cargo clippy");
+        assert_eq!(hooks["audit"].command.to_string(), "cargo audit");
+        let group = &ci.groups.unwrap()["pre-commit"];
+        assert_eq!(group.includes, ["lint", "audit"]);
+        assert_eq!(group.description.as_deref(), Some("Checks"));
+    }
+
+    #[test]
+    fn test_profile_comes_from_the_overrides_of_each_load() {
+        use tempfile::TempDir;
+        let td = TempDir::new().unwrap();
+        std::fs::create_dir_all(td.path().join(".git")).unwrap();
+        let config_path = td.path().join("hooks.toml");
+        std::fs::write(
+            &config_path,
+            "[hooks.lint]\ncommand = \"lint\"\n\n[profiles.ci.hooks.lint]\ntimeout_seconds = 600\n",
+        )
+        .unwrap();
+
+        let ci = ConfigOverrides::default().with_profile("ci");
+        let config = HookConfig::from_file_cached(&config_path, &ci).unwrap();
+        assert_eq!(config.hooks.unwrap()["lint"].timeout_seconds, 600);
+
+        // A load with other overrides does not reuse the cached ci config
+        let local = ConfigOverrides::default().with_profile("local");
+        let config = HookConfig::from_file_cached(&config_path, &local).unwrap();
+        assert_ne!(config.hooks.unwrap()["lint"].timeout_seconds, 600);
+    }
+
+    #[test]
+    fn test_fragments_merge_in_file_name_order() {
+        use std::fs;
+        use tempfile::TempDir;
+        let td = TempDir::new().unwrap();
+        let dir = td.path();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        let fragments = dir.join(FRAGMENTS_DIR);
+        fs::create_dir_all(&fragments).unwrap();
+        fs::write(
+            fragments.join("10-lint.toml"),
+            "[hooks.lint]\ncommand = \"echo lint\"\n\n[hooks.shared]\ncommand = \"echo 10\"\n",
+        )
+        .unwrap();
+        fs::write(
+            fragments.join("20-test.toml"),
+            "[hooks.test]\ncommand = \"echo test\"\n\n[hooks.shared]\ncommand = \"echo 20\"\n",
+        )
+        .unwrap();
+        fs::write(fragments.join("notes.md"), "not a fragment").unwrap();
+        let base = dir.join("hooks.toml");
+        fs::write(
+            &base,
+            "[hooks.test]\ncommand = \"echo local-test\"\n\n[groups.pre-commit]\nincludes =              [\"lint\", \"test\", \"shared\"]\n",
+        )
+        .unwrap();
+
+        let config = HookConfig::from_file(&base, &ConfigOverrides::default()).unwrap();
+        let hooks = config.hooks.unwrap();
+        assert_eq!(hooks["lint"].command.to_string(), "echo lint");
+        // Later fragments override earlier ones; the config overrides both
+        assert_eq!(hooks["shared"].command.to_string(), "echo 20");
+        assert_eq!(hooks["test"].command.to_string(), "echo local-test");
+        assert!(config.groups.unwrap().contains_key("pre-commit"));
+    }
+
+    #[test]
+    fn test_cached_config_tracks_imports_and_fragments() {
+        use std::fs;
+        use tempfile::TempDir;
+        let td = TempDir::new().unwrap();
+        let dir = td.path();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::write(
+            dir.join("lib.toml"),
+            "[hooks.lint]\ncommand = \"echo v1\"\n",
+        )
+        .unwrap();
+        let base = dir.join("hooks.toml");
+        fs::write(&base, "imports = [\"lib.toml\"]\n").unwrap();
+
+        let config = HookConfig::from_file_cached(&base, &ConfigOverrides::default()).unwrap();
+        assert_eq!(config.hooks.unwrap()["lint"].command.to_string(), "echo v1");
+        HookConfig::from_file_cached(&base, &ConfigOverrides::default()).unwrap();
+        assert_eq!(HookConfig::cached_parse_count(&base), 1);
+
+        // Editing an imported file invalidates the cached parse
+        fs::write(
+            dir.join("lib.toml"),
+            "[hooks.lint]\ncommand = \"echo version2\"\n",
+        )
+        .unwrap();
+        let config = HookConfig::from_file_cached(&base, &ConfigOverrides::default()).unwrap();
+        assert_eq!(
+            config.hooks.unwrap()["lint"].command.to_string(),
+            "echo version2"
+        );
+        assert_eq!(HookConfig::cached_parse_count(&base), 2);
+
+        // So does adding a fragment
+        fs::create_dir_all(dir.join(FRAGMENTS_DIR)).unwrap();
+        fs::write(
+            dir.join(FRAGMENTS_DIR).join("test.toml"),
+            "[hooks.test]\ncommand = \"echo test\"\n",
+        )
+        .unwrap();
+        let config = HookConfig::from_file_cached(&base, &ConfigOverrides::default()).unwrap();
+        assert!(config.hooks.unwrap().contains_key("test"));
+        assert_eq!(HookConfig::cached_parse_count(&base), 3);
+    }
+
     #[test]
     fn test_imports_merge_and_override() {
         use std::fs;
@@ -714,7 +2089,7 @@ includes = ["common", "lint", "test"]
         )
         .unwrap();
 
-        let cfg = HookConfig::from_file(&base).unwrap();
+        let cfg = HookConfig::from_file(&base, &ConfigOverrides::default()).unwrap();
         let names = cfg.get_hook_names();
         assert!(names.contains(&"lint".to_string()));
         assert!(names.contains(&"test".to_string()));
@@ -725,7 +2100,57 @@ includes = ["common", "lint", "test"]
         // local override should win
         match &hooks["lint"].command {
             HookCommand::Shell(s) => assert_eq!(s, "echo local-lint"),
-            HookCommand::Args(_) => panic!("expected shell"),
+            HookCommand::Args(_) | HookCommand::Steps { .. } => panic!("expected shell"),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_hooks_across_imports_reported() {
+        use std::fs;
+        use tempfile::TempDir;
+        let td = TempDir::new().unwrap();
+        let dir = td.path();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        let lib = dir.join("hooks.lib.toml");
+        let base = dir.join("hooks.toml");
+
+        fs::write(
+            &lib,
+            r#"
+[hooks.lint]
+command = "echo lib-lint"
+
+[hooks.format]
+command = "echo format"
+"#,
+        )
+        .unwrap();
+
+        fs::write(
+            &base,
+            r#"
+imports = ["hooks.lib.toml"]
+
+[hooks.lint]
+command = "echo local-lint"
+"#,
+        )
+        .unwrap();
+
+        let (cfg, diag) =
+            HookConfig::from_file_with_trace(&base, &ConfigOverrides::default()).unwrap();
+        let duplicates = diag.duplicate_hooks();
+        assert_eq!(duplicates.len(), 1);
+        let (name, sources) = &duplicates[0];
+        assert_eq!(name, "lint");
+        assert_eq!(sources.len(), 2);
+        assert!(sources[0].ends_with("hooks.lib.toml"));
+        assert!(sources[1].ends_with("hooks.toml"));
+
+        // Local definition still wins per documented precedence
+        match &cfg.hooks.unwrap()["lint"].command {
+            HookCommand::Shell(s) => assert_eq!(s, "echo local-lint"),
+            HookCommand::Args(_) | HookCommand::Steps { .. } => panic!("expected shell"),
         }
     }
 
@@ -755,7 +2180,7 @@ includes = ["common", "lint", "test"]
         )
         .unwrap();
 
-        let cfg = HookConfig::from_file(&a).unwrap();
+        let cfg = HookConfig::from_file(&a, &ConfigOverrides::default()).unwrap();
         let names = cfg.get_hook_names();
         assert!(names.contains(&"a".to_string()));
         assert!(names.contains(&"b".to_string()));
@@ -770,7 +2195,7 @@ includes = ["common", "lint", "test"]
         std::fs::create_dir_all(dir.join(".git")).unwrap();
         let base = dir.join("hooks.toml");
         fs::write(&base, "imports = [\"/etc/passwd\"]\n").unwrap();
-        let err = HookConfig::from_file(&base).unwrap_err();
+        let err = HookConfig::from_file(&base, &ConfigOverrides::default()).unwrap_err();
         // Now we expect it to be rejected because it's not in home directory/allowlist
         assert!(format!("{err:#}").contains("Absolute import path not allowed"));
     }
@@ -789,7 +2214,7 @@ includes = ["common", "lint", "test"]
         // hooks.toml at repo root trying to import ../evil.toml
         let base = outer_dir.join("repo/hooks.toml");
         fs::write(&base, "imports = [\"../evil.toml\"]\n").unwrap();
-        let err = HookConfig::from_file(&base).unwrap_err();
+        let err = HookConfig::from_file(&base, &ConfigOverrides::default()).unwrap_err();
         assert!(format!("{err:#}").contains("outside repository root"));
     }
 
@@ -938,6 +2363,57 @@ workdir = "{REPO_ROOT}/target"
         assert_eq!(test_hook.workdir, Some("{REPO_ROOT}/target".to_string()));
     }
 
+    #[test]
+    fn test_group_depends_on_expands_to_the_groups_hooks() {
+        let config = HookConfig::parse(
+            r#"
+[hooks.compile]
+command = "cargo build"
+
+[hooks.bundle]
+command = "npm run build"
+
+[hooks.unit]
+command = "cargo test"
+
+[hooks.smoke]
+command = "./smoke.sh"
+depends_on = ["checks"]
+
+[groups.build]
+includes = ["compile", "bundle"]
+
+[groups.checks]
+includes = ["unit"]
+
+[groups.test]
+includes = ["checks"]
+depends_on = ["build"]
+"#,
+        )
+        .unwrap();
+
+        let mut unit = config.effective_depends_on("unit").unwrap();
+        unit.sort();
+        assert_eq!(unit, ["bundle", "compile"]);
+        assert_eq!(
+            config.effective_depends_on("smoke"),
+            Some(vec!["unit".to_string()])
+        );
+        assert_eq!(config.effective_depends_on("compile"), None);
+        assert_eq!(config.dependency_cycle(), None);
+
+        let mut cyclic = config;
+        cyclic
+            .groups
+            .as_mut()
+            .unwrap()
+            .get_mut("build")
+            .unwrap()
+            .depends_on = Some(vec!["test".to_string()]);
+        assert!(cyclic.dependency_cycle().is_some());
+    }
+
     #[test]
     fn test_validation_conflicting_files_and_run_always() {
         let toml = r#"
@@ -1135,7 +2611,7 @@ command = "echo test"
 
         fs::write(&hooks_file, toml_content).unwrap();
 
-        let err = HookConfig::from_file(&hooks_file).unwrap_err();
+        let err = HookConfig::from_file(&hooks_file, &ConfigOverrides::default()).unwrap_err();
         assert!(err.to_string().contains("Absolute import path not allowed"));
         assert!(
             err.to_string()
@@ -1177,7 +2653,7 @@ command = "echo local"
 
             // This test will likely fail unless the file actually exists
             // which is expected - it's testing the path validation logic
-            let result = HookConfig::from_file(&hooks_file);
+            let result = HookConfig::from_file(&hooks_file, &ConfigOverrides::default());
 
             // Should get a file not found error, not a path validation error
             if let Err(e) = result {
@@ -1240,7 +2716,8 @@ command = "echo test"
                 fs::write(&hooks_file, toml_content).unwrap();
 
                 // This should be rejected because the symlink resolves outside allowlist
-                let err = HookConfig::from_file(&hooks_file).unwrap_err();
+                let err =
+                    HookConfig::from_file(&hooks_file, &ConfigOverrides::default()).unwrap_err();
 
                 // The symlink test might fail differently depending on environment
                 // Check for either expected error message
@@ -1289,7 +2766,7 @@ command = "echo local"
         .unwrap();
 
         // This should still work (relative imports unchanged)
-        let config = HookConfig::from_file(&hooks_file).unwrap();
+        let config = HookConfig::from_file(&hooks_file, &ConfigOverrides::default()).unwrap();
         let hook_names = config.get_hook_names();
 
         assert!(hook_names.contains(&"shared".to_string()));
@@ -1355,7 +2832,7 @@ placeholder = true
         .unwrap();
 
         // Validation happens during from_file, so expect error there
-        let err = HookConfig::from_file(&config_file).unwrap_err();
+        let err = HookConfig::from_file(&config_file, &ConfigOverrides::default()).unwrap_err();
 
         assert!(err.to_string().contains("placeholder = true"));
         assert!(err.to_string().contains("non-empty 'includes'"));
@@ -1419,6 +2896,114 @@ run_always = true
         assert!(err.to_string().contains("contradictory"));
     }
 
+    #[test]
+    fn test_validation_of_autofix_settings() {
+        let valid = r#"
+[hooks.fmt]
+command = "cargo fmt"
+modifies_repository = true
+autofix = true
+autofix_check = "fmt-check"
+
+[hooks.fmt-check]
+command = "cargo fmt --check"
+"#;
+        let config = HookConfig::parse(valid).unwrap();
+        let hook = &config.hooks.as_ref().unwrap()["fmt"];
+        assert!(hook.autofix);
+        assert_eq!(hook.autofix_check.as_deref(), Some("fmt-check"));
+
+        let err =
+            HookConfig::parse("[hooks.fmt]\ncommand = \"fmt\"\nautofix = true\n").unwrap_err();
+        assert!(err.to_string().contains("modifies_repository"));
+
+        let err = HookConfig::parse(
+            "[hooks.fmt]\ncommand = \"fmt\"\nmodifies_repository = true\nautofix = \
+             true\nautofix_check = \"missing\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'missing'"));
+
+        let err = HookConfig::parse(
+            "[hooks.fmt]\ncommand = \"fmt\"\nautofix_check = \"check\"\n[hooks.check]\ncommand = \
+             \"check\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("without 'autofix = true'"));
+    }
+
+    #[test]
+    fn test_validation_of_warning_threshold() {
+        let config = HookConfig::parse(
+            "[hooks.lint]\ncommand = \"lint\"\nmax_warnings = 3\nwarning_pattern = \"^W\\\\d+\"\n",
+        )
+        .unwrap();
+        let hook = &config.hooks.as_ref().unwrap()["lint"];
+        assert_eq!(hook.max_warnings, Some(3));
+        assert_eq!(hook.warning_pattern.as_deref(), Some(r"^W\d+"));
+
+        let err = HookConfig::parse("[hooks.lint]\ncommand = \"lint\"\nwarning_pattern = \"W\"\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("without 'max_warnings'"));
+
+        let err = HookConfig::parse(
+            "[hooks.lint]\ncommand = \"lint\"\nmax_warnings = 0\nwarning_pattern = \"(\"\n",
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("invalid warning pattern"));
+    }
+
+    #[test]
+    fn test_validation_of_output_filters() {
+        let config = HookConfig::parse(
+            "[hooks.lint]\ncommand = \"lint\"\noutput_filter = \"^E\"\noutput_exclude = \"E999\"\n",
+        )
+        .unwrap();
+        let hook = &config.hooks.as_ref().unwrap()["lint"];
+        assert_eq!(hook.output_filter.as_deref(), Some("^E"));
+        assert_eq!(hook.output_exclude.as_deref(), Some("E999"));
+
+        let err = HookConfig::parse("[hooks.lint]\ncommand = \"lint\"\noutput_exclude = \"(\"\n")
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("invalid output_exclude"));
+    }
+
+    #[test]
+    fn test_changed_file_count_thresholds() {
+        let toml = r#"
+[hooks.format]
+command = "prettier --write"
+files = ["**/*.js"]
+min_changed_files = 1
+max_changed_files = 2
+"#;
+
+        let config = HookConfig::parse(toml).unwrap();
+        let hook = &config.hooks.as_ref().unwrap()["format"];
+        assert_eq!(hook.min_changed_files, Some(1));
+        assert_eq!(hook.max_changed_files, Some(2));
+        assert!(
+            hook.changed_file_count_skip_reason(0)
+                .unwrap()
+                .contains("min_changed_files")
+        );
+        assert!(hook.changed_file_count_skip_reason(2).is_none());
+        assert!(
+            hook.changed_file_count_skip_reason(3)
+                .unwrap()
+                .contains("max_changed_files")
+        );
+
+        let inverted = r#"
+[hooks.format]
+command = "prettier --write"
+min_changed_files = 3
+max_changed_files = 2
+"#;
+        let err = HookConfig::parse(inverted).unwrap_err();
+        assert!(err.to_string().contains("greater than"));
+    }
+
     #[test]
     fn test_requires_files_with_files_pattern_valid() {
         let toml = r#"
@@ -1439,6 +3024,26 @@ modifies_repository = false
         );
         config.validate().unwrap();
     }
+
+    #[test]
+    fn test_unresolved_includes() {
+        let toml = r#"
+[hooks.lint]
+command = "cargo clippy"
+
+[groups.checks]
+includes = ["lint"]
+
+[groups.pre-commit]
+includes = ["checks", "lnit", "lint"]
+"#;
+
+        let config = HookConfig::parse(toml).unwrap();
+        assert_eq!(
+            config.unresolved_includes(),
+            vec![("pre-commit".to_string(), "lnit".to_string())]
+        );
+    }
 }
 
 impl std::fmt::Display for HookCommand {
@@ -1446,6 +3051,7 @@ impl std::fmt::Display for HookCommand {
         match self {
             Self::Shell(cmd) => write!(f, "{cmd}"),
             Self::Args(args) => write!(f, "{}", args.join(" ")),
+            Self::Steps { steps } => write!(f, "{}", steps.join(" && ")),
         }
     }
 }
@@ -5,14 +5,49 @@
 //! Unlike shell expansion, this system uses a whitelist of allowed variables
 //! and does not expose arbitrary environment variables.
 
-use crate::hooks::resolver::WorktreeContext;
+use crate::{hooks::resolver::WorktreeContext, trace};
 use anyhow::{Context, Result};
 use std::{
     collections::HashMap,
-    io::IsTerminal,
     path::{Path, PathBuf},
 };
 
+/// Files git passed to the hook being run, for templates and hooks that
+/// want them
+#[derive(Debug, Clone, Default)]
+pub struct GitHookInputs {
+    /// Message file git passed to a message hook (`commit-msg` and friends)
+    pub commit_msg_file: Option<PathBuf>,
+    /// File holding the raw stdin git passed to the hook (pre-push ref lines)
+    pub stdin_file: Option<PathBuf>,
+}
+
+impl GitHookInputs {
+    /// Save the stdin git passed to the hook, for hooks that want the raw lines
+    ///
+    /// `content` is written to a temporary file named by `{GIT_STDIN_FILE}` and
+    /// fed to hooks with `stdin_from_git = true`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save_stdin(&mut self, content: &str) -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("peter-hook-git-stdin-{}.txt", std::process::id()));
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        self.stdin_file = Some(path);
+        Ok(())
+    }
+
+    /// Delete the file saved by `save_stdin` once no hook needs it
+    pub fn remove_stdin_file(&self) {
+        if let Some(path) = &self.stdin_file {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
 /// Template resolver for predefined template variables
 ///
 /// This resolver maintains a whitelist of allowed template variables and
@@ -74,6 +109,7 @@ impl TemplateResolver {
         variables.insert("CHANGED_FILES".to_string(), String::new());
         variables.insert("CHANGED_FILES_LIST".to_string(), String::new());
         variables.insert("CHANGED_FILES_FILE".to_string(), String::new());
+        variables.insert("GIT_STDIN_FILE".to_string(), String::new());
 
         Self { variables }
     }
@@ -146,20 +182,48 @@ impl TemplateResolver {
         variables.insert("CHANGED_FILES".to_string(), String::new());
         variables.insert("CHANGED_FILES_LIST".to_string(), String::new());
         variables.insert("CHANGED_FILES_FILE".to_string(), String::new());
+        variables.insert("GIT_STDIN_FILE".to_string(), String::new());
 
         Self { variables }
     }
 
+    /// Add the variables for the files git passed to the hook:
+    /// `{GIT_STDIN_FILE}`, and for a message hook `{COMMIT_MSG_FILE}` (the
+    /// path) and `{COMMIT_MSG}` (its contents, read now)
+    #[must_use]
+    pub fn with_git_inputs(mut self, inputs: &GitHookInputs) -> Self {
+        if let Some(path) = &inputs.stdin_file {
+            self.variables
+                .insert("GIT_STDIN_FILE".to_string(), path.display().to_string());
+        }
+        if let Some(path) = &inputs.commit_msg_file {
+            self.variables
+                .insert("COMMIT_MSG_FILE".to_string(), path.display().to_string());
+            self.variables.insert(
+                "COMMIT_MSG".to_string(),
+                std::fs::read_to_string(path).unwrap_or_default(),
+            );
+        }
+        self
+    }
+
     /// Resolve templates in a string using `{VARIABLE_NAME}` syntax
     ///
+    /// `${VARIABLE_NAME}` is accepted as an equivalent spelling and resolves
+    /// against the same whitelist, so it never reads the process environment.
+    ///
     /// # Errors
     ///
     /// Returns an error if template resolution fails
     pub fn resolve_string(&self, input: &str) -> Result<String> {
         let mut result = input.to_string();
+        let mut search_from = 0;
 
-        // Find all {VAR} patterns and replace them
-        while let Some(start) = result.find('{') {
+        // Find all {VAR} patterns and replace them. Scanning resumes after each
+        // replacement so braces inside values (e.g. a commit message) are not
+        // treated as templates.
+        while let Some(offset) = result[search_from..].find('{') {
+            let start = search_from + offset;
             let end = result[start..].find('}').ok_or_else(|| {
                 anyhow::anyhow!("Unclosed template variable: {}", &result[start..])
             })?;
@@ -170,7 +234,14 @@ impl TemplateResolver {
                 .resolve_variable(var_name)
                 .with_context(|| format!("Failed to resolve template variable: {var_name}"))?;
 
+            // Swallow a leading `$` so `${VAR}` behaves like `{VAR}`
+            let start = if start > search_from && result.as_bytes()[start - 1] == b'$' {
+                start - 1
+            } else {
+                start
+            };
             result.replace_range(start..=end, &replacement);
+            search_from = start + replacement.len();
         }
 
         Ok(result)
@@ -237,7 +308,7 @@ impl TemplateResolver {
             .join("\n");
 
         if crate::debug::is_enabled() {
-            if std::io::stderr().is_terminal() {
+            if crate::debug::decorated() {
                 eprintln!(
                     "\x1b[95m🔍 \x1b[1m\x1b[38;5;213mCHANGED_FILES\x1b[0m \x1b[95mtemplate \
                      variables:\x1b[0m"
@@ -255,14 +326,14 @@ impl TemplateResolver {
                     )
                 );
             } else {
-                eprintln!("[DEBUG] Setting CHANGED_FILES template variables:");
-                eprintln!("[DEBUG]   CHANGED_FILES: '{changed_space}'");
-                eprintln!(
-                    "[DEBUG]   CHANGED_FILES_LIST: '{}'",
+                trace!(level: crate::debug::TRACE_PHASE, "Setting CHANGED_FILES template variables:");
+                trace!(level: crate::debug::TRACE_PHASE, "  CHANGED_FILES: '{changed_space}'");
+                trace!(level: crate::debug::TRACE_PHASE,
+                    "  CHANGED_FILES_LIST: '{}'",
                     changed_list.replace('\n', "\\n")
                 );
-                eprintln!(
-                    "[DEBUG]   CHANGED_FILES_FILE: '{}'",
+                trace!(level: crate::debug::TRACE_PHASE,
+                    "  CHANGED_FILES_FILE: '{}'",
                     changed_files_file_path
                         .map_or_else(|| "(empty)".to_string(), |p| p.display().to_string())
                 );
@@ -354,6 +425,78 @@ mod tests {
         assert!(result.contains("changed.txt"));
     }
 
+    #[test]
+    fn test_git_inputs_templating() {
+        let temp_dir = TempDir::new().expect("failed to create temp dir");
+        let message_file = temp_dir.path().join("COMMIT_EDITMSG");
+        std::fs::write(&message_file, "Fix the parser\n").expect("failed to write message");
+
+        let without = TemplateResolver::new(temp_dir.path(), temp_dir.path());
+        assert_eq!(
+            without
+                .resolve_string("[{GIT_STDIN_FILE}]")
+                .expect("resolve_string"),
+            "[]"
+        );
+        assert!(without.resolve_string("{COMMIT_MSG}").is_err());
+
+        let inputs = GitHookInputs {
+            commit_msg_file: Some(message_file.clone()),
+            stdin_file: Some(temp_dir.path().join("stdin.txt")),
+        };
+        let resolver =
+            TemplateResolver::new(temp_dir.path(), temp_dir.path()).with_git_inputs(&inputs);
+        assert_eq!(
+            resolver
+                .resolve_string("{COMMIT_MSG}")
+                .expect("resolve_string"),
+            "Fix the parser\n"
+        );
+        assert_eq!(
+            resolver
+                .resolve_string("{COMMIT_MSG_FILE}")
+                .expect("resolve_string"),
+            message_file.display().to_string()
+        );
+        assert!(
+            resolver
+                .resolve_string("{GIT_STDIN_FILE}")
+                .expect("resolve_string")
+                .ends_with("stdin.txt")
+        );
+    }
+
+    #[test]
+    fn test_braces_in_values_are_not_expanded() {
+        let temp_dir = TempDir::new().expect("failed to create temp dir");
+        let mut template_resolver = TemplateResolver::new(temp_dir.path(), temp_dir.path());
+        template_resolver.set_changed_files(&[PathBuf::from("docs/{draft}.md")], None);
+
+        let result = template_resolver
+            .resolve_string("lint {CHANGED_FILES} in {PROJECT_NAME}")
+            .expect("resolve_string");
+        assert!(result.starts_with("lint docs/{draft}.md in "));
+    }
+
+    #[test]
+    fn test_dollar_brace_syntax_matches_plain_braces() {
+        let temp_dir = TempDir::new().expect("failed to create temp dir");
+        let resolver = TemplateResolver::new(temp_dir.path(), temp_dir.path());
+
+        let plain = resolver
+            .resolve_string("cd {HOOK_DIR}")
+            .expect("resolve_string");
+        let dollar = resolver
+            .resolve_string("cd ${HOOK_DIR}")
+            .expect("resolve_string");
+        assert_eq!(plain, dollar);
+        assert!(!dollar.contains('$'));
+
+        let result = resolver.resolve_string("echo ${UNKNOWN}");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("UNKNOWN"));
+    }
+
     #[test]
     fn test_command_args_templating() {
         let temp_dir = TempDir::new().expect("failed to create temp dir");
@@ -1,12 +1,24 @@
 //! Global debug state management
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 /// Global debug state
 static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
 
-/// Global trace state
-static TRACE_ENABLED: AtomicBool = AtomicBool::new(false);
+/// Global trace verbosity (0 = off)
+static TRACE_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Trace level for high-level phase markers (`-v`)
+pub const TRACE_PHASE: u8 = 1;
+
+/// Trace level for per-config and per-hook decisions (`-vv`)
+pub const TRACE_DETAIL: u8 = 2;
+
+/// Trace level for per-file mapping (`-vvv`, `--trace`)
+pub const TRACE_FILE: u8 = 3;
+
+/// Whether trace/debug events are emitted as JSON objects
+static JSON_LOG_FORMAT: AtomicBool = AtomicBool::new(false);
 
 /// Enable debug mode
 pub fn enable() {
@@ -18,24 +30,88 @@ pub fn is_enabled() -> bool {
     DEBUG_ENABLED.load(Ordering::Relaxed)
 }
 
-/// Enable trace mode
+/// Enable trace mode at the most verbose level
 pub fn enable_trace() {
-    TRACE_ENABLED.store(true, Ordering::Relaxed);
+    set_trace_level(TRACE_FILE);
+}
+
+/// Raise the trace verbosity to at least `level`
+pub fn set_trace_level(level: u8) {
+    TRACE_LEVEL.fetch_max(level, Ordering::Relaxed);
+}
+
+/// Current trace verbosity (0 when tracing is off)
+pub fn trace_level() -> u8 {
+    TRACE_LEVEL.load(Ordering::Relaxed)
 }
 
-/// Check if trace mode is enabled
+/// Check if trace mode is enabled at any level
+#[must_use]
 pub fn is_trace_enabled() -> bool {
-    TRACE_ENABLED.load(Ordering::Relaxed)
+    trace_level() > 0
+}
+
+/// Check if events at `level` should be traced
+#[must_use]
+pub fn is_trace_enabled_at(level: u8) -> bool {
+    level > 0 && trace_level() >= level
 }
 
-/// Print trace message if trace mode is enabled
+/// Emit trace/debug events as one JSON object per line
+pub fn enable_json_log_format() {
+    JSON_LOG_FORMAT.store(true, Ordering::Relaxed);
+}
+
+/// Check if trace/debug events are emitted as JSON
+pub fn is_json_log_format() -> bool {
+    JSON_LOG_FORMAT.load(Ordering::Relaxed)
+}
+
+/// Whether debug output may be decorated with colors and emoji instead of
+/// going through the log format: stderr takes colors and the log format is
+/// not JSON
+#[must_use]
+pub fn decorated() -> bool {
+    !is_json_log_format() && crate::output::stderr_colors()
+}
+
+/// Format a log event for the active log format
+///
+/// Plain format is `[LEVEL] message`; JSON format is an object with
+/// `level`, `message` and `target` (the emitting module).
+#[must_use]
+pub fn format_event(level: &str, target: &str, message: &str) -> String {
+    if is_json_log_format() {
+        serde_json::json!({
+            "level": level,
+            "message": message,
+            "target": target,
+        })
+        .to_string()
+    } else {
+        format!("[{}] {message}", level.to_uppercase())
+    }
+}
+
+/// Write a log event to stderr in the active log format
+pub fn emit(level: &str, target: &str, message: &str) {
+    eprintln!("{}", format_event(level, target, message));
+}
+
+/// Print trace message if the trace level is high enough
+///
+/// `trace!(level: TRACE_FILE, ...)` sets the verbosity threshold explicitly;
+/// without it, messages are traced at `TRACE_DETAIL`.
 #[macro_export]
 macro_rules! trace {
-    ($($arg:tt)*) => {
-        if $crate::debug::is_trace_enabled() {
-            eprintln!("[TRACE] {}", format!($($arg)*));
+    (level: $level:expr, $($arg:tt)*) => {
+        if $crate::debug::is_trace_enabled_at($level) {
+            $crate::debug::emit("trace", module_path!(), &format!($($arg)*));
         }
     };
+    ($($arg:tt)*) => {
+        $crate::trace!(level: $crate::debug::TRACE_DETAIL, $($arg)*)
+    };
 }
 
 /// Disable debug mode (for testing)
@@ -47,7 +123,13 @@ pub fn disable() {
 /// Disable trace mode (for testing)
 #[cfg(test)]
 pub fn disable_trace() {
-    TRACE_ENABLED.store(false, Ordering::Relaxed);
+    TRACE_LEVEL.store(0, Ordering::Relaxed);
+}
+
+/// Restore the plain log format (for testing)
+#[cfg(test)]
+pub fn disable_json_log_format() {
+    JSON_LOG_FORMAT.store(false, Ordering::Relaxed);
 }
 
 #[cfg(test)]
@@ -107,4 +189,40 @@ mod tests {
         // Clean up
         disable();
     }
+
+    #[test]
+    fn test_format_event_plain_and_json() {
+        disable_json_log_format();
+        assert_eq!(
+            format_event("trace", "peter_hook::hooks", "resolved 3 hooks"),
+            "[TRACE] resolved 3 hooks"
+        );
+
+        enable_json_log_format();
+        let line = format_event("trace", "peter_hook::hooks", "quote \" here");
+        disable_json_log_format();
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "trace");
+        assert_eq!(value["message"], "quote \" here");
+        assert_eq!(value["target"], "peter_hook::hooks");
+    }
+
+    #[test]
+    fn test_trace_level_thresholds() {
+        disable_trace();
+        assert!(!is_trace_enabled());
+        assert!(!is_trace_enabled_at(TRACE_PHASE));
+
+        set_trace_level(TRACE_PHASE);
+        assert!(is_trace_enabled_at(TRACE_PHASE));
+        assert!(!is_trace_enabled_at(TRACE_FILE));
+
+        // Lower levels never reduce verbosity
+        set_trace_level(TRACE_FILE);
+        set_trace_level(TRACE_PHASE);
+        assert!(is_trace_enabled_at(TRACE_FILE));
+
+        disable_trace();
+    }
 }
@@ -1,10 +1,19 @@
 //! Health check and diagnostics module.
 
-use crate::{HookConfig, git::GitRepository, hooks::HookResolver};
+use crate::{
+    HookConfig,
+    config::ConfigOverrides,
+    git::{GitHookInstaller, GitRepository},
+    hooks::HookResolver,
+};
+use anyhow::{Context, Result};
 use workhelix_cli_common::{DoctorCheck, DoctorChecks, RepoInfo};
 
 /// Peter-hook doctor checks implementation.
-pub struct PeterHookDoctor;
+pub struct PeterHookDoctor {
+    /// Config selection from the command line
+    overrides: ConfigOverrides,
+}
 
 impl DoctorChecks for PeterHookDoctor {
     fn repo_info() -> RepoInfo {
@@ -22,7 +31,7 @@ impl DoctorChecks for PeterHookDoctor {
         checks.extend(check_git_repository());
 
         // Configuration checks
-        checks.extend(check_configuration());
+        checks.extend(check_configuration(&self.overrides));
 
         checks
     }
@@ -32,11 +41,75 @@ impl DoctorChecks for PeterHookDoctor {
 ///
 /// Returns exit code: 0 if healthy, 1 if issues found.
 #[must_use]
-pub fn run_doctor() -> i32 {
-    let doctor = PeterHookDoctor;
+pub fn run_doctor(overrides: &ConfigOverrides) -> i32 {
+    let doctor = PeterHookDoctor {
+        overrides: overrides.clone(),
+    };
     workhelix_cli_common::run_doctor(&doctor)
 }
 
+/// Check that every git hook `install` would install is installed and
+/// managed by peter-hook, as a hard gate for CI
+///
+/// Prints the expected hooks that are missing, unmanaged or not executable.
+/// Returns exit code: 0 if all are managed, 1 otherwise.
+#[must_use]
+pub fn run_assert_managed(overrides: &ConfigOverrides) -> i32 {
+    match unmanaged_hooks(overrides) {
+        Ok((expected, _)) if expected.is_empty() => {
+            println!("No git hooks are configured, so none can be managed by peter-hook");
+            1
+        }
+        Ok((expected, problems)) if problems.is_empty() => {
+            println!(
+                "All {} expected git hook(s) are managed by peter-hook: {}",
+                expected.len(),
+                expected.join(", ")
+            );
+            0
+        }
+        Ok((_, problems)) => {
+            println!("Expected git hooks not managed by peter-hook:");
+            for (hook, problem) in &problems {
+                println!("  {hook}: {problem}");
+            }
+            println!("Run 'peter-hook install' to install them");
+            1
+        }
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            1
+        }
+    }
+}
+
+/// An expected git hook and why it is not managed by peter-hook
+type UnmanagedHook = (&'static str, &'static str);
+
+/// The git hooks `install` would install, and those of them that are not
+/// installed, not managed by peter-hook or not executable, with why
+///
+/// # Errors
+///
+/// Returns an error if there is no git repository or its hooks or config
+/// cannot be read
+fn unmanaged_hooks(overrides: &ConfigOverrides) -> Result<(Vec<&'static str>, Vec<UnmanagedHook>)> {
+    let installer = GitHookInstaller::new()?.with_overrides(overrides.clone());
+    let expected = installer.expected_hooks()?;
+    let repo = GitRepository::find_from_current_dir().context("Failed to find git repository")?;
+    let mut problems = Vec::new();
+    for &hook in &expected {
+        let problem = match repo.get_hook_info(hook)? {
+            None => "not installed",
+            Some(info) if !info.is_managed => "installed, but not managed by peter-hook",
+            Some(info) if !info.is_executable => "managed by peter-hook, but not executable",
+            Some(_) => continue,
+        };
+        problems.push((hook, problem));
+    }
+    Ok((expected, problems))
+}
+
 fn check_git_repository() -> Vec<DoctorCheck> {
     let mut checks = Vec::new();
 
@@ -92,9 +165,10 @@ fn check_git_repository() -> Vec<DoctorCheck> {
     checks
 }
 
-fn check_configuration() -> Vec<DoctorCheck> {
+fn check_configuration(overrides: &ConfigOverrides) -> Vec<DoctorCheck> {
     let mut checks = Vec::new();
-    let resolver = HookResolver::new(std::env::current_dir().unwrap_or_default());
+    let resolver = HookResolver::new(std::env::current_dir().unwrap_or_default())
+        .with_overrides(overrides.clone());
 
     match resolver.find_config_file() {
         Ok(Some(config_path)) => {
@@ -104,7 +178,7 @@ fn check_configuration() -> Vec<DoctorCheck> {
             )));
 
             // Try to parse it
-            match HookConfig::from_file(&config_path) {
+            match HookConfig::from_file(&config_path, overrides) {
                 Ok(config) => {
                     let hook_names = config.get_hook_names();
                     if hook_names.is_empty() {
@@ -118,6 +192,7 @@ fn check_configuration() -> Vec<DoctorCheck> {
                             hook_names.len()
                         )));
                     }
+                    checks.extend(check_container_runtime(&config));
                 }
                 Err(e) => {
                     checks.push(DoctorCheck::fail(
@@ -140,3 +215,41 @@ fn check_configuration() -> Vec<DoctorCheck> {
 
     checks
 }
+
+/// Check that docker is available when any hook runs in a `container`
+fn check_container_runtime(config: &HookConfig) -> Option<DoctorCheck> {
+    let mut container_hooks: Vec<&str> = config
+        .hooks
+        .iter()
+        .flatten()
+        .filter(|(_, hook)| hook.container.is_some())
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if container_hooks.is_empty() {
+        return None;
+    }
+    container_hooks.sort_unstable();
+
+    let version = std::process::Command::new("docker")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    Some(version.map_or_else(
+        || {
+            DoctorCheck::fail(
+                "Container runtime",
+                format!(
+                    "docker not found, but these hooks run in a container: {}",
+                    container_hooks.join(", ")
+                ),
+            )
+        },
+        |output| {
+            DoctorCheck::pass(format!(
+                "Container runtime: {}",
+                String::from_utf8_lossy(&output.stdout).trim()
+            ))
+        },
+    ))
+}
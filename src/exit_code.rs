@@ -0,0 +1,30 @@
+//! Exit codes of the `peter-hook` binary
+//!
+//! `run`, `run-all` and `validate` use these so scripts can tell a failing
+//! hook from a timeout or a broken configuration.
+
+use crate::{config::ConfigError, hooks::HookTimeout};
+
+/// Every hook passed, or there was nothing to run
+pub const SUCCESS: i32 = 0;
+/// A hook failed, or an error not covered by another code occurred
+pub const FAILURE: i32 = 1;
+/// A hooks.toml could not be read, parsed or validated
+pub const CONFIG_ERROR: i32 = 2;
+/// No hooks.toml was found
+pub const NO_CONFIG: i32 = 3;
+/// A hook was killed for exceeding its timeout (the exit code of coreutils
+/// `timeout`)
+pub const TIMEOUT: i32 = 124;
+
+/// Exit code for an error that ended the command
+#[must_use]
+pub fn for_error(error: &anyhow::Error) -> i32 {
+    if error.downcast_ref::<HookTimeout>().is_some() {
+        TIMEOUT
+    } else if error.downcast_ref::<ConfigError>().is_some() {
+        CONFIG_ERROR
+    } else {
+        FAILURE
+    }
+}
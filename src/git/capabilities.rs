@@ -12,7 +12,9 @@
 /// Hooks that can provide files:
 /// - pre-commit: staged files
 /// - pre-push: files changed between local and remote branches
-/// - post-commit, post-merge, post-checkout: files in the recent commit(s)
+/// - post-commit: files in the recent commit
+/// - post-merge: files brought in by the merge (`ORIG_HEAD` to `HEAD`)
+/// - post-checkout: files that differ between the previous and new HEAD
 /// - Working directory hooks: all changed files
 ///
 /// Hooks that cannot provide files:
@@ -44,6 +46,18 @@ pub fn can_provide_files(hook_type: &str) -> bool {
     )
 }
 
+/// Determine if a git hook type receives a message file as its first argument
+///
+/// Git passes the path of the commit (or patch) message to these hooks, which
+/// is exposed to templates as `{COMMIT_MSG_FILE}` and `{COMMIT_MSG}`.
+#[must_use]
+pub fn is_message_hook(hook_type: &str) -> bool {
+    matches!(
+        hook_type,
+        "commit-msg" | "prepare-commit-msg" | "applypatch-msg"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,6 +102,15 @@ mod tests {
         assert!(can_provide_files("post-applypatch"));
     }
 
+    #[test]
+    fn test_is_message_hook() {
+        assert!(is_message_hook("commit-msg"));
+        assert!(is_message_hook("prepare-commit-msg"));
+        assert!(is_message_hook("applypatch-msg"));
+        assert!(!is_message_hook("pre-commit"));
+        assert!(!is_message_hook("pre-push"));
+    }
+
     #[test]
     fn test_cannot_provide_files_for_unknown_hook() {
         assert!(!can_provide_files("unknown-hook"));
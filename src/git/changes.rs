@@ -1,5 +1,6 @@
 //! Git change detection utilities
 
+use crate::config::RenameDetection;
 use anyhow::{Context, Result};
 use std::{
     collections::HashSet,
@@ -11,6 +12,10 @@ use std::{
 pub struct GitChangeDetector {
     /// Git repository root
     repo_root: PathBuf,
+    /// Rename/copy detection for diffs (git's default if `None`)
+    rename_detection: Option<RenameDetection>,
+    /// Whether working-directory changes include untracked files
+    include_untracked: bool,
 }
 
 /// Types of git changes to detect
@@ -27,6 +32,9 @@ pub enum ChangeDetectionMode {
         /// Remote commit OID
         remote_oid: String,
     },
+    /// Files changed by the commit at HEAD (for post-commit), diffed against
+    /// the empty tree when HEAD is the first commit
+    LastCommit,
     /// Changes in a specific commit range
     CommitRange {
         /// Start commit (exclusive)
@@ -34,6 +42,65 @@ pub enum ChangeDetectionMode {
         /// End commit (inclusive)
         to: String,
     },
+    /// Every tracked file, changed or not (for `--all-files`)
+    AllTracked,
+    /// Files that differ between the previous and new HEAD of a branch
+    /// checkout (for post-checkout)
+    Checkout {
+        /// HEAD before the checkout
+        previous_oid: String,
+        /// HEAD after the checkout
+        new_oid: String,
+    },
+    /// Files brought in by a merge (for post-merge): `ORIG_HEAD` to `HEAD`,
+    /// or the staged result of a squash merge, which does not commit
+    Merge {
+        /// Whether git reported a squash merge
+        squash: bool,
+    },
+    /// Files that differ between two trees (for `run --incremental`)
+    TreeDiff {
+        /// Tree recorded by the last successful run
+        from: String,
+        /// Tree of the current state
+        to: String,
+    },
+    /// Repo-relative files listed by the caller (for `run --files-from`),
+    /// used as-is without asking git
+    Files(Vec<PathBuf>),
+}
+
+impl std::fmt::Display for ChangeDetectionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// Abbreviated object id, as git shows them
+        fn short(oid: &str) -> &str {
+            oid.get(..7).unwrap_or(oid)
+        }
+
+        match self {
+            Self::WorkingDirectory => {
+                write!(f, "working directory (staged, unstaged and untracked)")
+            }
+            Self::Staged => write!(f, "staged changes"),
+            Self::Push {
+                local_oid,
+                remote_oid,
+            } => write!(f, "push ({}..{})", short(remote_oid), short(local_oid)),
+            Self::LastCommit => write!(f, "last commit (HEAD)"),
+            Self::CommitRange { from, to } => write!(f, "commit range ({from}..{to})"),
+            Self::AllTracked => write!(f, "all tracked files (--all-files)"),
+            Self::Checkout {
+                previous_oid,
+                new_oid,
+            } => write!(f, "checkout ({}..{})", short(previous_oid), short(new_oid)),
+            Self::Merge { squash: false } => write!(f, "merge (ORIG_HEAD..HEAD)"),
+            Self::Merge { squash: true } => write!(f, "squash merge (staged changes)"),
+            Self::TreeDiff { from, to } => {
+                write!(f, "changes since last run ({}..{})", short(from), short(to))
+            }
+            Self::Files(_) => write!(f, "files listed by the caller"),
+        }
+    }
 }
 
 impl GitChangeDetector {
@@ -45,15 +112,43 @@ impl GitChangeDetector {
     pub fn new<P: AsRef<Path>>(repo_root: P) -> Result<Self> {
         let repo_root = repo_root.as_ref().to_path_buf();
 
-        // Verify this is a git repository
-        if !repo_root.join(".git").exists() {
+        // Verify this is a git work tree. Ask git rather than looking for a
+        // `.git` directory, which is a file in worktrees and submodules.
+        let is_work_tree = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .current_dir(&repo_root)
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !is_work_tree {
             return Err(anyhow::anyhow!(
                 "Not a git repository: {}",
                 repo_root.display()
             ));
         }
 
-        Ok(Self { repo_root })
+        Ok(Self {
+            repo_root,
+            rename_detection: None,
+            include_untracked: true,
+        })
+    }
+
+    /// Use the given rename/copy detection setting for diff commands
+    #[must_use]
+    pub const fn with_rename_detection(
+        mut self,
+        rename_detection: Option<RenameDetection>,
+    ) -> Self {
+        self.rename_detection = rename_detection;
+        self
+    }
+
+    /// Include (the default) or leave out untracked files in
+    /// working-directory changes
+    #[must_use]
+    pub const fn with_untracked(mut self, include_untracked: bool) -> Self {
+        self.include_untracked = include_untracked;
+        self
     }
 
     /// Get changed files based on the detection mode
@@ -64,14 +159,27 @@ impl GitChangeDetector {
     pub fn get_changed_files(&self, mode: &ChangeDetectionMode) -> Result<Vec<PathBuf>> {
         match mode {
             ChangeDetectionMode::WorkingDirectory => self.get_working_directory_changes(),
-            ChangeDetectionMode::Staged => self.get_staged_changes(),
+            ChangeDetectionMode::Staged | ChangeDetectionMode::Merge { squash: true } => {
+                self.get_staged_changes()
+            }
             ChangeDetectionMode::Push {
                 local_oid,
                 remote_oid,
             } => self.get_push_changes(remote_oid, local_oid),
+            ChangeDetectionMode::LastCommit => self.get_last_commit_changes(),
             ChangeDetectionMode::CommitRange { from, to } => {
                 self.get_commit_range_changes(from, to)
             }
+            ChangeDetectionMode::AllTracked => self.get_tracked_files(),
+            ChangeDetectionMode::Checkout {
+                previous_oid,
+                new_oid,
+            } => self.get_push_changes(previous_oid, new_oid),
+            ChangeDetectionMode::Merge { squash: false } => {
+                self.get_push_changes("ORIG_HEAD", "HEAD")
+            }
+            ChangeDetectionMode::TreeDiff { from, to } => self.get_push_changes(from, to),
+            ChangeDetectionMode::Files(files) => Ok(files.clone()),
         }
     }
 
@@ -79,128 +187,86 @@ impl GitChangeDetector {
     fn get_working_directory_changes(&self) -> Result<Vec<PathBuf>> {
         let mut changed_files = HashSet::new();
 
-        // Get staged changes (exclude deleted files)
-        let staged_output = self.run_git_command(&["diff", "--cached", "--name-status"])?;
-        for line in staged_output.lines() {
-            if let Some((status, rest)) = line.split_once('\t') {
-                if !status.starts_with('D') {
-                    // Skip deleted files
-                    // Handle renames (R) and copies (C): format is "status\told_name\tnew_name"
-                    // For renames/copies, we want the destination (new) file
-                    let filename = if status.starts_with('R') || status.starts_with('C') {
-                        // Split on tab to get old and new filenames, use the new one
-                        rest.split('\t').nth(1).unwrap_or(rest)
-                    } else {
-                        rest
-                    };
-                    changed_files.insert(PathBuf::from(filename));
-                }
-            }
-        }
-
-        // Get unstaged changes (exclude deleted files)
-        let unstaged_output = self.run_git_command(&["diff", "--name-status"])?;
-        for line in unstaged_output.lines() {
-            if let Some((status, rest)) = line.split_once('\t') {
-                if !status.starts_with('D') {
-                    // Skip deleted files
-                    // Handle renames (R) and copies (C): format is "status\told_name\tnew_name"
-                    let filename = if status.starts_with('R') || status.starts_with('C') {
-                        rest.split('\t').nth(1).unwrap_or(rest)
-                    } else {
-                        rest
-                    };
-                    changed_files.insert(PathBuf::from(filename));
-                }
-            }
-        }
+        // Staged and unstaged changes (deleted files excluded)
+        changed_files.extend(self.run_name_status(&["--cached"])?);
+        changed_files.extend(self.run_name_status(&[])?);
 
         // Get untracked files (these are always additions, never deletions)
-        let untracked_output =
-            self.run_git_command(&["ls-files", "--others", "--exclude-standard"])?;
-        for line in untracked_output.lines() {
-            if !line.trim().is_empty() {
-                changed_files.insert(PathBuf::from(line.trim()));
-            }
+        if self.include_untracked {
+            let untracked_output =
+                self.run_git_command(&["ls-files", "-z", "--others", "--exclude-standard"])?;
+            changed_files.extend(parse_nul_paths(&untracked_output)?);
         }
 
         Ok(changed_files.into_iter().collect())
     }
 
+    /// Get every tracked file that still exists in the working tree
+    fn get_tracked_files(&self) -> Result<Vec<PathBuf>> {
+        let output = self.run_git_command(&["ls-files", "-z", "--cached"])?;
+        Ok(parse_nul_paths(&output)?
+            .into_iter()
+            .filter(|file| self.repo_root.join(file).is_file())
+            .collect())
+    }
+
     /// Get only staged changes (for pre-commit hooks)
     fn get_staged_changes(&self) -> Result<Vec<PathBuf>> {
-        // Get only staged changes using git diff --cached (exclude deleted files)
-        let staged_output = self.run_git_command(&["diff", "--cached", "--name-status"])?;
-
-        let mut changed_files = Vec::new();
-        for line in staged_output.lines() {
-            if let Some((status, rest)) = line.split_once('\t') {
-                if !status.starts_with('D') {
-                    // Skip deleted files
-                    // Handle renames (R) and copies (C): format is "status\told_name\tnew_name"
-                    let filename = if status.starts_with('R') || status.starts_with('C') {
-                        rest.split('\t').nth(1).unwrap_or(rest)
-                    } else {
-                        rest
-                    };
-                    changed_files.push(PathBuf::from(filename));
-                }
-            }
-        }
-
-        Ok(changed_files)
+        self.run_name_status(&["--cached"])
     }
 
     /// Get files changed in push (compare local OID with remote OID)
     fn get_push_changes(&self, remote_oid: &str, local_oid: &str) -> Result<Vec<PathBuf>> {
-        let diff_output =
-            self.run_git_command(&["diff", "--name-status", remote_oid, local_oid])?;
-
-        let mut changed_files = Vec::new();
-        for line in diff_output.lines() {
-            if let Some((status, rest)) = line.split_once('\t') {
-                if !status.starts_with('D') {
-                    // Skip deleted files
-                    // Handle renames (R) and copies (C): format is "status\told_name\tnew_name"
-                    let filename = if status.starts_with('R') || status.starts_with('C') {
-                        rest.split('\t').nth(1).unwrap_or(rest)
-                    } else {
-                        rest
-                    };
-                    changed_files.push(PathBuf::from(filename));
-                }
-            }
-        }
+        self.run_name_status(&[remote_oid, local_oid])
+    }
 
-        Ok(changed_files)
+    /// Get files changed by the commit at HEAD
+    ///
+    /// The first commit has no parent, so it is compared with the empty tree.
+    fn get_last_commit_changes(&self) -> Result<Vec<PathBuf>> {
+        let parent = if self
+            .run_git_command(&["rev-parse", "--verify", "--quiet", "HEAD^"])
+            .is_ok()
+        {
+            "HEAD^"
+        } else {
+            ObjectFormat::detect(&self.repo_root).empty_tree_oid()
+        };
+        self.get_push_changes(parent, "HEAD")
     }
 
     /// Get files changed in a commit range
+    ///
+    /// Uses `from..to`; for two unrelated points (a push or a checkout) use
+    /// `get_push_changes`, which diffs the two trees directly.
     fn get_commit_range_changes(&self, from: &str, to: &str) -> Result<Vec<PathBuf>> {
         let range = format!("{from}..{to}");
-        let diff_output = self.run_git_command(&["diff", "--name-status", &range])?;
-
-        let mut changed_files = Vec::new();
-        for line in diff_output.lines() {
-            if let Some((status, rest)) = line.split_once('\t') {
-                if !status.starts_with('D') {
-                    // Skip deleted files
-                    // Handle renames (R) and copies (C): format is "status\told_name\tnew_name"
-                    let filename = if status.starts_with('R') || status.starts_with('C') {
-                        rest.split('\t').nth(1).unwrap_or(rest)
-                    } else {
-                        rest
-                    };
-                    changed_files.push(PathBuf::from(filename));
-                }
-            }
-        }
+        self.run_name_status(&[&range])
+    }
+
+    /// Run `git diff --name-status -z` and return the changed files,
+    /// excluding deletions
+    fn run_name_status(&self, args: &[&str]) -> Result<Vec<PathBuf>> {
+        let mut diff_args = vec!["--name-status", "-z"];
+        diff_args.extend_from_slice(args);
+        parse_name_status(&self.run_diff_command(&diff_args)?)
+    }
 
-        Ok(changed_files)
+    /// Run `git diff` with the configured rename detection and return stdout
+    fn run_diff_command(&self, args: &[&str]) -> Result<Vec<u8>> {
+        let rename_args = self
+            .rename_detection
+            .map(RenameDetection::git_args)
+            .unwrap_or_default();
+        let mut diff_args = vec!["diff"];
+        diff_args.extend(rename_args.iter().map(String::as_str));
+        diff_args.extend_from_slice(args);
+        self.run_git_command(&diff_args)
     }
 
-    /// Run a git command and return stdout
-    fn run_git_command(&self, args: &[&str]) -> Result<String> {
+    /// Run a git command and return its raw stdout, which may hold paths
+    /// that are not valid UTF-8
+    fn run_git_command(&self, args: &[&str]) -> Result<Vec<u8>> {
         let output = Command::new("git")
             .args(args)
             .current_dir(&self.repo_root)
@@ -216,10 +282,146 @@ impl GitChangeDetector {
             ));
         }
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        Ok(output.stdout)
     }
 }
 
+/// Parse `git diff --name-status -z` output into the changed files
+///
+/// Records are NUL-separated: a status, then one path, or the source and
+/// destination paths for renames (`R`) and copies (`C`), of which the
+/// destination is kept. Deleted files are skipped.
+///
+/// # Errors
+///
+/// Returns an error if a path cannot be represented on this platform
+fn parse_name_status(output: &[u8]) -> Result<Vec<PathBuf>> {
+    let mut fields = output.split(|&b| b == 0).filter(|field| !field.is_empty());
+    let mut changed_files = Vec::new();
+    while let Some(status) = fields.next() {
+        if matches!(status.first(), Some(b'R' | b'C')) {
+            // Skip the source path
+            fields.next();
+        }
+        let Some(path) = fields.next() else {
+            anyhow::bail!(
+                "Truncated git diff output after status '{}'",
+                String::from_utf8_lossy(status)
+            );
+        };
+        if status.first() != Some(&b'D') {
+            changed_files.push(path_from_bytes(path)?);
+        }
+    }
+    Ok(changed_files)
+}
+
+/// Parse NUL-separated paths, as printed by `git ls-files -z`
+///
+/// # Errors
+///
+/// Returns an error if a path cannot be represented on this platform
+pub(crate) fn parse_nul_paths(output: &[u8]) -> Result<Vec<PathBuf>> {
+    output
+        .split(|&b| b == 0)
+        .filter(|path| !path.is_empty())
+        .map(path_from_bytes)
+        .collect()
+}
+
+/// Convert a path git printed into a `PathBuf`, keeping its bytes intact
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn path_from_bytes(bytes: &[u8]) -> Result<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(PathBuf::from(std::ffi::OsStr::from_bytes(bytes)))
+}
+
+/// Convert a path git printed into a `PathBuf`
+///
+/// # Errors
+///
+/// Returns an error if the path is not valid UTF-8, which cannot be
+/// represented faithfully here
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> Result<PathBuf> {
+    std::str::from_utf8(bytes).map(PathBuf::from).map_err(|_| {
+        anyhow::anyhow!(
+            "Git reported a path that is not valid UTF-8: {}",
+            String::from_utf8_lossy(bytes)
+        )
+    })
+}
+
+/// Hash function a repository names its objects with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectFormat {
+    /// SHA-1, 40 hex digits (git's default)
+    #[default]
+    Sha1,
+    /// SHA-256, 64 hex digits (`git init --object-format=sha256`)
+    Sha256,
+}
+
+impl ObjectFormat {
+    /// Ask git for the object format of the repository containing `dir`
+    ///
+    /// Falls back to SHA-1 when git cannot tell (e.g. a git too old to
+    /// know `--show-object-format`, which only supports SHA-1).
+    #[must_use]
+    pub fn detect(dir: &Path) -> Self {
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-object-format"])
+            .current_dir(dir)
+            .output();
+        match output {
+            Ok(output) if output.status.success() && output.stdout.trim_ascii() == b"sha256" => {
+                Self::Sha256
+            }
+            _ => Self::Sha1,
+        }
+    }
+
+    /// Hash of the empty tree (a well-known constant per format), the base
+    /// for diffs that have no earlier commit
+    #[must_use]
+    pub const fn empty_tree_oid(self) -> &'static str {
+        match self {
+            Self::Sha1 => "4b825dc642cb6eb9a060e54bf8d69288fbee4904",
+            Self::Sha256 => "6ef19b41225c5369f1c104d45d8d85efa9b057b53b14b4b9b939dd74decc5321",
+        }
+    }
+
+    /// Number of hex digits in an object ID
+    #[must_use]
+    pub const fn hex_len(self) -> usize {
+        match self {
+            Self::Sha1 => 40,
+            Self::Sha256 => 64,
+        }
+    }
+}
+
+/// The most recent tag reachable from HEAD (`git describe --tags --abbrev=0`)
+///
+/// # Errors
+///
+/// Returns an error if git fails or no tag is reachable from HEAD
+pub fn latest_tag(dir: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git describe")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "No tag is reachable from HEAD, so there is no release to diff against: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 /// Parse pre-push hook stdin to extract commit OIDs
 ///
 /// Git's pre-push hook receives on stdin lines in the format:
@@ -241,11 +443,12 @@ impl GitChangeDetector {
 ///
 /// # Errors
 /// Returns an error if the stdin format is invalid or cannot be parsed
-/// Validate that a string is a valid git OID (SHA-1 hash)
+/// Validate that a string is a valid git OID in the repository's object format
 ///
-/// A valid OID must be exactly 40 hexadecimal characters (0-9, a-f, A-F)
-fn is_valid_oid(oid: &str) -> bool {
-    oid.len() == 40 && oid.chars().all(|c| c.is_ascii_hexdigit())
+/// A valid OID must be exactly `format.hex_len()` hexadecimal characters
+/// (0-9, a-f, A-F): 40 for SHA-1, 64 for SHA-256
+fn is_valid_oid(oid: &str, format: ObjectFormat) -> bool {
+    oid.len() == format.hex_len() && oid.chars().all(|c| c.is_ascii_hexdigit())
 }
 
 /// Parse the stdin content from a git pre-push hook
@@ -255,6 +458,8 @@ fn is_valid_oid(oid: &str) -> bool {
 ///
 /// # Arguments
 /// * `stdin_content` - The content from stdin
+/// * `format` - Object format of the repository, which decides the OID length
+///   and the empty tree used for new branches
 ///
 /// # Returns
 /// A tuple of (`local_oid`, `remote_oid`) on success
@@ -262,7 +467,7 @@ fn is_valid_oid(oid: &str) -> bool {
 /// # Errors
 /// Returns an error if the stdin format is invalid, cannot be parsed, or OIDs
 /// are malformed
-pub fn parse_push_stdin(stdin_content: &str) -> Result<(String, String)> {
+pub fn parse_push_stdin(stdin_content: &str, format: ObjectFormat) -> Result<(String, String)> {
     let line = stdin_content
         .lines()
         .next()
@@ -280,25 +485,26 @@ pub fn parse_push_stdin(stdin_content: &str) -> Result<(String, String)> {
     let remote_oid = parts[3];
 
     // Validate OID format
-    if !is_valid_oid(local_oid) {
+    if !is_valid_oid(local_oid, format) {
         return Err(anyhow::anyhow!(
-            "Invalid local OID format: '{local_oid}'. Expected 40-character hex string"
+            "Invalid local OID format: '{local_oid}'. Expected {}-character hex string",
+            format.hex_len()
         ));
     }
 
     // Remote OID can be all zeros (new branch) or a valid OID
     let is_new_branch = remote_oid.chars().all(|c| c == '0');
-    if !is_new_branch && !is_valid_oid(remote_oid) {
+    if !is_new_branch && !is_valid_oid(remote_oid, format) {
         return Err(anyhow::anyhow!(
-            "Invalid remote OID format: '{remote_oid}'. Expected 40-character hex string"
+            "Invalid remote OID format: '{remote_oid}'. Expected {}-character hex string",
+            format.hex_len()
         ));
     }
 
     // If remote OID is all zeros, the remote branch doesn't exist (new branch)
     // Use the empty tree hash as the base for comparison
     let remote_oid = if is_new_branch {
-        // Git empty tree hash (this is a well-known constant)
-        "4b825dc642cb6eb9a060e54bf8d69288fbee4904".to_string()
+        format.empty_tree_oid().to_string()
     } else {
         remote_oid.to_string()
     };
@@ -306,12 +512,84 @@ pub fn parse_push_stdin(stdin_content: &str) -> Result<(String, String)> {
     Ok((local_oid.to_string(), remote_oid))
 }
 
-/// File pattern matcher using glob patterns
+/// Parse the arguments git passes to a post-checkout hook
+///
+/// Git passes `<previous HEAD> <new HEAD> <flag>`, where the flag is `1` for
+/// a branch checkout and `0` for a file checkout (`git checkout -- path`).
+/// File checkouts do not move HEAD, so they return `None`. A previous HEAD
+/// of all zeros (the checkout of a fresh clone) is replaced by the empty tree
+/// of `format`.
+///
+/// # Returns
+/// `Some((previous_oid, new_oid))` for a branch checkout
+///
+/// # Errors
+/// Returns an error if the arguments are missing or malformed
+pub fn parse_checkout_args(
+    args: &[String],
+    format: ObjectFormat,
+) -> Result<Option<(String, String)>> {
+    let [previous_oid, new_oid, flag] = args else {
+        return Err(anyhow::anyhow!(
+            "Invalid post-checkout arguments. Expected: <previous HEAD> <new HEAD> <flag>, got: \
+             {args:?}"
+        ));
+    };
+
+    match flag.as_str() {
+        "1" => {}
+        "0" => return Ok(None),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Invalid post-checkout flag: '{other}'. Expected 1 (branch) or 0 (file)"
+            ));
+        }
+    }
+
+    let is_initial = previous_oid.chars().all(|c| c == '0');
+    if !is_initial && !is_valid_oid(previous_oid, format) {
+        return Err(anyhow::anyhow!(
+            "Invalid previous HEAD: '{previous_oid}'. Expected {}-character hex string",
+            format.hex_len()
+        ));
+    }
+    if !is_valid_oid(new_oid, format) {
+        return Err(anyhow::anyhow!(
+            "Invalid new HEAD: '{new_oid}'. Expected {}-character hex string",
+            format.hex_len()
+        ));
+    }
+
+    let previous_oid = if is_initial {
+        format.empty_tree_oid().to_string()
+    } else {
+        previous_oid.clone()
+    };
+
+    Ok(Some((previous_oid, new_oid.clone())))
+}
+
+/// File pattern matcher using glob patterns and optional first-line content
+/// patterns
 pub struct FilePatternMatcher {
     /// Compiled glob patterns
+    ///
+    /// Patterns starting with `/` match the canonicalized absolute path;
+    /// all others match the (scoped) repo-relative path.
     patterns: Vec<glob::Pattern>,
+    /// Glob patterns for files that never match
+    exclude_patterns: Vec<glob::Pattern>,
+    /// Regexes matched against the first line of each file (e.g. shebangs)
+    content_patterns: Vec<regex::Regex>,
+    /// Directory that relative paths are resolved against when reading files
+    base_dir: PathBuf,
+    /// Directory globs are relative to; files outside it never match
+    scope: Option<PathBuf>,
 }
 
+/// Number of leading bytes read when checking a file's first line
+const CONTENT_SNIFF_BYTES: u64 = 512;
+
 impl FilePatternMatcher {
     /// Create a new pattern matcher from glob patterns
     ///
@@ -319,46 +597,254 @@ impl FilePatternMatcher {
     ///
     /// Returns an error if any glob pattern is invalid
     pub fn new(patterns: &[String]) -> Result<Self> {
-        let mut compiled_patterns = Vec::new();
+        Self::with_content_patterns(patterns, &[], Path::new("."))
+    }
 
-        for pattern in patterns {
-            let compiled = glob::Pattern::new(pattern)
-                .with_context(|| format!("Invalid glob pattern: {pattern}"))?;
-            compiled_patterns.push(compiled);
+    /// Create a matcher from glob patterns plus regexes tested against the
+    /// first line of each file
+    ///
+    /// A file matches if it matches any glob or its first line matches any
+    /// content pattern. Relative paths are read from `base_dir`; binary and
+    /// unreadable files never match a content pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any glob pattern or regex is invalid
+    pub fn with_content_patterns(
+        patterns: &[String],
+        content_patterns: &[String],
+        base_dir: &Path,
+    ) -> Result<Self> {
+        let compiled_patterns = compile_globs(patterns)?;
+
+        let mut compiled_content = Vec::new();
+        for pattern in content_patterns {
+            let compiled = regex::Regex::new(pattern)
+                .with_context(|| format!("Invalid content pattern: {pattern}"))?;
+            compiled_content.push(compiled);
         }
 
         Ok(Self {
             patterns: compiled_patterns,
+            exclude_patterns: Vec::new(),
+            content_patterns: compiled_content,
+            base_dir: base_dir.to_path_buf(),
+            scope: None,
         })
     }
 
+    /// Never match files that match any of these glob patterns
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any glob pattern is invalid
+    pub fn with_exclude_patterns(mut self, patterns: &[String]) -> Result<Self> {
+        self.exclude_patterns = compile_globs(patterns)?;
+        Ok(self)
+    }
+
+    /// Match globs against paths relative to `scope` instead of the full path
+    ///
+    /// Files outside `scope` never match. Content patterns still read the
+    /// file at its full path.
+    #[must_use]
+    pub fn with_scope(mut self, scope: Option<&Path>) -> Self {
+        self.scope = scope.map(Path::to_path_buf);
+        self
+    }
+
+    /// Build the matcher for a hook's `files`, `extensions`,
+    /// `content_patterns` and `exclude_files`
+    ///
+    /// Returns `None` when the hook has none of them, meaning every file
+    /// matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any glob pattern or regex is invalid
+    pub fn for_hook(hook: &crate::config::HookDefinition, base_dir: &Path) -> Result<Option<Self>> {
+        let files = hook.file_globs();
+        if files.is_none() && hook.content_patterns.is_none() && hook.exclude_files.is_none() {
+            return Ok(None);
+        }
+
+        Self::with_content_patterns(
+            files.as_deref().unwrap_or_default(),
+            hook.content_patterns.as_deref().unwrap_or_default(),
+            base_dir,
+        )?
+        .with_exclude_patterns(hook.exclude_files.as_deref().unwrap_or_default())
+        .map(Some)
+    }
+
+    /// Whether no patterns of any kind are configured
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+            && self.content_patterns.is_empty()
+            && self.exclude_patterns.is_empty()
+    }
+
     /// Check if any of the patterns match the given file path
     #[must_use]
     pub fn matches(&self, file_path: &Path) -> bool {
-        if self.patterns.is_empty() {
+        if self.is_empty() {
             return true; // No patterns means match everything
         }
 
-        let path_str = file_path.to_string_lossy();
+        if self
+            .scope
+            .as_ref()
+            .is_some_and(|scope| !file_path.starts_with(scope))
+        {
+            return false;
+        }
+
+        let absolute_path = std::cell::OnceCell::new();
+        let absolute = || {
+            absolute_path
+                .get_or_init(|| {
+                    let full_path = self.full_path(file_path);
+                    full_path
+                        .canonicalize()
+                        .unwrap_or(full_path)
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .clone()
+        };
+
+        if self
+            .exclude_patterns
+            .iter()
+            .any(|pattern| self.glob_matches(pattern, file_path, absolute))
+        {
+            return false;
+        }
+
+        if self.patterns.is_empty() && self.content_patterns.is_empty() {
+            return true; // Only exclusions configured
+        }
+
+        let glob_match = self
+            .patterns
+            .iter()
+            .any(|pattern| self.glob_matches(pattern, file_path, absolute));
+
+        glob_match || self.matches_content(file_path)
+    }
+
+    /// Match one glob against a file, using the absolute path for absolute
+    /// patterns and the (scoped) relative path or file name otherwise
+    ///
+    /// A pattern ending in `/` names directories: it matches every file
+    /// anywhere beneath a directory it matches (`docs/` is `docs/**`).
+    fn glob_matches(
+        &self,
+        pattern: &glob::Pattern,
+        file_path: &Path,
+        absolute: impl Fn() -> String,
+    ) -> bool {
+        let is_absolute = pattern.as_str().starts_with('/');
+        let scoped_path = self
+            .scope
+            .as_ref()
+            .and_then(|scope| file_path.strip_prefix(scope).ok())
+            .unwrap_or(file_path);
+
+        if pattern.as_str().ends_with('/') {
+            let path = if is_absolute {
+                PathBuf::from(absolute())
+            } else {
+                scoped_path.to_path_buf()
+            };
+            return path
+                .ancestors()
+                .skip(1)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .any(|dir| pattern.matches(&format!("{}/", dir.display())));
+        }
+        if is_absolute {
+            return pattern.matches(&absolute());
+        }
 
-        self.patterns.iter().any(|pattern| {
-            pattern.matches(&path_str) ||
+        pattern.matches(&scoped_path.to_string_lossy()) ||
             // Also try with just the filename
-            file_path.file_name()
+            scoped_path.file_name()
                 .and_then(|name| name.to_str())
                 .is_some_and(|name| pattern.matches(name))
+    }
+
+    /// Resolve a possibly relative file path against `base_dir`
+    fn full_path(&self, file_path: &Path) -> PathBuf {
+        if file_path.is_absolute() {
+            file_path.to_path_buf()
+        } else {
+            self.base_dir.join(file_path)
+        }
+    }
+
+    /// Check the file's first line against the content patterns
+    fn matches_content(&self, file_path: &Path) -> bool {
+        if self.content_patterns.is_empty() {
+            return false;
+        }
+
+        read_first_line(&self.full_path(file_path)).is_some_and(|line| {
+            self.content_patterns
+                .iter()
+                .any(|pattern| pattern.is_match(&line))
         })
     }
 
     /// Check if any files in the list match the patterns
     #[must_use]
     pub fn matches_any(&self, files: &[PathBuf]) -> bool {
-        if self.patterns.is_empty() {
+        if self.is_empty() {
             return true; // No patterns means always match
         }
 
         files.iter().any(|file| self.matches(file))
     }
+
+    /// Count the files in the list that match the patterns
+    #[must_use]
+    pub fn count_matches(&self, files: &[PathBuf]) -> usize {
+        if self.is_empty() {
+            return files.len();
+        }
+
+        files.iter().filter(|file| self.matches(file)).count()
+    }
+}
+
+/// Compile glob patterns, naming the first invalid one in the error
+fn compile_globs(patterns: &[String]) -> Result<Vec<glob::Pattern>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern).with_context(|| format!("Invalid glob pattern: {pattern}"))
+        })
+        .collect()
+}
+
+/// Read the first line of a text file
+///
+/// Returns `None` for unreadable files, files that look binary (contain a NUL
+/// byte near the start), and first lines that are not valid UTF-8.
+fn read_first_line(path: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut head = Vec::new();
+    file.take(CONTENT_SNIFF_BYTES).read_to_end(&mut head).ok()?;
+
+    if head.contains(&0) {
+        return None;
+    }
+
+    let line = head.split(|&b| b == b'\n').next().unwrap_or_default();
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8(line.to_vec()).ok()
 }
 
 #[cfg(test)]
@@ -410,6 +896,81 @@ mod tests {
         assert_eq!(detector.repo_root, repo_dir);
     }
 
+    #[test]
+    fn test_change_detector_rejects_non_git_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let err = GitChangeDetector::new(temp_dir.path()).err().unwrap();
+        assert!(err.to_string().contains("Not a git repository"));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn test_change_detector_in_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(&temp_dir.path().join("main"));
+        fs::write(repo_dir.join("README.md"), "main").unwrap();
+        git(&repo_dir, &["add", "README.md"]);
+        git(&repo_dir, &["commit", "-m", "initial"]);
+
+        let worktree_dir = temp_dir.path().join("feature");
+        git(
+            &repo_dir,
+            &["worktree", "add", worktree_dir.to_str().unwrap()],
+        );
+        assert!(worktree_dir.join(".git").is_file());
+
+        fs::write(worktree_dir.join("feature.rs"), "fn feature() {}").unwrap();
+        git(&worktree_dir, &["add", "feature.rs"]);
+
+        let detector = GitChangeDetector::new(&worktree_dir).unwrap();
+        let staged = detector.get_staged_changes().unwrap();
+        assert_eq!(staged, vec![PathBuf::from("feature.rs")]);
+    }
+
+    #[test]
+    fn test_change_detector_in_submodule() {
+        let temp_dir = TempDir::new().unwrap();
+        let library_dir = create_test_git_repo(&temp_dir.path().join("library"));
+        fs::write(library_dir.join("lib.rs"), "pub fn lib() {}").unwrap();
+        git(&library_dir, &["add", "lib.rs"]);
+        git(&library_dir, &["commit", "-m", "library"]);
+
+        let app_dir = create_test_git_repo(&temp_dir.path().join("app"));
+        git(
+            &app_dir,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                library_dir.to_str().unwrap(),
+                "vendor/library",
+            ],
+        );
+        let submodule_dir = app_dir.join("vendor/library");
+        assert!(submodule_dir.join(".git").is_file());
+
+        fs::write(submodule_dir.join("new.rs"), "fn new() {}").unwrap();
+
+        let detector = GitChangeDetector::new(&submodule_dir).unwrap();
+        let changes = detector.get_working_directory_changes().unwrap();
+        assert_eq!(changes, vec![PathBuf::from("new.rs")]);
+    }
+
     #[test]
     fn test_working_directory_changes() {
         let temp_dir = TempDir::new().unwrap();
@@ -425,6 +986,81 @@ mod tests {
         assert!(changes.contains(&PathBuf::from("test.rs")));
     }
 
+    #[test]
+    fn test_working_directory_changes_without_untracked() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+
+        fs::write(repo_dir.join("tracked.rs"), "fn a() {}").unwrap();
+        git(&repo_dir, &["add", "tracked.rs"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add tracked.rs"]);
+        fs::write(repo_dir.join("tracked.rs"), "fn b() {}").unwrap();
+        fs::write(repo_dir.join("untracked.rs"), "fn c() {}").unwrap();
+
+        let mode = ChangeDetectionMode::WorkingDirectory;
+        let changes = GitChangeDetector::new(&repo_dir)
+            .unwrap()
+            .get_changed_files(&mode)
+            .unwrap();
+        assert!(changes.contains(&PathBuf::from("tracked.rs")));
+        assert!(changes.contains(&PathBuf::from("untracked.rs")));
+
+        let changes = GitChangeDetector::new(&repo_dir)
+            .unwrap()
+            .with_untracked(false)
+            .get_changed_files(&mode)
+            .unwrap();
+        assert_eq!(changes, vec![PathBuf::from("tracked.rs")]);
+    }
+
+    #[test]
+    fn test_unicode_file_names_are_not_quoted() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+        // git would print this as "\303\244nderung \"1\".txt" without -z
+        let name = "änderung \"1\".txt";
+        fs::write(repo_dir.join(name), "content").unwrap();
+        git(&repo_dir, &["add", name]);
+
+        let changes = GitChangeDetector::new(&repo_dir)
+            .unwrap()
+            .get_changed_files(&ChangeDetectionMode::Staged)
+            .unwrap();
+        assert_eq!(changes, vec![PathBuf::from(name)]);
+        assert!(repo_dir.join(&changes[0]).is_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_file_names_round_trip() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+        let staged = PathBuf::from(OsStr::from_bytes(b"caf\xe9 tab\there.rs"));
+        let untracked = PathBuf::from(OsStr::from_bytes(b"new\xff.rs"));
+        fs::write(repo_dir.join(&staged), "fn a() {}").unwrap();
+        fs::write(repo_dir.join(&untracked), "fn b() {}").unwrap();
+        Command::new("git")
+            .arg("add")
+            .arg(&staged)
+            .current_dir(&repo_dir)
+            .output()
+            .unwrap();
+
+        let detector = GitChangeDetector::new(&repo_dir).unwrap();
+        let changes = detector
+            .get_changed_files(&ChangeDetectionMode::Staged)
+            .unwrap();
+        assert_eq!(changes, vec![staged.clone()]);
+
+        let changes = detector
+            .get_changed_files(&ChangeDetectionMode::WorkingDirectory)
+            .unwrap();
+        assert!(changes.contains(&staged), "Got: {changes:?}");
+        assert!(changes.contains(&untracked), "Got: {changes:?}");
+    }
+
     #[test]
     fn test_file_pattern_matcher() {
         let patterns = vec!["**/*.rs".to_string(), "*.toml".to_string()];
@@ -476,6 +1112,172 @@ mod tests {
         assert!(matcher.matches_any(&[PathBuf::from("test.rs")]));
     }
 
+    #[test]
+    fn test_content_patterns_match_shebang() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("deploy"), "#!/usr/bin/env bash\necho hi\n").unwrap();
+        fs::write(dir.join("notes"), "just some text\n").unwrap();
+        fs::write(dir.join("blob"), b"#!/bin/bash\0\x01\x02").unwrap();
+
+        let matcher = FilePatternMatcher::with_content_patterns(
+            &["*.sh".to_string()],
+            &["^#!.*\\bbash\\b".to_string()],
+            dir,
+        )
+        .unwrap();
+
+        assert!(matcher.matches(&PathBuf::from("deploy")));
+        assert!(matcher.matches(&PathBuf::from("script.sh"))); // glob still applies
+        assert!(!matcher.matches(&PathBuf::from("notes")));
+        assert!(!matcher.matches(&PathBuf::from("blob"))); // binary files are skipped
+        assert!(!matcher.matches(&PathBuf::from("missing"))); // unreadable files are skipped
+        assert!(matcher.matches_any(&[PathBuf::from("notes"), PathBuf::from("deploy")]));
+    }
+
+    #[test]
+    fn test_scoped_patterns_match_relative_to_scope() {
+        let matcher = FilePatternMatcher::new(&["*.rs".to_string(), "lib/*.toml".to_string()])
+            .unwrap()
+            .with_scope(Some(Path::new("src")));
+
+        assert!(matcher.matches(&PathBuf::from("src/a.rs")));
+        assert!(matcher.matches(&PathBuf::from("src/lib/Cargo.toml")));
+        assert!(!matcher.matches(&PathBuf::from("other/b.rs")));
+        assert!(!matcher.matches(&PathBuf::from("lib/Cargo.toml")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_absolute_exclude_matches_canonical_path() {
+        let repo = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let vendor = outside.path().canonicalize().unwrap();
+        fs::write(vendor.join("vendored.rs"), "fn v() {}\n").unwrap();
+        fs::write(repo.path().join("local.rs"), "fn l() {}\n").unwrap();
+        std::os::unix::fs::symlink(vendor.join("vendored.rs"), repo.path().join("linked.rs"))
+            .unwrap();
+
+        let exclude = format!("{}/**", vendor.display());
+        let matcher =
+            FilePatternMatcher::with_content_patterns(&["*.rs".to_string()], &[], repo.path())
+                .unwrap()
+                .with_exclude_patterns(&[exclude])
+                .unwrap();
+        assert!(matcher.matches(Path::new("local.rs")));
+        assert!(!matcher.matches(Path::new("linked.rs")));
+
+        // Relative patterns still see the link's own repo-relative name
+        let matcher =
+            FilePatternMatcher::with_content_patterns(&["linked.rs".to_string()], &[], repo.path())
+                .unwrap()
+                .with_exclude_patterns(&["local.rs".to_string()])
+                .unwrap();
+        assert!(matcher.matches(Path::new("linked.rs")));
+        assert!(!matcher.matches(Path::new("local.rs")));
+    }
+
+    #[test]
+    fn test_extensions_match_case_insensitively() {
+        let config = crate::config::HookConfig::parse(
+            r#"
+[hooks.rust]
+command = "true"
+extensions = ["rs"]
+files = ["docs/*.txt"]
+"#,
+        )
+        .unwrap();
+        let hook = &config.hooks.unwrap()["rust"];
+        let matcher = FilePatternMatcher::for_hook(hook, Path::new("."))
+            .unwrap()
+            .unwrap();
+
+        assert!(matcher.matches(&PathBuf::from("src/deep/main.RS")));
+        assert!(matcher.matches(&PathBuf::from("lib.rs")));
+        assert!(!matcher.matches(&PathBuf::from("readme.md")));
+        // Explicit `files` patterns still apply
+        assert!(matcher.matches(&PathBuf::from("docs/notes.txt")));
+    }
+
+    #[test]
+    fn test_directory_patterns_match_files_beneath() {
+        let matcher =
+            FilePatternMatcher::new(&["docs/".to_string(), "packages/*/".to_string()]).unwrap();
+
+        assert!(matcher.matches(&PathBuf::from("docs/a/b.md")));
+        assert!(matcher.matches(&PathBuf::from("docs/index.md")));
+        assert!(matcher.matches(&PathBuf::from("packages/web/src/main.ts")));
+        assert!(!matcher.matches(&PathBuf::from("src/docs.rs")));
+        assert!(!matcher.matches(&PathBuf::from("docs")));
+        assert!(!matcher.matches(&PathBuf::from("packages/README.md")));
+
+        // Relative to the scope, like other globs
+        let scoped = FilePatternMatcher::new(&["docs/".to_string()])
+            .unwrap()
+            .with_scope(Some(Path::new("site")));
+        assert!(scoped.matches(&PathBuf::from("site/docs/guide.md")));
+        assert!(!scoped.matches(&PathBuf::from("docs/guide.md")));
+    }
+
+    #[test]
+    fn test_invalid_content_pattern() {
+        let result =
+            FilePatternMatcher::with_content_patterns(&[], &["(".to_string()], Path::new("."));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rename_detection_threshold() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+
+        // Ten lines, four of which survive the rename: ~40% similar, below
+        // git's default 50% rename threshold
+        let original =
+            "line 0\nline 1\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8\nline 9\n";
+        fs::write(repo_dir.join("old.txt"), original).unwrap();
+        git(&repo_dir, &["add", "old.txt"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add old.txt"]);
+
+        let edited =
+            "line 0\nline 1\nline 2\nline 3\nedit 4\nedit 5\nedit 6\nedit 7\nedit 8\nedit 9\n";
+        git(&repo_dir, &["mv", "old.txt", "new.txt"]);
+        fs::write(repo_dir.join("new.txt"), edited).unwrap();
+        git(&repo_dir, &["add", "new.txt"]);
+
+        let tuned = GitChangeDetector::new(&repo_dir)
+            .unwrap()
+            .with_rename_detection(Some(RenameDetection::Threshold(30)));
+        let status = String::from_utf8(
+            tuned
+                .run_diff_command(&["--cached", "--name-status"])
+                .unwrap(),
+        )
+        .unwrap();
+        let lines: Vec<_> = status.lines().collect();
+        assert_eq!(lines.len(), 1, "Expected a single rename: {status}");
+        assert!(lines[0].starts_with('R'), "Expected a rename: {status}");
+        assert!(lines[0].ends_with("old.txt\tnew.txt"), "Got: {status}");
+        assert_eq!(
+            tuned
+                .get_changed_files(&ChangeDetectionMode::Staged)
+                .unwrap(),
+            vec![PathBuf::from("new.txt")]
+        );
+
+        // With git's default threshold the same change is a delete plus an add
+        let default = GitChangeDetector::new(&repo_dir).unwrap();
+        let status = String::from_utf8(
+            default
+                .run_diff_command(&["--cached", "--name-status"])
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(status.contains("D\told.txt"), "Got: {status}");
+        assert!(status.contains("A\tnew.txt"), "Got: {status}");
+    }
+
     #[test]
     fn test_deleted_files_excluded() {
         let temp_dir = TempDir::new().unwrap();
@@ -579,6 +1381,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_last_commit_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+        let detector = GitChangeDetector::new(&repo_dir).unwrap();
+
+        fs::write(repo_dir.join("first.txt"), "one\n").unwrap();
+        git(&repo_dir, &["add", "first.txt"]);
+        git(&repo_dir, &["commit", "-q", "-m", "First"]);
+
+        // The initial commit has no parent and is diffed against the empty tree
+        let changes = detector
+            .get_changed_files(&ChangeDetectionMode::LastCommit)
+            .unwrap();
+        assert_eq!(changes, vec![PathBuf::from("first.txt")]);
+
+        fs::write(repo_dir.join("second.txt"), "two\n").unwrap();
+        fs::write(repo_dir.join("first.txt"), "one\nmore\n").unwrap();
+        git(&repo_dir, &["add", "second.txt"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Second"]);
+
+        // Only what the latest commit changed; the unstaged edit is ignored
+        let changes = detector
+            .get_changed_files(&ChangeDetectionMode::LastCommit)
+            .unwrap();
+        assert_eq!(changes, vec![PathBuf::from("second.txt")]);
+    }
+
     #[test]
     fn test_renamed_files_in_commit_range() {
         let temp_dir = TempDir::new().unwrap();
@@ -701,7 +1531,7 @@ mod tests {
     fn test_parse_push_stdin_valid() {
         let stdin = "refs/heads/main a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0 refs/heads/main \
                      0fedcba9876543210fedcba9876543210fedcba9";
-        let (local_oid, remote_oid) = parse_push_stdin(stdin).unwrap();
+        let (local_oid, remote_oid) = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap();
         assert_eq!(local_oid, "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0");
         assert_eq!(remote_oid, "0fedcba9876543210fedcba9876543210fedcba9");
     }
@@ -711,7 +1541,7 @@ mod tests {
         // When pushing a new branch, remote OID is all zeros
         let stdin = "refs/heads/feature a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0 \
                      refs/heads/feature 0000000000000000000000000000000000000000";
-        let (local_oid, remote_oid) = parse_push_stdin(stdin).unwrap();
+        let (local_oid, remote_oid) = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap();
         assert_eq!(local_oid, "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0");
         // Should be replaced with empty tree hash
         assert_eq!(remote_oid, "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
@@ -720,14 +1550,14 @@ mod tests {
     #[test]
     fn test_parse_push_stdin_empty() {
         let stdin = "";
-        let err = parse_push_stdin(stdin).unwrap_err();
+        let err = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap_err();
         assert!(err.to_string().contains("No input received"));
     }
 
     #[test]
     fn test_parse_push_stdin_invalid_format() {
         let stdin = "refs/heads/main a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0"; // Missing fields
-        let err = parse_push_stdin(stdin).unwrap_err();
+        let err = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap_err();
         assert!(err.to_string().contains("Invalid pre-push stdin format"));
     }
 
@@ -738,7 +1568,7 @@ mod tests {
                      0fedcba9876543210fedcba9876543210fedcba9\nrefs/heads/other \
                      1234567890abcdef1234567890abcdef12345678 refs/heads/other \
                      fedcba0987654321fedcba0987654321fedcba09";
-        let (local_oid, remote_oid) = parse_push_stdin(stdin).unwrap();
+        let (local_oid, remote_oid) = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap();
         assert_eq!(local_oid, "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0");
         assert_eq!(remote_oid, "0fedcba9876543210fedcba9876543210fedcba9");
     }
@@ -747,7 +1577,7 @@ mod tests {
     fn test_parse_push_stdin_invalid_local_oid_too_short() {
         let stdin =
             "refs/heads/main abc123 refs/heads/main 0fedcba9876543210fedcba9876543210fedcba9";
-        let err = parse_push_stdin(stdin).unwrap_err();
+        let err = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap_err();
         assert!(
             err.to_string().contains("Invalid local OID format"),
             "Error should mention invalid local OID: {err}"
@@ -758,7 +1588,7 @@ mod tests {
     fn test_parse_push_stdin_invalid_local_oid_non_hex() {
         let stdin = "refs/heads/main xyz123def456xyz123def456xyz123def456xy refs/heads/main \
                      0fedcba9876543210fedcba9876543210fedcba9";
-        let err = parse_push_stdin(stdin).unwrap_err();
+        let err = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap_err();
         assert!(
             err.to_string().contains("Invalid local OID format"),
             "Error should mention invalid local OID: {err}"
@@ -769,7 +1599,7 @@ mod tests {
     fn test_parse_push_stdin_invalid_remote_oid_too_long() {
         let stdin = "refs/heads/main a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0 refs/heads/main \
                      0fedcba9876543210fedcba9876543210fedcba9extra";
-        let err = parse_push_stdin(stdin).unwrap_err();
+        let err = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap_err();
         assert!(
             err.to_string().contains("Invalid remote OID format"),
             "Error should mention invalid remote OID: {err}"
@@ -781,8 +1611,174 @@ mod tests {
         // OIDs can be mixed case - should be valid
         let stdin = "refs/heads/main A1B2C3D4E5F6a7b8c9d0E1F2A3B4C5D6e7f8a9b0 refs/heads/main \
                      0FEDcba9876543210FEDcba9876543210FEDcba9";
-        let (local_oid, remote_oid) = parse_push_stdin(stdin).unwrap();
+        let (local_oid, remote_oid) = parse_push_stdin(stdin, ObjectFormat::Sha1).unwrap();
         assert_eq!(local_oid, "A1B2C3D4E5F6a7b8c9d0E1F2A3B4C5D6e7f8a9b0");
         assert_eq!(remote_oid, "0FEDcba9876543210FEDcba9876543210FEDcba9");
     }
+
+    fn checkout_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_checkout_args_branch_checkout() {
+        let previous = "a".repeat(40);
+        let new = "b".repeat(40);
+        let parsed =
+            parse_checkout_args(&checkout_args(&[&previous, &new, "1"]), ObjectFormat::Sha1)
+                .unwrap();
+        assert_eq!(parsed, Some((previous, new)));
+    }
+
+    #[test]
+    fn test_parse_checkout_args_file_checkout_is_skipped() {
+        let oid = "a".repeat(40);
+        let parsed =
+            parse_checkout_args(&checkout_args(&[&oid, &oid, "0"]), ObjectFormat::Sha1).unwrap();
+        assert_eq!(parsed, None);
+    }
+
+    #[test]
+    fn test_sha256_repositories_use_the_sha256_empty_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = temp_dir.path();
+        let init = Command::new("git")
+            .args(["init", "-q", "--object-format=sha256"])
+            .current_dir(repo_dir)
+            .output()
+            .unwrap();
+        if !init.status.success() {
+            eprintln!("Skipping: this git cannot create SHA-256 repositories");
+            return;
+        }
+
+        let format = ObjectFormat::detect(repo_dir);
+        assert_eq!(format, ObjectFormat::Sha256);
+        let local = "a".repeat(64);
+        let stdin = format!("refs/heads/main {local} refs/heads/main {}", "0".repeat(64));
+        let (_, remote_oid) = parse_push_stdin(&stdin, format).unwrap();
+        assert_eq!(remote_oid, format.empty_tree_oid());
+        assert!(parse_push_stdin(&stdin, ObjectFormat::Sha1).is_err());
+
+        // The first commit is diffed against the SHA-256 empty tree
+        git(repo_dir, &["config", "user.name", "Test User"]);
+        git(repo_dir, &["config", "user.email", "test@example.com"]);
+        fs::write(repo_dir.join("first.rs"), "fn main() {}").unwrap();
+        git(repo_dir, &["add", "first.rs"]);
+        git(repo_dir, &["commit", "-q", "-m", "First"]);
+        let changes = GitChangeDetector::new(repo_dir)
+            .unwrap()
+            .get_changed_files(&ChangeDetectionMode::LastCommit)
+            .unwrap();
+        assert_eq!(changes, vec![PathBuf::from("first.rs")]);
+        assert_eq!(ObjectFormat::detect(Path::new("/")), ObjectFormat::Sha1);
+    }
+
+    #[test]
+    fn test_parse_checkout_args_initial_clone() {
+        let new = "b".repeat(40);
+        let (previous, _) = parse_checkout_args(
+            &checkout_args(&[&"0".repeat(40), &new, "1"]),
+            ObjectFormat::Sha1,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(previous, "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+    }
+
+    #[test]
+    fn test_parse_checkout_args_invalid() {
+        let oid = "a".repeat(40);
+        let err =
+            parse_checkout_args(&checkout_args(&[&oid, &oid]), ObjectFormat::Sha1).unwrap_err();
+        assert!(err.to_string().contains("Invalid post-checkout arguments"));
+
+        let err = parse_checkout_args(&checkout_args(&[&oid, &oid, "2"]), ObjectFormat::Sha1)
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid post-checkout flag"));
+
+        let err = parse_checkout_args(&checkout_args(&["abc", &oid, "1"]), ObjectFormat::Sha1)
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid previous HEAD"));
+    }
+
+    #[test]
+    fn test_checkout_changes_between_heads() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+
+        fs::write(repo_dir.join("shared.rs"), "fn shared() {}").unwrap();
+        git(&repo_dir, &["add", "shared.rs"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add shared.rs"]);
+        let rev_parse = |rev: &str| {
+            let output = Command::new("git")
+                .args(["rev-parse", rev])
+                .current_dir(&repo_dir)
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        let previous_oid = rev_parse("HEAD");
+
+        git(&repo_dir, &["checkout", "-q", "-b", "feature"]);
+        fs::write(repo_dir.join("feature.rs"), "fn feature() {}").unwrap();
+        git(&repo_dir, &["add", "feature.rs"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add feature.rs"]);
+        let new_oid = rev_parse("HEAD");
+
+        let changes = GitChangeDetector::new(&repo_dir)
+            .unwrap()
+            .get_changed_files(&ChangeDetectionMode::Checkout {
+                previous_oid,
+                new_oid,
+            })
+            .unwrap();
+        assert_eq!(changes, vec![PathBuf::from("feature.rs")]);
+    }
+
+    #[test]
+    fn test_merge_changes_from_orig_head() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+
+        fs::write(repo_dir.join("main.rs"), "fn main() {}").unwrap();
+        git(&repo_dir, &["add", "main.rs"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add main.rs"]);
+
+        git(&repo_dir, &["checkout", "-q", "-b", "feature"]);
+        fs::write(repo_dir.join("feature.rs"), "fn feature() {}").unwrap();
+        git(&repo_dir, &["add", "feature.rs"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add feature.rs"]);
+        fs::write(repo_dir.join("Cargo.lock"), "# lock").unwrap();
+        git(&repo_dir, &["add", "Cargo.lock"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add Cargo.lock"]);
+
+        git(&repo_dir, &["checkout", "-q", "-"]);
+        fs::write(repo_dir.join("other.rs"), "fn other() {}").unwrap();
+        git(&repo_dir, &["add", "other.rs"]);
+        git(&repo_dir, &["commit", "-q", "-m", "Add other.rs"]);
+
+        // Squash merge: nothing is committed, the result is staged
+        git(&repo_dir, &["merge", "-q", "--squash", "feature"]);
+        let detector = GitChangeDetector::new(&repo_dir).unwrap();
+        let mut changes = detector
+            .get_changed_files(&ChangeDetectionMode::Merge { squash: true })
+            .unwrap();
+        changes.sort();
+        assert_eq!(
+            changes,
+            vec![PathBuf::from("Cargo.lock"), PathBuf::from("feature.rs")]
+        );
+        git(&repo_dir, &["reset", "-q", "--hard"]);
+
+        git(&repo_dir, &["merge", "-q", "--no-edit", "feature"]);
+        let mut changes = detector
+            .get_changed_files(&ChangeDetectionMode::Merge { squash: false })
+            .unwrap();
+        changes.sort();
+        assert_eq!(
+            changes,
+            vec![PathBuf::from("Cargo.lock"), PathBuf::from("feature.rs")]
+        );
+    }
 }
@@ -0,0 +1,116 @@
+//! Last successful run state for `run --incremental`
+//!
+//! After a successful run the tree that was checked is recorded under the git
+//! directory, one file per event: the index for staged runs, otherwise the
+//! working tree. The next incremental run only looks at files that differ
+//! from that tree.
+
+use super::snapshot::{run_git, write_worktree_tree};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Recorded state of the last successful run of one event
+pub struct LastRun {
+    /// Git repository root
+    repo_root: PathBuf,
+    /// File holding the tree id
+    path: PathBuf,
+}
+
+impl LastRun {
+    /// State for `event` in the repository at `repo_root`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the git directory cannot be located
+    pub fn for_event<P: AsRef<Path>>(repo_root: P, event: &str) -> Result<Self> {
+        let repo_root = repo_root.as_ref().to_path_buf();
+        let name = format!("peter-hook/last-run/{event}");
+        let path = run_git(&repo_root, &["rev-parse", "--git-path", &name], None)?;
+        let path = repo_root.join(path.trim());
+        Ok(Self { repo_root, path })
+    }
+
+    /// Tree id of the current state: the index for staged runs, otherwise the
+    /// working tree (including untracked, non-ignored files)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git cannot write the tree
+    pub fn current_tree(&self, staged: bool) -> Result<String> {
+        if staged {
+            Ok(run_git(&self.repo_root, &["write-tree"], None)?
+                .trim()
+                .to_string())
+        } else {
+            write_worktree_tree(&self.repo_root)
+        }
+    }
+
+    /// Tree recorded by the last successful run, if there is one and git
+    /// still has it
+    #[must_use]
+    pub fn load(&self) -> Option<String> {
+        let tree = std::fs::read_to_string(&self.path).ok()?;
+        let tree = tree.trim();
+        let object = format!("{tree}^{{tree}}");
+        run_git(&self.repo_root, &["cat-file", "-e", &object], None).ok()?;
+        Some(tree.to_string())
+    }
+
+    /// Record `tree` as the state of the last successful run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state file cannot be written
+    pub fn save(&self, tree: &str) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&self.path, format!("{tree}\n"))
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, process::Command};
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_last_run_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        git(root, &["init", "-q"]);
+        fs::write(root.join("file.txt"), "one\n").unwrap();
+        git(root, &["add", "file.txt"]);
+
+        let last_run = LastRun::for_event(root, "pre-commit").unwrap();
+        assert!(last_run.load().is_none());
+
+        let tree = last_run.current_tree(true).unwrap();
+        last_run.save(&tree).unwrap();
+        assert_eq!(last_run.load(), Some(tree.clone()));
+
+        // Unstaged edits change the working tree but not the index
+        fs::write(root.join("file.txt"), "two\n").unwrap();
+        assert_eq!(last_run.current_tree(true).unwrap(), tree);
+        assert_ne!(last_run.current_tree(false).unwrap(), tree);
+
+        // A tree git no longer has is ignored
+        last_run.save(&"0".repeat(40)).unwrap();
+        assert!(last_run.load().is_none());
+    }
+}
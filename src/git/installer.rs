@@ -1,7 +1,7 @@
 //! Git hook installation and management
 
 use crate::{
-    config::HookConfig,
+    config::{ConfigOverrides, HookConfig},
     git::{GitRepository, WorktreeHookStrategy},
     hooks::HookResolver,
 };
@@ -16,6 +16,8 @@ pub struct GitHookInstaller {
     binary_path: String,
     /// Strategy for handling worktree hooks
     worktree_strategy: WorktreeHookStrategy,
+    /// Config selection used to decide which hooks are configured
+    overrides: ConfigOverrides,
 }
 
 /// Supported git hook events
@@ -65,6 +67,7 @@ impl GitHookInstaller {
             repository,
             binary_path,
             worktree_strategy: strategy,
+            overrides: ConfigOverrides::default(),
         })
     }
 
@@ -76,6 +79,7 @@ impl GitHookInstaller {
             repository,
             binary_path,
             worktree_strategy: WorktreeHookStrategy::default(),
+            overrides: ConfigOverrides::default(),
         }
     }
 
@@ -92,16 +96,26 @@ impl GitHookInstaller {
             repository,
             binary_path,
             worktree_strategy: strategy,
+            overrides: ConfigOverrides::default(),
         }
     }
 
+    /// Decide which hooks are configured with the config file, repository
+    /// root and profile selected by `overrides`
+    #[must_use]
+    pub fn with_overrides(mut self, overrides: ConfigOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
     /// Install hooks for all events that have configurations
     ///
     /// # Errors
     ///
     /// Returns an error if hook installation fails
     pub fn install_all(&self) -> Result<InstallationReport> {
-        let resolver = HookResolver::new(&self.repository.root);
+        let resolver =
+            HookResolver::new(&self.repository.root).with_overrides(self.overrides.clone());
         let mut report = InstallationReport {
             installed: Vec::new(),
             skipped: Vec::new(),
@@ -139,29 +153,54 @@ impl GitHookInstaller {
     ///
     /// Returns an error if hook installation fails
     pub fn install_hook(&self, hook_event: &str, resolver: &HookResolver) -> Result<InstallAction> {
-        // Check if we have configuration for this event
-        if (resolver.resolve_hooks(hook_event)?).is_some() {
-            // We have real hooks configuration, install the hook
+        if Self::is_configured(hook_event, resolver)? {
             self.install_hook_script(hook_event)
         } else {
-            // No hooks resolved - but check if there's a placeholder group
-            if let Some(config_path) = resolver.find_config_file()? {
-                let config = HookConfig::from_file(&config_path)?;
-                if let Some(groups) = &config.groups {
-                    if let Some(group) = groups.get(hook_event) {
-                        if group.placeholder == Some(true) {
-                            // Placeholder group found - install hook script for hierarchical
-                            // resolution
-                            return self.install_hook_script(hook_event);
-                        }
-                    }
-                }
-            }
             // No configuration and no placeholder
             Ok(InstallAction::Skipped("No configuration found".to_string()))
         }
     }
 
+    /// The git hooks `install_all` would install, for the events the
+    /// repository's config configures
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be read
+    pub fn expected_hooks(&self) -> Result<Vec<&'static str>> {
+        let resolver =
+            HookResolver::new(&self.repository.root).with_overrides(self.overrides.clone());
+        let mut expected = Vec::new();
+        for &hook_event in SUPPORTED_HOOKS {
+            if Self::is_configured(hook_event, &resolver)? {
+                expected.push(hook_event);
+            }
+        }
+        Ok(expected)
+    }
+
+    /// Whether the config resolves hooks for `hook_event`, or has a
+    /// placeholder group for it
+    fn is_configured(hook_event: &str, resolver: &HookResolver) -> Result<bool> {
+        // Check if we have configuration for this event
+        if (resolver.resolve_hooks(hook_event)?).is_some() {
+            return Ok(true);
+        }
+        // No hooks resolved - but a placeholder group still gets a hook
+        // script, for hierarchical resolution
+        if let Some(config_path) = resolver.find_config_file()? {
+            let config = HookConfig::from_file(&config_path, resolver.overrides())?;
+            if let Some(group) = config
+                .groups
+                .as_ref()
+                .and_then(|groups| groups.get(hook_event))
+            {
+                return Ok(group.placeholder == Some(true));
+            }
+        }
+        Ok(false)
+    }
+
     /// Get the effective hooks directory based on worktree strategy
     fn get_effective_hooks_dir(&self) -> std::path::PathBuf {
         let effective_strategy = match self.worktree_strategy {
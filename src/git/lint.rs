@@ -51,6 +51,7 @@ impl LintFileDiscovery {
         let output = Command::new("git")
             .args([
                 "ls-files",
+                "-z",                 // Unquoted, NUL-separated paths
                 "--cached",           // Tracked files
                 "--others",           // Untracked files
                 "--exclude-standard", // Respect .gitignore
@@ -64,21 +65,12 @@ impl LintFileDiscovery {
             return Err(anyhow::anyhow!("git ls-files failed: {stderr}"));
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut files = Vec::new();
-
-        for line in stdout.lines() {
-            let line = line.trim();
-            if !line.is_empty() {
-                // git ls-files returns paths relative to current directory
-                let file_path = self.start_dir.join(line);
-                if file_path.exists() && file_path.is_file() {
-                    files.push(file_path);
-                }
-            }
-        }
-
-        Ok(files)
+        // git ls-files returns paths relative to current directory
+        Ok(super::changes::parse_nul_paths(&output.stdout)?
+            .into_iter()
+            .map(|path| self.start_dir.join(path))
+            .filter(|file_path| file_path.is_file())
+            .collect())
     }
 
     /// Manual file discovery (fallback for non-git directories)
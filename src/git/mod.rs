@@ -2,14 +2,22 @@
 
 pub mod capabilities;
 pub mod changes;
+pub mod incremental;
 pub mod installer;
 pub mod lint;
 pub mod repository;
+pub mod snapshot;
+pub mod stash;
+pub mod watch;
 pub mod worktree;
 
 pub use capabilities::*;
 pub use changes::*;
+pub use incremental::*;
 pub use installer::*;
 pub use lint::*;
 pub use repository::*;
+pub use snapshot::*;
+pub use stash::*;
+pub use watch::*;
 pub use worktree::*;
@@ -204,6 +204,21 @@ impl GitRepository {
         Ok(hooks)
     }
 
+    /// The rebase or merge in progress in this worktree, if any
+    ///
+    /// Detected from `rebase-merge`, `rebase-apply` or `MERGE_HEAD` in the
+    /// worktree's git directory. Returns the operation and the marker found.
+    #[must_use]
+    pub fn operation_in_progress(&self) -> Option<(&'static str, &'static str)> {
+        [
+            ("rebase", "rebase-merge"),
+            ("rebase", "rebase-apply"),
+            ("merge", "MERGE_HEAD"),
+        ]
+        .into_iter()
+        .find(|(_, marker)| self.git_dir.join(marker).exists())
+    }
+
     /// Check if this is the main repository (not a worktree)
     #[must_use]
     pub const fn is_main_worktree(&self) -> bool {
@@ -335,6 +350,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_operation_in_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = create_test_git_repo(temp_dir.path());
+        let repo = GitRepository::find_from_dir(&repo_dir).unwrap();
+        assert_eq!(repo.operation_in_progress(), None);
+
+        std::fs::write(repo.git_dir.join("MERGE_HEAD"), "0".repeat(40)).unwrap();
+        assert_eq!(repo.operation_in_progress(), Some(("merge", "MERGE_HEAD")));
+
+        std::fs::create_dir(repo.git_dir.join("rebase-merge")).unwrap();
+        assert_eq!(
+            repo.operation_in_progress(),
+            Some(("rebase", "rebase-merge"))
+        );
+    }
+
     // TODO: Add proper worktree tests once git2 worktree creation is implemented
     // #[test]
     // fn test_find_repository_worktree() {
@@ -0,0 +1,341 @@
+//! Working tree snapshots for `run --check`
+//!
+//! A snapshot records the working tree (tracked and untracked, non-ignored
+//! files) as a git tree object using a throwaway index, so neither the real
+//! index nor the working tree is touched. After hooks run, the working tree
+//! is compared against the snapshot and any changes are rolled back.
+//!
+//! The real index is copied aside as well, so changes hooks stage (for
+//! example a formatter running `git add`) are rolled back too.
+
+use anyhow::{Context, Result};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// A recorded state of the working tree
+pub struct WorktreeSnapshot {
+    /// Git repository root
+    repo_root: PathBuf,
+    /// Tree object id of the snapshot
+    tree: String,
+    /// The repository's real index
+    index: PathBuf,
+    /// Copy of the real index taken with the snapshot (`None` if there was
+    /// no index yet)
+    saved_index: Option<PathBuf>,
+}
+
+/// How a file differs from the snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotChange {
+    /// File did not exist in the snapshot
+    Added,
+    /// File content or mode changed
+    Modified,
+    /// File existed in the snapshot and is now gone
+    Deleted,
+}
+
+/// Differences between a snapshot and the current working tree
+#[derive(Debug, Default)]
+pub struct SnapshotDiff {
+    /// Changed files (repo-relative) and how they changed
+    pub files: Vec<(PathBuf, SnapshotChange)>,
+    /// `git diff --stat` style summary of the changes
+    pub stat: String,
+}
+
+impl SnapshotDiff {
+    /// Whether the working tree matches the snapshot
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl WorktreeSnapshot {
+    /// Record the current working tree and index of the repository at
+    /// `repo_root`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git cannot hash the working tree or the index
+    /// cannot be copied
+    pub fn capture<P: AsRef<Path>>(repo_root: P) -> Result<Self> {
+        let repo_root = repo_root.as_ref().to_path_buf();
+        let tree = write_worktree_tree(&repo_root)?;
+
+        let index = real_index_path(&repo_root)?;
+        let saved_index = if index.exists() {
+            let saved = temp_index_path("saved");
+            std::fs::copy(&index, &saved)
+                .with_context(|| format!("Failed to copy index {}", index.display()))?;
+            Some(saved)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            repo_root,
+            tree,
+            index,
+            saved_index,
+        })
+    }
+
+    /// Compare the current working tree against the snapshot
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git cannot hash or diff the working tree
+    pub fn diff(&self) -> Result<SnapshotDiff> {
+        let current = write_worktree_tree(&self.repo_root)?;
+        if current == self.tree {
+            return Ok(SnapshotDiff::default());
+        }
+
+        let name_status = run_git(
+            &self.repo_root,
+            &[
+                "diff",
+                "--no-renames",
+                "--name-status",
+                "-z",
+                &self.tree,
+                &current,
+            ],
+            None,
+        )?;
+        // NUL-separated status and path pairs, with paths left unquoted
+        let mut fields = name_status.split('\0').filter(|field| !field.is_empty());
+        let mut files = Vec::new();
+        while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+            let change = match status.chars().next() {
+                Some('A') => SnapshotChange::Added,
+                Some('D') => SnapshotChange::Deleted,
+                _ => SnapshotChange::Modified,
+            };
+            files.push((PathBuf::from(path), change));
+        }
+
+        let stat = run_git(
+            &self.repo_root,
+            &["diff", "--no-renames", "--stat", &self.tree, &current],
+            None,
+        )?;
+
+        Ok(SnapshotDiff { files, stat })
+    }
+
+    /// Put every file in `diff` back the way it was in the snapshot, and the
+    /// index as it was when the snapshot was taken
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file or the index cannot be removed or restored
+    pub fn restore(&self, diff: &SnapshotDiff) -> Result<()> {
+        let mut to_restore = Vec::new();
+        for (path, change) in &diff.files {
+            if *change == SnapshotChange::Added {
+                let full_path = self.repo_root.join(path);
+                std::fs::remove_file(&full_path)
+                    .with_context(|| format!("Failed to remove {}", full_path.display()))?;
+            } else {
+                to_restore.push(path.to_string_lossy().to_string());
+            }
+        }
+
+        if !to_restore.is_empty() {
+            let source = format!("--source={}", self.tree);
+            let mut args = vec!["restore", source.as_str(), "--worktree", "--"];
+            args.extend(to_restore.iter().map(String::as_str));
+            run_git(&self.repo_root, &args, None)?;
+        }
+
+        self.restore_index()
+    }
+
+    /// Copy the saved index back over the real one if hooks changed it
+    fn restore_index(&self) -> Result<()> {
+        let current = std::fs::read(&self.index).ok();
+        match &self.saved_index {
+            Some(saved) => {
+                let saved_bytes = std::fs::read(saved)
+                    .with_context(|| format!("Failed to read saved index {}", saved.display()))?;
+                if current.as_ref() != Some(&saved_bytes) {
+                    std::fs::write(&self.index, saved_bytes).with_context(|| {
+                        format!("Failed to restore index {}", self.index.display())
+                    })?;
+                }
+            }
+            None if current.is_some() => {
+                std::fs::remove_file(&self.index)
+                    .with_context(|| format!("Failed to remove index {}", self.index.display()))?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WorktreeSnapshot {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved_index {
+            let _ = std::fs::remove_file(saved);
+        }
+    }
+}
+
+/// Path of the repository's real index (per worktree)
+fn real_index_path(repo_root: &Path) -> Result<PathBuf> {
+    let index = run_git(repo_root, &["rev-parse", "--git-path", "index"], None)?;
+    Ok(repo_root.join(index.trim()))
+}
+
+/// A unique path in the temp directory for a copy of an index
+fn temp_index_path(kind: &str) -> PathBuf {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!(
+        "peter-hook-{kind}-{}-{now}.index",
+        std::process::id()
+    ))
+}
+
+/// Hash the working tree into a tree object without touching the real index
+pub(crate) fn write_worktree_tree(repo_root: &Path) -> Result<String> {
+    let temp_index = temp_index_path("snapshot");
+
+    // Start from the real index so unchanged files reuse cached stat data
+    let real_index = real_index_path(repo_root)?;
+    if real_index.exists() {
+        std::fs::copy(&real_index, &temp_index)
+            .with_context(|| format!("Failed to copy index {}", real_index.display()))?;
+    }
+
+    let result = run_git(repo_root, &["add", "-A"], Some(&temp_index))
+        .and_then(|_| run_git(repo_root, &["write-tree"], Some(&temp_index)));
+    let _ = std::fs::remove_file(&temp_index);
+
+    Ok(result?.trim().to_string())
+}
+
+/// Run a git command in `repo_root`, optionally against a different index
+pub(crate) fn run_git(
+    repo_root: &Path,
+    args: &[&str],
+    index_file: Option<&Path>,
+) -> Result<String> {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(repo_root);
+    if let Some(index_file) = index_file {
+        command.env("GIT_INDEX_FILE", index_file);
+    }
+
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run git command: git {}", args.join(" ")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "Git command failed: git {}\nError: {}",
+            args.join(" "),
+            stderr
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn init_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        fs::write(temp_dir.path().join("tracked.txt"), "original\n").unwrap();
+        git(&["add", "tracked.txt"]);
+        git(&["commit", "-q", "-m", "init"]);
+        temp_dir
+    }
+
+    #[test]
+    fn test_snapshot_detects_and_restores_changes() {
+        let temp_dir = init_repo();
+        let root = temp_dir.path();
+        fs::write(root.join("untracked.txt"), "keep me\n").unwrap();
+
+        let snapshot = WorktreeSnapshot::capture(root).unwrap();
+        assert!(snapshot.diff().unwrap().is_empty());
+
+        fs::write(root.join("tracked.txt"), "reformatted\n").unwrap();
+        fs::remove_file(root.join("untracked.txt")).unwrap();
+        fs::write(root.join("new.txt"), "generated\n").unwrap();
+
+        let diff = snapshot.diff().unwrap();
+        assert_eq!(diff.files.len(), 3);
+        assert!(
+            diff.files
+                .contains(&(PathBuf::from("tracked.txt"), SnapshotChange::Modified))
+        );
+        assert!(
+            diff.files
+                .contains(&(PathBuf::from("untracked.txt"), SnapshotChange::Deleted))
+        );
+        assert!(
+            diff.files
+                .contains(&(PathBuf::from("new.txt"), SnapshotChange::Added))
+        );
+        assert!(diff.stat.contains("tracked.txt"));
+
+        snapshot.restore(&diff).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "original\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("untracked.txt")).unwrap(),
+            "keep me\n"
+        );
+        assert!(!root.join("new.txt").exists());
+        assert!(snapshot.diff().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_restores_staged_changes() {
+        let temp_dir = init_repo();
+        let root = temp_dir.path();
+
+        let snapshot = WorktreeSnapshot::capture(root).unwrap();
+        fs::write(root.join("tracked.txt"), "reformatted\n").unwrap();
+        fs::write(root.join("new.txt"), "generated\n").unwrap();
+        run_git(root, &["add", "tracked.txt", "new.txt"], None).unwrap();
+
+        let diff = snapshot.diff().unwrap();
+        snapshot.restore(&diff).unwrap();
+
+        let staged = run_git(root, &["diff", "--cached", "--name-only"], None).unwrap();
+        assert_eq!(staged, "", "Staged changes should be rolled back");
+        let status = run_git(root, &["status", "--porcelain"], None).unwrap();
+        assert_eq!(status, "");
+    }
+}
@@ -0,0 +1,247 @@
+//! Setting unstaged changes aside for `run --stash`
+//!
+//! Unstaged changes to tracked files are saved as a patch under the git
+//! directory and removed from the working tree, so hooks see exactly what is
+//! being committed. Afterwards the patch is applied again, also when the run
+//! is cut short by an error. Untracked files are left alone.
+
+use super::snapshot::run_git;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Unstaged changes that were removed from the working tree
+///
+/// Dropping it without calling [`UnstagedStash::restore`] still puts the
+/// changes back, reporting any problem on stderr.
+pub struct UnstagedStash {
+    /// Git repository root
+    repo_root: PathBuf,
+    /// Patch holding the unstaged changes
+    patch: PathBuf,
+    /// Whether the changes still have to be put back
+    pending: bool,
+}
+
+impl UnstagedStash {
+    /// Save unstaged changes in the repository at `repo_root` and reset the
+    /// working tree to the index
+    ///
+    /// Returns `None` when there are no unstaged changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the patch cannot be written or the working tree
+    /// cannot be reset
+    pub fn save<P: AsRef<Path>>(repo_root: P) -> Result<Option<Self>> {
+        let repo_root = repo_root.as_ref().to_path_buf();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let name = format!("peter-hook-unstaged-{}-{}.patch", std::process::id(), now);
+        let patch = run_git(&repo_root, &["rev-parse", "--git-path", &name], None)?;
+        let patch = repo_root.join(patch.trim());
+
+        // Written with --output so non-UTF-8 content survives unchanged
+        let output = format!("--output={}", patch.display());
+        run_git(
+            &repo_root,
+            &[
+                "diff",
+                "--binary",
+                "--no-color",
+                "--no-ext-diff",
+                "--ignore-submodules",
+                &output,
+            ],
+            None,
+        )?;
+
+        let is_empty = std::fs::metadata(&patch).map_or(true, |meta| meta.len() == 0);
+        if is_empty {
+            let _ = std::fs::remove_file(&patch);
+            return Ok(None);
+        }
+
+        run_git(&repo_root, &["checkout", "--", "."], None).with_context(|| {
+            format!(
+                "Failed to set aside unstaged changes (saved in {})",
+                patch.display()
+            )
+        })?;
+
+        Ok(Some(Self {
+            repo_root,
+            patch,
+            pending: true,
+        }))
+    }
+
+    /// Path of the patch holding the unstaged changes
+    #[must_use]
+    pub const fn patch_path(&self) -> &PathBuf {
+        &self.patch
+    }
+
+    /// Put the unstaged changes back
+    ///
+    /// If hooks modified the same lines, both sides are kept: the hooks'
+    /// modifications are saved as a patch next to the unstaged one, the
+    /// working tree is reset so the unstaged changes can be applied, and the
+    /// path of the hooks' patch is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changes cannot be applied; the patches are kept
+    /// so they can be recovered by hand
+    pub fn restore(mut self) -> Result<Option<PathBuf>> {
+        self.put_back()
+    }
+
+    /// Apply the patch again, keeping conflicting hook modifications in a
+    /// patch of their own
+    fn put_back(&mut self) -> Result<Option<PathBuf>> {
+        self.pending = false;
+        let patch = self.patch.display().to_string();
+        let apply = ["apply", "--whitespace=nowarn", patch.as_str()];
+
+        let conflict = if run_git(&self.repo_root, &apply, None).is_ok() {
+            None
+        } else {
+            let hook_changes = self.patch.with_extension("hooks.patch");
+            let output = format!("--output={}", hook_changes.display());
+            run_git(
+                &self.repo_root,
+                &[
+                    "diff",
+                    "--binary",
+                    "--no-color",
+                    "--no-ext-diff",
+                    "--ignore-submodules",
+                    &output,
+                ],
+                None,
+            )
+            .and_then(|_| run_git(&self.repo_root, &["checkout", "--", "."], None))
+            .and_then(|_| run_git(&self.repo_root, &apply, None))
+            .with_context(|| {
+                format!(
+                    "Failed to restore unstaged changes; recover them with `git apply {patch}` \
+                     (hook changes: {})",
+                    hook_changes.display()
+                )
+            })?;
+            Some(hook_changes)
+        };
+
+        let _ = std::fs::remove_file(&self.patch);
+        Ok(conflict)
+    }
+}
+
+impl Drop for UnstagedStash {
+    fn drop(&mut self) {
+        if !self.pending {
+            return;
+        }
+        match self.put_back() {
+            Ok(None) => eprintln!("Restored unstaged changes"),
+            Ok(Some(hook_changes)) => eprintln!(
+                "Warning: hook changes conflicted with unstaged changes; they were saved in {}",
+                hook_changes.display()
+            ),
+            Err(e) => eprintln!("Error: {e:#}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, process::Command};
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    /// Repository with "one\ntwo\n" staged and "three\n" unstaged on top
+    fn partially_staged_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        git(root, &["init", "-q"]);
+        git(root, &["config", "user.email", "test@example.com"]);
+        git(root, &["config", "user.name", "Test"]);
+        fs::write(root.join("file.txt"), "one\n").unwrap();
+        git(root, &["add", "file.txt"]);
+        git(root, &["commit", "-q", "-m", "init"]);
+
+        fs::write(root.join("file.txt"), "one\ntwo\n").unwrap();
+        git(root, &["add", "file.txt"]);
+        fs::write(root.join("file.txt"), "one\ntwo\nthree\n").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_stash_hides_and_restores_unstaged_changes() {
+        let temp_dir = partially_staged_repo();
+        let root = temp_dir.path();
+
+        let stash = UnstagedStash::save(root).unwrap().unwrap();
+        assert!(stash.patch_path().exists());
+        assert_eq!(
+            fs::read_to_string(root.join("file.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+
+        assert!(stash.restore().unwrap().is_none());
+        assert_eq!(
+            fs::read_to_string(root.join("file.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        // Nothing unstaged left to set aside
+        git(root, &["add", "file.txt"]);
+        assert!(UnstagedStash::save(root).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stash_keeps_conflicting_hook_changes() {
+        let temp_dir = partially_staged_repo();
+        let root = temp_dir.path();
+
+        let stash = UnstagedStash::save(root).unwrap().unwrap();
+        // A hook rewrites the lines the unstaged change touches
+        fs::write(root.join("file.txt"), "one\nTWO\n").unwrap();
+
+        let hook_changes = stash.restore().unwrap().unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("file.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+        let hook_patch = fs::read_to_string(&hook_changes).unwrap();
+        assert!(hook_patch.contains("+TWO"), "{hook_patch}");
+    }
+
+    #[test]
+    fn test_stash_restores_on_drop() {
+        let temp_dir = partially_staged_repo();
+        let root = temp_dir.path();
+
+        let stash = UnstagedStash::save(root).unwrap().unwrap();
+        let patch = stash.patch_path().clone();
+        drop(stash);
+
+        assert_eq!(
+            fs::read_to_string(root.join("file.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+        assert!(!patch.exists());
+    }
+}
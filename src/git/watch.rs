@@ -0,0 +1,147 @@
+//! Watching the working tree for `run --watch`
+//!
+//! File system events under the repository root are collected until the tree
+//! has been quiet for a moment, so a burst of saves triggers a single run.
+//! Files in the git directory and files git ignores are left out.
+
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+/// How long events keep arriving after the last change that caused them
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// Recursive watch on a repository's working tree
+pub struct WorktreeWatcher {
+    /// Git repository root, canonicalized to match event paths
+    repo_root: PathBuf,
+    /// Repository used to check ignore rules
+    repo: git2::Repository,
+    /// Keeps the watch alive
+    _watcher: RecommendedWatcher,
+    /// File system events from the watcher
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl WorktreeWatcher {
+    /// Start watching the working tree of the repository at `repo_root`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened or the watch
+    /// cannot be set up
+    pub fn new<P: AsRef<Path>>(repo_root: P) -> Result<Self> {
+        let repo_root = repo_root.as_ref();
+        let repo_root = repo_root
+            .canonicalize()
+            .unwrap_or_else(|_| repo_root.to_path_buf());
+        let repo = git2::Repository::open(&repo_root)
+            .with_context(|| format!("Failed to open repository at {}", repo_root.display()))?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(sender).context("Failed to create file system watcher")?;
+        watcher
+            .watch(&repo_root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", repo_root.display()))?;
+
+        Ok(Self {
+            repo_root,
+            repo,
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Block until files change, then until no change has been seen for
+    /// `debounce`; returns the changed files that still exist, repo-relative
+    /// and sorted
+    ///
+    /// Reads and changes to ignored files neither count as changes nor
+    /// extend the debounce.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the watcher stops or reports a failure
+    pub fn wait_for_changes(&self, debounce: Duration) -> Result<Vec<PathBuf>> {
+        let mut changed = BTreeSet::new();
+        let mut settled_at: Option<Instant> = None;
+        loop {
+            let event = match settled_at {
+                None => self.events.recv().context("File system watcher stopped")?,
+                Some(settled_at) => {
+                    match self
+                        .events
+                        .recv_timeout(settled_at.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => {
+                            let existing: Vec<PathBuf> = std::mem::take(&mut changed)
+                                .into_iter()
+                                .filter(|file| self.repo_root.join(file).is_file())
+                                .collect();
+                            if !existing.is_empty() {
+                                return Ok(existing);
+                            }
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => {
+                            anyhow::bail!("File system watcher stopped")
+                        }
+                    }
+                }
+            };
+            if self.collect(event, &mut changed)? {
+                settled_at = Some(Instant::now() + debounce);
+            }
+        }
+    }
+
+    /// Drop the changes seen so far, e.g. those made by the hooks themselves
+    ///
+    /// Returns once nothing but reads has arrived for a moment.
+    pub fn discard_pending(&self) {
+        let mut settled_at = Instant::now() + SETTLE_TIME;
+        while let Ok(event) = self
+            .events
+            .recv_timeout(settled_at.saturating_duration_since(Instant::now()))
+        {
+            if !event.is_ok_and(|event| event.kind.is_access()) {
+                settled_at = Instant::now() + SETTLE_TIME;
+            }
+        }
+    }
+
+    /// Add the relevant paths of one event to `changed`, returning whether
+    /// there were any
+    fn collect(
+        &self,
+        event: notify::Result<notify::Event>,
+        changed: &mut BTreeSet<PathBuf>,
+    ) -> Result<bool> {
+        let event = event.context("File system watcher failed")?;
+        if event.kind.is_access() {
+            return Ok(false);
+        }
+        let mut relevant = false;
+        for path in event.paths {
+            let Ok(relative) = path.strip_prefix(&self.repo_root) else {
+                continue;
+            };
+            if relative.starts_with(".git") || relative.as_os_str().is_empty() {
+                continue;
+            }
+            if self.repo.is_path_ignored(relative).unwrap_or(false) {
+                continue;
+            }
+            changed.insert(relative.to_path_buf());
+            relevant = true;
+        }
+        Ok(relevant)
+    }
+}
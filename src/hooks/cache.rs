@@ -0,0 +1,137 @@
+//! Result cache for `cacheable` hooks
+//!
+//! A hook's inputs (its command, workdir and env, plus the path and contents
+//! of every file it would check) are hashed into one key. The outcome of the
+//! hook's last run is kept under the git directory, one file per hook, and a
+//! hook whose key matches a recorded pass does not need to run again. Any
+//! change to the inputs changes the key, so nothing has to be invalidated
+//! explicitly.
+
+use crate::{config::HookDefinition, git::run_git};
+use anyhow::{Context, Result};
+use git2::{ObjectType, Oid};
+use std::path::{Path, PathBuf};
+
+/// Recorded outcome of one hook's last run
+pub struct HookCache {
+    /// File holding `<key> pass` or `<key> fail`
+    path: PathBuf,
+}
+
+impl HookCache {
+    /// Cache entry for hook `name` defined in `config_file`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the git directory cannot be located
+    pub fn for_hook(repo_root: &Path, config_file: &Path, name: &str) -> Result<Self> {
+        let id = Oid::hash_object(
+            ObjectType::Blob,
+            format!("{}\0{name}", config_file.display()).as_bytes(),
+        )?;
+        let name = format!("peter-hook/cache/{id}");
+        let path = run_git(repo_root, &["rev-parse", "--git-path", &name], None)?;
+        Ok(Self {
+            path: repo_root.join(path.trim()),
+        })
+    }
+
+    /// Key for running `definition` on `files` (repo-relative) as they are
+    /// now on disk
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file exists but cannot be read
+    pub fn key(definition: &HookDefinition, repo_root: &Path, files: &[PathBuf]) -> Result<String> {
+        use std::fmt::Write as _;
+
+        let mut inputs = format!(
+            "command {}\0workdir {}\0",
+            definition.command,
+            definition.workdir.as_deref().unwrap_or_default()
+        );
+        let mut env: Vec<_> = definition.env.iter().flatten().collect();
+        env.sort();
+        for (key, value) in env {
+            let _ = write!(inputs, "env {key}={value}\0");
+        }
+
+        let mut files = files.to_vec();
+        files.sort();
+        for file in files {
+            let path = repo_root.join(&file);
+            let contents = if path.is_file() {
+                Oid::hash_file(ObjectType::Blob, &path)
+                    .with_context(|| format!("Failed to hash {}", path.display()))?
+                    .to_string()
+            } else {
+                "missing".to_string()
+            };
+            let _ = write!(inputs, "file {} {contents}\0", file.display());
+        }
+
+        Ok(Oid::hash_object(ObjectType::Blob, inputs.as_bytes())?.to_string())
+    }
+
+    /// Whether the last run had inputs `key` and passed
+    #[must_use]
+    pub fn is_cached_pass(&self, key: &str) -> bool {
+        std::fs::read_to_string(&self.path).is_ok_and(|entry| entry.trim() == format!("{key} pass"))
+    }
+
+    /// Record the outcome of a run with inputs `key`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be written
+    pub fn record(&self, key: &str, passed: bool) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let outcome = if passed { "pass" } else { "fail" };
+        std::fs::write(&self.path, format!("{key} {outcome}\n"))
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HookConfig;
+    use std::{fs, process::Command};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hook_cache_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        assert!(
+            Command::new("git")
+                .args(["init", "-q"])
+                .current_dir(root)
+                .status()
+                .unwrap()
+                .success()
+        );
+        fs::write(root.join("a.txt"), "one\n").unwrap();
+        let config = HookConfig::parse("[hooks.lint]\ncommand = \"lint\"\n").unwrap();
+        let definition = &config.hooks.as_ref().unwrap()["lint"];
+        let files = [PathBuf::from("a.txt")];
+
+        let cache = HookCache::for_hook(root, &root.join("hooks.toml"), "lint").unwrap();
+        let key = HookCache::key(definition, root, &files).unwrap();
+        assert!(!cache.is_cached_pass(&key));
+
+        cache.record(&key, false).unwrap();
+        assert!(!cache.is_cached_pass(&key));
+        cache.record(&key, true).unwrap();
+        assert!(cache.is_cached_pass(&key));
+
+        // Editing an input file changes the key
+        fs::write(root.join("a.txt"), "two\n").unwrap();
+        let edited = HookCache::key(definition, root, &files).unwrap();
+        assert_ne!(edited, key);
+        assert!(!cache.is_cached_pass(&edited));
+    }
+}
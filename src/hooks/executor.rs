@@ -1,31 +1,343 @@
 //! Hook execution engine
 
 use crate::{
-    config::{ExecutionStrategy, ExecutionType, HookCommand, TemplateResolver},
+    config::{
+        ConfigOverrides, ExecutionStrategy, ExecutionType, GitHookInputs, HookCommand,
+        HookDefinition, TemplateResolver,
+    },
     git::FilePatternMatcher,
-    hooks::{DependencyResolver, ResolvedHook, ResolvedHooks},
+    hooks::{DependencyResolver, HookCache, ResolvedHook, ResolvedHooks},
     output::formatter,
+    trace,
 };
 use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    ffi::OsString,
     io::IsTerminal,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     process::{Command, Stdio},
-    sync::{Arc, Mutex},
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+/// Settings of one run of hooks
+#[derive(Debug, Clone, Default)]
+pub struct ExecutorOptions {
+    /// Total wall-clock budget of the run (`--max-time`)
+    ///
+    /// Once the budget is spent, running hooks are killed and hooks that have
+    /// not started yet are not started; both are reported as cut off.
+    pub max_time: Option<Duration>,
+    /// Most hooks started at once by a parallel group or dependency phase
+    /// (`--jobs`; `None` starts them all together)
+    ///
+    /// Waiting hooks start by descending `priority`.
+    pub hook_jobs: Option<usize>,
+    /// Hook name and arguments appended to its command (`lint <name> --
+    /// ARGS`)
+    ///
+    /// They are added after template expansion, so they reach the hook as
+    /// given.
+    pub extra_args: Option<(String, Vec<String>)>,
+    /// Show a `[3/50 done, 2 failed]` line on stdout while hooks run, updated
+    /// in place as each one finishes
+    ///
+    /// It is only drawn when stdout is a TTY, and is cleared before any hook
+    /// output is printed.
+    pub progress: bool,
+    /// Files git passed to the hook, for templates and `stdin_from_git`
+    pub git_inputs: GitHookInputs,
+    /// Config selection to reload a hook's config with (`autofix_check`)
+    pub config_overrides: ConfigOverrides,
+}
+
+/// Most times an `autofix` hook runs before it must have stopped rewriting
+/// files
+const AUTOFIX_MAX_PASSES: usize = 3;
+
+/// Parent environment variables always forwarded to hook processes
+pub const DEFAULT_PASS_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    "LANG",
+    "LANGUAGE",
+    "SSH_AUTH_SOCK",
+];
+
+/// Prefixes of parent environment variables always forwarded to hook
+/// processes
+///
+/// These are the variables git sets for its hooks (`GIT_INDEX_FILE`,
+/// `GIT_DIR`, `GIT_AUTHOR_NAME`, ...), which e.g. `git commit -a` relies on
+/// for hooks to see the right index, and the locale categories.
+pub const DEFAULT_PASS_ENV_PREFIXES: &[&str] = &["GIT_", "LC_"];
+
+/// Progress line of a run, with its count of failed hooks
+type Progress = Mutex<Option<(ProgressBar, usize)>>;
+
+/// Clears the progress line of a run when the run ends
+struct ProgressGuard<'a>(&'a Progress);
+
+impl<'a> ProgressGuard<'a> {
+    /// Start the progress line for a run of `total` hooks
+    ///
+    /// `None` when progress is off, stdout is not a TTY, there is only one
+    /// hook, or a progress line is already showing.
+    fn start(progress: &'a Progress, enabled: bool, total: usize) -> Option<Self> {
+        if !enabled || total < 2 || !std::io::stdout().is_terminal() {
+            return None;
+        }
+        let mut line = progress.lock().unwrap_or_else(PoisonError::into_inner);
+        if line.is_some() {
+            return None;
+        }
+        let bar = ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stdout())
+            .with_style(
+                ProgressStyle::with_template("[{pos}/{len} done, {msg}]")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            )
+            .with_message("0 failed");
+        bar.tick();
+        *line = Some((bar, 0));
+        drop(line);
+        Some(Self(progress))
+    }
+
+    /// Count a finished hook on the progress line, if one is showing
+    fn record(progress: &Progress, failed: bool) {
+        let mut line = progress.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((bar, failures)) = line.as_mut() {
+            if failed {
+                *failures += 1;
+                bar.set_message(format!("{failures} failed"));
+            }
+            bar.inc(1);
+        }
+    }
+}
+
+impl Drop for ProgressGuard<'_> {
+    fn drop(&mut self) {
+        let line = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some((bar, _)) = line {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// Where a variable of a hook's environment comes from, in increasing
+/// precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvSource {
+    /// Forwarded from peter-hook's environment (`DEFAULT_PASS_ENV`,
+    /// `DEFAULT_PASS_ENV_PREFIXES`, `pass_env`)
+    Forwarded,
+    /// Read from the hook's `env_file`
+    EnvFile,
+    /// Set by the hook's inline `env`
+    Inline,
+}
+
+impl std::fmt::Display for EnvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Forwarded => "forwarded",
+            Self::EnvFile => "env_file",
+            Self::Inline => "env",
+        })
+    }
+}
+
+/// Parse the `KEY=VALUE` lines of an env file
+///
+/// Blank lines and `#` comments are skipped, a leading `export ` is ignored
+/// and values may be wrapped in matching single or double quotes.
+///
+/// # Errors
+///
+/// Returns an error naming the first line that is not `KEY=VALUE`
+fn parse_env_file(contents: &str) -> Result<Vec<(String, String)>> {
+    let mut variables = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .filter(|(key, _)| !key.trim().is_empty())
+            .with_context(|| format!("Line {} is not KEY=VALUE: {line}", number + 1))?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| {
+                value
+                    .strip_prefix(*quote)
+                    .and_then(|value| value.strip_suffix(*quote))
+            })
+            .unwrap_or(value);
+        variables.push((key.trim().to_string(), value.to_string()));
+    }
+    Ok(variables)
+}
+
+/// Process groups led by running hooks, to pass signals on to
+#[cfg(unix)]
+static LIVE_GROUPS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Keeps the process group of a running hook registered for signal
+/// forwarding until dropped
+pub(crate) struct LiveGroup(Option<u32>);
+
+impl LiveGroup {
+    /// Spawn `command`, registering the process group it leads when it was
+    /// built with `own_group` (see `HookExecutor::hook_command`)
+    ///
+    /// Signal forwarding starts on first use. The registry stays locked while
+    /// spawning, so a signal arriving meanwhile still reaches the new group.
+    pub(crate) fn spawn(
+        command: &mut Command,
+        own_group: bool,
+    ) -> std::io::Result<(std::process::Child, Self)> {
+        #[cfg(unix)]
+        if own_group {
+            forward_signals();
+            let mut groups = LIVE_GROUPS.lock().unwrap_or_else(PoisonError::into_inner);
+            let child = command.spawn()?;
+            groups.push(child.id());
+            drop(groups);
+            let pgid = child.id();
+            return Ok((child, Self(Some(pgid))));
+        }
+        #[cfg(not(unix))]
+        let _ = own_group;
+        Ok((command.spawn()?, Self(None)))
+    }
+}
+
+impl Drop for LiveGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.0 {
+            LIVE_GROUPS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|live| *live != pgid);
+        }
+    }
+}
+
+/// Pass SIGINT, SIGTERM and SIGHUP on to the process group of every running
+/// hook, then let the signal end peter-hook as it would by default
+///
+/// Hooks in their own process group do not get the terminal's Ctrl-C, and
+/// would otherwise outlive peter-hook.
+#[cfg(unix)]
+fn forward_signals() {
+    use rustix::process::{Pid, Signal, kill_process_group};
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    STARTED.call_once(|| {
+        let mut signals = match signal_hook::iterator::Signals::new([SIGINT, SIGTERM, SIGHUP]) {
+            Ok(signals) => signals,
+            Err(e) => {
+                eprintln!("Warning: signals will not be passed on to hooks: {e}");
+                return;
+            }
+        };
+        thread::spawn(move || {
+            for signal in signals.forever() {
+                let forwarded = match signal {
+                    SIGINT => Signal::INT,
+                    SIGTERM => Signal::TERM,
+                    _ => Signal::HUP,
+                };
+                for pgid in LIVE_GROUPS
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .iter()
+                {
+                    if let Some(pid) = i32::try_from(*pgid).ok().and_then(Pid::from_raw) {
+                        let _ = kill_process_group(pid, forwarded);
+                    }
+                }
+                let _ = signal_hook::low_level::emulate_default_handler(signal);
+            }
+        });
+    });
+}
+
 /// Executes resolved hooks
 pub struct HookExecutor {
     /// Whether to run hooks in parallel when possible
     #[allow(dead_code)]
     parallel: bool,
+    /// Settings of the run
+    options: ExecutorOptions,
+    /// End of the run budget, counted from the executor's creation
+    deadline: Option<Instant>,
+    /// Progress line of the run, while one is showing
+    progress: Progress,
+}
+
+/// A hook that was killed for exceeding its timeout
+///
+/// Carries the full report, including partial output; results built from it
+/// get exit code [`crate::exit_code::TIMEOUT`].
+#[derive(Debug)]
+pub struct HookTimeout {
+    /// Report of the timeout and the output captured before the kill
+    message: String,
+}
+
+impl std::fmt::Display for HookTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for HookTimeout {}
+
+/// A hook that was killed, or never started, because the run budget
+/// (`--max-time`) was spent
+#[derive(Debug)]
+struct BudgetExhausted {
+    /// Report of the kill and the output captured before it
+    message: String,
+}
+
+impl std::fmt::Display for BudgetExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BudgetExhausted {}
+
+/// Exit code recorded for a hook that could not run to completion
+fn execution_error_code(error: &anyhow::Error) -> i32 {
+    if error.downcast_ref::<HookTimeout>().is_some() {
+        crate::exit_code::TIMEOUT
+    } else {
+        -1
+    }
 }
 
 /// Result of hook execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ExecutionResult {
     /// Exit code of the hook
     pub exit_code: i32,
@@ -35,28 +347,72 @@ pub struct ExecutionResult {
     pub stderr: String,
     /// Whether the hook succeeded (exit code 0)
     pub success: bool,
+    /// Whether the hook failed but has `allow_failure = true`, so the failure
+    /// does not fail the run
+    pub allowed_failure: bool,
+    /// Config file that defined the hook (repo-relative when possible)
+    pub source_file: PathBuf,
+    /// The hook's `description`, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Wall-clock time spent running the hook
+    pub duration: Duration,
+    /// Whether the hook was not run because its inputs match a cached pass
+    /// (`cacheable = true`)
+    pub cached: bool,
+    /// Whether the hook was killed or not started because the run budget
+    /// (`--max-time`) was spent
+    pub cut_off: bool,
+}
+
+impl ExecutionResult {
+    /// Whether this result fails the run (a failure not covered by
+    /// `allow_failure`)
+    #[must_use]
+    pub const fn is_blocking_failure(&self) -> bool {
+        !self.success && !self.allowed_failure
+    }
 }
 
 /// Results from executing multiple hooks
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExecutionResults {
     /// Results for each hook by name
     pub results: HashMap<String, ExecutionResult>,
-    /// Overall success (all hooks succeeded)
+    /// Overall success (no hook failed without `allow_failure`)
     pub success: bool,
+    /// Descriptions of the groups that were run, in execution order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub group_descriptions: Vec<String>,
 }
 
 impl HookExecutor {
     /// Create a new hook executor
     #[must_use]
-    pub const fn new() -> Self {
-        Self { parallel: false }
+    pub fn new() -> Self {
+        Self::with_options(ExecutorOptions::default())
     }
 
     /// Create a new hook executor with parallel execution enabled
     #[must_use]
-    pub const fn with_parallel() -> Self {
-        Self { parallel: true }
+    pub fn with_parallel() -> Self {
+        Self {
+            parallel: true,
+            ..Self::new()
+        }
+    }
+
+    /// Create a hook executor for a run with `options`
+    ///
+    /// The run budget starts now.
+    #[must_use]
+    pub fn with_options(options: ExecutorOptions) -> Self {
+        Self {
+            parallel: false,
+            deadline: options.max_time.map(|budget| Instant::now() + budget),
+            options,
+            progress: Mutex::new(None),
+        }
     }
 
     /// Execute multiple configuration groups (for hierarchical resolution)
@@ -81,12 +437,21 @@ impl HookExecutor {
     ///
     /// Returns an error if any hook fails to execute due to system issues
     /// (e.g., command not found, permission denied)
-    pub fn execute_multiple(groups: &[crate::hooks::ConfigGroup]) -> Result<ExecutionResults> {
+    pub fn execute_multiple(
+        &self,
+        groups: &[crate::hooks::ConfigGroup],
+    ) -> Result<ExecutionResults> {
+        let _progress = ProgressGuard::start(
+            &self.progress,
+            self.options.progress,
+            Self::hook_count(groups),
+        );
         let mut all_results = HashMap::new();
         let mut overall_success = true;
+        let mut group_descriptions = Vec::new();
 
         for group in groups {
-            let results = Self::execute(&group.resolved_hooks).with_context(|| {
+            let results = self.execute(&group.resolved_hooks).with_context(|| {
                 format!(
                     "Failed to execute hooks from config: {}",
                     group.config_path.display()
@@ -98,13 +463,9 @@ impl HookExecutor {
             }
 
             // Merge results (prefix hook names with config path for uniqueness if needed)
+            group_descriptions.extend(results.group_descriptions);
             for (name, result) in results.results {
-                // If we have multiple configs, prefix the hook name to avoid collisions
-                let unique_name = if groups.len() > 1 {
-                    format!("{}:{}", group.config_path.display(), name)
-                } else {
-                    name
-                };
+                let unique_name = Self::result_key(groups.len(), &group.config_path, &name);
                 all_results.insert(unique_name, result);
             }
 
@@ -117,16 +478,169 @@ impl HookExecutor {
         Ok(ExecutionResults {
             results: all_results,
             success: overall_success,
+            group_descriptions,
         })
     }
 
+    /// Execute multiple configuration groups, up to `jobs` at a time
+    ///
+    /// Groups from different configs touch different directories, so they can
+    /// run concurrently; each group still follows its own execution strategy,
+    /// so modifying hooks stay serialized within their group. Once a group
+    /// fails, no further groups are started, but groups already running
+    /// finish. With `jobs <= 1` this is the same as `execute_multiple`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a worker thread panics while recording its result
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any hook fails to execute due to system issues
+    pub fn execute_multiple_parallel(
+        &self,
+        groups: &[crate::hooks::ConfigGroup],
+        jobs: usize,
+    ) -> Result<ExecutionResults> {
+        if jobs <= 1 || groups.len() <= 1 {
+            return self.execute_multiple(groups);
+        }
+        let _progress = ProgressGuard::start(
+            &self.progress,
+            self.options.progress,
+            Self::hook_count(groups),
+        );
+
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let outcomes = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for _ in 0..jobs.min(groups.len()) {
+                scope.spawn(|| {
+                    while !failed.load(Ordering::SeqCst) {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        let Some(group) = groups.get(index) else {
+                            break;
+                        };
+                        let outcome = self.execute(&group.resolved_hooks);
+                        if !outcome.as_ref().is_ok_and(|results| results.success) {
+                            failed.store(true, Ordering::SeqCst);
+                        }
+                        outcomes.lock().unwrap().push((index, outcome));
+                    }
+                });
+            }
+        });
+
+        let mut outcomes = outcomes.into_inner().unwrap();
+        outcomes.sort_by_key(|(index, _)| *index);
+
+        let mut all_results = HashMap::new();
+        let mut overall_success = true;
+        let mut group_descriptions = Vec::new();
+        for (index, outcome) in outcomes {
+            let group = &groups[index];
+            let results = outcome.with_context(|| {
+                format!(
+                    "Failed to execute hooks from config: {}",
+                    group.config_path.display()
+                )
+            })?;
+
+            overall_success &= results.success;
+            group_descriptions.extend(results.group_descriptions);
+            for (name, result) in results.results {
+                let unique_name = Self::result_key(groups.len(), &group.config_path, &name);
+                all_results.insert(unique_name, result);
+            }
+        }
+
+        Ok(ExecutionResults {
+            results: all_results,
+            success: overall_success,
+            group_descriptions,
+        })
+    }
+
+    /// Number of hooks across all `groups`
+    fn hook_count(groups: &[crate::hooks::ConfigGroup]) -> usize {
+        groups
+            .iter()
+            .map(|group| group.resolved_hooks.hooks.len())
+            .sum()
+    }
+
+    /// Name under which a hook's result is stored by `execute_multiple`
+    ///
+    /// With multiple configs, the hook name is prefixed with the config path
+    /// to avoid collisions.
+    fn result_key(group_count: usize, config_path: &Path, name: &str) -> String {
+        if group_count > 1 {
+            format!("{}:{}", config_path.display(), name)
+        } else {
+            name.to_string()
+        }
+    }
+
     /// Execute all resolved hooks using their configured execution strategy
     ///
+    /// A group's `setup` hook runs first; if it fails, no other hook runs. The
+    /// `teardown` hook runs last, whatever happened before it, and its failure
+    /// fails the run. Neither runs when no hook of the group is left to run.
+    ///
     /// # Errors
     ///
     /// Returns an error if any hook fails to execute due to system issues
     /// (not hook failure - that's reported in the results)
-    pub fn execute(resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
+    pub fn execute(&self, resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
+        if resolved_hooks.hooks.is_empty() {
+            return self.execute_group_hooks(resolved_hooks);
+        }
+
+        let run_step = |(name, hook): &(String, ResolvedHook)| {
+            self.execute_single_hook(name, hook, &resolved_hooks.worktree_context, None)
+                .with_context(|| format!("Failed to run group step: {name}"))
+        };
+
+        let mut outcome = match &resolved_hooks.setup {
+            Some(setup) => {
+                let mut result = run_step(setup)?;
+                if result.success {
+                    self.execute_group_hooks(resolved_hooks).map(|mut results| {
+                        results.results.insert(setup.0.clone(), result);
+                        results
+                    })
+                } else {
+                    // A failed setup aborts the group, even with allow_failure
+                    result.allowed_failure = false;
+                    Ok(ExecutionResults {
+                        results: HashMap::from([(setup.0.clone(), result)]),
+                        success: false,
+                        group_descriptions: resolved_hooks.description.iter().cloned().collect(),
+                    })
+                }
+            }
+            None => self.execute_group_hooks(resolved_hooks),
+        };
+
+        if let Some(teardown) = &resolved_hooks.teardown {
+            let result = run_step(teardown);
+            if let Ok(results) = &mut outcome {
+                let result = result?;
+                results.success &= !result.is_blocking_failure();
+                results.results.insert(teardown.0.clone(), result);
+            }
+        }
+        outcome
+    }
+
+    /// Execute a group's hooks, without its `setup` and `teardown`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any hook fails to execute due to system issues
+    fn execute_group_hooks(&self, resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
         // Check if we need dependency resolution
         let needs_dependencies = resolved_hooks
             .hooks
@@ -134,9 +648,9 @@ impl HookExecutor {
             .any(|hook| hook.definition.depends_on.is_some());
 
         if needs_dependencies {
-            Self::execute_with_dependencies(resolved_hooks)
+            self.execute_with_dependencies(resolved_hooks)
         } else {
-            Self::execute_with_strategy(resolved_hooks, resolved_hooks.execution_strategy)
+            self.execute_with_strategy(resolved_hooks, resolved_hooks.execution_strategy)
         }
     }
 
@@ -146,31 +660,33 @@ impl HookExecutor {
     ///
     /// Returns an error if any hook fails to execute due to system issues
     pub fn execute_with_strategy(
+        &self,
         resolved_hooks: &ResolvedHooks,
         strategy: ExecutionStrategy,
     ) -> Result<ExecutionResults> {
         match strategy {
-            ExecutionStrategy::Sequential => Self::execute_sequential(resolved_hooks),
-            ExecutionStrategy::Parallel => Self::execute_parallel_safe(resolved_hooks),
-            ExecutionStrategy::ForceParallel => Ok(Self::execute_parallel_unsafe(resolved_hooks)),
+            ExecutionStrategy::Sequential => self.execute_sequential(resolved_hooks),
+            ExecutionStrategy::Parallel => self.execute_parallel_safe(resolved_hooks),
+            ExecutionStrategy::ForceParallel => Ok(self.execute_parallel_unsafe(resolved_hooks)),
         }
     }
 
     /// Execute hooks sequentially (original behavior)
-    fn execute_sequential(resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
+    fn execute_sequential(&self, resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
         let mut results = HashMap::new();
         let mut overall_success = true;
 
-        for (name, hook) in &resolved_hooks.hooks {
-            let result = Self::execute_single_hook(
-                name,
-                hook,
-                &resolved_hooks.worktree_context,
-                resolved_hooks.changed_files.as_deref(),
-            )
-            .with_context(|| format!("Failed to execute hook: {name}"))?;
+        for (name, hook) in Self::in_run_order(resolved_hooks.hooks.iter()) {
+            let result = self
+                .execute_single_hook(
+                    name,
+                    hook,
+                    &resolved_hooks.worktree_context,
+                    resolved_hooks.changed_files.as_deref(),
+                )
+                .with_context(|| format!("Failed to execute hook: {name}"))?;
 
-            if !result.success {
+            if result.is_blocking_failure() {
                 overall_success = false;
             }
 
@@ -180,11 +696,25 @@ impl HookExecutor {
         Ok(ExecutionResults {
             results,
             success: overall_success,
+            group_descriptions: resolved_hooks.description.iter().cloned().collect(),
         })
     }
 
+    /// Sort hooks that run one after another by their `order` (default 0),
+    /// then by name
+    fn in_run_order<'a, N: AsRef<str>>(
+        hooks: impl IntoIterator<Item = (N, &'a ResolvedHook)>,
+    ) -> Vec<(N, &'a ResolvedHook)> {
+        let mut hooks: Vec<_> = hooks.into_iter().collect();
+        hooks.sort_by(|(a_name, a), (b_name, b)| {
+            (a.definition.order.unwrap_or(0), a_name.as_ref())
+                .cmp(&(b.definition.order.unwrap_or(0), b_name.as_ref()))
+        });
+        hooks
+    }
+
     /// Execute hooks in parallel, respecting repository modification safety
-    fn execute_parallel_safe(resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
+    fn execute_parallel_safe(&self, resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
         // Separate hooks into safe-to-parallelize and repository-modifying
         let mut safe_hooks = Vec::new();
         let mut modifying_hooks = Vec::new();
@@ -197,274 +727,738 @@ impl HookExecutor {
             }
         }
 
-        let results = Arc::new(Mutex::new(HashMap::new()));
-        let overall_success = Arc::new(Mutex::new(true));
-
         // First, run all safe hooks in parallel
-        if !safe_hooks.is_empty() {
-            let mut handles = Vec::new();
-
-            for (name, hook) in safe_hooks {
-                let name = name.clone();
-                let hook = hook.clone();
-                let results = Arc::clone(&results);
-                let overall_success = Arc::clone(&overall_success);
-
-                let worktree_context = resolved_hooks.worktree_context.clone();
-                let changed_files = resolved_hooks.changed_files.clone();
-                let handle = thread::spawn(move || {
-                    match Self::execute_single_hook(
-                        &name,
-                        &hook,
-                        &worktree_context,
-                        changed_files.as_deref(),
-                    ) {
-                        Ok(result) => {
-                            let success = result.success;
-                            results.lock().unwrap().insert(name, result);
-                            if !success {
-                                *overall_success.lock().unwrap() = false;
+        let (mut results, mut overall_success) =
+            self.execute_concurrently(resolved_hooks, safe_hooks);
+
+        // Then, run repository-modifying hooks sequentially
+        for (name, hook) in Self::in_run_order(modifying_hooks) {
+            let result = self
+                .execute_single_hook(
+                    &name,
+                    hook,
+                    &resolved_hooks.worktree_context,
+                    resolved_hooks.changed_files.as_deref(),
+                )
+                .with_context(|| format!("Failed to execute hook: {name}"))?;
+
+            if result.is_blocking_failure() {
+                overall_success = false;
+            }
+
+            results.insert(name.clone(), result);
+        }
+
+        Ok(ExecutionResults {
+            results,
+            success: overall_success,
+            group_descriptions: resolved_hooks.description.iter().cloned().collect(),
+        })
+    }
+
+    /// Execute all hooks in parallel (unsafe - ignores repository modification)
+    fn execute_parallel_unsafe(&self, resolved_hooks: &ResolvedHooks) -> ExecutionResults {
+        let hooks = resolved_hooks
+            .hooks
+            .iter()
+            .map(|(name, hook)| (name.clone(), hook))
+            .collect();
+        let (results, success) = self.execute_concurrently(resolved_hooks, hooks);
+
+        ExecutionResults {
+            results,
+            success,
+            group_descriptions: resolved_hooks.description.iter().cloned().collect(),
+        }
+    }
+
+    /// Run `hooks` at the same time, at most `--jobs` of them at once when a
+    /// limit is set
+    ///
+    /// Hooks are started by descending `priority` (default 0), then in name
+    /// order, so long-running hooks can start first. An execution error
+    /// becomes a failed result. Returns the results and whether no hook
+    /// failed.
+    fn execute_concurrently(
+        &self,
+        resolved_hooks: &ResolvedHooks,
+        hooks: Vec<(String, &ResolvedHook)>,
+    ) -> (HashMap<String, ExecutionResult>, bool) {
+        let mut hooks = hooks;
+        hooks.sort_by(|(a_name, a), (b_name, b)| {
+            (b.definition.priority.unwrap_or(0), a_name)
+                .cmp(&(a.definition.priority.unwrap_or(0), b_name))
+        });
+        let workers = self
+            .options
+            .hook_jobs
+            .map_or(hooks.len(), |jobs| jobs.max(1).min(hooks.len()));
+
+        let next = AtomicUsize::new(0);
+        let success = AtomicBool::new(true);
+        let results = Mutex::new(HashMap::new());
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        while let Some((name, hook)) =
+                            hooks.get(next.fetch_add(1, Ordering::SeqCst))
+                        {
+                            let result = self
+                                .execute_single_hook(
+                                    name,
+                                    hook,
+                                    &resolved_hooks.worktree_context,
+                                    resolved_hooks.changed_files.as_deref(),
+                                )
+                                .unwrap_or_else(|e| ExecutionResult {
+                                    exit_code: execution_error_code(&e),
+                                    stdout: String::new(),
+                                    stderr: format!("Execution error: {e:#}"),
+                                    success: false,
+                                    allowed_failure: false,
+                                    source_file: hook.source_file.clone(),
+                                    description: hook.definition.description.clone(),
+                                    duration: Duration::ZERO,
+                                    cached: false,
+                                    cut_off: false,
+                                });
+                            if result.is_blocking_failure() {
+                                success.store(false, Ordering::SeqCst);
                             }
+                            results.lock().unwrap().insert(name.clone(), result);
                         }
-                        Err(e) => {
-                            // Create a failed result for execution errors
-                            let result = ExecutionResult {
-                                exit_code: -1,
-                                stdout: String::new(),
-                                stderr: format!("Execution error: {e:#}"),
-                                success: false,
-                            };
-                            results.lock().unwrap().insert(name, result);
-                            *overall_success.lock().unwrap() = false;
-                        }
-                    }
-                });
-                handles.push(handle);
-            }
+                    })
+                })
+                .collect();
 
-            // Wait for all parallel hooks to complete
+            // A panicking hook thread fails the run instead of aborting it
             for handle in handles {
                 if handle.join().is_err() {
-                    *overall_success.lock().unwrap() = false;
+                    success.store(false, Ordering::SeqCst);
                 }
             }
+        });
+
+        (
+            results.into_inner().unwrap_or_else(PoisonError::into_inner),
+            success.into_inner(),
+        )
+    }
+
+    /// Execute hooks respecting dependencies
+    #[allow(clippy::too_many_lines)]
+    fn execute_with_dependencies(
+        &self,
+        resolved_hooks: &ResolvedHooks,
+    ) -> Result<ExecutionResults> {
+        let mut resolver = DependencyResolver::new();
+        let hook_names: Vec<String> = resolved_hooks.hooks.keys().cloned().collect();
+
+        // Build dependency graph
+        for (name, hook) in &resolved_hooks.hooks {
+            let dependencies = hook.definition.depends_on.clone().unwrap_or_default();
+            resolver.add_hook(name.clone(), dependencies);
         }
 
-        // Then, run repository-modifying hooks sequentially
-        for (name, hook) in modifying_hooks {
-            let result = Self::execute_single_hook(
-                &name,
-                hook,
-                &resolved_hooks.worktree_context,
-                resolved_hooks.changed_files.as_deref(),
-            )
-            .with_context(|| format!("Failed to execute hook: {name}"))?;
+        // Resolve execution plan
+        let plan = resolver
+            .resolve(&hook_names)
+            .context("Failed to resolve hook dependencies")?;
 
-            if !result.success {
-                *overall_success.lock().unwrap() = false;
+        let mut all_results = HashMap::new();
+        let mut overall_success = true;
+
+        // Execute hooks phase by phase
+        for phase in &plan.phases {
+            let mut phase_results = HashMap::new();
+
+            if phase.parallel && phase.hooks.len() > 1 {
+                // Execute phase hooks in parallel
+                let hooks = phase
+                    .hooks
+                    .iter()
+                    .map(|hook_name| (hook_name.clone(), &resolved_hooks.hooks[hook_name]))
+                    .collect();
+                let (results, phase_success) = self.execute_concurrently(resolved_hooks, hooks);
+                phase_results = results;
+
+                if !phase_success {
+                    overall_success = false;
+                    // Stop execution if any hook in this phase failed
+                    all_results.extend(phase_results);
+                    break;
+                }
+            } else {
+                // Execute phase hooks sequentially
+                let phase_hooks = phase
+                    .hooks
+                    .iter()
+                    .map(|hook_name| (hook_name, &resolved_hooks.hooks[hook_name]));
+                for (hook_name, hook) in Self::in_run_order(phase_hooks) {
+                    let result = self
+                        .execute_single_hook(
+                            hook_name,
+                            hook,
+                            &resolved_hooks.worktree_context,
+                            resolved_hooks.changed_files.as_deref(),
+                        )
+                        .with_context(|| format!("Failed to execute hook: {hook_name}"))?;
+
+                    let success = !result.is_blocking_failure();
+                    phase_results.insert(hook_name.clone(), result);
+
+                    if !success {
+                        // Stop execution if hook failed
+                        all_results.extend(phase_results);
+                        return Ok(ExecutionResults {
+                            results: all_results,
+                            success: false,
+                            group_descriptions: resolved_hooks
+                                .description
+                                .iter()
+                                .cloned()
+                                .collect(),
+                        });
+                    }
+                }
             }
 
-            results.lock().unwrap().insert(name.clone(), result);
+            all_results.extend(phase_results);
         }
 
-        let results = Arc::try_unwrap(results).unwrap().into_inner().unwrap();
-        let overall_success = Arc::try_unwrap(overall_success)
-            .unwrap()
-            .into_inner()
-            .unwrap();
-
         Ok(ExecutionResults {
-            results,
+            results: all_results,
             success: overall_success,
+            group_descriptions: resolved_hooks.description.iter().cloned().collect(),
         })
     }
 
-    /// Execute all hooks in parallel (unsafe - ignores repository modification)
-    fn execute_parallel_unsafe(resolved_hooks: &ResolvedHooks) -> ExecutionResults {
-        let results = Arc::new(Mutex::new(HashMap::new()));
-        let overall_success = Arc::new(Mutex::new(true));
-        let mut handles = Vec::new();
+    /// Execute a single hook, counting it on the progress line
+    fn execute_single_hook(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult> {
+        let result = self.run_single_hook(name, hook, worktree_context, changed_files);
+        ProgressGuard::record(
+            &self.progress,
+            result.as_ref().map_or(true, |result| !result.success),
+        );
+        result
+    }
 
-        for (name, hook) in &resolved_hooks.hooks {
-            let name = name.clone();
-            let hook = hook.clone();
-            let results = Arc::clone(&results);
-            let overall_success = Arc::clone(&overall_success);
-
-            let worktree_context = resolved_hooks.worktree_context.clone();
-            let changed_files = resolved_hooks.changed_files.clone();
-            let handle = thread::spawn(move || {
-                match Self::execute_single_hook(
-                    &name,
-                    &hook,
-                    &worktree_context,
-                    changed_files.as_deref(),
-                ) {
-                    Ok(result) => {
-                        let success = result.success;
-                        results.lock().unwrap().insert(name, result);
-                        if !success {
-                            *overall_success.lock().unwrap() = false;
-                        }
-                    }
-                    Err(e) => {
-                        let result = ExecutionResult {
-                            exit_code: -1,
-                            stdout: String::new(),
-                            stderr: format!("Execution error: {e:#}"),
-                            success: false,
-                        };
-                        results.lock().unwrap().insert(name, result);
-                        *overall_success.lock().unwrap() = false;
-                    }
+    /// The environment a hook's command receives, by variable name, with
+    /// where each value comes from
+    ///
+    /// Forwarded variables are overridden by `env_file` entries, which the
+    /// inline `env` overrides in turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `env_file` cannot be read or a template
+    /// cannot be expanded
+    pub fn hook_environment(
+        &self,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+    ) -> Result<BTreeMap<String, (String, EnvSource)>> {
+        let config_dir = hook
+            .source_file
+            .parent()
+            .context("Hook source file has no parent directory")?;
+        let template_resolver = TemplateResolver::with_worktree_context(
+            config_dir,
+            &hook.working_directory,
+            worktree_context,
+        )
+        .with_git_inputs(&self.options.git_inputs);
+        let mut environment: BTreeMap<_, _> = Self::forwarded_env(&hook.definition)
+            .into_iter()
+            .map(|(name, value)| {
+                (
+                    name,
+                    (value.to_string_lossy().into_owned(), EnvSource::Forwarded),
+                )
+            })
+            .collect();
+        environment.extend(Self::configured_env(hook, &template_resolver)?);
+        Ok(environment)
+    }
+
+    /// The parent variables forwarded to a hook: `DEFAULT_PASS_ENV`, those
+    /// starting with a `DEFAULT_PASS_ENV_PREFIXES` entry, and its
+    /// `pass_env`, where set
+    fn forwarded_env(definition: &HookDefinition) -> Vec<(String, OsString)> {
+        let pass_env = definition.pass_env.as_deref().unwrap_or_default();
+        std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value)))
+            .filter(|(name, _)| {
+                DEFAULT_PASS_ENV.contains(&name.as_str())
+                    || DEFAULT_PASS_ENV_PREFIXES
+                        .iter()
+                        .any(|prefix| name.starts_with(prefix))
+                    || pass_env.contains(name)
+            })
+            .collect()
+    }
+
+    /// The variables a hook sets itself: its `env_file` entries, overridden
+    /// by its inline `env`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `env_file` cannot be read or parsed, or a
+    /// template cannot be expanded
+    fn configured_env(
+        hook: &ResolvedHook,
+        template_resolver: &TemplateResolver,
+    ) -> Result<BTreeMap<String, (String, EnvSource)>> {
+        let mut environment = BTreeMap::new();
+        if let Some(env_file) = &hook.definition.env_file {
+            let env_file = template_resolver
+                .resolve_string(env_file)
+                .context("Failed to resolve env_file template")?;
+            // Relative paths are relative to the config file's directory
+            let path = hook
+                .source_file
+                .parent()
+                .map_or_else(|| PathBuf::from(&env_file), |dir| dir.join(&env_file));
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read env_file {}", path.display()))?;
+            let variables = parse_env_file(&contents)
+                .with_context(|| format!("Invalid env_file {}", path.display()))?;
+            for (key, value) in variables {
+                environment.insert(key, (value, EnvSource::EnvFile));
+            }
+        }
+        if let Some(env) = &hook.definition.env {
+            let resolved_env = template_resolver
+                .resolve_env(env)
+                .context("Failed to resolve environment variable templates")?;
+            for (key, value) in resolved_env {
+                environment.insert(key, (value, EnvSource::Inline));
+            }
+        }
+        Ok(environment)
+    }
+
+    /// Expand every template in a hook's command, workdir, env and `env_file`
+    /// path without running anything
+    ///
+    /// Changed-file variables expand to empty values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first template that cannot be expanded
+    /// (e.g. an unknown variable)
+    pub fn check_templates(
+        &self,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+    ) -> Result<()> {
+        let config_dir = hook
+            .source_file
+            .parent()
+            .context("Hook source file has no parent directory")?;
+        let template_resolver = TemplateResolver::with_worktree_context(
+            config_dir,
+            &hook.working_directory,
+            worktree_context,
+        )
+        .with_git_inputs(&self.options.git_inputs);
+
+        match &hook.definition.command {
+            HookCommand::Shell(cmd) => {
+                template_resolver
+                    .resolve_string(cmd)
+                    .context("Failed to resolve command template")?;
+            }
+            HookCommand::Args(args) => {
+                template_resolver
+                    .resolve_command_args(args)
+                    .context("Failed to resolve command arguments")?;
+            }
+            HookCommand::Steps { steps } => {
+                for step in steps {
+                    template_resolver
+                        .resolve_string(step)
+                        .with_context(|| format!("Failed to resolve step template: {step}"))?;
                 }
-            });
-            handles.push(handle);
+            }
+        }
+        if let Some(workdir_template) = &hook.definition.workdir {
+            template_resolver
+                .resolve_string(workdir_template)
+                .context("Failed to resolve workdir template")?;
+        }
+        if let Some(env) = &hook.definition.env {
+            template_resolver
+                .resolve_env(env)
+                .context("Failed to resolve environment variable templates")?;
         }
+        if let Some(env_file) = &hook.definition.env_file {
+            template_resolver
+                .resolve_string(env_file)
+                .context("Failed to resolve env_file template")?;
+        }
+        Ok(())
+    }
 
-        // Wait for all hooks to complete
-        for handle in handles {
-            if handle.join().is_err() {
-                *overall_success.lock().unwrap() = false;
+    /// Execute a single hook
+    #[allow(clippy::too_many_lines, clippy::option_if_let_else)]
+    fn run_single_hook(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult> {
+        let started = std::time::Instant::now();
+        let cache = self.result_cache(name, hook, worktree_context, changed_files);
+        if let Some((cache, key)) = &cache {
+            if cache.is_cached_pass(key) {
+                return Ok(ExecutionResult {
+                    exit_code: 0,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    success: true,
+                    allowed_failure: false,
+                    source_file: PathBuf::new(),
+                    description: hook.definition.description.clone(),
+                    duration: started.elapsed(),
+                    cached: true,
+                    cut_off: false,
+                });
             }
         }
 
-        let results = Arc::try_unwrap(results).unwrap().into_inner().unwrap();
-        let overall_success = Arc::try_unwrap(overall_success)
-            .unwrap()
-            .into_inner()
-            .unwrap();
+        if self.budget_spent() {
+            return Ok(Self::cut_off_result(
+                format!("Hook '{name}' was not started: the run budget (--max-time) was spent\n"),
+                hook,
+            ));
+        }
 
-        ExecutionResults {
-            results,
-            success: overall_success,
+        let dirty = if hook.definition.require_clean_worktree {
+            Self::unstaged_hook_files(hook, &worktree_context.repo_root)?
+        } else {
+            Vec::new()
+        };
+        let outcome = if !dirty.is_empty() {
+            let files: Vec<String> = dirty.iter().map(|f| f.display().to_string()).collect();
+            Ok(ExecutionResult {
+                exit_code: 1,
+                stdout: String::new(),
+                stderr: format!(
+                    "Refusing to run (require_clean_worktree): unstaged changes to {}\n",
+                    files.join(", ")
+                ),
+                success: false,
+                allowed_failure: false,
+                source_file: PathBuf::new(),
+                description: None,
+                duration: Duration::ZERO,
+                cached: false,
+                cut_off: false,
+            })
+        } else if hook.definition.autofix {
+            self.execute_autofix(name, hook, worktree_context, changed_files)
+        } else {
+            self.execute_command(name, hook, worktree_context, changed_files)
+        };
+        let mut result = match outcome {
+            Err(e) if e.downcast_ref::<BudgetExhausted>().is_some() => {
+                Self::cut_off_result(format!("{e:#}\n"), hook)
+            }
+            outcome => outcome?,
+        };
+        if dirty.is_empty() && !result.cut_off {
+            Self::apply_output_filters(&hook.definition, &mut result)?;
+        }
+        if result.success {
+            Self::apply_warning_threshold(&hook.definition, &mut result)?;
+        }
+        result.duration = started.elapsed();
+        result.allowed_failure = !result.success && hook.definition.allow_failure;
+        result.source_file = hook
+            .source_file
+            .strip_prefix(&worktree_context.repo_root)
+            .unwrap_or(&hook.source_file)
+            .to_path_buf();
+        result.description.clone_from(&hook.definition.description);
+
+        if let Some((cache, _)) = &cache {
+            // Hooks may have changed their inputs (e.g. formatters), so the
+            // outcome is recorded for the files as they are now
+            let relevant =
+                Self::filter_relevant_files(hook, changed_files, &worktree_context.repo_root);
+            let recorded = HookCache::key(&hook.definition, &worktree_context.repo_root, &relevant)
+                .and_then(|key| cache.record(&key, result.success));
+            if let Err(e) = recorded {
+                eprintln!("Warning: failed to cache result of hook '{name}': {e:#}");
+            }
+        }
+        Ok(result)
+    }
+
+    /// The extra arguments for hook `name`
+    fn extra_args(&self, name: &str) -> &[String] {
+        self.options
+            .extra_args
+            .as_ref()
+            .filter(|(hook, _)| hook == name)
+            .map_or(&[], |(_, args)| args)
+    }
+
+    /// Append hook `name`'s extra arguments to its resolved command
+    ///
+    /// Shell commands get them single-quoted at the end of the script.
+    fn append_extra_args(&self, name: &str, command_parts: &mut Vec<String>, shell: bool) {
+        let args = self.extra_args(name);
+        match command_parts.last_mut() {
+            Some(script) if shell => {
+                for arg in args {
+                    script.push_str(" '");
+                    script.push_str(&arg.replace('\'', r"'\''"));
+                    script.push('\'');
+                }
+            }
+            _ => command_parts.extend_from_slice(args),
         }
     }
 
-    /// Execute hooks respecting dependencies
-    fn execute_with_dependencies(resolved_hooks: &ResolvedHooks) -> Result<ExecutionResults> {
-        let mut resolver = DependencyResolver::new();
-        let hook_names: Vec<String> = resolved_hooks.hooks.keys().cloned().collect();
-
-        // Build dependency graph
-        for (name, hook) in &resolved_hooks.hooks {
-            let dependencies = hook.definition.depends_on.clone().unwrap_or_default();
-            resolver.add_hook(name.clone(), dependencies);
+    /// Result cache entry and current input key for a `cacheable` hook
+    ///
+    /// `None` if the hook is not cacheable, the run has no file list to key
+    /// on, or the cache is unavailable (e.g. outside a git repository).
+    fn result_cache(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files: Option<&[PathBuf]>,
+    ) -> Option<(HookCache, String)> {
+        // Extra arguments change what the hook checks
+        if !hook.definition.cacheable || !self.extra_args(name).is_empty() {
+            return None;
         }
+        let changed_files = changed_files?;
+        let repo_root = &worktree_context.repo_root;
+        let relevant = Self::filter_relevant_files(hook, Some(changed_files), repo_root);
+        let cache = HookCache::for_hook(repo_root, &hook.source_file, name).ok()?;
+        let key = HookCache::key(&hook.definition, repo_root, &relevant).ok()?;
+        Some((cache, key))
+    }
 
-        // Resolve execution plan
-        let plan = resolver
-            .resolve(&hook_names)
-            .context("Failed to resolve hook dependencies")?;
-
-        let mut all_results = HashMap::new();
-        let mut overall_success = true;
+    /// Files with unstaged changes that the hook would consider
+    ///
+    /// Unstaged modifications (`git diff --name-only -z`) are filtered by the
+    /// hook's file patterns; a hook without patterns considers every file.
+    fn unstaged_hook_files(hook: &ResolvedHook, repo_root: &Path) -> Result<Vec<PathBuf>> {
+        let output = crate::git::run_git(repo_root, &["diff", "--name-only", "-z"], None)
+            .context("Failed to check for unstaged changes")?;
+        let unstaged: Vec<PathBuf> = output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        Ok(Self::filter_relevant_files(
+            hook,
+            Some(&unstaged),
+            repo_root,
+        ))
+    }
 
-        // Execute hooks phase by phase
-        for phase in &plan.phases {
-            let mut phase_results = HashMap::new();
+    /// Drop the lines of a result's stdout and stderr that do not match the
+    /// hook's `output_filter` or that match its `output_exclude`
+    fn apply_output_filters(
+        definition: &HookDefinition,
+        result: &mut ExecutionResult,
+    ) -> Result<()> {
+        let compile = |pattern: Option<&str>| {
+            pattern
+                .map(|pattern| {
+                    regex::Regex::new(pattern)
+                        .with_context(|| format!("Invalid output filter: {pattern}"))
+                })
+                .transpose()
+        };
+        let keep = compile(definition.output_filter.as_deref())?;
+        let drop = compile(definition.output_exclude.as_deref())?;
+        if keep.is_none() && drop.is_none() {
+            return Ok(());
+        }
+        let filter = |output: &str| {
+            let mut kept = String::new();
+            for line in output
+                .lines()
+                .filter(|line| keep.as_ref().is_none_or(|keep| keep.is_match(line)))
+                .filter(|line| !drop.as_ref().is_some_and(|drop| drop.is_match(line)))
+            {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            kept
+        };
+        result.stdout = filter(&result.stdout);
+        result.stderr = filter(&result.stderr);
+        Ok(())
+    }
 
-            if phase.parallel && phase.hooks.len() > 1 {
-                // Execute phase hooks in parallel
-                let results = Arc::new(Mutex::new(HashMap::new()));
-                let phase_success = Arc::new(Mutex::new(true));
-                let mut handles = Vec::new();
-
-                for hook_name in &phase.hooks {
-                    let hook = &resolved_hooks.hooks[hook_name];
-                    let name = hook_name.clone();
-                    let hook = hook.clone();
-                    let results = Arc::clone(&results);
-                    let phase_success = Arc::clone(&phase_success);
-
-                    let worktree_context = resolved_hooks.worktree_context.clone();
-                    let changed_files = resolved_hooks.changed_files.clone();
-                    let handle = thread::spawn(move || {
-                        match Self::execute_single_hook(
-                            &name,
-                            &hook,
-                            &worktree_context,
-                            changed_files.as_deref(),
-                        ) {
-                            Ok(result) => {
-                                let success = result.success;
-                                results.lock().unwrap().insert(name, result);
-                                if !success {
-                                    *phase_success.lock().unwrap() = false;
-                                }
-                            }
-                            Err(e) => {
-                                let result = ExecutionResult {
-                                    exit_code: -1,
-                                    stdout: String::new(),
-                                    stderr: format!("Execution error: {e:#}"),
-                                    success: false,
-                                };
-                                results.lock().unwrap().insert(name, result);
-                                *phase_success.lock().unwrap() = false;
-                            }
-                        }
-                    });
-                    handles.push(handle);
-                }
+    /// Fail a passing result whose output has more warnings than the hook's
+    /// `max_warnings`
+    ///
+    /// Lines of stdout and stderr matching `warning_pattern` are counted.
+    fn apply_warning_threshold(
+        definition: &HookDefinition,
+        result: &mut ExecutionResult,
+    ) -> Result<()> {
+        use std::fmt::Write as _;
+
+        let Some(max_warnings) = definition.max_warnings else {
+            return Ok(());
+        };
+        let pattern = definition
+            .warning_pattern
+            .as_deref()
+            .unwrap_or(r"(?i)\bwarning\b");
+        let pattern = regex::Regex::new(pattern)
+            .with_context(|| format!("Invalid warning pattern: {pattern}"))?;
+        let warnings = result
+            .stdout
+            .lines()
+            .chain(result.stderr.lines())
+            .filter(|line| pattern.is_match(line))
+            .count();
+        if warnings > max_warnings {
+            result.success = false;
+            result.exit_code = 1;
+            let _ = writeln!(
+                result.stderr,
+                "{warnings} warnings exceed max_warnings = {max_warnings}"
+            );
+        }
+        Ok(())
+    }
 
-                // Wait for all hooks in this phase
-                for handle in handles {
-                    if handle.join().is_err() {
-                        *phase_success.lock().unwrap() = false;
-                    }
-                }
+    /// Execute a hook's command or `steps`
+    fn execute_command(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult> {
+        if let HookCommand::Steps { steps } = &hook.definition.command {
+            self.execute_steps(name, hook, steps, worktree_context, changed_files)
+        } else {
+            self.execute_by_type(name, hook, worktree_context, changed_files)
+        }
+    }
 
-                phase_results = Arc::try_unwrap(results).unwrap().into_inner().unwrap();
-                let phase_success = Arc::try_unwrap(phase_success)
-                    .unwrap()
-                    .into_inner()
-                    .unwrap();
+    /// Execute an `autofix` hook
+    ///
+    /// The hook runs again while a pass rewrites any of its files (at most
+    /// `AUTOFIX_MAX_PASSES` times), and the rewritten files are staged after
+    /// every pass. A failing pass is only retried if it rewrote something, so
+    /// formatters that exit non-zero after fixing files settle on the next
+    /// pass. With an `autofix_check`, that hook runs last and decides the
+    /// outcome.
+    fn execute_autofix(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult> {
+        use std::fmt::Write as _;
 
-                if !phase_success {
-                    overall_success = false;
-                    // Stop execution if any hook in this phase failed
-                    all_results.extend(phase_results);
-                    break;
-                }
-            } else {
-                // Execute phase hooks sequentially
-                for hook_name in &phase.hooks {
-                    let hook = &resolved_hooks.hooks[hook_name];
-                    let result = Self::execute_single_hook(
-                        hook_name,
-                        hook,
-                        &resolved_hooks.worktree_context,
-                        resolved_hooks.changed_files.as_deref(),
-                    )
-                    .with_context(|| format!("Failed to execute hook: {hook_name}"))?;
+        let repo_root = &worktree_context.repo_root;
+        let files = Self::filter_relevant_files(hook, changed_files, repo_root);
+        let hash = |file: &PathBuf| {
+            git2::Oid::hash_file(git2::ObjectType::Blob, repo_root.join(file)).ok()
+        };
 
-                    let success = result.success;
-                    phase_results.insert(hook_name.clone(), result);
+        let mut staged = std::collections::BTreeSet::new();
+        let mut passes = 0;
+        let mut result = loop {
+            let before: Vec<_> = files.iter().map(hash).collect();
+            let result = self.execute_command(name, hook, worktree_context, changed_files)?;
+            passes += 1;
 
-                    if !success {
-                        // Stop execution if hook failed
-                        all_results.extend(phase_results);
-                        return Ok(ExecutionResults {
-                            results: all_results,
-                            success: false,
-                        });
-                    }
+            let rewritten: Vec<&str> = files
+                .iter()
+                .zip(&before)
+                .filter(|&(file, before)| hash(file) != *before)
+                .filter_map(|(file, _)| file.to_str())
+                .collect();
+            if rewritten.is_empty() {
+                break result;
+            }
+            let mut args = vec!["add", "--"];
+            args.extend(&rewritten);
+            crate::git::run_git(repo_root, &args, None)
+                .with_context(|| format!("Failed to stage files fixed by hook {name}"))?;
+            staged.extend(rewritten.iter().map(ToString::to_string));
+
+            if passes == AUTOFIX_MAX_PASSES {
+                let mut result = result;
+                result.success = false;
+                if result.exit_code == 0 {
+                    result.exit_code = 1;
                 }
+                let _ = writeln!(
+                    result.stderr,
+                    "Hook '{name}' still rewrote files after {AUTOFIX_MAX_PASSES} passes: {}",
+                    rewritten.join(", ")
+                );
+                break result;
             }
-
-            all_results.extend(phase_results);
+        };
+        let fixed = !staged.is_empty();
+        if fixed {
+            let staged: Vec<_> = staged.into_iter().collect();
+            let _ = writeln!(result.stdout, "autofix: staged {}", staged.join(", "));
         }
 
-        Ok(ExecutionResults {
-            results: all_results,
-            success: overall_success,
-        })
+        let Some(check_name) = &hook.definition.autofix_check else {
+            return Ok(result);
+        };
+        if !result.success && !fixed {
+            // The hook failed without fixing anything, so there is nothing
+            // for the check to confirm
+            return Ok(result);
+        }
+        let config = crate::config::HookConfig::from_file_cached(
+            &hook.source_file,
+            &self.options.config_overrides,
+        )?;
+        let definition = config
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.get(check_name))
+            .with_context(|| format!("autofix_check '{check_name}' of hook {name} not found"))?;
+        let mut check_hook = hook.clone();
+        check_hook.definition = definition.clone();
+        let check =
+            self.execute_command(check_name, &check_hook, worktree_context, changed_files)?;
+
+        result.stdout.push_str(&check.stdout);
+        result.stderr.push_str(&check.stderr);
+        result.exit_code = check.exit_code;
+        result.success = check.success;
+        if !check.success {
+            let _ = writeln!(result.stderr, "autofix_check '{check_name}' failed");
+        }
+        Ok(result)
     }
 
-    /// Execute a single hook
-    #[allow(clippy::too_many_lines, clippy::option_if_let_else)]
-    fn execute_single_hook(
+    /// Execute a hook's command according to its execution type
+    fn execute_by_type(
+        &self,
         name: &str,
         hook: &ResolvedHook,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
@@ -472,26 +1466,127 @@ impl HookExecutor {
     ) -> Result<ExecutionResult> {
         match hook.definition.execution_type {
             ExecutionType::PerFile => {
-                Self::execute_per_file_hook(name, hook, worktree_context, changed_files)
+                self.execute_per_file_hook(name, hook, worktree_context, changed_files)
             }
             ExecutionType::InPlace => {
-                Self::execute_in_place_hook(name, hook, worktree_context, changed_files)
+                self.execute_in_place_hook(name, hook, worktree_context, changed_files)
             }
             ExecutionType::Other => {
-                Self::execute_other_hook(name, hook, worktree_context, changed_files)
+                self.execute_other_hook(name, hook, worktree_context, changed_files)
+            }
+        }
+    }
+
+    /// Execute a hook's `steps` one at a time, stopping at the first failure
+    ///
+    /// Each step runs as if it were the hook's only (shell) command, so
+    /// templates and the execution type apply to every step. Output of the
+    /// steps that ran is combined, and a failing step is named in stderr.
+    fn execute_steps(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        steps: &[String],
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult> {
+        use std::fmt::Write as _;
+
+        if steps.is_empty() {
+            return Err(anyhow::anyhow!("Empty command for hook: {name}"));
+        }
+
+        let mut combined = ExecutionResult {
+            exit_code: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            success: true,
+            allowed_failure: false,
+            source_file: PathBuf::new(),
+            description: None,
+            duration: Duration::ZERO,
+            cached: false,
+            cut_off: false,
+        };
+
+        for (index, step) in steps.iter().enumerate() {
+            let mut step_hook = hook.clone();
+            step_hook.definition.command = HookCommand::Shell(step.clone());
+            let result = self
+                .execute_by_type(name, &step_hook, worktree_context, changed_files)
+                .with_context(|| format!("Failed to run step {} of hook {name}", index + 1))?;
+
+            combined.stdout.push_str(&result.stdout);
+            combined.stderr.push_str(&result.stderr);
+            if !result.success {
+                combined.exit_code = result.exit_code;
+                combined.success = false;
+                let _ = writeln!(
+                    combined.stderr,
+                    "Step {} of {} failed: {step}",
+                    index + 1,
+                    steps.len()
+                );
+                break;
+            }
+        }
+
+        Ok(combined)
+    }
+
+    /// Read a hook's output stream to the end, keeping at most `max_bytes`
+    ///
+    /// Past the cap only the head and tail are kept, joined by a
+    /// `[... N bytes truncated ...]` marker. The stream is still drained so the
+    /// hook never blocks on a full pipe.
+    fn read_capped<R: std::io::Read>(mut reader: R, max_bytes: usize) -> Vec<u8> {
+        let head_len = max_bytes / 2;
+        let tail_len = max_bytes - head_len;
+        let mut head = Vec::new();
+        let mut tail = VecDeque::<u8>::new();
+        let mut total = 0usize;
+        let mut chunk = [0u8; 8192];
+
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            total = total.saturating_add(read);
+
+            let mut data = &chunk[..read];
+            if head.len() < head_len {
+                let take = (head_len - head.len()).min(data.len());
+                head.extend_from_slice(&data[..take]);
+                data = &data[take..];
             }
+            tail.extend(data);
+            if tail.len() > tail_len {
+                tail.drain(..tail.len() - tail_len);
+            }
+        }
+
+        if total > max_bytes {
+            let truncated = total - head.len() - tail.len();
+            head.extend_from_slice(format!("\n[... {truncated} bytes truncated ...]\n").as_bytes());
         }
+        head.extend(tail);
+        head
     }
 
     /// Execute hook with files passed as individual arguments (per-file mode)
     fn execute_per_file_hook(
+        &self,
         name: &str,
         hook: &ResolvedHook,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
         changed_files: Option<&[PathBuf]>,
     ) -> Result<ExecutionResult> {
         // Get relevant changed files based on hook's file patterns
-        let relevant_changed = Self::filter_relevant_files(hook, changed_files);
+        let relevant_changed =
+            Self::filter_relevant_files(hook, changed_files, &worktree_context.repo_root);
 
         // Skip execution if no files match (whether pattern specified or not)
         if relevant_changed.is_empty() && !hook.definition.run_always {
@@ -500,6 +1595,12 @@ impl HookExecutor {
                 stdout: String::new(),
                 stderr: String::new(),
                 success: true,
+                allowed_failure: false,
+                source_file: PathBuf::new(),
+                description: None,
+                duration: Duration::ZERO,
+                cached: false,
+                cut_off: false,
             });
         }
 
@@ -515,6 +1616,7 @@ impl HookExecutor {
             &relevant_changed,
             &worktree_context.repo_root,
             execution_dir,
+            hook.relative_paths,
         );
 
         // Build base command without template resolution (per-file doesn't use
@@ -527,7 +1629,8 @@ impl HookExecutor {
             config_dir,
             &hook.working_directory,
             worktree_context,
-        );
+        )
+        .with_git_inputs(&self.options.git_inputs);
 
         let mut base_command_parts = match &hook.definition.command {
             HookCommand::Shell(cmd) => {
@@ -544,7 +1647,15 @@ impl HookExecutor {
                     .resolve_command_args(args)
                     .context("Failed to resolve command arguments")?
             }
+            HookCommand::Steps { .. } => {
+                return Err(anyhow::anyhow!("Steps of hook {name} run one at a time"));
+            }
         };
+        self.append_extra_args(
+            name,
+            &mut base_command_parts,
+            matches!(hook.definition.command, HookCommand::Shell(_)),
+        );
 
         // Add transformed files as individual arguments
         for file in &transformed_files {
@@ -552,19 +1663,21 @@ impl HookExecutor {
         }
 
         // Execute the command with file arguments
-        Self::execute_command_parts(name, hook, worktree_context, &base_command_parts)
+        self.execute_command_parts(name, hook, worktree_context, &base_command_parts)
     }
 
     /// Execute hook once in config directory without file arguments (in-place
     /// mode)
     fn execute_in_place_hook(
+        &self,
         name: &str,
         hook: &ResolvedHook,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
         changed_files: Option<&[PathBuf]>,
     ) -> Result<ExecutionResult> {
         // Get relevant changed files for filtering check
-        let relevant_changed = Self::filter_relevant_files(hook, changed_files);
+        let relevant_changed =
+            Self::filter_relevant_files(hook, changed_files, &worktree_context.repo_root);
 
         // Skip execution if no files match (whether pattern specified or not)
         if relevant_changed.is_empty() && !hook.definition.run_always {
@@ -573,6 +1686,12 @@ impl HookExecutor {
                 stdout: String::new(),
                 stderr: String::new(),
                 success: true,
+                allowed_failure: false,
+                source_file: PathBuf::new(),
+                description: None,
+                duration: Duration::ZERO,
+                cached: false,
+                cut_off: false,
             });
         }
 
@@ -585,9 +1704,10 @@ impl HookExecutor {
             config_dir,
             &hook.working_directory,
             worktree_context,
-        );
+        )
+        .with_git_inputs(&self.options.git_inputs);
 
-        let command_parts = match &hook.definition.command {
+        let mut command_parts = match &hook.definition.command {
             HookCommand::Shell(cmd) => {
                 let resolved_cmd = template_resolver
                     .resolve_string(cmd)
@@ -602,42 +1722,52 @@ impl HookExecutor {
                     .resolve_command_args(args)
                     .context("Failed to resolve command arguments")?
             }
+            HookCommand::Steps { .. } => {
+                return Err(anyhow::anyhow!("Steps of hook {name} run one at a time"));
+            }
         };
+        self.append_extra_args(
+            name,
+            &mut command_parts,
+            matches!(hook.definition.command, HookCommand::Shell(_)),
+        );
 
         // Execute once in the config directory (or custom workdir)
-        Self::execute_command_parts(name, hook, worktree_context, &command_parts)
+        self.execute_command_parts(name, hook, worktree_context, &command_parts)
     }
 
     /// Execute hook using template variables (other/manual mode) - original
     /// behavior
     fn execute_other_hook(
+        &self,
         name: &str,
         hook: &ResolvedHook,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
         changed_files: Option<&[PathBuf]>,
     ) -> Result<ExecutionResult> {
         // This is the original implementation - delegate to the original logic
-        Self::execute_original_hook(name, hook, worktree_context, changed_files)
+        self.execute_original_hook(name, hook, worktree_context, changed_files)
     }
 
-    /// Filter files based on hook's file patterns
+    /// Filter files based on hook's file and content patterns
+    ///
+    /// Content patterns read files relative to `repo_root`.
     fn filter_relevant_files(
         hook: &ResolvedHook,
         changed_files: Option<&[PathBuf]>,
+        repo_root: &Path,
     ) -> Vec<PathBuf> {
         let Some(cf) = changed_files else {
             return Vec::new();
         };
 
-        hook.definition.files.as_ref().map_or_else(
-            || cf.to_vec(),
-            |patterns| {
-                FilePatternMatcher::new(patterns).map_or_else(
-                    |_| cf.to_vec(),
-                    |matcher| cf.iter().filter(|p| matcher.matches(p)).cloned().collect(),
-                )
-            },
-        )
+        match FilePatternMatcher::for_hook(&hook.definition, repo_root) {
+            Ok(Some(matcher)) => {
+                let matcher = matcher.with_scope(hook.file_scope.as_deref());
+                cf.iter().filter(|p| matcher.matches(p)).cloned().collect()
+            }
+            Ok(None) | Err(_) => cf.to_vec(),
+        }
     }
 
     /// Transform file paths from repo-relative to execution-directory-relative
@@ -651,11 +1781,20 @@ impl HookExecutor {
     /// - Execution dir: /repo/projects/backend
     /// - Git path: projects/backend/test.py
     /// - Transformed: test.py
+    ///
+    /// `relative_paths` picks the behavior for files outside the execution
+    /// directory: `None` leaves them repo-relative, `Some(true)` rewrites them
+    /// with `..` components, and `Some(false)` keeps every path repo-relative.
     fn transform_file_paths(
         files: &[PathBuf],
         repo_root: &Path,
         execution_dir: &Path,
+        relative_paths: Option<bool>,
     ) -> Vec<PathBuf> {
+        if relative_paths == Some(false) {
+            return files.to_vec();
+        }
+
         files
             .iter()
             .map(|file| {
@@ -664,24 +1803,50 @@ impl HookExecutor {
 
                 // Try to strip the execution directory prefix to get relative path
                 // If execution_dir is not a prefix of abs_file, keep the original path
-                abs_file
-                    .strip_prefix(execution_dir)
-                    .map_or_else(|_| file.clone(), std::path::Path::to_path_buf)
+                // unless relative paths were requested
+                abs_file.strip_prefix(execution_dir).map_or_else(
+                    |_| {
+                        if relative_paths == Some(true) {
+                            Self::relative_to(&abs_file, execution_dir)
+                        } else {
+                            file.clone()
+                        }
+                    },
+                    std::path::Path::to_path_buf,
+                )
             })
             .collect()
     }
 
+    /// Express `path` relative to `base`, stepping up with `..` as needed
+    fn relative_to(path: &Path, base: &Path) -> PathBuf {
+        let path_components: Vec<_> = path.components().collect();
+        let base_components: Vec<_> = base.components().collect();
+        let common = path_components
+            .iter()
+            .zip(&base_components)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut relative = PathBuf::new();
+        for _ in common..base_components.len() {
+            relative.push("..");
+        }
+        for component in &path_components[common..] {
+            relative.push(component);
+        }
+        relative
+    }
+
     /// Execute command parts with proper setup
     #[allow(clippy::too_many_lines)]
     fn execute_command_parts(
+        &self,
         name: &str,
         hook: &ResolvedHook,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
         command_parts: &[String],
     ) -> Result<ExecutionResult> {
-        use std::io::Read;
-        use wait_timeout::ChildExt;
-
         if command_parts.is_empty() {
             return Err(anyhow::anyhow!("Empty command for hook: {name}"));
         }
@@ -694,37 +1859,33 @@ impl HookExecutor {
             config_dir,
             &hook.working_directory,
             worktree_context,
-        );
-
-        // Build command
-        let mut command = Command::new(&command_parts[0]);
-        if command_parts.len() > 1 {
-            command.args(&command_parts[1..]);
-        }
+        )
+        .with_git_inputs(&self.options.git_inputs);
 
         // Set working directory
         let working_dir = if let Some(workdir_template) = &hook.definition.workdir {
             let resolved_workdir = template_resolver
                 .resolve_string(workdir_template)
                 .context("Failed to resolve workdir template")?;
-            PathBuf::from(resolved_workdir)
+            // Relative workdirs are relative to the config file's directory
+            config_dir.join(resolved_workdir)
         } else if hook.definition.run_at_root {
             // If run_at_root is true, use the repository root
             worktree_context.repo_root.clone()
         } else {
             hook.working_directory.clone()
         };
-        command.current_dir(&working_dir);
 
-        // Set environment variables
-        if let Some(env) = &hook.definition.env {
-            let resolved_env = template_resolver
-                .resolve_env(env)
-                .context("Failed to resolve environment variable templates")?;
-            for (key, value) in resolved_env {
-                command.env(key, value);
-            }
-        }
+        // Build command, with its working directory and environment
+        let mut command = self.prepare_command(
+            name,
+            hook,
+            command_parts,
+            &working_dir,
+            worktree_context,
+            &template_resolver,
+            None,
+        )?;
 
         // Configure stdio
         command.stdout(Stdio::piped());
@@ -732,45 +1893,38 @@ impl HookExecutor {
 
         // Debug output
         if crate::debug::is_enabled() {
-            if std::io::stderr().is_terminal() {
+            if crate::debug::decorated() {
                 eprintln!(
                     "\x1b[38;5;220m⚡ \x1b[1m\x1b[38;5;196mEXECUTING:\x1b[0m \
                      \x1b[38;5;226m{name}\x1b[0m"
                 );
                 eprintln!("\x1b[38;5;75m🎬 Command: \x1b[38;5;155m{command_parts:?}\x1b[0m");
             } else {
-                eprintln!("[DEBUG] Executing hook: {name}");
-                eprintln!("[DEBUG] Command: {command_parts:?}");
+                trace!(level: crate::debug::TRACE_PHASE, "Executing hook: {name}");
+                trace!(level: crate::debug::TRACE_PHASE, "Command: {command_parts:?}");
             }
         }
 
         // Execute command with timeout
-        let timeout = std::time::Duration::from_secs(hook.definition.timeout_seconds);
-        let mut child = command
-            .spawn()
+        let started = std::time::Instant::now();
+        let (mut child, _group) = LiveGroup::spawn(&mut command, self.needs_own_group(hook))
             .with_context(|| format!("Failed to spawn hook command: {name}"))?;
 
         // Take stdout and stderr handles before waiting
-        let mut stdout_handle = child.stdout.take().context("Failed to capture stdout")?;
-        let mut stderr_handle = child.stderr.take().context("Failed to capture stderr")?;
+        let stdout_handle = child.stdout.take().context("Failed to capture stdout")?;
+        let stderr_handle = child.stderr.take().context("Failed to capture stderr")?;
 
         // Spawn threads to read stdout and stderr in parallel
         // This prevents deadlocks from full pipe buffers
-        let stdout_thread = std::thread::spawn(move || {
-            let mut buf = Vec::new();
-            stdout_handle.read_to_end(&mut buf).ok();
-            buf
-        });
-
-        let stderr_thread = std::thread::spawn(move || {
-            let mut buf = Vec::new();
-            stderr_handle.read_to_end(&mut buf).ok();
-            buf
-        });
+        let max_output = usize::try_from(hook.definition.max_output_bytes).unwrap_or(usize::MAX);
+        let stdout_thread =
+            std::thread::spawn(move || Self::read_capped(stdout_handle, max_output));
+        let stderr_thread =
+            std::thread::spawn(move || Self::read_capped(stderr_handle, max_output));
 
         // Wait for the command with timeout
-        let status_option = child
-            .wait_timeout(timeout)
+        let status_option = self
+            .wait_for_hook(&mut child, hook.definition.timeout_seconds)
             .with_context(|| format!("Failed to wait for hook command: {name}"))?;
 
         let (exit_code, stdout, stderr, success) = if let Some(status) = status_option {
@@ -785,25 +1939,31 @@ impl HookExecutor {
 
             (exit_code, stdout, stderr, success)
         } else {
-            // Timeout occurred - kill the process
-            let _ = child.kill();
-            let _ = child.wait(); // Reap the process
+            // Timeout occurred - terminate the process, escalating to SIGKILL
+            let elapsed = started.elapsed();
+            Self::terminate_child(
+                &mut child,
+                Duration::from_secs(hook.definition.kill_grace_seconds),
+            );
 
             // Still try to collect partial output
             let stdout_buf = stdout_thread.join().unwrap_or_default();
             let stderr_buf = stderr_thread.join().unwrap_or_default();
-            let stdout = String::from_utf8_lossy(&stdout_buf);
-            let stderr = String::from_utf8_lossy(&stderr_buf);
 
-            return Err(anyhow::anyhow!(
-                "Hook '{name}' exceeded timeout of {} seconds and was killed\nPartial stdout: \
-                 {stdout}\nPartial stderr: {stderr}",
-                hook.definition.timeout_seconds
+            if self.budget_spent() {
+                return Err(Self::budget_error(name, elapsed, &stdout_buf, &stderr_buf));
+            }
+            return Err(Self::timeout_error(
+                name,
+                hook.definition.timeout_seconds,
+                elapsed,
+                &stdout_buf,
+                &stderr_buf,
             ));
         };
 
         // Debug output for result
-        if crate::debug::is_enabled() && std::io::stderr().is_terminal() {
+        if crate::debug::is_enabled() && crate::debug::decorated() {
             if success {
                 eprintln!(
                     "\x1b[38;5;46m🎉 \x1b[1m\x1b[38;5;82mSUCCESS:\x1b[0m \
@@ -822,41 +1982,46 @@ impl HookExecutor {
             stdout,
             stderr,
             success,
+            allowed_failure: false,
+            source_file: PathBuf::new(),
+            description: None,
+            duration: Duration::ZERO,
+            cached: false,
+            cut_off: false,
         })
     }
 
     /// Create temporary file for changed files list
+    ///
+    /// The file is created even when there are no changed files, so
+    /// `{CHANGED_FILES_FILE}` always names a readable (possibly empty) file.
     fn create_changed_files_temp_file(relevant_changed: &[PathBuf]) -> Option<PathBuf> {
-        if relevant_changed.is_empty() {
-            None
-        } else {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos();
-            let tmp_path = std::env::temp_dir().join(format!(
-                "peter-hook-changed-{}-{}.lst",
-                std::process::id(),
-                now
-            ));
-            let changed_list = relevant_changed
-                .iter()
-                .map(|p| p.to_string_lossy())
-                .collect::<Vec<_>>()
-                .join("\n");
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let tmp_path = std::env::temp_dir().join(format!(
+            "peter-hook-changed-{}-{}.lst",
+            std::process::id(),
+            now
+        ));
+        let changed_list = relevant_changed
+            .iter()
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\n");
 
-            if std::fs::write(&tmp_path, &changed_list).is_ok() {
-                Some(tmp_path)
-            } else {
-                None
-            }
+        if std::fs::write(&tmp_path, &changed_list).is_ok() {
+            Some(tmp_path)
+        } else {
+            None
         }
     }
 
     /// Print debug output for changed files
     fn print_changed_files_debug(name: &str, relevant_changed: &[PathBuf]) {
         if crate::debug::is_enabled() {
-            if std::io::stderr().is_terminal() {
+            if crate::debug::decorated() {
                 eprintln!(
                     "\x1b[38;5;200m🎯 \x1b[1m\x1b[38;5;51mExecuting hook:\x1b[0m \
                      \x1b[38;5;226m{name}\x1b[0m"
@@ -878,33 +2043,399 @@ impl HookExecutor {
                     );
                 }
             } else {
-                eprintln!("[DEBUG] Executing hook: {name}");
-                eprintln!(
-                    "[DEBUG]   Files matching patterns: {}",
+                trace!(level: crate::debug::TRACE_PHASE, "Executing hook: {name}");
+                trace!(level: crate::debug::TRACE_PHASE,
+                    "Files matching patterns: {}",
                     relevant_changed.len()
                 );
                 for file in relevant_changed {
-                    eprintln!("[DEBUG]     {}", file.display());
+                    trace!(level: crate::debug::TRACE_PHASE, "  {}", file.display());
+                }
+            }
+        }
+    }
+
+    /// Start building the command for a hook program
+    ///
+    /// With `nice`, the program is started through `nice -n` so it runs at
+    /// lower (or higher) priority. Platforms without `nice` run the program
+    /// as is and warn once. With `own_group` on Unix the hook leads its own
+    /// process group, so `terminate_child` can stop everything it started;
+    /// spawn it with [`LiveGroup::spawn`] so signals peter-hook receives reach
+    /// it too.
+    pub(crate) fn hook_command<S: AsRef<std::ffi::OsStr>>(
+        program: S,
+        nice: Option<i32>,
+        own_group: bool,
+    ) -> Command {
+        let mut command = match nice {
+            Some(nice) if cfg!(unix) => {
+                let mut command = Command::new("nice");
+                command.arg("-n").arg(nice.to_string()).arg(program);
+                command
+            }
+            Some(_) => {
+                static WARNED: std::sync::Once = std::sync::Once::new();
+                WARNED.call_once(|| {
+                    eprintln!("Warning: `nice` is not supported on this platform and is ignored");
+                });
+                Command::new(program)
+            }
+            None => Command::new(program),
+        };
+        #[cfg(unix)]
+        if own_group {
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        }
+        command
+    }
+
+    /// Wait for a hook process for up to `timeout_seconds`, or for what is
+    /// left of the run budget if that is shorter
+    ///
+    /// Returns `None` if the time ran out first. A timeout of 0 means no
+    /// timeout: without a budget, the hook is waited for however long it
+    /// takes.
+    fn wait_for_hook(
+        &self,
+        child: &mut std::process::Child,
+        timeout_seconds: u64,
+    ) -> std::io::Result<Option<std::process::ExitStatus>> {
+        use wait_timeout::ChildExt;
+
+        let timeout = (timeout_seconds > 0).then(|| Duration::from_secs(timeout_seconds));
+        match (timeout, self.remaining_budget()) {
+            (Some(timeout), Some(left)) => child.wait_timeout(timeout.min(left)),
+            (None, Some(limit)) | (Some(limit), None) => child.wait_timeout(limit),
+            (None, None) => child.wait().map(Some),
+        }
+    }
+
+    /// Time left of the run budget (`None` without a budget)
+    fn remaining_budget(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether the run budget has been spent
+    fn budget_spent(&self) -> bool {
+        self.remaining_budget().is_some_and(|left| left.is_zero())
+    }
+
+    /// Result for a hook cut off by the run budget, reported like a timeout
+    fn cut_off_result(stderr: String, hook: &ResolvedHook) -> ExecutionResult {
+        ExecutionResult {
+            exit_code: crate::exit_code::TIMEOUT,
+            stdout: String::new(),
+            stderr,
+            success: false,
+            allowed_failure: false,
+            source_file: hook.source_file.clone(),
+            description: hook.definition.description.clone(),
+            duration: Duration::ZERO,
+            cached: false,
+            cut_off: true,
+        }
+    }
+
+    /// Stop a hook process that exceeded its timeout
+    ///
+    /// Sends SIGTERM first so the hook can clean up (e.g. finish writing
+    /// files), waits up to `grace`, then sends SIGKILL if it is still alive.
+    /// The signals go to the hook's whole process group (see
+    /// `hook_command`), so processes it started (e.g. the command under
+    /// `sh -c`) stop too instead of holding the output pipes open. The
+    /// process is always reaped before returning.
+    pub(crate) fn terminate_child(child: &mut std::process::Child, grace: Duration) {
+        use wait_timeout::ChildExt;
+
+        #[cfg(unix)]
+        if !grace.is_zero()
+            && Self::signal_group(child, rustix::process::Signal::TERM)
+            && matches!(child.wait_timeout(grace), Ok(Some(_)))
+        {
+            trace!("Hook process exited after SIGTERM");
+            // Processes it started may have ignored SIGTERM
+            Self::signal_group(child, rustix::process::Signal::KILL);
+            return;
+        }
+
+        trace!("Hook process still running after grace period, sending SIGKILL");
+        #[cfg(unix)]
+        Self::signal_group(child, rustix::process::Signal::KILL);
+        let _ = child.kill();
+        let _ = child.wait(); // Reap the process
+    }
+
+    /// Send `signal` to the process group `child` leads, returning whether
+    /// it was delivered
+    #[cfg(unix)]
+    fn signal_group(child: &std::process::Child, signal: rustix::process::Signal) -> bool {
+        rustix::process::kill_process_group(rustix::process::Pid::from_child(child), signal).is_ok()
+    }
+
+    /// Build the error reported when a hook is killed because the run budget
+    /// was spent
+    fn budget_error(name: &str, elapsed: Duration, stdout: &[u8], stderr: &[u8]) -> anyhow::Error {
+        anyhow::Error::new(BudgetExhausted {
+            message: format!(
+                "Hook '{name}' was cut off after {:.1}s: the run budget (--max-time) was \
+                 spent\n--- partial stdout ({} bytes) ---\n{}\n--- partial stderr ({} bytes) \
+                 ---\n{}",
+                elapsed.as_secs_f64(),
+                stdout.len(),
+                String::from_utf8_lossy(stdout).trim_end(),
+                stderr.len(),
+                String::from_utf8_lossy(stderr).trim_end(),
+            ),
+        })
+    }
+
+    /// Build the error reported when a hook is killed for exceeding its timeout
+    ///
+    /// Partial stdout and stderr are kept in separately labeled sections, each
+    /// with the number of bytes captured before the kill.
+    fn timeout_error(
+        name: &str,
+        timeout_seconds: u64,
+        elapsed: Duration,
+        stdout: &[u8],
+        stderr: &[u8],
+    ) -> anyhow::Error {
+        anyhow::Error::new(HookTimeout {
+            message: format!(
+                "Hook '{name}' exceeded timeout of {timeout_seconds} seconds and was killed after \
+                 {:.1}s\n--- partial stdout ({} bytes) ---\n{}\n--- partial stderr ({} bytes) \
+                 ---\n{}",
+                elapsed.as_secs_f64(),
+                stdout.len(),
+                String::from_utf8_lossy(stdout).trim_end(),
+                stderr.len(),
+                String::from_utf8_lossy(stderr).trim_end(),
+            ),
+        })
+    }
+
+    /// Start building the command that runs `command_parts` for a hook in
+    /// `working_dir`, with the hook's environment
+    ///
+    /// Hooks with a `container` run the parts through `docker run` instead
+    /// (see `container_command`); `changed_files_file` is mounted into the
+    /// container so `{CHANGED_FILES_FILE}` stays readable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `working_dir` does not exist (and the hook does
+    /// not set `create_workdir`) or environment variable templates cannot be
+    /// resolved
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_command(
+        &self,
+        name: &str,
+        hook: &ResolvedHook,
+        command_parts: &[String],
+        working_dir: &Path,
+        worktree_context: &crate::hooks::resolver::WorktreeContext,
+        template_resolver: &TemplateResolver,
+        changed_files_file: Option<&Path>,
+    ) -> Result<Command> {
+        if !working_dir.is_dir() {
+            if hook.definition.create_workdir {
+                std::fs::create_dir_all(working_dir).with_context(|| {
+                    format!(
+                        "Failed to create working directory {} for hook '{name}'",
+                        working_dir.display()
+                    )
+                })?;
+            } else {
+                return Err(anyhow::anyhow!(
+                    "Working directory {} of hook '{name}' does not exist (check `workdir`, or \
+                     set `create_workdir = true` to create it)",
+                    working_dir.display()
+                ));
+            }
+        }
+
+        let container_parts;
+        let command_parts = if hook.definition.container.is_some() {
+            let env = Self::configured_env(hook, template_resolver)?
+                .into_iter()
+                .map(|(key, (value, _))| (key, value))
+                .collect();
+            container_parts = Self::container_command(
+                &hook.definition,
+                command_parts,
+                working_dir,
+                &worktree_context.repo_root,
+                &env,
+                changed_files_file,
+            );
+            &container_parts
+        } else {
+            command_parts
+        };
+
+        let own_group = self.needs_own_group(hook);
+        let mut command = Self::hook_command(&command_parts[0], hook.definition.nice, own_group);
+        command.args(&command_parts[1..]);
+        command.current_dir(working_dir);
+        // A container gets the hook's environment through `-e`; the docker CLI
+        // itself keeps peter-hook's environment (DOCKER_HOST, DOCKER_CONFIG,
+        // XDG_RUNTIME_DIR, ...) so it reaches the configured runtime
+        if hook.definition.container.is_none() {
+            Self::apply_environment(&mut command, hook, template_resolver)?;
+        }
+        if hook.definition.stdin_from_git {
+            let stdin = match &self.options.git_inputs.stdin_file {
+                Some(path) => Stdio::from(
+                    std::fs::File::open(path)
+                        .with_context(|| format!("Failed to open {}", path.display()))?,
+                ),
+                // Git passed nothing, so the hook reads an empty stdin
+                None => Stdio::null(),
+            };
+            command.stdin(stdin);
+        } else if own_group && std::io::stdin().is_terminal() {
+            // The hook runs in its own process group, where reading the
+            // terminal would stop it
+            command.stdin(Stdio::null());
+        }
+        Ok(command)
+    }
+
+    /// Whether a hook gets its own process group: only when it may have to
+    /// be killed (a timeout, or a run budget)
+    ///
+    /// Without one it stays in peter-hook's process group, so it can read the
+    /// terminal and gets Ctrl-C from it directly.
+    fn needs_own_group(&self, hook: &ResolvedHook) -> bool {
+        hook.definition.timeout_seconds > 0 || self.remaining_budget().is_some()
+    }
+
+    /// The `docker run` command line that runs `command_parts` in the hook's
+    /// `container` image
+    ///
+    /// The repository is mounted at `/work` and the container starts in the
+    /// matching subdirectory of `working_dir`. Repository paths in `env`
+    /// values and in the parts (word by word, for shell commands) are
+    /// rewritten to the mount; `pass_env` variables are forwarded by name.
+    /// Returns `command_parts` unchanged if the hook has no container.
+    fn container_command(
+        definition: &HookDefinition,
+        command_parts: &[String],
+        working_dir: &Path,
+        repo_root: &Path,
+        env: &HashMap<String, String>,
+        changed_files_file: Option<&Path>,
+    ) -> Vec<String> {
+        const MOUNT: &str = "/work";
+
+        let Some(image) = &definition.container else {
+            return command_parts.to_vec();
+        };
+        let root = repo_root.to_string_lossy();
+        // Only whole paths under the root are rewritten, so a sibling such as
+        // `/repo-other` keeps pointing outside the mount
+        let container_path = |value: &str| {
+            Path::new(value).strip_prefix(repo_root).ok().map(|rest| {
+                if rest.as_os_str().is_empty() {
+                    MOUNT.to_string()
+                } else {
+                    Path::new(MOUNT).join(rest).to_string_lossy().into_owned()
                 }
-            }
+            })
+        };
+        let translate = |value: &str| {
+            container_path(value).unwrap_or_else(|| {
+                value
+                    .split_inclusive(char::is_whitespace)
+                    .map(|piece| {
+                        let word = piece.trim_end_matches(char::is_whitespace);
+                        container_path(word).unwrap_or_else(|| word.to_string())
+                            + &piece[word.len()..]
+                    })
+                    .collect()
+            })
+        };
+        let container_dir = working_dir
+            .strip_prefix(repo_root)
+            .map_or_else(|_| PathBuf::from(MOUNT), |dir| Path::new(MOUNT).join(dir));
+
+        let mut parts = vec![
+            "docker".to_string(),
+            "run".to_string(),
+            "--rm".to_string(),
+            "-v".to_string(),
+            format!("{root}:{MOUNT}"),
+        ];
+        if let Some(file) = changed_files_file {
+            parts.push("-v".to_string());
+            parts.push(format!("{0}:{0}:ro", file.display()));
+        }
+        parts.push("-w".to_string());
+        parts.push(container_dir.to_string_lossy().into_owned());
+
+        let mut env: Vec<_> = env.iter().collect();
+        env.sort();
+        for (key, value) in env {
+            parts.push("-e".to_string());
+            parts.push(format!("{key}={}", translate(value)));
+        }
+        for name in definition.pass_env.iter().flatten() {
+            parts.push("-e".to_string());
+            parts.push(name.clone());
+        }
+
+        parts.push(image.clone());
+        parts.extend(command_parts.iter().map(|part| translate(part)));
+        parts
+    }
+
+    /// Give the command a clean environment plus the hook's `env_file` and
+    /// `env` settings
+    ///
+    /// Only `DEFAULT_PASS_ENV`, `DEFAULT_PASS_ENV_PREFIXES` and the hook's
+    /// `pass_env` variables are forwarded from the parent process; everything
+    /// else is dropped so hooks behave the same regardless of the caller's
+    /// shell.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `env_file` cannot be read or environment
+    /// variable templates cannot be resolved
+    fn apply_environment(
+        command: &mut Command,
+        hook: &ResolvedHook,
+        template_resolver: &TemplateResolver,
+    ) -> Result<()> {
+        command.env_clear();
+        for (name, value) in Self::forwarded_env(&hook.definition) {
+            command.env(name, value);
+        }
+        for (key, (value, _)) in Self::configured_env(hook, template_resolver)? {
+            command.env(key, value);
         }
+
+        Ok(())
     }
 
     /// Build command from hook definition with template resolution
     fn build_command_from_hook(
+        &self,
         hook: &ResolvedHook,
         template_resolver: &TemplateResolver,
         name: &str,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
+        changed_files_file: Option<&Path>,
     ) -> Result<Command> {
-        let mut command = match &hook.definition.command {
+        let mut command_parts = match &hook.definition.command {
             HookCommand::Shell(cmd) => {
                 let resolved_cmd = template_resolver
                     .resolve_string(cmd)
                     .context("Failed to resolve command template")?;
 
                 if crate::debug::is_enabled() {
-                    if std::io::stderr().is_terminal() {
+                    if crate::debug::decorated() {
                         eprintln!(
                             "\x1b[38;5;208m🧙‍♂️ \x1b[1m\x1b[38;5;198mShell command resolved:\x1b[0m"
                         );
@@ -913,15 +2444,13 @@ impl HookExecutor {
                             "\x1b[38;5;141m  ✨ Resolved: \x1b[38;5;155m{resolved_cmd}\x1b[0m"
                         );
                     } else {
-                        eprintln!("[DEBUG] Shell command resolved:");
-                        eprintln!("[DEBUG]   Original: {cmd}");
-                        eprintln!("[DEBUG]   Resolved: {resolved_cmd}");
+                        trace!(level: crate::debug::TRACE_PHASE, "Shell command resolved:");
+                        trace!(level: crate::debug::TRACE_PHASE, "  Original: {cmd}");
+                        trace!(level: crate::debug::TRACE_PHASE, "  Resolved: {resolved_cmd}");
                     }
                 }
 
-                let mut command = Command::new("sh");
-                command.args(["-c", &resolved_cmd]);
-                command
+                vec!["sh".to_string(), "-c".to_string(), resolved_cmd]
             }
             HookCommand::Args(args) => {
                 if args.is_empty() {
@@ -932,7 +2461,7 @@ impl HookExecutor {
                     .context("Failed to resolve command arguments")?;
 
                 if crate::debug::is_enabled() {
-                    if std::io::stderr().is_terminal() {
+                    if crate::debug::decorated() {
                         eprintln!(
                             "\x1b[38;5;165m🚀 \x1b[1m\x1b[38;5;51mArgs command resolved:\x1b[0m"
                         );
@@ -950,43 +2479,51 @@ impl HookExecutor {
                         }
                         eprintln!();
                     } else {
-                        eprintln!("[DEBUG] Args command resolved:");
-                        eprintln!("[DEBUG]   Original: {args:?}");
-                        eprintln!("[DEBUG]   Resolved: {resolved_args:?}");
+                        trace!(level: crate::debug::TRACE_PHASE, "Args command resolved:");
+                        trace!(level: crate::debug::TRACE_PHASE, "  Original: {args:?}");
+                        trace!(level: crate::debug::TRACE_PHASE, "  Resolved: {resolved_args:?}");
                     }
                 }
 
-                let mut command = Command::new(&resolved_args[0]);
-                if resolved_args.len() > 1 {
-                    command.args(&resolved_args[1..]);
-                }
-                command
+                resolved_args
+            }
+            HookCommand::Steps { .. } => {
+                return Err(anyhow::anyhow!("Steps of hook {name} run one at a time"));
             }
         };
+        self.append_extra_args(
+            name,
+            &mut command_parts,
+            matches!(hook.definition.command, HookCommand::Shell(_)),
+        );
 
         // Set working directory (resolve template if needed)
         let working_dir = if let Some(workdir_template) = &hook.definition.workdir {
             let resolved_workdir = template_resolver
                 .resolve_string(workdir_template)
                 .context("Failed to resolve workdir template")?;
-            PathBuf::from(resolved_workdir)
+            // Relative workdirs are relative to the config file's directory
+            hook.source_file.parent().map_or_else(
+                || PathBuf::from(&resolved_workdir),
+                |config_dir| config_dir.join(&resolved_workdir),
+            )
         } else if hook.definition.run_at_root {
             // If run_at_root is true, use the repository root
             worktree_context.repo_root.clone()
         } else {
             hook.working_directory.clone()
         };
-        command.current_dir(&working_dir);
 
-        // Set environment variables with template resolution
-        if let Some(env) = &hook.definition.env {
-            let resolved_env = template_resolver
-                .resolve_env(env)
-                .context("Failed to resolve environment variable templates")?;
-            for (key, value) in resolved_env {
-                command.env(key, value);
-            }
-        }
+        // Build command, with its working directory and environment
+        let mut command = self.prepare_command(
+            name,
+            hook,
+            &command_parts,
+            &working_dir,
+            worktree_context,
+            template_resolver,
+            changed_files_file,
+        )?;
 
         // Configure stdio
         command.stdout(Stdio::piped());
@@ -1004,7 +2541,7 @@ impl HookExecutor {
         stderr: &str,
     ) {
         if crate::debug::is_enabled() {
-            if std::io::stderr().is_terminal() {
+            if crate::debug::decorated() {
                 if success {
                     eprintln!(
                         "\x1b[38;5;46m🎉 \x1b[1m\x1b[38;5;82mHook SUCCESS:\x1b[0m \
@@ -1029,17 +2566,17 @@ impl HookExecutor {
                     );
                 }
             } else {
-                eprintln!(
-                    "[DEBUG] Hook {}: {} (exit: {})",
+                trace!(level: crate::debug::TRACE_PHASE,
+                    "Hook {}: {} (exit: {})",
                     if success { "SUCCESS" } else { "FAILED" },
                     name,
                     exit_code
                 );
                 if !stdout.is_empty() {
-                    eprintln!("[DEBUG]   stdout: {}", stdout.trim());
+                    trace!(level: crate::debug::TRACE_PHASE, "  stdout: {}", stdout.trim());
                 }
                 if !stderr.is_empty() {
-                    eprintln!("[DEBUG]   stderr: {}", stderr.trim());
+                    trace!(level: crate::debug::TRACE_PHASE, "  stderr: {}", stderr.trim());
                 }
             }
         }
@@ -1048,14 +2585,12 @@ impl HookExecutor {
     /// Original hook execution logic (for Other execution type)
     #[allow(clippy::too_many_lines)]
     fn execute_original_hook(
+        &self,
         name: &str,
         hook: &ResolvedHook,
         worktree_context: &crate::hooks::resolver::WorktreeContext,
         changed_files: Option<&[PathBuf]>,
     ) -> Result<ExecutionResult> {
-        use std::io::Read;
-        use wait_timeout::ChildExt;
-
         // Create template resolver with worktree context
         let config_dir = hook
             .source_file
@@ -1065,20 +2600,12 @@ impl HookExecutor {
             config_dir,
             &hook.working_directory,
             worktree_context,
-        );
+        )
+        .with_git_inputs(&self.options.git_inputs);
 
         // Determine relevant changed files based on patterns
-        let relevant_changed: Vec<PathBuf> = changed_files.map_or_else(Vec::new, |cf| {
-            hook.definition.files.as_ref().map_or_else(
-                || cf.to_vec(),
-                |patterns| {
-                    FilePatternMatcher::new(patterns).map_or_else(
-                        |_| cf.to_vec(),
-                        |matcher| cf.iter().filter(|p| matcher.matches(p)).cloned().collect(),
-                    )
-                },
-            )
-        });
+        let relevant_changed =
+            Self::filter_relevant_files(hook, changed_files, &worktree_context.repo_root);
 
         // Determine execution directory (same logic as execute_command_parts)
         let execution_dir = if hook.definition.run_at_root {
@@ -1092,6 +2619,7 @@ impl HookExecutor {
             &relevant_changed,
             &worktree_context.repo_root,
             execution_dir,
+            hook.relative_paths,
         );
 
         // Create temp file for changed files if needed (using transformed paths)
@@ -1104,50 +2632,48 @@ impl HookExecutor {
         template_resolver.set_changed_files(&transformed_files, changed_files_file.as_deref());
 
         // Build command with template resolution
-        let mut command =
-            Self::build_command_from_hook(hook, &template_resolver, name, worktree_context)?;
+        let mut command = self.build_command_from_hook(
+            hook,
+            &template_resolver,
+            name,
+            worktree_context,
+            changed_files_file.as_deref(),
+        )?;
 
         // Debug output right before execution
         if crate::debug::is_enabled() {
-            if std::io::stderr().is_terminal() {
+            if crate::debug::decorated() {
                 eprintln!(
                     "\x1b[38;5;220m⚡ \x1b[1m\x1b[38;5;196mABOUT TO EXECUTE:\x1b[0m \
                      \x1b[38;5;226m{name}\x1b[0m"
                 );
                 eprintln!("\x1b[38;5;75m🎬 \x1b[1mStarting execution NOW...\x1b[0m");
             } else {
-                eprintln!("[DEBUG] About to execute hook: {name}");
-                eprintln!("[DEBUG] Starting execution...");
+                trace!(level: crate::debug::TRACE_PHASE, "About to execute hook: {name}");
+                trace!(level: crate::debug::TRACE_PHASE, "Starting execution...");
             }
         }
 
         // Execute command with timeout
-        let timeout = std::time::Duration::from_secs(hook.definition.timeout_seconds);
-        let mut child = command
-            .spawn()
+        let started = std::time::Instant::now();
+        let (mut child, _group) = LiveGroup::spawn(&mut command, self.needs_own_group(hook))
             .with_context(|| format!("Failed to spawn hook command: {name}"))?;
 
         // Take stdout and stderr handles before waiting
-        let mut stdout_handle = child.stdout.take().context("Failed to capture stdout")?;
-        let mut stderr_handle = child.stderr.take().context("Failed to capture stderr")?;
+        let stdout_handle = child.stdout.take().context("Failed to capture stdout")?;
+        let stderr_handle = child.stderr.take().context("Failed to capture stderr")?;
 
         // Spawn threads to read stdout and stderr in parallel
         // This prevents deadlocks from full pipe buffers
-        let stdout_thread = std::thread::spawn(move || {
-            let mut buf = Vec::new();
-            stdout_handle.read_to_end(&mut buf).ok();
-            buf
-        });
-
-        let stderr_thread = std::thread::spawn(move || {
-            let mut buf = Vec::new();
-            stderr_handle.read_to_end(&mut buf).ok();
-            buf
-        });
+        let max_output = usize::try_from(hook.definition.max_output_bytes).unwrap_or(usize::MAX);
+        let stdout_thread =
+            std::thread::spawn(move || Self::read_capped(stdout_handle, max_output));
+        let stderr_thread =
+            std::thread::spawn(move || Self::read_capped(stderr_handle, max_output));
 
         // Wait for the command with timeout
-        let status_option = child
-            .wait_timeout(timeout)
+        let status_option = self
+            .wait_for_hook(&mut child, hook.definition.timeout_seconds)
             .with_context(|| format!("Failed to wait for hook command: {name}"))?;
 
         let (exit_code, stdout, stderr, success) = if let Some(status) = status_option {
@@ -1162,25 +2688,31 @@ impl HookExecutor {
 
             (exit_code, stdout, stderr, success)
         } else {
-            // Timeout occurred - kill the process
-            let _ = child.kill();
-            let _ = child.wait(); // Reap the process
+            // Timeout occurred - terminate the process, escalating to SIGKILL
+            let elapsed = started.elapsed();
+            Self::terminate_child(
+                &mut child,
+                Duration::from_secs(hook.definition.kill_grace_seconds),
+            );
 
             // Still try to collect partial output
             let stdout_buf = stdout_thread.join().unwrap_or_default();
             let stderr_buf = stderr_thread.join().unwrap_or_default();
-            let stdout = String::from_utf8_lossy(&stdout_buf);
-            let stderr = String::from_utf8_lossy(&stderr_buf);
 
             // Cleanup temp file before returning error
             if let Some(p) = changed_files_file {
                 let _ = std::fs::remove_file(p);
             }
 
-            return Err(anyhow::anyhow!(
-                "Hook '{name}' exceeded timeout of {} seconds and was killed\nPartial stdout: \
-                 {stdout}\nPartial stderr: {stderr}",
-                hook.definition.timeout_seconds
+            if self.budget_spent() {
+                return Err(Self::budget_error(name, elapsed, &stdout_buf, &stderr_buf));
+            }
+            return Err(Self::timeout_error(
+                name,
+                hook.definition.timeout_seconds,
+                elapsed,
+                &stdout_buf,
+                &stderr_buf,
             ));
         };
 
@@ -1197,6 +2729,12 @@ impl HookExecutor {
             stdout,
             stderr,
             success,
+            allowed_failure: false,
+            source_file: PathBuf::new(),
+            description: None,
+            duration: Duration::ZERO,
+            cached: false,
+            cut_off: false,
         })
     }
 }
@@ -1208,17 +2746,61 @@ impl Default for HookExecutor {
 }
 
 impl ExecutionResults {
+    /// Process exit code for these results
+    ///
+    /// [`crate::exit_code::TIMEOUT`] if a blocking failure was a timeout,
+    /// [`crate::exit_code::FAILURE`] for any other failure.
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        if self.success {
+            crate::exit_code::SUCCESS
+        } else if self.results.values().any(|result| {
+            result.is_blocking_failure() && result.exit_code == crate::exit_code::TIMEOUT
+        }) {
+            crate::exit_code::TIMEOUT
+        } else {
+            crate::exit_code::FAILURE
+        }
+    }
+
+    /// Format the status line for one hook, marking allowed failures
+    ///
+    /// Failing hooks name the config that defined them, since several
+    /// configs may define a hook with the same name.
+    fn hook_line(name: &str, result: &ExecutionResult) -> String {
+        let fmt = formatter();
+        let name = if result.cached {
+            format!("{name} (cached pass)")
+        } else if result.cut_off {
+            format!("{name} (cut off by --max-time)")
+        } else if result.success || result.source_file.as_os_str().is_empty() {
+            name.to_string()
+        } else {
+            format!("{name} (from {})", result.source_file.display())
+        };
+        if result.allowed_failure {
+            fmt.allowed_failure_result(&name, result.exit_code)
+        } else {
+            fmt.hook_result(&name, result.success, result.exit_code)
+        }
+    }
+
     /// Print a summary of execution results
     pub fn print_summary(&self) {
         let fmt = formatter();
 
         println!("{}", fmt.section_header("Hook Execution Summary"));
 
+        for description in &self.group_descriptions {
+            println!("{description}");
+        }
+
         for (name, result) in &self.results {
-            println!(
-                "{}",
-                fmt.hook_result(name, result.success, result.exit_code)
-            );
+            println!("{}", Self::hook_line(name, result));
+
+            if let Some(description) = &result.description {
+                println!("  {description}");
+            }
 
             if !result.stdout.is_empty() {
                 println!("  stdout: {}", result.stdout.trim());
@@ -1229,9 +2811,291 @@ impl ExecutionResults {
             }
         }
 
+        let allowed = self.get_allowed_failures();
+        if !allowed.is_empty() {
+            println!("Failed (allowed, not blocking): {}", allowed.join(", "));
+        }
+
         println!("{}", fmt.overall_result(self.success));
     }
 
+    /// Print output only for failed hooks, followed by a one-line summary
+    pub fn print_failures_only(&self) {
+        let mut failed: Vec<_> = self.results.iter().filter(|(_, r)| !r.success).collect();
+        failed.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, result) in &failed {
+            println!("{}", Self::hook_line(name, result));
+
+            if !result.stdout.is_empty() {
+                println!("  stdout: {}", result.stdout.trim());
+            }
+
+            if !result.stderr.is_empty() {
+                println!("  stderr: {}", result.stderr.trim());
+            }
+        }
+
+        let passed = self.results.len() - failed.len();
+        let allowed = failed.iter().filter(|(_, r)| r.allowed_failure).count();
+        if allowed == 0 {
+            println!("{passed} passed, {} failed", failed.len());
+        } else {
+            println!(
+                "{passed} passed, {} failed (blocking), {allowed} failed (allowed)",
+                failed.len() - allowed
+            );
+        }
+    }
+
+    /// Write each hook's output to `<dir>/<group>/<config dir>/<hook>.stdout`,
+    /// `.stderr` and `.meta.json` (exit code, success and duration)
+    ///
+    /// `<config dir>` is the directory of the hook's config relative to the
+    /// repository root, so it is empty for the root config. Names are
+    /// sanitized so they are safe as file names; when two hooks sanitize to
+    /// the same file name, the later one (by name) gets a `-2`, `-3`, ...
+    /// suffix instead of overwriting the first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory or file cannot be written
+    pub fn write_output_dir(
+        &self,
+        dir: &Path,
+        group: &str,
+        groups: &[crate::hooks::ConfigGroup],
+    ) -> Result<()> {
+        let group_dir = dir.join(sanitize_file_name(group));
+
+        let mut keys: Vec<_> = self.results.keys().collect();
+        keys.sort();
+        let mut taken = HashSet::new();
+        for key in keys {
+            let result = &self.results[key];
+            let (config_dir, name) = Self::output_location(groups, key);
+            let hook_dir = group_dir.join(&config_dir);
+            std::fs::create_dir_all(&hook_dir).with_context(|| {
+                format!("Failed to create output directory {}", hook_dir.display())
+            })?;
+
+            let stem = sanitize_file_name(name);
+            let mut base = stem.clone();
+            let mut suffix = 1;
+            while !taken.insert(hook_dir.join(&base)) {
+                suffix += 1;
+                base = format!("{stem}-{suffix}");
+            }
+
+            let write = |extension: &str, contents: &[u8]| {
+                let path = hook_dir.join(format!("{base}.{extension}"));
+                std::fs::write(&path, contents)
+                    .with_context(|| format!("Failed to write {}", path.display()))
+            };
+
+            write("stdout", result.stdout.as_bytes())?;
+            write("stderr", result.stderr.as_bytes())?;
+
+            let meta = serde_json::json!({
+                "hook": name,
+                "exit_code": result.exit_code,
+                "success": result.success,
+                "allowed_failure": result.allowed_failure,
+                "duration_secs": result.duration.as_secs_f64(),
+            });
+            let meta =
+                serde_json::to_string_pretty(&meta).context("Failed to serialize hook metadata")?;
+            write("meta.json", meta.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Sanitized directory of the config that ran the result `key`, relative
+    /// to the repository root, and the hook name without the config prefix
+    /// `result_key` adds when several configs ran
+    fn output_location<'a>(
+        groups: &[crate::hooks::ConfigGroup],
+        key: &'a str,
+    ) -> (PathBuf, &'a str) {
+        for group in groups {
+            let name = if groups.len() > 1 {
+                let prefix = format!("{}:", group.config_path.display());
+                match key.strip_prefix(prefix.as_str()) {
+                    Some(name) => name,
+                    None => continue,
+                }
+            } else {
+                key
+            };
+
+            let config_dir = group.config_path.parent().unwrap_or_else(|| Path::new(""));
+            let relative = config_dir
+                .strip_prefix(&group.resolved_hooks.worktree_context.repo_root)
+                .unwrap_or(config_dir);
+            let sanitized = relative
+                .components()
+                .filter_map(|component| match component {
+                    Component::Normal(part) => Some(sanitize_file_name(&part.to_string_lossy())),
+                    _ => None,
+                })
+                .collect();
+            return (sanitized, name);
+        }
+
+        (PathBuf::new(), key)
+    }
+
+    /// Print per-hook durations (slowest first), the total wall-clock time,
+    /// and the longest chain of `depends_on` hooks by duration
+    pub fn print_timings(&self, groups: &[crate::hooks::ConfigGroup], wall_time: Duration) {
+        let fmt = formatter();
+
+        println!("{}", fmt.section_header("Hook Timings"));
+
+        let mut timings: Vec<_> = self
+            .results
+            .iter()
+            .map(|(name, result)| (name.as_str(), result.duration))
+            .collect();
+        timings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        let width = timings
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, duration) in &timings {
+            println!("  {name:<width$}  {:>8.2}s", duration.as_secs_f64());
+        }
+
+        println!("Total: {:.2}s", wall_time.as_secs_f64());
+
+        let critical_path = self.critical_path(groups);
+        if !critical_path.is_empty() {
+            let length: Duration = critical_path.iter().map(|(_, d)| *d).sum();
+            let names: Vec<_> = critical_path
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            println!(
+                "Critical path: {} ({:.2}s)",
+                names.join(" -> "),
+                length.as_secs_f64()
+            );
+        }
+    }
+
+    /// Names of the hooks cut off by the run budget, sorted
+    #[must_use]
+    pub fn cut_off_hooks(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .results
+            .iter()
+            .filter(|(_, result)| result.cut_off)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Print each hook's duration from a run with one hook at a time, slowest
+    /// first
+    pub fn print_profile(&self) {
+        let fmt = formatter();
+
+        println!("{}", fmt.section_header("Hook Profile"));
+
+        let mut timings: Vec<_> = self
+            .results
+            .iter()
+            .map(|(name, result)| (name.as_str(), result.duration))
+            .collect();
+        timings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        let width = timings
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, duration) in &timings {
+            println!("  {name:<width$}  {:>8.2}s", duration.as_secs_f64());
+        }
+
+        let total: Duration = timings.iter().map(|(_, duration)| *duration).sum();
+        println!("Total isolated: {:.2}s", total.as_secs_f64());
+    }
+
+    /// Longest chain of executed hooks linked by `depends_on`, by total
+    /// duration, ordered from first to last
+    fn critical_path(&self, groups: &[crate::hooks::ConfigGroup]) -> Vec<(String, Duration)> {
+        /// Finish time of the longest chain ending at `name`, memoized
+        fn chain_end(
+            name: &str,
+            hooks: &HashMap<String, ResolvedHook>,
+            durations: &HashMap<&str, Duration>,
+            memo: &mut HashMap<String, (Duration, Option<String>)>,
+        ) -> Duration {
+            if let Some((total, _)) = memo.get(name) {
+                return *total;
+            }
+
+            let mut best: (Duration, Option<String>) = (Duration::ZERO, None);
+            let deps = hooks
+                .get(name)
+                .and_then(|hook| hook.definition.depends_on.as_deref())
+                .unwrap_or_default();
+            for dep in deps {
+                if durations.contains_key(dep.as_str()) {
+                    let end = chain_end(dep, hooks, durations, memo);
+                    if end > best.0 {
+                        best = (end, Some(dep.clone()));
+                    }
+                }
+            }
+
+            let total = best.0 + durations.get(name).copied().unwrap_or_default();
+            memo.insert(name.to_string(), (total, best.1));
+            total
+        }
+
+        let mut longest: (Duration, Vec<(String, Duration)>) = (Duration::ZERO, Vec::new());
+
+        for group in groups {
+            let hooks = &group.resolved_hooks.hooks;
+            let durations: HashMap<&str, Duration> = hooks
+                .keys()
+                .filter_map(|name| {
+                    let key = HookExecutor::result_key(groups.len(), &group.config_path, name);
+                    self.results
+                        .get(&key)
+                        .map(|result| (name.as_str(), result.duration))
+                })
+                .collect();
+
+            let mut memo = HashMap::new();
+            for name in durations.keys() {
+                let end = chain_end(name, hooks, &durations, &mut memo);
+                if end <= longest.0 {
+                    continue;
+                }
+
+                let mut path = Vec::new();
+                let mut current = Some((*name).to_string());
+                while let Some(hook_name) = current {
+                    current = memo.get(&hook_name).and_then(|(_, prev)| prev.clone());
+                    let key =
+                        HookExecutor::result_key(groups.len(), &group.config_path, &hook_name);
+                    path.push((key, durations[hook_name.as_str()]));
+                }
+                path.reverse();
+                longest = (end, path);
+            }
+        }
+
+        longest.1
+    }
+
     /// Print execution with progress bar (TTY only)
     pub fn print_with_progress(&self, hook_names: &[String]) {
         let fmt = formatter();
@@ -1247,7 +3111,13 @@ impl ExecutionResults {
                 std::thread::sleep(Duration::from_millis(100));
 
                 if let Some(result) = self.results.get(name) {
-                    let status = if result.success { "✅" } else { "❌" };
+                    let status = if result.success {
+                        "✅"
+                    } else if result.allowed_failure {
+                        "⚠️"
+                    } else {
+                        "❌"
+                    };
                     pb.println(format!("{status} {name}"));
                 }
             }
@@ -1259,20 +3129,54 @@ impl ExecutionResults {
         }
     }
 
-    /// Get failed hooks
+    /// Get failed hooks (excluding failures allowed by `allow_failure`)
     #[must_use]
     pub fn get_failed_hooks(&self) -> Vec<&str> {
         self.results
             .iter()
             .filter_map(|(name, result)| {
-                if result.success {
-                    None
-                } else {
+                if result.is_blocking_failure() {
                     Some(name.as_str())
+                } else {
+                    None
                 }
             })
             .collect()
     }
+
+    /// Get hooks that failed with `allow_failure = true`, sorted by name
+    #[must_use]
+    pub fn get_allowed_failures(&self) -> Vec<&str> {
+        let mut allowed: Vec<&str> = self
+            .results
+            .iter()
+            .filter(|(_, result)| result.allowed_failure)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        allowed.sort_unstable();
+        allowed
+    }
+}
+
+/// Replace characters that are unsafe in file names with `_`
+///
+/// Hierarchical result keys contain the config path (`/path/hooks.toml:lint`),
+/// so path separators and `:` must not survive.
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match sanitized.trim_start_matches('.') {
+        "" => format!("_{sanitized}"),
+        _ => sanitized,
+    }
 }
 
 #[cfg(test)]
@@ -1287,18 +3191,46 @@ mod tests {
                 command,
                 workdir,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository: false,
                 files: None,
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: true, // Always run in tests since we pass None for changed_files
-                requires_files: false, // Default to false for tests
+                requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false, // Default to false for tests
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::PerFile,
                 run_at_root: false,
+                create_workdir: false,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
             },
             working_directory: std::env::temp_dir(),
             source_file: PathBuf::from("test.toml"),
+            file_scope: None,
+            relative_paths: None,
         }
     }
 
@@ -1317,8 +3249,9 @@ mod tests {
         let hook = create_test_hook(HookCommand::Shell("echo 'hello world'".to_string()), None);
 
         let worktree_context = create_test_worktree_context();
-        let result =
-            HookExecutor::execute_single_hook("test", &hook, &worktree_context, None).unwrap();
+        let result = HookExecutor::new()
+            .execute_single_hook("test", &hook, &worktree_context, None)
+            .unwrap();
 
         assert!(result.success);
         assert_eq!(result.exit_code, 0);
@@ -1331,13 +3264,38 @@ mod tests {
         let hook = create_test_hook(HookCommand::Shell("exit 1".to_string()), None);
 
         let worktree_context = create_test_worktree_context();
-        let result =
-            HookExecutor::execute_single_hook("test", &hook, &worktree_context, None).unwrap();
+        let result = HookExecutor::new()
+            .execute_single_hook("test", &hook, &worktree_context, None)
+            .unwrap();
 
         assert!(!result.success);
         assert_eq!(result.exit_code, 1);
     }
 
+    #[test]
+    fn test_run_options_belong_to_their_executor() {
+        let hook = create_test_hook(HookCommand::Shell("echo 'hello world'".to_string()), None);
+        let worktree_context = create_test_worktree_context();
+
+        let spent = HookExecutor::with_options(ExecutorOptions {
+            max_time: Some(Duration::ZERO),
+            extra_args: Some(("test".to_string(), vec!["again".to_string()])),
+            ..ExecutorOptions::default()
+        });
+        let result = spent
+            .execute_single_hook("test", &hook, &worktree_context, None)
+            .unwrap();
+        assert!(result.cut_off);
+
+        // A later run starts without the earlier run's budget or arguments
+        let result = HookExecutor::new()
+            .execute_single_hook("test", &hook, &worktree_context, None)
+            .unwrap();
+        assert!(result.success);
+        assert!(!result.cut_off);
+        assert_eq!(result.stdout.trim(), "hello world");
+    }
+
     #[test]
     fn test_execute_args_command() {
         let hook = create_test_hook(
@@ -1350,8 +3308,9 @@ mod tests {
         );
 
         let worktree_context = create_test_worktree_context();
-        let result =
-            HookExecutor::execute_single_hook("test", &hook, &worktree_context, None).unwrap();
+        let result = HookExecutor::new()
+            .execute_single_hook("test", &hook, &worktree_context, None)
+            .unwrap();
 
         assert!(result.success);
         assert_eq!(result.stdout.trim(), "hello args");
@@ -1375,11 +3334,15 @@ mod tests {
             config_path: PathBuf::from("test.toml"),
             hooks,
             execution_strategy: ExecutionStrategy::Sequential,
+            description: None,
+            setup: None,
+            teardown: None,
             changed_files: None,
+            skipped: Vec::new(),
             worktree_context: create_test_worktree_context(),
         };
 
-        let results = HookExecutor::execute(&resolved_hooks).unwrap();
+        let results = HookExecutor::new().execute(&resolved_hooks).unwrap();
 
         assert!(!results.success); // Overall failure due to one failed hook
         assert_eq!(results.results.len(), 2);
@@ -1416,11 +3379,15 @@ mod tests {
             config_path: PathBuf::from("test.toml"),
             hooks,
             execution_strategy: ExecutionStrategy::Parallel,
+            description: None,
+            setup: None,
+            teardown: None,
             changed_files: None,
+            skipped: Vec::new(),
             worktree_context: create_test_worktree_context(),
         };
 
-        let results = HookExecutor::execute(&resolved_hooks).unwrap();
+        let results = HookExecutor::new().execute(&resolved_hooks).unwrap();
 
         assert!(results.success);
         assert_eq!(results.results.len(), 3);
@@ -1447,11 +3414,15 @@ mod tests {
             config_path: PathBuf::from("test.toml"),
             hooks,
             execution_strategy: ExecutionStrategy::Sequential,
+            description: None,
+            setup: None,
+            teardown: None,
             changed_files: None,
+            skipped: Vec::new(),
             worktree_context: create_test_worktree_context(),
         };
 
-        let results = HookExecutor::execute(&resolved_hooks).unwrap();
+        let results = HookExecutor::new().execute(&resolved_hooks).unwrap();
 
         assert!(results.success);
         assert_eq!(results.results.len(), 2);
@@ -1482,11 +3453,15 @@ mod tests {
             config_path: PathBuf::from("test.toml"),
             hooks,
             execution_strategy: ExecutionStrategy::ForceParallel,
+            description: None,
+            setup: None,
+            teardown: None,
             changed_files: None,
+            skipped: Vec::new(),
             worktree_context: create_test_worktree_context(),
         };
 
-        let results = HookExecutor::execute(&resolved_hooks).unwrap();
+        let results = HookExecutor::new().execute(&resolved_hooks).unwrap();
 
         assert!(results.success);
         assert_eq!(results.results.len(), 2);
@@ -1501,18 +3476,46 @@ mod tests {
                 command,
                 workdir: None,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository,
                 files: None,
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: false,
                 requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false,
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::PerFile,
                 run_at_root: false,
+                create_workdir: false,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
             },
             working_directory: std::env::temp_dir(),
             source_file: PathBuf::from("test.toml"),
+            file_scope: None,
+            relative_paths: None,
         }
     }
 
@@ -1528,24 +3531,52 @@ mod tests {
                 ),
                 workdir: None,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository: false,
                 files: Some(vec!["**/*.rs".to_string()]),
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: false,
                 requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false,
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::Other,
                 run_at_root: false,
+                create_workdir: false,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
             },
             working_directory: std::env::temp_dir(),
             source_file: PathBuf::from("test.toml"),
+            file_scope: None,
+            relative_paths: None,
         };
         let worktree_context = create_test_worktree_context();
         let changes = vec![PathBuf::from("src/a.rs"), PathBuf::from("README.md")];
-        let result =
-            HookExecutor::execute_single_hook("filtered", &hook, &worktree_context, Some(&changes))
-                .unwrap();
+        let result = HookExecutor::new()
+            .execute_single_hook("filtered", &hook, &worktree_context, Some(&changes))
+            .unwrap();
         assert!(result.success);
         let out = result.stdout;
         assert!(out.contains("src/a.rs"));
@@ -1559,24 +3590,52 @@ mod tests {
                 command: HookCommand::Shell("printf '%s\n' '{CHANGED_FILES}'".to_string()),
                 workdir: None,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository: false,
                 files: None,
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: false,
                 requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false,
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::Other,
                 run_at_root: false,
+                create_workdir: false,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
             },
             working_directory: std::env::temp_dir(),
             source_file: PathBuf::from("test.toml"),
+            file_scope: None,
+            relative_paths: None,
         };
         let worktree_context = create_test_worktree_context();
         let changes = vec![PathBuf::from("a"), PathBuf::from("b/c")];
-        let result =
-            HookExecutor::execute_single_hook("nofilter", &hook, &worktree_context, Some(&changes))
-                .unwrap();
+        let result = HookExecutor::new()
+            .execute_single_hook("nofilter", &hook, &worktree_context, Some(&changes))
+            .unwrap();
         assert!(result.success);
         let out = result.stdout;
         assert!(out.contains('a'));
@@ -1594,27 +3653,245 @@ mod tests {
                 ),
                 workdir: None,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository: false,
                 files: None,
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: false,
                 requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false,
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::Other,
                 run_at_root: false,
+                create_workdir: false,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
             },
             working_directory: std::env::temp_dir(),
             source_file: PathBuf::from("test.toml"),
+            file_scope: None,
+            relative_paths: None,
         };
         let worktree_context = create_test_worktree_context();
-        let result =
-            HookExecutor::execute_single_hook("empty", &hook, &worktree_context, None).unwrap();
+        let result = HookExecutor::new()
+            .execute_single_hook("empty", &hook, &worktree_context, None)
+            .unwrap();
+        assert!(result.success);
+        // CHANGED_FILES_FILE still names a (now removed) empty list file
+        assert!(result.stdout.contains("[]-[]-["));
+        assert!(result.stdout.trim_end().ends_with(".lst]"));
+    }
+
+    #[test]
+    fn test_read_capped_keeps_head_and_tail() {
+        let input = format!("{}{}{}", "a".repeat(10), "b".repeat(100), "c".repeat(10));
+        let output = HookExecutor::read_capped(input.as_bytes(), 20);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "{}\n[... 100 bytes truncated ...]\n{}",
+                "a".repeat(10),
+                "c".repeat(10)
+            )
+        );
+
+        let output = HookExecutor::read_capped(&b"short"[..], 20);
+        assert_eq!(output, b"short");
+    }
+
+    #[test]
+    fn test_transform_file_paths_modes() {
+        let repo_root = Path::new("/repo");
+        let execution_dir = Path::new("/repo/src");
+        let files = vec![PathBuf::from("src/a.rs"), PathBuf::from("other/b.rs")];
+
+        assert_eq!(
+            HookExecutor::transform_file_paths(&files, repo_root, execution_dir, None),
+            vec![PathBuf::from("a.rs"), PathBuf::from("other/b.rs")]
+        );
+        assert_eq!(
+            HookExecutor::transform_file_paths(&files, repo_root, execution_dir, Some(true)),
+            vec![PathBuf::from("a.rs"), PathBuf::from("../other/b.rs")]
+        );
+        assert_eq!(
+            HookExecutor::transform_file_paths(&files, repo_root, execution_dir, Some(false)),
+            files
+        );
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let variables = parse_env_file(
+            "# comment\n\nPLAIN=value\nexport EXPORTED=1\nQUOTED=\"a b\"\nSINGLE='c=d'\n",
+        )
+        .unwrap();
+        assert_eq!(
+            variables,
+            [
+                ("PLAIN".to_string(), "value".to_string()),
+                ("EXPORTED".to_string(), "1".to_string()),
+                ("QUOTED".to_string(), "a b".to_string()),
+                ("SINGLE".to_string(), "c=d".to_string()),
+            ]
+        );
+
+        let err = parse_env_file("OK=1\nnot a variable\n").unwrap_err();
+        assert!(err.to_string().contains("Line 2"), "{err}");
+    }
+
+    #[test]
+    fn test_check_templates_covers_env_file() {
+        let mut hook = create_test_hook(HookCommand::Shell("true".to_string()), None);
+        let worktree_context = create_test_worktree_context();
+        hook.definition.env_file = Some("{REPO_ROOT}/.env".to_string());
+        assert!(
+            HookExecutor::new()
+                .check_templates(&hook, &worktree_context)
+                .is_ok()
+        );
+
+        hook.definition.env_file = Some("{UNKNOWN}/.env".to_string());
+        let err = HookExecutor::new()
+            .check_templates(&hook, &worktree_context)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("env_file"), "{err:#}");
+    }
+
+    #[test]
+    fn test_container_command() {
+        let mut hook = create_test_hook(HookCommand::Shell("true".to_string()), None);
+        hook.definition.container = Some("node:20".to_string());
+        hook.definition.pass_env = Some(vec!["CI".to_string()]);
+        let env = HashMap::from([
+            ("CACHE".to_string(), "/repo/.cache".to_string()),
+            ("OTHER".to_string(), "/repo-other/.cache".to_string()),
+        ]);
+        let parts = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "npm test /repo/web/a.js  /repo-other/b.js /repo".to_string(),
+        ];
+
+        assert_eq!(
+            HookExecutor::container_command(
+                &hook.definition,
+                &parts,
+                Path::new("/repo/web"),
+                Path::new("/repo"),
+                &env,
+                Some(Path::new("/tmp/changed.lst")),
+            ),
+            [
+                "docker",
+                "run",
+                "--rm",
+                "-v",
+                "/repo:/work",
+                "-v",
+                "/tmp/changed.lst:/tmp/changed.lst:ro",
+                "-w",
+                "/work/web",
+                "-e",
+                "CACHE=/work/.cache",
+                "-e",
+                "OTHER=/repo-other/.cache",
+                "-e",
+                "CI",
+                "node:20",
+                "sh",
+                "-c",
+                "npm test /work/web/a.js  /repo-other/b.js /work",
+            ]
+        );
+
+        hook.definition.container = None;
+        assert_eq!(
+            HookExecutor::container_command(
+                &hook.definition,
+                &parts,
+                Path::new("/repo/web"),
+                Path::new("/repo"),
+                &env,
+                None,
+            ),
+            parts
+        );
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("lint"), "lint");
+        assert_eq!(sanitize_file_name("fmt-check_v2.1"), "fmt-check_v2.1");
+        assert_eq!(
+            sanitize_file_name("/repo/sub/hooks.toml:lint"),
+            "_repo_sub_hooks.toml_lint"
+        );
+        assert_eq!(sanitize_file_name("a b*c"), "a_b_c");
+        assert_eq!(sanitize_file_name(".."), "_..");
+    }
+
+    #[test]
+    fn test_missing_workdir_names_hook_and_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("no-such-dir");
+        let hook = create_test_hook(
+            HookCommand::Shell("true".to_string()),
+            Some(missing.display().to_string()),
+        );
+
+        let error = HookExecutor::new()
+            .execute_single_hook("build", &hook, &create_test_worktree_context(), None)
+            .unwrap_err();
+        let message = format!("{error:#}");
+        assert!(message.contains("hook 'build'"), "{message}");
+        assert!(
+            message.contains(&missing.display().to_string()),
+            "{message}"
+        );
+        assert!(message.contains("does not exist"), "{message}");
+    }
+
+    #[test]
+    fn test_create_workdir_creates_missing_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("out/reports");
+        let mut hook = create_test_hook(
+            HookCommand::Shell("pwd".to_string()),
+            Some(missing.display().to_string()),
+        );
+        hook.definition.create_workdir = true;
+
+        let result = HookExecutor::new()
+            .execute_single_hook("build", &hook, &create_test_worktree_context(), None)
+            .unwrap();
         assert!(result.success);
-        assert!(result.stdout.contains("[]-[]-[]"));
+        assert!(missing.is_dir());
     }
 
     #[test]
+    #[allow(clippy::too_many_lines)]
     fn test_run_at_root_flag_execution() {
         use std::fs;
         use tempfile::TempDir;
@@ -1637,17 +3914,45 @@ mod tests {
                 command: HookCommand::Shell("pwd".to_string()),
                 workdir: None,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository: false,
                 files: None,
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: false,
                 requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false,
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::Other,
                 run_at_root: true,
+                create_workdir: false,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
             },
             source_file: config_dir.join("hooks.toml"),
+            file_scope: None,
+            relative_paths: None,
             working_directory: config_dir.clone(),
         };
 
@@ -1657,24 +3962,52 @@ mod tests {
                 command: HookCommand::Shell("pwd".to_string()),
                 workdir: None,
                 env: None,
+                env_file: None,
+                pass_env: None,
+                mask_env: None,
                 description: None,
                 modifies_repository: false,
                 files: None,
+                extensions: None,
+                content_patterns: None,
+                exclude_files: None,
+                nice: None,
+                container: None,
                 run_always: false,
                 requires_files: false,
+                min_changed_files: None,
+                max_changed_files: None,
+                allow_failure: false,
+                max_warnings: None,
+                warning_pattern: None,
+                output_filter: None,
+                output_exclude: None,
+                require_clean_worktree: false,
+                cacheable: false,
+                autofix: false,
+                autofix_check: None,
+                stdin_from_git: false,
+                if_exists: None,
                 depends_on: None,
+                order: None,
+                priority: None,
                 execution_type: crate::config::parser::ExecutionType::Other,
                 timeout_seconds: 300,
+                kill_grace_seconds: 2,
+                max_output_bytes: 4 * 1024 * 1024,
                 run_at_root: false,
+                create_workdir: false,
             },
             source_file: config_dir.join("hooks.toml"),
+            file_scope: None,
+            relative_paths: None,
             working_directory: config_dir.clone(),
         };
 
         // Test hook with run_at_root = true
-        let result_root =
-            HookExecutor::execute_single_hook("root", &hook_at_root, &worktree_context, None)
-                .unwrap();
+        let result_root = HookExecutor::new()
+            .execute_single_hook("root", &hook_at_root, &worktree_context, None)
+            .unwrap();
         assert!(result_root.success);
         let root_pwd = result_root.stdout.trim();
         // Use canonical paths for comparison due to macOS temp directory symlinks
@@ -1685,9 +4018,9 @@ mod tests {
         assert_eq!(canonical_root_pwd, canonical_temp);
 
         // Test hook with run_at_root = false
-        let result_config =
-            HookExecutor::execute_single_hook("config", &hook_at_config, &worktree_context, None)
-                .unwrap();
+        let result_config = HookExecutor::new()
+            .execute_single_hook("config", &hook_at_config, &worktree_context, None)
+            .unwrap();
         assert!(result_config.success);
         let config_pwd = result_config.stdout.trim();
         // Use canonical paths for comparison due to macOS temp directory symlinks
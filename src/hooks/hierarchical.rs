@@ -6,15 +6,24 @@
 //! gates.
 
 use crate::{
-    config::{ExecutionStrategy, HookConfig, HookDefinition},
+    config::{
+        ConfigOverrides, ExecutionStrategy, FilesRelativeTo, HookConfig, HookDefinition,
+        WorkdirMode, fallback_config,
+    },
+    debug::{TRACE_FILE, TRACE_PHASE},
     git::ChangeDetectionMode,
-    hooks::{ResolvedHooks, WorktreeContext},
+    hooks::{
+        GroupStep, ResolvedHook, ResolvedHooks, SkipReason, SkippedHook, WorktreeContext,
+        resolver::{apply_group_dependencies, resolve_group_steps},
+    },
     trace,
 };
 use anyhow::{Context, Result};
+use rayon::prelude::*;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 /// A group of files that share the same hook configuration
@@ -28,11 +37,11 @@ pub struct ConfigGroup {
     pub resolved_hooks: ResolvedHooks,
 }
 
-
 /// Find the nearest hooks.toml file for a given file path
 ///
 /// Walks up from the file's directory to find the nearest hooks.toml file.
-/// Stops at the repository root.
+/// Stops at the repository root, then falls back to the repo default or user
+/// config (see `fallback_config`).
 ///
 /// # Arguments
 ///
@@ -70,53 +79,369 @@ fn find_nearest_config_for_file(file_path: &Path, repo_root: &Path) -> Option<Pa
         current = current.parent()?;
     }
 
-    None
+    fallback_config(Some(repo_root)).map(|(path, _)| path)
+}
+
+/// Config nearest to `current_dir`, whose change detection settings apply
+///
+/// Changed files are detected once for the whole repository, before configs
+/// are grouped, so the settings (`rename_detection`, `include_untracked`)
+/// come from the config `run` would use.
+fn nearest_detection_config(
+    repo_root: &Path,
+    current_dir: &Path,
+    overrides: &ConfigOverrides,
+) -> Option<HookConfig> {
+    let config_path = overrides.config().map_or_else(
+        || find_nearest_config_for_file(current_dir, repo_root),
+        |config_path| Some(config_path.to_path_buf()),
+    )?;
+    let config = HookConfig::from_file_cached(&config_path, overrides).ok()?;
+    trace!(
+        "Rename detection: {:?}, include untracked: {:?}",
+        config.rename_detection, config.include_untracked
+    );
+    Some(config)
+}
+
+/// Detect the changed files for `mode`, with the change detection settings
+/// of the config nearest `current_dir`
+///
+/// This is the file list `resolve_hooks_hierarchically` starts from.
+///
+/// # Errors
+///
+/// Returns an error if git operations fail
+pub fn detect_changed_files(
+    mode: &ChangeDetectionMode,
+    repo_root: &Path,
+    current_dir: &Path,
+    overrides: &ConfigOverrides,
+) -> Result<Vec<PathBuf>> {
+    let config = nearest_detection_config(repo_root, current_dir, overrides);
+    crate::git::GitChangeDetector::new(repo_root)
+        .context("Failed to create git change detector")?
+        .with_rename_detection(config.as_ref().and_then(|c| c.rename_detection))
+        .with_untracked(config.and_then(|c| c.include_untracked).unwrap_or(true))
+        .get_changed_files(mode)
+        .context("Failed to detect changed files")
+}
+
+/// Memoized nearest-config lookup shared across worker threads
+///
+/// Maps each directory visited during a walk to the nearest hooks.toml found
+/// from it, so sibling files (and files in nested directories sharing a
+/// prefix) don't repeat the filesystem walk.
+struct NearestConfigCache {
+    /// Canonical repository root, computed once
+    repo_root_canonical: Option<PathBuf>,
+    /// Directory -> nearest config file (None if there is none)
+    entries: Mutex<HashMap<PathBuf, Option<PathBuf>>>,
+    /// Config for files with no hooks.toml up to the root, looked up once
+    fallback: Option<PathBuf>,
+}
+
+impl NearestConfigCache {
+    fn new(repo_root: &Path) -> Self {
+        Self {
+            repo_root_canonical: repo_root.canonicalize().ok(),
+            entries: Mutex::new(HashMap::new()),
+            fallback: fallback_config(Some(repo_root)).map(|(path, _)| path),
+        }
+    }
+
+    /// Find the nearest config for a file, consulting and filling the cache
+    ///
+    /// Returns the same result as `find_nearest_config_for_file`.
+    fn lookup(&self, file_path: &Path) -> Option<PathBuf> {
+        let repo_root_canonical = self.repo_root_canonical.as_ref()?;
+        let start = if file_path.is_file() {
+            file_path.parent()?
+        } else {
+            file_path
+        };
+
+        let mut visited = Vec::new();
+        let mut current = start;
+        let result = loop {
+            let cached = self
+                .entries
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .get(current)
+                .cloned();
+            if let Some(cached) = cached {
+                break cached;
+            }
+            visited.push(current.to_path_buf());
+
+            let config_path = current.join("hooks.toml");
+            if config_path.exists() {
+                break Some(config_path);
+            }
+
+            // Check if we've reached the repo root
+            if current
+                .canonicalize()
+                .is_ok_and(|canonical| &canonical == repo_root_canonical)
+            {
+                break None;
+            }
+
+            // Move up one directory
+            match current.parent() {
+                Some(parent) => current = parent,
+                None => break None,
+            }
+        };
+
+        // Every directory on the walk resolves to the same config
+        {
+            let mut entries = self
+                .entries
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            for dir in visited {
+                entries.insert(dir, result.clone());
+            }
+        }
+
+        result.or_else(|| self.fallback.clone())
+    }
+}
+
+/// Map each changed file to its nearest config, in parallel
+///
+/// The returned map is keyed by config path (sorted), and each file list keeps
+/// the order of `changed_files`, so the result does not depend on how many
+/// threads performed the lookups. Files without a config are omitted.
+///
+/// With a `--config` override every file maps to that config.
+fn map_files_to_configs(
+    changed_files: &[PathBuf],
+    repo_root: &Path,
+    overrides: &ConfigOverrides,
+) -> BTreeMap<PathBuf, Vec<PathBuf>> {
+    if let Some(config_path) = overrides.config() {
+        trace!(
+            "  All files -> {} (--config override)",
+            config_path.display()
+        );
+        return BTreeMap::from([(config_path.to_path_buf(), changed_files.to_vec())]);
+    }
+
+    let cache = NearestConfigCache::new(repo_root);
+
+    let nearest: Vec<Option<PathBuf>> = changed_files
+        .par_iter()
+        .map(|file| {
+            let absolute_file = if file.is_absolute() {
+                file.clone()
+            } else {
+                repo_root.join(file)
+            };
+            cache.lookup(&absolute_file)
+        })
+        .collect();
+
+    let mut config_map: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for (file, nearest_config) in changed_files.iter().zip(nearest) {
+        if let Some(nearest_config) = nearest_config {
+            trace!(level: TRACE_FILE, "  {} -> {}", file.display(), nearest_config.display());
+            config_map
+                .entry(nearest_config)
+                .or_default()
+                .push(file.clone());
+        } else {
+            trace!(level: TRACE_FILE, "  {} -> NO CONFIG (will be skipped)", file.display());
+            // No config found for this file - it will be skipped
+            // This is expected behavior for files without hook configuration
+        }
+    }
+
+    config_map
+}
+
+/// Every config that governs a file in the repository, sorted
+///
+/// Each non-ignored file (tracked or not) is mapped to its nearest config the
+/// way changed files are, so nested configs anywhere in the repository are
+/// found, along with the repo default or user config if a file falls back to
+/// it. With a `--config` override that is the only config.
+///
+/// # Errors
+///
+/// Returns an error if the repository's files cannot be listed
+pub fn configs_in_repo(repo_root: &Path, overrides: &ConfigOverrides) -> Result<Vec<PathBuf>> {
+    let files = crate::git::LintFileDiscovery::new(repo_root)
+        .discover_files()
+        .context("Failed to list repository files")?;
+    Ok(map_files_to_configs(&files, repo_root, overrides)
+        .into_keys()
+        .collect())
+}
+
+/// Changed files that no config governs
+///
+/// These are the files `group_files_by_config` leaves out. With a `--config`
+/// override, a repo default or a user config every file is covered.
+#[must_use]
+pub fn uncovered_files(
+    changed_files: &[PathBuf],
+    repo_root: &Path,
+    overrides: &ConfigOverrides,
+) -> Vec<PathBuf> {
+    if overrides.config().is_some() {
+        return Vec::new();
+    }
+
+    let cache = NearestConfigCache::new(repo_root);
+    changed_files
+        .iter()
+        .filter(|file| cache.lookup(&repo_root.join(file)).is_none())
+        .cloned()
+        .collect()
 }
 
-/// Check if a hook should run based on file patterns and changed files
+/// Check whether a hook must be skipped based on its file requirements,
+/// patterns and the changed files
+///
+/// For `requires_files` hooks, `None` means the event cannot provide a file
+/// list (e.g. `--no-git` or message hooks) and `Some` with no files means a
+/// list was provided but nothing changed; both skip the hook, for different
+/// reasons. Content patterns are checked against files read relative to
+/// `repo_root`. With a `file_scope`, globs match paths relative to that
+/// directory. Hooks with `min_changed_files`/`max_changed_files` are also
+/// skipped when the number of matching files is out of range.
 ///
 /// # Errors
 ///
-/// Returns an error if glob patterns are invalid
-fn should_run_hook(
+/// Returns an error if glob or content patterns are invalid
+fn skip_reason(
     hook_def: &HookDefinition,
     changed_files: Option<&[PathBuf]>,
-) -> Result<bool> {
+    repo_root: &Path,
+    file_scope: Option<&Path>,
+) -> Result<Option<SkipReason>> {
     use crate::git::FilePatternMatcher;
 
+    if hook_def.requires_files {
+        match changed_files {
+            None => return Ok(Some(SkipReason::NoFileList)),
+            Some([]) => return Ok(Some(SkipReason::NoChangedFiles)),
+            Some(_) => {}
+        }
+    }
+
     // If run_always is true, always run
     if hook_def.run_always {
-        return Ok(true);
+        return Ok(None);
     }
 
-    // If no file patterns specified, always run
-    let Some(patterns) = &hook_def.files else {
-        return Ok(true);
-    };
+    let matcher = FilePatternMatcher::for_hook(hook_def, repo_root)
+        .context("Failed to compile file patterns")?
+        .map(|matcher| matcher.with_scope(file_scope));
 
     // If no changed files provided, always run (file filtering disabled)
     let Some(files) = changed_files else {
-        return Ok(true);
+        return Ok(None);
     };
 
-    // Check if any changed files match the patterns
-    let matcher = FilePatternMatcher::new(patterns).context("Failed to compile file patterns")?;
+    if hook_def.min_changed_files.is_none() && hook_def.max_changed_files.is_none() {
+        // Without patterns always run, otherwise check if any changed files
+        // match them
+        let any_match = matcher.is_none_or(|matcher| matcher.matches_any(files));
+        return Ok((!any_match).then_some(SkipReason::NoMatchingFiles));
+    }
 
+    let has_patterns = matcher.is_some();
+    let count = matcher.map_or(files.len(), |matcher| matcher.count_matches(files));
+    if has_patterns && count == 0 {
+        return Ok(Some(SkipReason::NoMatchingFiles));
+    }
+    Ok(hook_def
+        .changed_file_count_skip_reason(count)
+        .map(SkipReason::ChangedFileCount))
+}
+
+/// The first `if_exists` file missing from `working_directory`, if any
+fn missing_required_file(
+    hook_def: &HookDefinition,
+    working_directory: &Path,
+) -> Option<SkipReason> {
+    hook_def
+        .if_exists
+        .iter()
+        .flatten()
+        .find(|file| !working_directory.join(file).exists())
+        .map(|file| SkipReason::MissingFile(file.clone()))
+}
+
+/// Record that resolution left `name` out, tracing why
+fn record_skip(name: &str, reason: SkipReason, skipped: &mut Vec<SkippedHook>) {
+    trace!("Skipping hook '{}': {}", name, reason);
+    skipped.push(SkippedHook {
+        name: name.to_string(),
+        reason,
+    });
+}
+
+/// Repo-relative directory that a config's `files` globs are relative to
+///
+/// `None` unless the config sets `files_relative_to = "config_dir"` and
+/// lives below the repository root.
+fn file_scope(config: &HookConfig, config_dir: &Path, repo_root: &Path) -> Option<PathBuf> {
+    match config.files_relative_to.unwrap_or_default() {
+        FilesRelativeTo::RepoRoot => None,
+        FilesRelativeTo::ConfigDir => config_dir
+            .strip_prefix(repo_root)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map(Path::to_path_buf),
+    }
+}
+
+/// Check if a group's own `files` patterns match any changed file
+///
+/// Groups without `files`, or runs without file filtering, always match.
+///
+/// # Errors
+///
+/// Returns an error if the group's glob patterns are invalid
+fn group_matches_files(
+    group: &crate::config::HookGroup,
+    changed_files: Option<&[PathBuf]>,
+    file_scope: Option<&Path>,
+) -> Result<bool> {
+    let (Some(patterns), Some(files)) = (&group.files, changed_files) else {
+        return Ok(true);
+    };
+
+    let matcher = crate::git::FilePatternMatcher::new(patterns)
+        .context("Failed to compile group file patterns")?
+        .with_scope(file_scope);
     Ok(matcher.matches_any(files))
 }
 
 /// Resolve the working directory for a hook
+///
+/// `run_at_root` and `workdir` on the hook take precedence over the group or
+/// config-level `workdir_mode`.
 fn resolve_working_directory(
     hook_def: &HookDefinition,
     config_dir: &Path,
     repo_root: &Path,
+    workdir_mode: WorkdirMode,
 ) -> PathBuf {
     if hook_def.run_at_root {
         return repo_root.to_path_buf();
     }
 
     hook_def.workdir.as_ref().map_or_else(
-        || config_dir.to_path_buf(),
+        || match workdir_mode {
+            WorkdirMode::ConfigDir => config_dir.to_path_buf(),
+            WorkdirMode::RepoRoot => repo_root.to_path_buf(),
+        },
         |workdir| {
             let path = Path::new(workdir);
             if path.is_absolute() {
@@ -130,9 +455,12 @@ fn resolve_working_directory(
 
 /// Resolve all hooks in a group recursively
 ///
+/// Hooks left out are recorded in `skipped`.
+///
 /// # Errors
 ///
 /// Returns an error if hook resolution fails
+#[allow(clippy::too_many_arguments)]
 fn resolve_group_hooks(
     group: &crate::config::HookGroup,
     config: &HookConfig,
@@ -140,9 +468,15 @@ fn resolve_group_hooks(
     config_path: &Path,
     repo_root: &Path,
     resolved_hooks: &mut HashMap<String, crate::hooks::ResolvedHook>,
+    skipped: &mut Vec<SkippedHook>,
     changed_files: Option<&[PathBuf]>,
 ) -> Result<()> {
     let mut visited = HashSet::new();
+    let workdir_mode = group
+        .workdir_mode
+        .or(config.workdir_mode)
+        .unwrap_or_default();
+    let relative_paths = group.relative_paths.or(config.relative_paths);
     resolve_group_hooks_recursive(
         group,
         config,
@@ -150,8 +484,11 @@ fn resolve_group_hooks(
         config_path,
         repo_root,
         resolved_hooks,
+        skipped,
         &mut visited,
         changed_files,
+        workdir_mode,
+        relative_paths,
     )
 }
 
@@ -168,9 +505,28 @@ fn resolve_group_hooks_recursive(
     config_path: &Path,
     repo_root: &Path,
     resolved_hooks: &mut HashMap<String, crate::hooks::ResolvedHook>,
+    skipped: &mut Vec<SkippedHook>,
     visited: &mut HashSet<String>,
     changed_files: Option<&[PathBuf]>,
+    workdir_mode: WorkdirMode,
+    relative_paths: Option<bool>,
 ) -> Result<()> {
+    let file_scope = file_scope(config, config_dir, repo_root);
+
+    if !group_matches_files(group, changed_files, file_scope.as_deref())? {
+        trace!(
+            "Skipping group [{}] because no changed files match its patterns",
+            group.includes.join(", ")
+        );
+        let hooks = config.hooks.as_ref();
+        for include in &group.includes {
+            if hooks.is_some_and(|hooks| hooks.contains_key(include)) {
+                record_skip(include, SkipReason::NoMatchingGroupFiles, skipped);
+            }
+        }
+        return Ok(());
+    }
+
     for include in &group.includes {
         if visited.contains(include) {
             continue; // Avoid infinite loops
@@ -180,24 +536,21 @@ fn resolve_group_hooks_recursive(
         // Try to resolve as individual hook first
         if let Some(hooks) = &config.hooks {
             if let Some(hook_def) = hooks.get(include) {
-                // Skip hooks that require files when no files are available
-                if hook_def.requires_files && changed_files.is_none() {
-                    trace!(
-                        "Skipping hook '{}' because it requires files but none are available",
-                        include
-                    );
-                    continue;
-                }
-
-                // Apply file filtering
-                if should_run_hook(hook_def, changed_files)? {
-                    let working_directory =
-                        resolve_working_directory(hook_def, config_dir, repo_root);
-
+                let working_directory =
+                    resolve_working_directory(hook_def, config_dir, repo_root, workdir_mode);
+                // Apply file filtering and required files
+                if let Some(reason) =
+                    skip_reason(hook_def, changed_files, repo_root, file_scope.as_deref())?
+                        .or_else(|| missing_required_file(hook_def, &working_directory))
+                {
+                    record_skip(include, reason, skipped);
+                } else {
                     let resolved = crate::hooks::ResolvedHook {
                         definition: hook_def.clone(),
                         working_directory,
                         source_file: config_path.to_path_buf(),
+                        file_scope: file_scope.clone(),
+                        relative_paths,
                     };
                     resolved_hooks.insert(include.clone(), resolved);
                 }
@@ -215,8 +568,11 @@ fn resolve_group_hooks_recursive(
                     config_path,
                     repo_root,
                     resolved_hooks,
+                    skipped,
                     visited,
                     changed_files,
+                    nested_group.workdir_mode.unwrap_or(workdir_mode),
+                    nested_group.relative_paths.or(relative_paths),
                 )?;
             }
         }
@@ -240,7 +596,8 @@ fn resolve_group_hooks_recursive(
 ///
 /// # Returns
 ///
-/// Resolved hooks if the event is defined, None otherwise
+/// Resolved hooks if the event is defined, None otherwise. When every hook
+/// was skipped the result has no hooks, only the skip reasons.
 ///
 /// # Errors
 ///
@@ -251,28 +608,45 @@ fn resolve_event_for_config(
     repo_root: &Path,
     changed_files: Option<&[PathBuf]>,
     worktree_context: &WorktreeContext,
+    overrides: &ConfigOverrides,
 ) -> Result<Option<ResolvedHooks>> {
     // Load ONLY the nearest config (no parent walking or merging)
-    let config = HookConfig::from_file(nearest_config_path)?;
+    let config = HookConfig::from_file_cached(nearest_config_path, overrides)?;
     let config_dir = nearest_config_path
         .parent()
         .context("Config file has no parent directory")?;
 
+    let file_scope = file_scope(&config, config_dir, repo_root);
+
     // Look for hooks that match the event name
     let mut resolved_hooks_map = HashMap::new();
+    let mut skipped = Vec::new();
     let mut execution_strategy = ExecutionStrategy::Sequential;
+    let mut description = None;
+    let (mut setup, mut teardown) = (None, None);
 
     // First, try to find a direct hook with the exact event name
     if let Some(hooks) = &config.hooks {
         if let Some(hook_def) = hooks.get(event) {
-            // Apply file filtering
-            if should_run_hook(hook_def, changed_files)? {
-                let working_directory = resolve_working_directory(hook_def, config_dir, repo_root);
-
+            let working_directory = resolve_working_directory(
+                hook_def,
+                config_dir,
+                repo_root,
+                config.workdir_mode.unwrap_or_default(),
+            );
+            // Apply file filtering and required files
+            if let Some(reason) =
+                skip_reason(hook_def, changed_files, repo_root, file_scope.as_deref())?
+                    .or_else(|| missing_required_file(hook_def, &working_directory))
+            {
+                record_skip(event, reason, &mut skipped);
+            } else {
                 let resolved = crate::hooks::ResolvedHook {
                     definition: hook_def.clone(),
                     working_directory,
                     source_file: nearest_config_path.to_path_buf(),
+                    file_scope: file_scope.clone(),
+                    relative_paths: config.relative_paths,
                 };
                 resolved_hooks_map.insert(event.to_string(), resolved);
             }
@@ -289,6 +663,7 @@ fn resolve_event_for_config(
             }
 
             execution_strategy = group.get_execution_strategy();
+            description.clone_from(&group.description);
             resolve_group_hooks(
                 group,
                 &config,
@@ -296,30 +671,118 @@ fn resolve_event_for_config(
                 nearest_config_path,
                 repo_root,
                 &mut resolved_hooks_map,
+                &mut skipped,
                 changed_files,
             )?;
+
+            let workdir_mode = group
+                .workdir_mode
+                .or(config.workdir_mode)
+                .unwrap_or_default();
+            (setup, teardown) = resolve_group_steps(event, group, &config, |definition| {
+                crate::hooks::ResolvedHook {
+                    working_directory: resolve_working_directory(
+                        &definition,
+                        config_dir,
+                        repo_root,
+                        workdir_mode,
+                    ),
+                    definition,
+                    source_file: nearest_config_path.to_path_buf(),
+                    file_scope: file_scope.clone(),
+                    relative_paths: group.relative_paths.or(config.relative_paths),
+                }
+            })?;
         }
     }
 
-    if resolved_hooks_map.is_empty() {
+    // A hook skipped via one include may still be resolved via another
+    let mut seen = HashSet::new();
+    skipped.retain(|skip| {
+        !resolved_hooks_map.contains_key(&skip.name) && seen.insert(skip.name.clone())
+    });
+
+    if resolved_hooks_map.is_empty() && skipped.is_empty() {
         return Ok(None);
     }
+    apply_group_dependencies(&config, &mut resolved_hooks_map);
 
     Ok(Some(ResolvedHooks {
         config_path: nearest_config_path.to_path_buf(),
         hooks: resolved_hooks_map,
         execution_strategy,
+        description,
+        setup,
+        teardown,
         changed_files: changed_files.map(<[PathBuf]>::to_vec),
+        skipped,
         worktree_context: worktree_context.clone(),
     }))
 }
 
+/// Resolve an event from the config nearest `current_dir`
+///
+/// Used when there is no file list (`None`) or nothing changed (`Some` of an
+/// empty list), so files cannot be grouped by config.
+///
+/// # Errors
+///
+/// Returns an error if config file parsing fails or hook resolution fails
+fn resolve_without_changed_files(
+    event: &str,
+    changed_files: Option<&[PathBuf]>,
+    repo_root: &Path,
+    current_dir: &Path,
+    worktree_context: &WorktreeContext,
+    overrides: &ConfigOverrides,
+) -> Result<Vec<ConfigGroup>> {
+    trace!(level: TRACE_PHASE, "No changed files - resolving from current directory");
+    let nearest_config = overrides.config().map_or_else(
+        || find_nearest_config_for_file(current_dir, repo_root),
+        |config_path| Some(config_path.to_path_buf()),
+    );
+    let Some(nearest_config) = nearest_config else {
+        trace!(level: TRACE_PHASE, "No config file found - returning empty result");
+        return Ok(Vec::new());
+    };
+
+    trace!(
+        "Resolving event '{}' from nearest config: {}",
+        event,
+        nearest_config.display()
+    );
+    if let Some(resolved) = resolve_event_for_config(
+        &nearest_config,
+        event,
+        repo_root,
+        changed_files,
+        worktree_context,
+        overrides,
+    )? {
+        trace!(
+            level: TRACE_PHASE,
+            "✓ Event resolved successfully with {} hooks",
+            resolved.hooks.len()
+        );
+        return Ok(vec![ConfigGroup {
+            config_path: nearest_config,
+            files: Vec::new(),
+            resolved_hooks: resolved,
+        }]);
+    }
+    trace!(level: TRACE_PHASE, "✗ Event '{}' not defined in any config", event);
+    Ok(Vec::new())
+}
+
 /// Group changed files by their nearest hooks.toml configuration
 ///
 /// This is the main entry point for hierarchical resolution. For each changed
 /// file, it finds the nearest hooks.toml that defines the requested event, then
 /// groups files that share the same configuration.
 ///
+/// Nearest-config lookups run in parallel; groups are returned sorted by
+/// config path regardless of thread count.
+///
 /// # Arguments
 ///
 /// * `changed_files` - List of files that have changed
@@ -339,39 +802,18 @@ pub fn group_files_by_config(
     repo_root: &Path,
     event: &str,
     worktree_context: &WorktreeContext,
+    overrides: &ConfigOverrides,
 ) -> Result<Vec<ConfigGroup>> {
-    trace!("--- Grouping Files by Config ---");
+    trace!(level: TRACE_PHASE, "--- Grouping Files by Config ---");
     trace!(
         "Grouping {} files by their nearest config",
         changed_files.len()
     );
 
-    // Map from config path to list of files
-    let mut config_map: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    // Map from config path to list of files (sorted by config path)
+    let config_map = map_files_to_configs(changed_files, repo_root, overrides);
 
-    // For each file, find its nearest config (for grouping)
-    for file in changed_files {
-        let absolute_file = if file.is_absolute() {
-            file.clone()
-        } else {
-            repo_root.join(file)
-        };
-
-        // Find the nearest config for grouping
-        if let Some(nearest_config) = find_nearest_config_for_file(&absolute_file, repo_root) {
-            trace!("  {} -> {}", file.display(), nearest_config.display());
-            config_map
-                .entry(nearest_config)
-                .or_default()
-                .push(file.clone());
-        } else {
-            trace!("  {} -> NO CONFIG (will be skipped)", file.display());
-            // No config found for this file - it will be skipped
-            // This is expected behavior for files without hook configuration
-        }
-    }
-
-    trace!("Found {} unique config locations", config_map.len());
+    trace!(level: TRACE_PHASE, "Found {} unique config locations", config_map.len());
 
     // Now resolve hooks for each config (standalone, no merging)
     let mut groups = Vec::new();
@@ -388,6 +830,7 @@ pub fn group_files_by_config(
             repo_root,
             Some(&files),
             worktree_context,
+            overrides,
         )? {
             trace!(
                 "  ✓ Resolved {} hooks for this group",
@@ -403,10 +846,95 @@ pub fn group_files_by_config(
         }
     }
 
-    trace!("--- End File Grouping ---");
+    trace!(level: TRACE_PHASE, "--- End File Grouping ---");
     Ok(groups)
 }
 
+/// Merge groups whose configs resolved to identical hooks
+///
+/// Sibling configs with the same hooks would otherwise run them once per
+/// config. Merged groups keep the first config's path and run once over the
+/// union of their files. Hooks only match when their definitions, working
+/// directories and file handling are equal and they do not depend on their
+/// config's directory (`workdir`, `{HOOK_DIR}`, `{PROJECT_NAME}`).
+#[must_use]
+pub fn merge_identical_groups(groups: Vec<ConfigGroup>) -> Vec<ConfigGroup> {
+    let mut merged: Vec<ConfigGroup> = Vec::new();
+    for group in groups {
+        let Some(existing) = merged
+            .iter_mut()
+            .find(|existing| same_hooks(&existing.resolved_hooks, &group.resolved_hooks))
+        else {
+            merged.push(group);
+            continue;
+        };
+        trace!(
+            "Merging config {} into identical config {}",
+            group.config_path.display(),
+            existing.config_path.display()
+        );
+        for file in group.files {
+            if !existing.files.contains(&file) {
+                existing.files.push(file);
+            }
+        }
+        let resolved = &mut existing.resolved_hooks;
+        if let (Some(changed), Some(more)) = (
+            resolved.changed_files.as_mut(),
+            group.resolved_hooks.changed_files,
+        ) {
+            for file in more {
+                if !changed.contains(&file) {
+                    changed.push(file);
+                }
+            }
+        }
+        for skip in group.resolved_hooks.skipped {
+            if !resolved.skipped.iter().any(|other| other.name == skip.name) {
+                resolved.skipped.push(skip);
+            }
+        }
+    }
+    merged
+}
+
+/// Whether two configs' resolved hooks would run identically
+fn same_hooks(a: &ResolvedHooks, b: &ResolvedHooks) -> bool {
+    let same_step = |a: &GroupStep, b: &GroupStep| match (a, b) {
+        (Some((a_name, a)), Some((b_name, b))) => a_name == b_name && same_hook(a, b),
+        (None, None) => true,
+        _ => false,
+    };
+    a.execution_strategy == b.execution_strategy
+        && a.description == b.description
+        && a.hooks.len() == b.hooks.len()
+        && a.hooks.iter().all(|(name, hook)| {
+            b.hooks
+                .get(name)
+                .is_some_and(|other| same_hook(hook, other))
+        })
+        && same_step(&a.setup, &b.setup)
+        && same_step(&a.teardown, &b.teardown)
+}
+
+fn same_hook(a: &ResolvedHook, b: &ResolvedHook) -> bool {
+    a.definition == b.definition
+        && a.working_directory == b.working_directory
+        && a.file_scope == b.file_scope
+        && a.relative_paths == b.relative_paths
+        && (a.source_file == b.source_file || !uses_config_dir(&a.definition))
+}
+
+/// Whether a hook's behaviour depends on the directory of its config
+fn uses_config_dir(definition: &HookDefinition) -> bool {
+    definition.workdir.is_some()
+        || serde_json::to_string(definition).map_or(true, |json| {
+            ["{HOOK_DIR", "{PROJECT_NAME}"]
+                .iter()
+                .any(|template| json.contains(template))
+        })
+}
+
 /// Resolve hooks hierarchically for all changed files
 ///
 /// This is the main public API for hierarchical resolution. It:
@@ -435,9 +963,10 @@ pub fn resolve_hooks_hierarchically(
     repo_root: &Path,
     current_dir: &Path,
     worktree_context: &WorktreeContext,
+    overrides: &ConfigOverrides,
 ) -> Result<Vec<ConfigGroup>> {
-    trace!("=== Hierarchical Resolution Started ===");
-    trace!("Event: {}", event);
+    trace!(level: TRACE_PHASE, "=== Hierarchical Resolution Started ===");
+    trace!(level: TRACE_PHASE, "Event: {}", event);
     trace!("Repo root: {}", repo_root.display());
     trace!("Current dir: {}", current_dir.display());
     trace!("Change mode: {:?}", change_mode);
@@ -445,66 +974,47 @@ pub fn resolve_hooks_hierarchically(
     // Get changed files if we have a detection mode
     let changed_files = if let Some(mode) = change_mode {
         trace!("Detecting changed files with mode: {:?}", mode);
-        let detector = crate::git::GitChangeDetector::new(repo_root)
-            .context("Failed to create git change detector")?;
-        let files = detector
-            .get_changed_files(&mode)
-            .context("Failed to detect changed files")?;
-        trace!("Detected {} changed files", files.len());
+        let files = detect_changed_files(&mode, repo_root, current_dir, overrides)?;
+        trace!(level: TRACE_PHASE, "Detected {} changed files", files.len());
         for (i, file) in files.iter().enumerate().take(10) {
-            trace!("  [{}] {}", i + 1, file.display());
+            trace!(level: TRACE_FILE, "  [{}] {}", i + 1, file.display());
         }
         if files.len() > 10 {
-            trace!("  ... and {} more files", files.len() - 10);
+            trace!(level: TRACE_FILE, "  ... and {} more files", files.len() - 10);
         }
-        files
+        Some(files)
     } else {
-        trace!("No change detection mode - using --all-files or dry-run");
-        // If no change mode (--all-files), use current directory to find config
-        // and return empty files list to trigger run_always hooks
-        Vec::new()
+        trace!(level: TRACE_PHASE, "No change detection mode - using --all-files or dry-run");
+        // No file list can be provided (distinct from an empty list of changes)
+        None
     };
 
-    if changed_files.is_empty() {
-        trace!("No changed files - resolving from current directory");
-        // No files changed - find nearest config from current directory
-        let Some(nearest_config) = find_nearest_config_for_file(current_dir, repo_root) else {
-            trace!("No config file found - returning empty result");
-            return Ok(Vec::new());
-        };
-
-        trace!(
-            "Resolving event '{}' from nearest config: {}",
-            event,
-            nearest_config.display()
-        );
-        if let Some(resolved) = resolve_event_for_config(
-            &nearest_config,
-            event,
-            repo_root,
-            None, // No files to filter
-            worktree_context,
-        )? {
-            trace!(
-                "✓ Event resolved successfully with {} hooks",
-                resolved.hooks.len()
+    let changed_files = match changed_files {
+        Some(files) if !files.is_empty() => files,
+        changed_files => {
+            return resolve_without_changed_files(
+                event,
+                changed_files.as_deref(),
+                repo_root,
+                current_dir,
+                worktree_context,
+                overrides,
             );
-            return Ok(vec![ConfigGroup {
-                config_path: nearest_config,
-                files: Vec::new(),
-                resolved_hooks: resolved,
-            }]);
         }
-        trace!("✗ Event '{}' not defined in any config", event);
-        return Ok(Vec::new());
-    }
+    };
 
     trace!(
         "Grouping {} changed files by their nearest config",
         changed_files.len()
     );
-    let groups = group_files_by_config(&changed_files, repo_root, event, worktree_context)?;
-    trace!("Created {} config groups", groups.len());
+    let groups = group_files_by_config(
+        &changed_files,
+        repo_root,
+        event,
+        worktree_context,
+        overrides,
+    )?;
+    trace!(level: TRACE_PHASE, "Created {} config groups", groups.len());
     for (i, group) in groups.iter().enumerate() {
         trace!(
             "  Group[{}]: {} (with {} files, {} hooks)",
@@ -514,7 +1024,7 @@ pub fn resolve_hooks_hierarchically(
             group.resolved_hooks.hooks.len()
         );
     }
-    trace!("=== Hierarchical Resolution Complete ===");
+    trace!(level: TRACE_PHASE, "=== Hierarchical Resolution Complete ===");
     Ok(groups)
 }
 
@@ -589,6 +1099,246 @@ command = "echo src"
         assert_eq!(config, Some(repo_root.join("hooks.toml")));
     }
 
+    #[test]
+    fn test_parallel_grouping_matches_sequential() {
+        let temp_dir = create_test_repo();
+        let repo_root = temp_dir.path();
+
+        // Several config dirs, plus a directory with no config of its own
+        let config_dirs = ["", "services/api", "services/web", "libs/core"];
+        for dir in config_dirs {
+            fs::create_dir_all(repo_root.join(dir)).unwrap();
+            fs::write(
+                repo_root.join(dir).join("hooks.toml"),
+                "[hooks.test]\ncommand = \"true\"\n",
+            )
+            .unwrap();
+        }
+        let file_dirs = [
+            "",
+            "docs",
+            "services/api/src",
+            "services/api/src/handlers",
+            "services/web",
+            "services/web/assets/css",
+            "libs/core/src",
+            "libs/util",
+        ];
+
+        let mut changed_files = Vec::new();
+        for i in 0..2000 {
+            let dir = file_dirs[i % file_dirs.len()];
+            fs::create_dir_all(repo_root.join(dir)).unwrap();
+            let relative = Path::new(dir).join(format!("file_{i}.rs"));
+            fs::write(repo_root.join(&relative), "").unwrap();
+            changed_files.push(relative);
+        }
+
+        // Sequential reference using the uncached walk
+        let mut expected: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for file in &changed_files {
+            if let Some(config) = find_nearest_config_for_file(&repo_root.join(file), repo_root) {
+                expected.entry(config).or_default().push(file.clone());
+            }
+        }
+        assert_eq!(expected.len(), config_dirs.len());
+
+        for threads in [1, 2, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let actual = pool.install(|| {
+                map_files_to_configs(&changed_files, repo_root, &ConfigOverrides::default())
+            });
+            assert_eq!(actual, expected, "mismatch with {threads} threads");
+        }
+
+        let worktree_context = WorktreeContext {
+            is_worktree: false,
+            worktree_name: None,
+            repo_root: repo_root.to_path_buf(),
+            common_dir: repo_root.to_path_buf(),
+            working_dir: repo_root.to_path_buf(),
+        };
+        let groups = group_files_by_config(
+            &changed_files,
+            repo_root,
+            "test",
+            &worktree_context,
+            &ConfigOverrides::default(),
+        )
+        .unwrap();
+        let grouped: Vec<(PathBuf, Vec<PathBuf>)> = groups
+            .into_iter()
+            .map(|group| (group.config_path, group.files))
+            .collect();
+        assert_eq!(grouped, expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_config_parsed_once_across_groups_and_events() {
+        let temp_dir = create_test_repo();
+        let repo_root = temp_dir.path();
+
+        fs::create_dir_all(repo_root.join("a")).unwrap();
+        fs::create_dir_all(repo_root.join("b")).unwrap();
+        let config = r#"
+[hooks.check]
+command = "true"
+
+[groups.pre-commit]
+includes = ["check"]
+
+[groups.pre-push]
+includes = ["check"]
+"#;
+        fs::write(repo_root.join("a/hooks.toml"), config).unwrap();
+        fs::write(repo_root.join("b/hooks.toml"), config).unwrap();
+
+        let changed_files = vec![
+            PathBuf::from("a/one.rs"),
+            PathBuf::from("a/two.rs"),
+            PathBuf::from("b/three.rs"),
+        ];
+        let worktree_context = WorktreeContext {
+            is_worktree: false,
+            worktree_name: None,
+            repo_root: repo_root.to_path_buf(),
+            common_dir: repo_root.to_path_buf(),
+            working_dir: repo_root.to_path_buf(),
+        };
+
+        for event in ["pre-commit", "pre-push", "pre-commit"] {
+            let groups = group_files_by_config(
+                &changed_files,
+                repo_root,
+                event,
+                &worktree_context,
+                &ConfigOverrides::default(),
+            )
+            .unwrap();
+            assert_eq!(groups.len(), 2);
+            assert!(
+                groups
+                    .iter()
+                    .all(|g| g.resolved_hooks.hooks.contains_key("check"))
+            );
+        }
+
+        assert_eq!(
+            HookConfig::cached_parse_count(repo_root.join("a/hooks.toml")),
+            1
+        );
+        assert_eq!(
+            HookConfig::cached_parse_count(repo_root.join("b/hooks.toml")),
+            1
+        );
+
+        // Editing the file invalidates the cached parse
+        fs::write(
+            repo_root.join("a/hooks.toml"),
+            "[hooks.other]\ncommand = \"true\"\n\n[groups.pre-commit]\nincludes = [\"other\"]\n",
+        )
+        .unwrap();
+        let groups = group_files_by_config(
+            &changed_files,
+            repo_root,
+            "pre-commit",
+            &worktree_context,
+            &ConfigOverrides::default(),
+        )
+        .unwrap();
+        assert!(groups[0].resolved_hooks.hooks.contains_key("other"));
+        assert_eq!(
+            HookConfig::cached_parse_count(repo_root.join("a/hooks.toml")),
+            2
+        );
+    }
+
+    #[test]
+    fn test_workdir_mode_defaults_and_overrides() {
+        let temp_dir = create_test_repo();
+        let repo_root = temp_dir.path();
+        fs::create_dir_all(repo_root.join("pkg")).unwrap();
+        fs::write(
+            repo_root.join("pkg/hooks.toml"),
+            r#"
+workdir_mode = "repo_root"
+
+[hooks.plain]
+command = "true"
+
+[hooks.explicit]
+command = "true"
+workdir = "."
+
+[hooks.pre-push]
+command = "true"
+
+[groups.pre-commit]
+includes = ["plain", "explicit", "nested"]
+
+[groups.nested]
+includes = ["nested-only"]
+workdir_mode = "config_dir"
+
+[hooks.nested-only]
+command = "true"
+
+[groups.commit-msg]
+includes = ["plain"]
+workdir_mode = "config_dir"
+"#,
+        )
+        .unwrap();
+
+        let worktree_context = WorktreeContext {
+            is_worktree: false,
+            worktree_name: None,
+            repo_root: repo_root.to_path_buf(),
+            common_dir: repo_root.to_path_buf(),
+            working_dir: repo_root.to_path_buf(),
+        };
+        let config_path = repo_root.join("pkg/hooks.toml");
+        let resolve = |event: &str| {
+            resolve_event_for_config(
+                &config_path,
+                event,
+                repo_root,
+                None,
+                &worktree_context,
+                &ConfigOverrides::default(),
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        // Config-level mode applies to groups and direct hooks
+        let resolved = resolve("pre-commit");
+        assert_eq!(resolved.hooks["plain"].working_directory, repo_root);
+        // Per-hook workdir still wins
+        assert_eq!(
+            resolved.hooks["explicit"].working_directory,
+            repo_root.join("pkg/.")
+        );
+        // Nested group sets its own mode
+        assert_eq!(
+            resolved.hooks["nested-only"].working_directory,
+            repo_root.join("pkg")
+        );
+        assert_eq!(
+            resolve("pre-push").hooks["pre-push"].working_directory,
+            repo_root
+        );
+
+        // Group-level mode overrides the config-level mode
+        assert_eq!(
+            resolve("commit-msg").hooks["plain"].working_directory,
+            repo_root.join("pkg")
+        );
+    }
+
     #[test]
     fn test_no_config_merging_child_only_uses_own_hooks() {
         // Test that child configs DO NOT inherit from parent configs
@@ -647,6 +1397,7 @@ execution = "parallel"
             repo_root,
             None,
             &worktree_context,
+            &ConfigOverrides::default(),
         )
         .unwrap()
         .unwrap();
@@ -657,4 +1408,79 @@ execution = "parallel"
         assert!(!resolved.hooks.contains_key("format"));
         assert!(!resolved.hooks.contains_key("lint"));
     }
+
+    #[test]
+    fn test_inherit_merges_parent_config_hooks() {
+        let temp_dir = create_test_repo();
+        let repo_root = temp_dir.path();
+
+        fs::create_dir_all(repo_root.join("src")).unwrap();
+
+        fs::write(
+            repo_root.join("hooks.toml"),
+            r#"
+[hooks.format]
+command = "cargo fmt"
+modifies_repository = false
+
+[hooks.lint]
+command = "cargo clippy"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["format", "lint"]
+execution = "parallel"
+"#,
+        )
+        .unwrap();
+
+        // Child opts in, adds test and overrides lint
+        fs::write(
+            repo_root.join("src/hooks.toml"),
+            r#"
+inherit = true
+
+[hooks.lint]
+command = "cargo clippy -- -D warnings"
+modifies_repository = false
+
+[hooks.test]
+command = "cargo test"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["test"]
+execution = "parallel"
+"#,
+        )
+        .unwrap();
+
+        let worktree_context = WorktreeContext {
+            is_worktree: false,
+            worktree_name: None,
+            repo_root: repo_root.to_path_buf(),
+            common_dir: repo_root.to_path_buf(),
+            working_dir: repo_root.to_path_buf(),
+        };
+
+        let resolved = resolve_event_for_config(
+            &repo_root.join("src/hooks.toml"),
+            "pre-commit",
+            repo_root,
+            None,
+            &worktree_context,
+            &ConfigOverrides::default(),
+        )
+        .unwrap()
+        .unwrap();
+
+        // The child's own hook plus the root's, with the child's lint winning
+        let mut names: Vec<_> = resolved.hooks.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["format", "lint", "test"]);
+        assert_eq!(
+            resolved.hooks["lint"].definition.command.to_string(),
+            "cargo clippy -- -D warnings"
+        );
+    }
 }
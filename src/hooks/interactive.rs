@@ -0,0 +1,162 @@
+//! Confirmation prompts for `run --interactive`
+//!
+//! Hooks with `modifies_repository = true` are shown with their command and
+//! only kept if the user answers yes. Everything else runs without asking.
+
+use crate::hooks::ConfigGroup;
+use anyhow::{Context, Result};
+use std::io::{BufRead, Write};
+
+/// Ask before each repository-modifying hook and drop the ones declined
+///
+/// Anything other than `y`/`yes` (including end of input) counts as no.
+/// Returns the names of the skipped hooks in prompt order.
+///
+/// # Errors
+///
+/// Returns an error if the prompt cannot be written or the answer read
+pub fn confirm_modifying_hooks<R: BufRead, W: Write>(
+    groups: &mut [ConfigGroup],
+    input: &mut R,
+    output: &mut W,
+) -> Result<Vec<String>> {
+    let mut skipped = Vec::new();
+
+    for group in groups {
+        let mut names: Vec<String> = group
+            .resolved_hooks
+            .hooks
+            .iter()
+            .filter(|(_, hook)| hook.definition.modifies_repository)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+
+        for name in names {
+            let command = group.resolved_hooks.hooks[&name]
+                .definition
+                .command
+                .to_string();
+            write!(
+                output,
+                "Hook '{name}' modifies the repository:\n  $ {command}\nRun it? [y/N] "
+            )
+            .and_then(|()| output.flush())
+            .context("Failed to write prompt")?;
+
+            let mut answer = String::new();
+            input
+                .read_line(&mut answer)
+                .context("Failed to read answer")?;
+
+            if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                group.resolved_hooks.hooks.remove(&name);
+                skipped.push(name);
+            }
+        }
+    }
+
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{ExecutionStrategy, HookConfig},
+        hooks::{ResolvedHook, ResolvedHooks, WorktreeContext},
+    };
+    use std::path::PathBuf;
+
+    fn group_from(toml: &str) -> ConfigGroup {
+        let config = HookConfig::parse(toml).unwrap();
+        let hooks = config
+            .hooks
+            .unwrap()
+            .into_iter()
+            .map(|(name, definition)| {
+                let hook = ResolvedHook {
+                    definition,
+                    working_directory: PathBuf::from("."),
+                    source_file: PathBuf::from("hooks.toml"),
+                    file_scope: None,
+                    relative_paths: None,
+                };
+                (name, hook)
+            })
+            .collect();
+
+        ConfigGroup {
+            config_path: PathBuf::from("hooks.toml"),
+            files: Vec::new(),
+            resolved_hooks: ResolvedHooks {
+                config_path: PathBuf::from("hooks.toml"),
+                hooks,
+                execution_strategy: ExecutionStrategy::Sequential,
+                description: None,
+                setup: None,
+                teardown: None,
+                changed_files: None,
+                skipped: Vec::new(),
+                worktree_context: WorktreeContext {
+                    repo_root: PathBuf::from("."),
+                    common_dir: PathBuf::from(".git"),
+                    working_dir: PathBuf::from("."),
+                    is_worktree: false,
+                    worktree_name: None,
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_prompts_only_for_modifying_hooks() {
+        let mut groups = vec![group_from(
+            r#"
+[hooks.format]
+command = "cargo fmt"
+modifies_repository = true
+
+[hooks.autofix]
+command = "eslint --fix"
+modifies_repository = true
+
+[hooks.lint]
+command = "cargo clippy"
+"#,
+        )];
+
+        // Prompts are in name order: autofix, then format
+        let mut input: &[u8] = b"y\nn\n";
+        let mut output = Vec::new();
+        let skipped = confirm_modifying_hooks(&mut groups, &mut input, &mut output).unwrap();
+
+        assert_eq!(skipped, vec!["format"]);
+        let hooks = &groups[0].resolved_hooks.hooks;
+        assert!(hooks.contains_key("autofix"));
+        assert!(hooks.contains_key("lint"));
+        assert!(!hooks.contains_key("format"));
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("$ cargo fmt"));
+        assert!(output.contains("[y/N]"));
+        assert!(!output.contains("cargo clippy"));
+    }
+
+    #[test]
+    fn test_end_of_input_declines() {
+        let mut groups = vec![group_from(
+            r#"
+[hooks.format]
+command = "cargo fmt"
+modifies_repository = true
+"#,
+        )];
+
+        let mut input: &[u8] = b"";
+        let skipped = confirm_modifying_hooks(&mut groups, &mut input, &mut Vec::new()).unwrap();
+
+        assert_eq!(skipped, vec!["format"]);
+        assert!(groups[0].resolved_hooks.hooks.is_empty());
+    }
+}
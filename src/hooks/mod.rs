@@ -1,9 +1,15 @@
+pub mod cache;
 pub mod dependencies;
 pub mod executor;
 pub mod hierarchical;
+pub mod interactive;
+pub mod notify;
 pub mod resolver;
 
+pub use cache::*;
 pub use dependencies::*;
 pub use executor::*;
 pub use hierarchical::*;
+pub use interactive::*;
+pub use notify::*;
 pub use resolver::*;
@@ -0,0 +1,206 @@
+//! Post-run notification and failure diagnostic commands
+//!
+//! A config may define a `[notify]` section whose command runs after every
+//! hook run, pass or fail. The run results are written to its stdin as JSON
+//! and the overall status is exported as `PETER_HOOK_STATUS`.
+//!
+//! An `[on_failure]` section runs only after a failed run, with the failing
+//! hook names exported as `PETER_HOOK_FAILED_HOOKS`.
+//!
+//! Both are killed like timed-out hooks once they run longer than their
+//! `timeout_seconds`.
+
+use crate::{
+    config::{HookCommand, NotifyConfig, OnFailureConfig},
+    hooks::{ExecutionResults, HookExecutor, executor::LiveGroup},
+    trace,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    io::Write,
+    path::Path,
+    process::{Child, Command, ExitStatus, Stdio},
+    time::Duration,
+};
+
+/// Time a notify-style command gets to exit after SIGTERM when it timed out
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// JSON document passed to the notify command on stdin
+#[derive(Debug, Serialize)]
+pub struct NotifyPayload<'a> {
+    /// Git event that was run
+    pub event: &'a str,
+    /// `success` or `failure`
+    pub status: &'static str,
+    /// Per-hook results (absent if execution aborted with an error)
+    pub results: Option<&'a ExecutionResults>,
+    /// Error that aborted execution, if any
+    pub error: Option<String>,
+}
+
+impl<'a> NotifyPayload<'a> {
+    /// Build a payload from the outcome of a hook run
+    #[must_use]
+    pub fn new(event: &'a str, outcome: Result<&'a ExecutionResults, String>) -> Self {
+        match outcome {
+            Ok(results) => Self {
+                event,
+                status: if results.success {
+                    "success"
+                } else {
+                    "failure"
+                },
+                results: Some(results),
+                error: None,
+            },
+            Err(error) => Self {
+                event,
+                status: "failure",
+                results: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// Run the notify command from `config_dir` with the payload on stdin
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be spawned or exits unsuccessfully
+pub fn run_notify(notify: &NotifyConfig, config_dir: &Path, payload: &NotifyPayload) -> Result<()> {
+    let mut command = build_command(&notify.command, "notify", notify.timeout_seconds > 0)?;
+
+    let json = serde_json::to_string(payload).context("Failed to serialize notify payload")?;
+
+    trace!(
+        "Running notify command in {} (status: {})",
+        config_dir.display(),
+        payload.status
+    );
+
+    command
+        .current_dir(config_dir)
+        .env("PETER_HOOK_STATUS", payload.status)
+        .env("PETER_HOOK_EVENT", payload.event)
+        .stdin(Stdio::piped());
+    let (mut child, _group) = LiveGroup::spawn(&mut command, notify.timeout_seconds > 0)
+        .context("Failed to spawn notify command")?;
+
+    // Written from a thread so a notifier that never reads stdin cannot
+    // block the run before its timeout applies
+    let writer = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            // A notifier that ignores stdin may close it early; that is fine
+            let _ = stdin.write_all(json.as_bytes());
+        })
+    });
+
+    let status = wait_with_timeout(&mut child, notify.timeout_seconds, "Notify");
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    let status = status?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "Notify command exited with code {}",
+            status.code().unwrap_or(-1)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run the `[on_failure]` command from `config_dir` after a failed run
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be spawned or exits unsuccessfully
+pub fn run_on_failure(
+    on_failure: &OnFailureConfig,
+    config_dir: &Path,
+    event: &str,
+    failed_hooks: &[&str],
+) -> Result<()> {
+    let mut command = build_command(
+        &on_failure.command,
+        "on_failure",
+        on_failure.timeout_seconds > 0,
+    )?;
+
+    trace!(
+        "Running on_failure command in {} (failed: {})",
+        config_dir.display(),
+        failed_hooks.join(",")
+    );
+
+    command
+        .current_dir(config_dir)
+        .env("PETER_HOOK_FAILED_HOOKS", failed_hooks.join(","))
+        .env("PETER_HOOK_EVENT", event)
+        .stdin(Stdio::null());
+    let (mut child, _group) = LiveGroup::spawn(&mut command, on_failure.timeout_seconds > 0)
+        .context("Failed to run on_failure command")?;
+    let status = wait_with_timeout(&mut child, on_failure.timeout_seconds, "on_failure")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "on_failure command exited with code {}",
+            status.code().unwrap_or(-1)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Wait for a notify-style command, killing it (and what it started) like a
+/// timed-out hook once it runs longer than `timeout_seconds` (0 waits
+/// however long it takes)
+///
+/// # Errors
+///
+/// Returns an error if the command timed out or cannot be waited for
+fn wait_with_timeout(child: &mut Child, timeout_seconds: u64, kind: &str) -> Result<ExitStatus> {
+    use wait_timeout::ChildExt;
+
+    let waited = if timeout_seconds == 0 {
+        child.wait().map(Some)
+    } else {
+        child.wait_timeout(Duration::from_secs(timeout_seconds))
+    };
+    if let Some(status) = waited.with_context(|| format!("Failed to wait for {kind} command"))? {
+        return Ok(status);
+    }
+    HookExecutor::terminate_child(child, KILL_GRACE);
+    Err(anyhow::anyhow!(
+        "{kind} command exceeded timeout of {timeout_seconds} seconds and was killed"
+    ))
+}
+
+/// Build the process for a notify-style command (`kind` names it in errors)
+///
+/// Like a hook, it leads its own process group when it has a timeout
+/// (`own_group`), so the timeout can stop it.
+fn build_command(command: &HookCommand, kind: &str, own_group: bool) -> Result<Command> {
+    match command {
+        HookCommand::Shell(cmd) => {
+            let mut command = HookExecutor::hook_command("sh", None, own_group);
+            command.args(["-c", cmd]);
+            Ok(command)
+        }
+        HookCommand::Args(args) => {
+            let (program, rest) = args
+                .split_first()
+                .ok_or_else(|| anyhow::anyhow!("Empty {kind} command"))?;
+            let mut command = HookExecutor::hook_command(program, None, own_group);
+            command.args(rest);
+            Ok(command)
+        }
+        HookCommand::Steps { steps } => {
+            let mut command = HookExecutor::hook_command("sh", None, own_group);
+            command.args(["-c", &steps.join(" && ")]);
+            Ok(command)
+        }
+    }
+}
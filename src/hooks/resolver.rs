@@ -1,7 +1,9 @@
 //! Hierarchical hook resolution system
 
 use crate::{
-    config::{ExecutionStrategy, HookConfig, HookDefinition, HookGroup},
+    config::{
+        ConfigOverrides, ConfigSource, ExecutionStrategy, HookConfig, HookDefinition, HookGroup,
+    },
     git::{
         ChangeDetectionMode, FilePatternMatcher, GitChangeDetector, GitRepository,
         LintFileDiscovery,
@@ -17,6 +19,8 @@ use std::{
 pub struct HookResolver {
     /// Current working directory where hook resolution starts
     current_dir: PathBuf,
+    /// Config selection from the command line
+    overrides: ConfigOverrides,
 }
 
 /// Result of hook resolution containing all applicable hooks
@@ -28,12 +32,121 @@ pub struct ResolvedHooks {
     pub hooks: HashMap<String, ResolvedHook>,
     /// Execution strategy for this group of hooks
     pub execution_strategy: ExecutionStrategy,
+    /// Description of the group being run, if it has one
+    pub description: Option<String>,
+    /// The group's `setup` hook, by name, run before all others
+    pub setup: Option<(String, ResolvedHook)>,
+    /// The group's `teardown` hook, by name, run after all others
+    pub teardown: Option<(String, ResolvedHook)>,
     /// Changed files (if file filtering is enabled)
     pub changed_files: Option<Vec<PathBuf>>,
+    /// Hooks of the event that were left out during resolution, and why
+    pub skipped: Vec<SkippedHook>,
     /// Worktree context information
     pub worktree_context: WorktreeContext,
 }
 
+/// A group's `setup` or `teardown` hook, by name
+pub type GroupStep = Option<(String, ResolvedHook)>;
+
+/// Resolve the `setup` and `teardown` hooks of group `group_name`
+///
+/// Both run once without a file list, so they are resolved as `run_always`;
+/// `resolve` places a definition in the group's context (working directory
+/// and so on).
+///
+/// # Errors
+///
+/// Returns an error if either names something other than a hook of `config`
+pub(crate) fn resolve_group_steps(
+    group_name: &str,
+    group: &HookGroup,
+    config: &HookConfig,
+    resolve: impl Fn(HookDefinition) -> ResolvedHook,
+) -> Result<(GroupStep, GroupStep)> {
+    let step = |name: &Option<String>| -> Result<GroupStep> {
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        let mut definition = config
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.get(name))
+            .with_context(|| {
+                format!(
+                    "Group '{group_name}' uses '{name}' as setup or teardown, which is not a \
+                     defined hook"
+                )
+            })?
+            .clone();
+        definition.run_always = true;
+        Ok(Some((name.clone(), resolve(definition))))
+    };
+    Ok((step(&group.setup)?, step(&group.teardown)?))
+}
+
+/// Replace the `depends_on` of each resolved hook with its effective
+/// dependencies in `config`, so groups named in its own `depends_on` or its
+/// groups' stand for the hooks they include
+pub(crate) fn apply_group_dependencies(
+    config: &HookConfig,
+    hooks: &mut HashMap<String, ResolvedHook>,
+) {
+    for (name, hook) in hooks {
+        hook.definition.depends_on = config.effective_depends_on(name);
+    }
+}
+
+/// A hook of the event that resolution left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedHook {
+    /// Name of the hook
+    pub name: String,
+    /// Why it was left out
+    pub reason: SkipReason,
+}
+
+/// Why resolution left a hook out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The hook requires files but the event cannot provide a file list
+    NoFileList,
+    /// The hook requires files but nothing changed
+    NoChangedFiles,
+    /// No changed file matches the hook's patterns
+    NoMatchingFiles,
+    /// No changed file matches the `files` patterns of the including group
+    NoMatchingGroupFiles,
+    /// The number of matching files is outside `min_changed_files` /
+    /// `max_changed_files`
+    ChangedFileCount(String),
+    /// A file listed in `if_exists` is missing from the working directory
+    MissingFile(String),
+    /// The hook is listed in `PETER_HOOK_SKIP`
+    SkipEnv,
+    /// The user declined the hook when asked (`run --interactive`)
+    Declined,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoFileList => write!(f, "requires files but none are available"),
+            Self::NoChangedFiles => write!(f, "requires files but no matching files changed"),
+            Self::NoMatchingFiles => write!(f, "no changed files match its patterns"),
+            Self::NoMatchingGroupFiles => {
+                write!(f, "no changed files match its group's patterns")
+            }
+            Self::ChangedFileCount(reason) => write!(f, "{reason}"),
+            Self::MissingFile(file) => {
+                write!(f, "{file} does not exist in its working directory")
+            }
+            Self::SkipEnv => write!(f, "listed in PETER_HOOK_SKIP"),
+            Self::Declined => write!(f, "declined interactively"),
+        }
+    }
+}
+
 /// A resolved hook ready for execution
 #[derive(Debug, Clone)]
 pub struct ResolvedHook {
@@ -43,6 +156,14 @@ pub struct ResolvedHook {
     pub working_directory: PathBuf,
     /// Source configuration file
     pub source_file: PathBuf,
+    /// Repo-relative directory that `files` patterns are relative to
+    /// (`None` when patterns match repo-root-relative paths)
+    pub file_scope: Option<PathBuf>,
+    /// How changed-file paths are presented to the hook: `Some(true)` makes
+    /// every path relative to the working directory (using `..` if needed),
+    /// `Some(false)` keeps them repo-relative, and `None` only rewrites paths
+    /// under the working directory
+    pub relative_paths: Option<bool>,
 }
 
 /// Worktree context information for template expansion and hook resolution
@@ -65,28 +186,68 @@ impl HookResolver {
     pub fn new<P: AsRef<Path>>(current_dir: P) -> Self {
         Self {
             current_dir: current_dir.as_ref().to_path_buf(),
+            overrides: ConfigOverrides::default(),
         }
     }
 
-    /// Find the nearest hooks.toml file by walking up the directory tree
+    /// Resolve with the config file, repository root and profile selected
+    /// by `overrides`
+    #[must_use]
+    pub fn with_overrides(mut self, overrides: ConfigOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// The overrides this resolver loads configs with
+    #[must_use]
+    pub const fn overrides(&self) -> &ConfigOverrides {
+        &self.overrides
+    }
+
+    /// Find the config governing the current directory
+    ///
+    /// See `find_config_source` for the precedence.
     ///
     /// # Errors
     ///
     /// Returns an error if there are filesystem access issues
     pub fn find_config_file(&self) -> Result<Option<PathBuf>> {
+        Ok(self.find_config_source()?.map(|(path, _)| path))
+    }
+
+    /// Find the config governing the current directory, and where it came
+    /// from
+    ///
+    /// In order: the `--config` override, the nearest hooks.toml found by
+    /// walking up the directory tree, `.peter-hook.toml` at the repository
+    /// root, then the user config (`$XDG_CONFIG_HOME/peter-hook/hooks.toml`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are filesystem access issues
+    pub fn find_config_source(&self) -> Result<Option<(PathBuf, ConfigSource)>> {
+        if let Some(config_path) = self.overrides.config() {
+            return Ok(Some((config_path.to_path_buf(), ConfigSource::Override)));
+        }
+
         let mut current = self.current_dir.as_path();
 
         loop {
             let config_path = current.join("hooks.toml");
             if config_path.exists() {
-                return Ok(Some(config_path));
+                return Ok(Some((config_path, ConfigSource::Hierarchical)));
             }
 
             match current.parent() {
                 Some(parent) => current = parent,
-                None => return Ok(None),
+                None => break,
             }
         }
+
+        let repo_root = GitRepository::find_from_dir(&self.current_dir)
+            .ok()
+            .map(|repo| repo.root);
+        Ok(crate::config::fallback_config(repo_root.as_deref()))
     }
 
     /// Resolve hooks for a given git event (e.g., "pre-commit", "pre-push")
@@ -113,7 +274,7 @@ impl HookResolver {
             return Ok(None);
         };
 
-        let config = HookConfig::from_file(&config_path)?;
+        let config = HookConfig::from_file(&config_path, &self.overrides)?;
         let config_dir = config_path
             .parent()
             .context("Config file has no parent directory")?;
@@ -134,7 +295,9 @@ impl HookResolver {
         // Get changed files if file filtering is requested
         let changed_files = if let Some(mode) = change_mode {
             let detector = GitChangeDetector::new(&self.current_dir)
-                .context("Failed to create git change detector")?;
+                .context("Failed to create git change detector")?
+                .with_rename_detection(config.rename_detection)
+                .with_untracked(config.include_untracked.unwrap_or(true));
             Some(
                 detector
                     .get_changed_files(&mode)
@@ -147,16 +310,20 @@ impl HookResolver {
         // Look for hooks that match the event name
         let mut resolved_hooks = HashMap::new();
         let mut execution_strategy = ExecutionStrategy::Sequential;
+        let mut description = None;
+        let (mut setup, mut teardown) = (None, None);
 
         // First, try to find a hook or group with the exact event name
         if let Some(hooks) = &config.hooks {
             if let Some(hook_def) = hooks.get(event) {
                 // Apply file filtering
-                if Self::should_run_hook(hook_def, changed_files.as_ref())? {
+                if Self::should_run_hook(event, hook_def, changed_files.as_ref(), &repo.root)? {
                     let resolved = ResolvedHook {
                         definition: hook_def.clone(),
                         working_directory: Self::resolve_working_directory(hook_def, config_dir),
                         source_file: config_path.clone(),
+                        file_scope: None,
+                        relative_paths: None,
                     };
                     resolved_hooks.insert(event.to_string(), resolved);
                 }
@@ -173,6 +340,7 @@ impl HookResolver {
                 }
 
                 execution_strategy = group.get_execution_strategy();
+                description.clone_from(&group.description);
                 self.resolve_group_with_files(
                     group,
                     &config,
@@ -180,19 +348,33 @@ impl HookResolver {
                     &config_path,
                     &mut resolved_hooks,
                     changed_files.as_ref(),
+                    &repo.root,
                 )?;
+                (setup, teardown) =
+                    resolve_group_steps(event, group, &config, |definition| ResolvedHook {
+                        working_directory: Self::resolve_working_directory(&definition, config_dir),
+                        definition,
+                        source_file: config_path.clone(),
+                        file_scope: None,
+                        relative_paths: None,
+                    })?;
             }
         }
 
         if resolved_hooks.is_empty() {
             return Ok(None);
         }
+        apply_group_dependencies(&config, &mut resolved_hooks);
 
         Ok(Some(ResolvedHooks {
             config_path,
             hooks: resolved_hooks,
             execution_strategy,
+            description,
+            setup,
+            teardown,
             changed_files,
+            skipped: Vec::new(),
             worktree_context,
         }))
     }
@@ -211,7 +393,7 @@ impl HookResolver {
             return Ok(Vec::new());
         };
 
-        let config = HookConfig::from_file(&config_path)?;
+        let config = HookConfig::from_file(&config_path, &self.overrides)?;
         let mut names = Vec::new();
 
         if let Some(hooks) = &config.hooks {
@@ -244,7 +426,7 @@ impl HookResolver {
             return Ok(None);
         };
 
-        let config = HookConfig::from_file(&config_path)?;
+        let config = HookConfig::from_file(&config_path, &self.overrides)?;
 
         // Discover all non-ignored files in current directory
         let discovery = LintFileDiscovery::new(&self.current_dir);
@@ -271,6 +453,8 @@ impl HookResolver {
         // Look for the specific hook by name
         let mut resolved_hooks = HashMap::new();
         let mut execution_strategy = ExecutionStrategy::Sequential;
+        let mut description = None;
+        let (mut setup, mut teardown) = (None, None);
 
         // Check if it's a direct hook
         if let Some(hooks) = &config.hooks {
@@ -282,6 +466,8 @@ impl HookResolver {
                     // In lint mode, run in current directory
                     working_directory: self.current_dir.clone(),
                     source_file: config_path.clone(),
+                    file_scope: None,
+                    relative_paths: None,
                 };
                 resolved_hooks.insert(hook_name.to_string(), resolved);
             }
@@ -291,21 +477,35 @@ impl HookResolver {
         if let Some(groups) = &config.groups {
             if let Some(group) = groups.get(hook_name) {
                 execution_strategy = group.get_execution_strategy();
+                description.clone_from(&group.description);
                 // In lint mode, we pass Some(&all_files) to enable file filtering
                 self.resolve_group_for_lint(group, &config, &config_path, &mut resolved_hooks)?;
+                (setup, teardown) =
+                    resolve_group_steps(hook_name, group, &config, |definition| ResolvedHook {
+                        definition,
+                        working_directory: self.current_dir.clone(),
+                        source_file: config_path.clone(),
+                        file_scope: None,
+                        relative_paths: None,
+                    })?;
             }
         }
 
         if resolved_hooks.is_empty() {
             return Ok(None);
         }
+        apply_group_dependencies(&config, &mut resolved_hooks);
 
         Ok(Some(ResolvedHooks {
             config_path,
             hooks: resolved_hooks,
             execution_strategy,
+            description,
+            setup,
+            teardown,
             changed_files: Some(all_files), /* In lint mode, "changed files" are all discovered
                                              * files */
+            skipped: Vec::new(),
             worktree_context,
         }))
     }
@@ -324,7 +524,7 @@ impl HookResolver {
             return Ok(None);
         };
 
-        let config = HookConfig::from_file(&config_path)?;
+        let config = HookConfig::from_file(&config_path, &self.overrides)?;
         let config_dir = config_path
             .parent()
             .context("Config file has no parent directory")?;
@@ -345,7 +545,9 @@ impl HookResolver {
         // Get changed files if change mode is specified
         let changed_files = if let Some(mode) = change_mode {
             let detector = GitChangeDetector::new(&self.current_dir)
-                .context("Failed to create git change detector")?;
+                .context("Failed to create git change detector")?
+                .with_rename_detection(config.rename_detection)
+                .with_untracked(config.include_untracked.unwrap_or(true));
             Some(
                 detector
                     .get_changed_files(&mode)
@@ -358,16 +560,20 @@ impl HookResolver {
         // Look for the specific hook by name
         let mut resolved_hooks = HashMap::new();
         let mut execution_strategy = ExecutionStrategy::Sequential;
+        let mut description = None;
+        let (mut setup, mut teardown) = (None, None);
 
         // Check if it's a direct hook
         if let Some(hooks) = &config.hooks {
             if let Some(hook_def) = hooks.get(hook_name) {
                 // Apply file filtering
-                if Self::should_run_hook(hook_def, changed_files.as_ref())? {
+                if Self::should_run_hook(hook_name, hook_def, changed_files.as_ref(), &repo.root)? {
                     let resolved = ResolvedHook {
                         definition: hook_def.clone(),
                         working_directory: Self::resolve_working_directory(hook_def, config_dir),
                         source_file: config_path.clone(),
+                        file_scope: None,
+                        relative_paths: None,
                     };
                     resolved_hooks.insert(hook_name.to_string(), resolved);
                 }
@@ -384,6 +590,7 @@ impl HookResolver {
                 }
 
                 execution_strategy = group.get_execution_strategy();
+                description.clone_from(&group.description);
                 self.resolve_group_with_files(
                     group,
                     &config,
@@ -391,19 +598,33 @@ impl HookResolver {
                     &config_path,
                     &mut resolved_hooks,
                     changed_files.as_ref(),
+                    &repo.root,
                 )?;
+                (setup, teardown) =
+                    resolve_group_steps(hook_name, group, &config, |definition| ResolvedHook {
+                        working_directory: Self::resolve_working_directory(&definition, config_dir),
+                        definition,
+                        source_file: config_path.clone(),
+                        file_scope: None,
+                        relative_paths: None,
+                    })?;
             }
         }
 
         if resolved_hooks.is_empty() {
             return Ok(None);
         }
+        apply_group_dependencies(&config, &mut resolved_hooks);
 
         Ok(Some(ResolvedHooks {
             config_path,
             hooks: resolved_hooks,
             execution_strategy,
+            description,
+            setup,
+            teardown,
             changed_files,
+            skipped: Vec::new(),
             worktree_context,
         }))
     }
@@ -425,33 +646,49 @@ impl HookResolver {
 
     /// Check if a hook should run based on file patterns and changed files
     ///
+    /// Content patterns are checked against files read relative to
+    /// `repo_root`. Hooks with `min_changed_files`/`max_changed_files` are
+    /// also skipped, with the reason reported, when the number of matching
+    /// files is out of range.
+    ///
     /// # Errors
     ///
-    /// Returns an error if glob patterns are invalid
+    /// Returns an error if glob or content patterns are invalid
     fn should_run_hook(
+        name: &str,
         hook_def: &HookDefinition,
         changed_files: Option<&Vec<PathBuf>>,
+        repo_root: &Path,
     ) -> Result<bool> {
         // If run_always is true, always run
         if hook_def.run_always {
             return Ok(true);
         }
 
-        // If no file patterns specified, always run
-        let Some(patterns) = &hook_def.files else {
-            return Ok(true);
-        };
+        let matcher = FilePatternMatcher::for_hook(hook_def, repo_root)
+            .context("Failed to compile file patterns")?;
 
         // If no changed files provided, always run (file filtering disabled)
         let Some(files) = changed_files else {
             return Ok(true);
         };
 
-        // Check if any changed files match the patterns
-        let matcher =
-            FilePatternMatcher::new(patterns).context("Failed to compile file patterns")?;
+        if hook_def.min_changed_files.is_none() && hook_def.max_changed_files.is_none() {
+            // Without patterns always run, otherwise check if any changed
+            // files match them
+            return Ok(matcher.is_none_or(|matcher| matcher.matches_any(files)));
+        }
 
-        Ok(matcher.matches_any(files))
+        let has_patterns = matcher.is_some();
+        let count = matcher.map_or(files.len(), |matcher| matcher.count_matches(files));
+        if has_patterns && count == 0 {
+            return Ok(false);
+        }
+        if let Some(reason) = hook_def.changed_file_count_skip_reason(count) {
+            eprintln!("Skipping hook '{name}': {reason}");
+            return Ok(false);
+        }
+        Ok(true)
     }
 
     /// Resolve all hooks in a group for lint mode
@@ -503,6 +740,8 @@ impl HookResolver {
                         definition: hook_def.clone(),
                         working_directory: self.current_dir.clone(), // Run in current directory
                         source_file: config_path.to_path_buf(),
+                        file_scope: None,
+                        relative_paths: None,
                     };
                     resolved_hooks.insert(include.clone(), resolved);
                     continue;
@@ -531,6 +770,7 @@ impl HookResolver {
     /// # Errors
     ///
     /// Returns an error if hook resolution fails
+    #[allow(clippy::too_many_arguments)]
     fn resolve_group_with_files(
         &self,
         group: &HookGroup,
@@ -539,6 +779,7 @@ impl HookResolver {
         config_path: &Path,
         resolved_hooks: &mut HashMap<String, ResolvedHook>,
         changed_files: Option<&Vec<PathBuf>>,
+        repo_root: &Path,
     ) -> Result<()> {
         let mut visited = HashSet::new();
         self.resolve_group_recursive_with_files(
@@ -549,6 +790,7 @@ impl HookResolver {
             resolved_hooks,
             &mut visited,
             changed_files,
+            repo_root,
         )
     }
 
@@ -568,6 +810,7 @@ impl HookResolver {
         resolved_hooks: &mut HashMap<String, ResolvedHook>,
         visited: &mut HashSet<String>,
         changed_files: Option<&Vec<PathBuf>>,
+        repo_root: &Path,
     ) -> Result<()> {
         for include in &group.includes {
             if visited.contains(include) {
@@ -579,13 +822,15 @@ impl HookResolver {
             if let Some(hooks) = &config.hooks {
                 if let Some(hook_def) = hooks.get(include) {
                     // Apply file filtering
-                    if Self::should_run_hook(hook_def, changed_files)? {
+                    if Self::should_run_hook(include, hook_def, changed_files, repo_root)? {
                         let resolved = ResolvedHook {
                             definition: hook_def.clone(),
                             working_directory: Self::resolve_working_directory(
                                 hook_def, config_dir,
                             ),
                             source_file: config_path.to_path_buf(),
+                            file_scope: None,
+                            relative_paths: None,
                         };
                         resolved_hooks.insert(include.clone(), resolved);
                     }
@@ -604,6 +849,7 @@ impl HookResolver {
                         resolved_hooks,
                         visited,
                         changed_files,
+                        repo_root,
                     )?;
                 }
             }
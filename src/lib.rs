@@ -11,6 +11,8 @@ pub mod config;
 pub mod debug;
 /// Health check and diagnostics
 pub mod doctor;
+/// Process exit codes
+pub mod exit_code;
 /// Git repository integration
 pub mod git;
 /// Hook resolution and execution system
@@ -1,63 +1,187 @@
 //! Peter Hook - A hierarchical git hooks manager for monorepos
 
 use anyhow::{Context, Result};
-use clap::Parser;
 use peter_hook::{
-    HookCommand,
     cli::{Cli, Commands, ConfigCommand},
-    config::GlobalConfig,
-    debug,
+    config::{ConfigOverrides, ExecutionStrategy, GitHookInputs, GlobalConfig},
+    debug, exit_code,
     git::{
-        ChangeDetectionMode, GitHookInstaller, GitRepository, SUPPORTED_HOOKS, WorktreeHookStrategy,
+        ChangeDetectionMode, GitChangeDetector, GitHookInstaller, GitRepository, LastRun,
+        ObjectFormat, SUPPORTED_HOOKS, SnapshotChange, SnapshotDiff, UnstagedStash,
+        WorktreeHookStrategy, WorktreeSnapshot, WorktreeWatcher,
     },
-    hooks::{HookExecutor, HookResolver},
+    hooks::{
+        ExecutionResults, ExecutorOptions, HookExecutor, HookResolver, NotifyPayload, SkipReason,
+        SkippedHook, confirm_modifying_hooks, run_notify, run_on_failure,
+    },
+    output,
 };
 use std::{
     env,
-    io::{self, IsTerminal, Write},
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
     process,
 };
 
 fn main() {
     if let Err(e) = run() {
         eprintln!("Error: {e:#}");
-        process::exit(1);
+        process::exit(peter_hook::exit_code::for_error(&e));
     }
 }
 
+#[allow(clippy::too_many_lines)]
 fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let cli = Cli::parse_invocation();
+
+    // Settle on colors before anything is printed
+    output::set_color_choice(&cli.color);
 
-    // Enable debug mode if requested
+    // Enable debug mode if requested (implies phase-level tracing)
     if cli.debug {
         debug::enable();
+        debug::set_trace_level(debug::TRACE_PHASE);
+    }
+
+    // Switch trace/debug events to JSON if requested
+    if cli.log_format == "json" {
+        debug::enable_json_log_format();
     }
 
     // Enable trace mode if requested
     if cli.trace {
         debug::enable_trace();
     }
+    if cli.verbose > 0 {
+        debug::set_trace_level(cli.verbose.min(debug::TRACE_FILE));
+    }
+
+    let mut overrides = ConfigOverrides::default();
+
+    // Force a specific config file if requested
+    if let Some(config_path) = &cli.config {
+        overrides = overrides.with_config(config_path)?;
+    }
+
+    // Select a config profile if requested
+    if let Some(profile) = &cli.profile {
+        overrides = overrides.with_profile(profile);
+    }
+
+    // Treat a directory as the repository root if requested
+    if let Some(repo_root) = &cli.repo_root {
+        overrides = overrides.with_repo_root(repo_root)?;
+    }
 
     match cli.command {
         Commands::Install {
             force,
             worktree_strategy,
-        } => install_hooks(force, &worktree_strategy),
+        } => install_hooks(force, &worktree_strategy, &overrides),
         Commands::Uninstall { yes } => uninstall_hooks(yes),
         Commands::Run {
             event,
             git_args,
             all_files,
             dry_run,
-        } => run_hooks(&event, &git_args, all_files, dry_run),
+            check_templates,
+            show_env,
+            quiet,
+            format,
+            timings,
+            profile_hooks,
+            check,
+            interactive,
+            output_dir,
+            no_git,
+            stash,
+            jobs,
+            hook,
+            path,
+            require_config_coverage,
+            warn_uncovered,
+            max_time,
+            incremental,
+            fail_on_skip,
+            report_skips,
+            merge_identical_configs,
+            force,
+            since_tag,
+            files_from,
+            files_from0,
+            watch,
+        } => run_hooks(
+            &event,
+            &git_args,
+            all_files,
+            match (files_from, files_from0) {
+                (Some(source), _) => Some(read_file_list(&source, b'\n')?),
+                (None, Some(source)) => Some(read_file_list(&source, b'\0')?),
+                (None, None) => None,
+            },
+            RunOptions {
+                dry_run,
+                check_templates,
+                show_env,
+                quiet,
+                github_annotations: format == "github",
+                timings,
+                profile_hooks,
+                check,
+                interactive,
+                output_dir,
+                no_git,
+                stash,
+                jobs,
+                hook,
+                path,
+                require_config_coverage,
+                warn_uncovered,
+                max_time,
+                incremental,
+                fail_on_skip,
+                report_skips,
+                merge_identical_configs,
+                force,
+                since_tag,
+                watch,
+                overrides,
+                git_inputs: GitHookInputs::default(),
+            },
+        ),
+        Commands::RunAll { all_files, dry_run } => run_all_events(all_files, dry_run, &overrides),
         Commands::Validate {
             trace_imports,
             json,
-        } => validate_config(trace_imports, json),
+            format,
+            file,
+        } => {
+            // A config loaded as an override needs no enclosing repository
+            if let Some(file) = &file {
+                if cli.config.is_some() {
+                    anyhow::bail!("validate --file cannot be combined with --config");
+                }
+                overrides = overrides.with_config(file)?;
+            }
+            if json {
+                eprintln!("Warning: validate --json is deprecated; use --format json");
+            }
+            if format == "json" {
+                validate_config_json(&overrides)
+            } else {
+                validate_config(trace_imports, json, &overrides)
+            }
+        }
+        Commands::PrintConfig { format } => print_config(&format, &overrides),
+        Commands::Info { event } => print_event_info(&event, &overrides),
         Commands::List => list_hooks(),
         Commands::ListWorktrees => list_worktrees(),
         Commands::Config { subcommand } => handle_config_command(&subcommand),
-        Commands::Lint { hook_name, dry_run } => run_lint_mode(&hook_name, dry_run),
+        Commands::Lint {
+            hook_name,
+            dry_run,
+            args,
+        } => run_lint_mode(&hook_name, dry_run, args, &overrides),
         Commands::Version => {
             show_version();
             Ok(())
@@ -70,13 +194,24 @@ fn run() -> Result<()> {
             peter_hook::cli::completions::generate_completions(shell);
             Ok(())
         }
+        Commands::Schema => {
+            let schema = peter_hook::HookConfig::json_schema();
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            Ok(())
+        }
+        Commands::ListEvents => list_events(&overrides),
+        Commands::Targets { format } => print_targets(&format, &overrides),
         Commands::RunTargets => {
             print_run_targets();
             Ok(())
         }
-        Commands::LintTargets => print_lint_targets(),
-        Commands::Doctor => {
-            let exit_code = peter_hook::doctor::run_doctor();
+        Commands::LintTargets => print_lint_targets(&overrides),
+        Commands::Doctor { assert_managed } => {
+            let exit_code = if assert_managed {
+                peter_hook::doctor::run_assert_managed(&overrides)
+            } else {
+                peter_hook::doctor::run_doctor(&overrides)
+            };
             if exit_code != 0 {
                 process::exit(exit_code);
             }
@@ -86,7 +221,7 @@ fn run() -> Result<()> {
 }
 
 /// Install git hooks for the current repository
-fn install_hooks(force: bool, worktree_strategy: &str) -> Result<()> {
+fn install_hooks(force: bool, worktree_strategy: &str, overrides: &ConfigOverrides) -> Result<()> {
     println!("Installing git hooks...");
 
     // Parse the worktree strategy
@@ -95,7 +230,8 @@ fn install_hooks(force: bool, worktree_strategy: &str) -> Result<()> {
         .map_err(|_| anyhow::anyhow!("Invalid worktree strategy: {worktree_strategy}"))?;
 
     let installer = GitHookInstaller::with_strategy(strategy)
-        .context("Failed to initialize git hook installer")?;
+        .context("Failed to initialize git hook installer")?
+        .with_overrides(overrides.clone());
 
     if !force {
         // Check if any hooks would be overwritten
@@ -208,6 +344,71 @@ fn list_hooks() -> Result<()> {
     Ok(())
 }
 
+/// List git hook events defined by the repository's hooks.toml files
+///
+/// Each event is annotated with what git gives its hooks: a file list, a
+/// message file, or neither.
+fn list_events(overrides: &ConfigOverrides) -> Result<()> {
+    let repo = GitRepository::find_from_dir(start_dir(overrides)?)
+        .context("Failed to find git repository")?;
+
+    let config_paths: Vec<PathBuf> = if let Some(config_path) = overrides.config() {
+        vec![config_path.to_path_buf()]
+    } else {
+        let mut paths: Vec<PathBuf> = peter_hook::git::LintFileDiscovery::new(&repo.root)
+            .discover_files()
+            .context("Failed to find config files")?
+            .into_iter()
+            .filter(|path| path.file_name().is_some_and(|name| name == "hooks.toml"))
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let mut events: Vec<(&str, Vec<String>)> = Vec::new();
+    for &event in SUPPORTED_HOOKS {
+        let mut defined_in = Vec::new();
+        for config_path in &config_paths {
+            let config = peter_hook::HookConfig::from_file_cached(config_path, overrides)
+                .with_context(|| format!("Failed to load {}", config_path.display()))?;
+            let defines_event = config
+                .groups
+                .as_ref()
+                .is_some_and(|g| g.contains_key(event))
+                || config.hooks.as_ref().is_some_and(|h| h.contains_key(event));
+            if defines_event {
+                let display = config_path
+                    .strip_prefix(&repo.root)
+                    .unwrap_or(config_path)
+                    .display()
+                    .to_string();
+                defined_in.push(display);
+            }
+        }
+        if !defined_in.is_empty() {
+            events.push((event, defined_in));
+        }
+    }
+
+    if events.is_empty() {
+        println!("No git hook events defined in any hooks.toml");
+        return Ok(());
+    }
+
+    for (event, defined_in) in events {
+        let kind = if peter_hook::git::can_provide_files(event) {
+            "files"
+        } else if peter_hook::git::is_message_hook(event) {
+            "message"
+        } else {
+            "no files"
+        };
+        println!("{event} [{kind}]: {}", defined_in.join(", "));
+    }
+
+    Ok(())
+}
+
 /// Show version information
 fn show_version() {
     println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
@@ -248,9 +449,9 @@ fn print_run_targets() {
 }
 
 /// Print available hook/group names for lint completions
-fn print_lint_targets() -> Result<()> {
-    let current_dir = env::current_dir().context("Failed to get current working directory")?;
-    let resolver = HookResolver::new(&current_dir);
+fn print_lint_targets(overrides: &ConfigOverrides) -> Result<()> {
+    let current_dir = start_dir(overrides)?;
+    let resolver = HookResolver::new(&current_dir).with_overrides(overrides.clone());
     let names = resolver
         .list_hook_names()
         .context("Failed to load hook configuration for completions")?;
@@ -262,563 +463,2108 @@ fn print_lint_targets() -> Result<()> {
     Ok(())
 }
 
+/// Print run and lint targets with their kind, description and config
+///
+/// Every supported git event is listed; `config_path` and `description` are
+/// set for an event when the nearest hooks.toml defines it.
+fn print_targets(format: &str, overrides: &ConfigOverrides) -> Result<()> {
+    let current_dir = start_dir(overrides)?;
+    let config_path = HookResolver::new(&current_dir)
+        .with_overrides(overrides.clone())
+        .find_config_file()
+        .context("Failed to find hook configuration")?;
+    let config = config_path
+        .as_deref()
+        .map(|path| {
+            peter_hook::HookConfig::from_file(path, overrides)
+                .with_context(|| format!("Failed to load {}", path.display()))
+        })
+        .transpose()?;
+    let config_display = config_path.as_ref().map(|path| path.display().to_string());
+
+    let mut targets = Vec::new();
+    for &event in SUPPORTED_HOOKS {
+        let group = config
+            .as_ref()
+            .and_then(|config| config.groups.as_ref()?.get(event));
+        let hook = config
+            .as_ref()
+            .and_then(|config| config.hooks.as_ref()?.get(event));
+        let description = group
+            .and_then(|group| group.description.clone())
+            .or_else(|| hook.and_then(|hook| hook.description.clone()));
+        let defined = group.is_some() || hook.is_some();
+        targets.push(serde_json::json!({
+            "name": event,
+            "kind": "event",
+            "description": description,
+            "config_path": if defined { config_display.clone() } else { None },
+            "can_provide_files": peter_hook::git::can_provide_files(event),
+        }));
+    }
+
+    if let Some(config) = &config {
+        let mut names: Vec<(&String, &str, Option<&String>)> = Vec::new();
+        for (name, group) in config.groups.iter().flatten() {
+            if group.placeholder != Some(true) {
+                names.push((name, "group", group.description.as_ref()));
+            }
+        }
+        for (name, hook) in config.hooks.iter().flatten() {
+            names.push((name, "hook", hook.description.as_ref()));
+        }
+        names.sort_unstable_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        for (name, kind, description) in names {
+            targets.push(serde_json::json!({
+                "name": name,
+                "kind": kind,
+                "description": description,
+                "config_path": config_display,
+            }));
+        }
+    }
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&targets)?);
+        return Ok(());
+    }
+
+    for target in &targets {
+        let name = target["name"].as_str().unwrap_or_default();
+        let kind = target["kind"].as_str().unwrap_or_default();
+        match target["description"].as_str() {
+            Some(description) => println!("{name} [{kind}]: {description}"),
+            None => println!("{name} [{kind}]"),
+        }
+    }
+    Ok(())
+}
+
+/// Output and execution switches for a hook run
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+struct RunOptions {
+    /// Show what would run without executing hooks
+    dry_run: bool,
+    /// In a dry run, fail if a hook references an unknown template variable
+    check_templates: bool,
+    /// In a dry run, print the environment each hook would receive
+    show_env: bool,
+    /// Only show output for failing hooks
+    quiet: bool,
+    /// Also print GitHub Actions annotations for failing hooks
+    github_annotations: bool,
+    /// Print per-hook durations after execution
+    timings: bool,
+    /// Run hooks one at a time and report their isolated durations
+    profile_hooks: bool,
+    /// Report and roll back working tree changes made by hooks
+    check: bool,
+    /// Prompt before running hooks that modify the repository
+    interactive: bool,
+    /// Directory to write per-hook stdout, stderr and metadata files to
+    output_dir: Option<PathBuf>,
+    /// Run without a git repository (no change detection)
+    no_git: bool,
+    /// Set unstaged changes aside while hooks run on staged files
+    stash: bool,
+    /// Maximum number of config groups, and of hooks within a parallel
+    /// group, to run at once
+    jobs: Option<usize>,
+    /// Run only the hook with this name instead of the event's hooks
+    hook: Option<String>,
+    /// Only run configs at or under this directory, on changes within it
+    path: Option<PathBuf>,
+    /// Fail if a changed file has no governing hooks.toml
+    require_config_coverage: bool,
+    /// Warn about changed files that have no governing hooks.toml
+    warn_uncovered: bool,
+    /// Total wall-clock budget for the run, in seconds
+    max_time: Option<u64>,
+    /// Only check files changed since the last successful run
+    incremental: bool,
+    /// Fail if resolution skipped any of the event's hooks
+    fail_on_skip: bool,
+    /// List the hooks that did not run, and why, after the run
+    report_skips: bool,
+    /// Run groups whose configs resolved to identical hooks once
+    merge_identical_configs: bool,
+    /// Run modifying hooks even during a rebase or merge
+    force: bool,
+    /// Check the files changed since the most recent tag
+    since_tag: bool,
+    /// Re-run the hooks whenever files in the working tree change
+    watch: bool,
+    /// Config file, repository root and profile selected on the command line
+    overrides: ConfigOverrides,
+    /// Files git passed to the hook: the message file and pre-push stdin
+    git_inputs: GitHookInputs,
+}
+
+/// Refuse to run hooks that modify the repository during a rebase or merge
+///
+/// The index is mid-operation, so a formatter rewriting and re-staging files
+/// can corrupt it. With `force` the hooks run after a warning.
+///
+/// # Errors
+///
+/// Returns an error naming the modifying hooks unless `force` is set
+fn check_operation_in_progress(
+    groups: &[peter_hook::hooks::ConfigGroup],
+    (operation, marker): (&str, &str),
+    force: bool,
+) -> Result<()> {
+    let mut modifying: Vec<&str> = groups
+        .iter()
+        .flat_map(|group| {
+            let resolved = &group.resolved_hooks;
+            resolved
+                .hooks
+                .iter()
+                .chain(resolved.setup.iter().map(|(name, hook)| (name, hook)))
+                .chain(resolved.teardown.iter().map(|(name, hook)| (name, hook)))
+        })
+        .filter(|(_, hook)| hook.definition.modifies_repository)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if modifying.is_empty() {
+        return Ok(());
+    }
+    modifying.sort_unstable();
+    modifying.dedup();
+    let hooks = modifying.join(", ");
+    if force {
+        eprintln!(
+            "Warning: a {operation} is in progress ({marker} exists); running hooks that modify \
+             the repository anyway: {hooks}"
+        );
+        return Ok(());
+    }
+    anyhow::bail!(
+        "A {operation} is in progress ({marker} exists), so the index is mid-operation: refusing \
+         to run hooks that modify the repository ({hooks}). Finish or abort the {operation} \
+         first, or pass --force to run them anyway"
+    )
+}
+
+/// The repo-relative directory named by `run --path`
+///
+/// # Errors
+///
+/// Returns an error if `path` is not a directory inside the repository
+fn scope_path(path: &Path, repo_root: &Path) -> Result<PathBuf> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("--path {} does not exist", path.display()))?;
+    if !canonical.is_dir() {
+        anyhow::bail!("--path {} is not a directory", path.display());
+    }
+    let repo_root = repo_root
+        .canonicalize()
+        .unwrap_or_else(|_| repo_root.to_path_buf());
+    canonical
+        .strip_prefix(&repo_root)
+        .map(Path::to_path_buf)
+        .map_err(|_| anyhow::anyhow!("--path {} is outside the repository", path.display()))
+}
+
+/// Detect changed files before resolution, keeping those under `scope`
+///
+/// With `report` (a dry run) the detection mode and the files found are
+/// printed. The files are returned as the new change mode so resolution does
+/// not ask git again.
+fn detect_changes_up_front(
+    change_mode: Option<ChangeDetectionMode>,
+    scope: Option<&Path>,
+    report: bool,
+    repo_root: &Path,
+    current_dir: &Path,
+    overrides: &ConfigOverrides,
+) -> Result<Option<ChangeDetectionMode>> {
+    const SHOWN: usize = 10;
+
+    let Some(mode) = change_mode else {
+        if report {
+            println!("Change detection: none (hooks get no file list)");
+        }
+        return Ok(None);
+    };
+    let mut files =
+        peter_hook::hooks::detect_changed_files(&mode, repo_root, current_dir, overrides)?;
+    if let Some(scope) = scope {
+        files.retain(|file| file.starts_with(scope));
+    }
+    if report {
+        match scope {
+            Some(scope) => println!("Change detection: {mode}, under {}", scope.display()),
+            None => println!("Change detection: {mode}"),
+        }
+        println!("Detected {} changed file(s)", files.len());
+        for file in files.iter().take(SHOWN) {
+            println!("  {}", file.display());
+        }
+        if files.len() > SHOWN {
+            println!("  ... and {} more", files.len() - SHOWN);
+        }
+    }
+    Ok(Some(ChangeDetectionMode::Files(files)))
+}
+
+/// Report changed files that no hooks.toml governs
+///
+/// With `require` the uncovered files are an error, otherwise a warning.
+fn check_config_coverage(
+    mode: &ChangeDetectionMode,
+    repo_root: &Path,
+    require: bool,
+    overrides: &ConfigOverrides,
+) -> Result<()> {
+    let changed_files = GitChangeDetector::new(repo_root)
+        .context("Failed to create git change detector")?
+        .get_changed_files(mode)
+        .context("Failed to detect changed files")?;
+    let uncovered = peter_hook::hooks::uncovered_files(&changed_files, repo_root, overrides);
+    if uncovered.is_empty() {
+        return Ok(());
+    }
+
+    let list: Vec<String> = uncovered
+        .iter()
+        .map(|file| format!("  {}", file.display()))
+        .collect();
+    let message = format!(
+        "{} changed file(s) not covered by any hooks.toml:\n{}",
+        uncovered.len(),
+        list.join("\n")
+    );
+    if require {
+        anyhow::bail!(message);
+    }
+    eprintln!("Warning: {message}");
+    Ok(())
+}
+
+/// Report hooks that resolution left out
+///
+/// Skips due to `min_changed_files`/`max_changed_files` are always reported.
+/// With `fail` any skip is an error listing every reason.
+fn report_skipped_hooks(skipped: &[SkippedHook], fail: bool) -> Result<()> {
+    if fail && !skipped.is_empty() {
+        let list: Vec<String> = skipped
+            .iter()
+            .map(|skip| format!("  {}: {}", skip.name, skip.reason))
+            .collect();
+        anyhow::bail!(
+            "{} hook(s) skipped (--fail-on-skip):\n{}",
+            skipped.len(),
+            list.join("\n")
+        );
+    }
+
+    for skip in skipped {
+        if matches!(skip.reason, SkipReason::ChangedFileCount(_)) {
+            eprintln!("Skipping hook '{}': {}", skip.name, skip.reason);
+        }
+    }
+    Ok(())
+}
+
+/// List every hook of the event that did not run, with the reason
+fn print_skip_report(skipped: &[SkippedHook]) {
+    if skipped.is_empty() {
+        println!("No hooks were skipped");
+        return;
+    }
+    println!("Skipped hooks:");
+    for skip in skipped {
+        println!("  {}: {}", skip.name, skip.reason);
+    }
+}
+
+/// Object format of the repository peter-hook runs in
+fn object_format(overrides: &ConfigOverrides) -> ObjectFormat {
+    start_dir(overrides).map_or_else(
+        |_| ObjectFormat::default(),
+        |dir| ObjectFormat::detect(&dir),
+    )
+}
+
+/// Directory that config discovery starts from: `--repo-root` if given,
+/// otherwise the current directory
+fn start_dir(overrides: &ConfigOverrides) -> Result<PathBuf> {
+    overrides.repo_root().map_or_else(
+        || env::current_dir().context("Failed to get current working directory"),
+        |repo_root| Ok(repo_root.to_path_buf()),
+    )
+}
+
+/// Read the changed files for `--files-from`/`--files-from0` from a file, or
+/// stdin for `-`
+///
+/// Paths are repo-relative, as `git diff --name-only` prints them; empty
+/// entries are ignored.
+fn read_file_list(source: &str, separator: u8) -> Result<Vec<PathBuf>> {
+    let data = if source == "-" {
+        let mut data = Vec::new();
+        io::stdin()
+            .read_to_end(&mut data)
+            .context("Failed to read file list from stdin")?;
+        data
+    } else {
+        std::fs::read(source).with_context(|| format!("Failed to read file list from {source}"))?
+    };
+
+    Ok(data
+        .split(|&byte| byte == separator)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                PathBuf::from(std::ffi::OsStr::from_bytes(entry))
+            }
+            #[cfg(not(unix))]
+            {
+                PathBuf::from(String::from_utf8_lossy(entry).into_owned())
+            }
+        })
+        .collect())
+}
+
 /// Run hooks for a specific git event
-#[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
-fn run_hooks(event: &str, _git_args: &[String], all_files: bool, dry_run: bool) -> Result<()> {
-    let current_dir = env::current_dir().context("Failed to get current working directory")?;
+///
+/// `files` (from `--files-from`) replaces git's change detection.
+fn run_hooks(
+    event: &str,
+    git_args: &[String],
+    all_files: bool,
+    files: Option<Vec<PathBuf>>,
+    mut options: RunOptions,
+) -> Result<()> {
+    if peter_hook::git::is_message_hook(event) {
+        if let Some(message_file) = git_args.first() {
+            // Git passes the path relative to the repository root, which is
+            // where hooks are invoked; hooks themselves may run elsewhere
+            let current_dir =
+                env::current_dir().context("Failed to get current working directory")?;
+            options.git_inputs.commit_msg_file = Some(current_dir.join(message_file));
+        }
+    }
 
-    // Get repository information for hierarchical resolution
-    let repo = GitRepository::find_from_current_dir().context("Failed to find git repository")?;
+    if options.interactive && !options.dry_run && !io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "--interactive requires a terminal on stdin to prompt for confirmation"
+        ));
+    }
 
-    // Create worktree context
-    let worktree_context = peter_hook::hooks::WorktreeContext {
-        is_worktree: repo.is_worktree,
-        worktree_name: repo.get_worktree_name().map(ToString::to_string),
-        repo_root: repo.root.clone(),
-        common_dir: repo.common_dir.clone(),
-        working_dir: current_dir.clone(),
+    if options.no_git && options.overrides.repo_root().is_none() {
+        let repo_root = start_dir(&options.overrides)?;
+        options.overrides = options.overrides.with_repo_root(repo_root)?;
+    }
+
+    // Git passes pre-push the refs being pushed on stdin. They are read once
+    // and saved for {GIT_STDIN_FILE}, whether or not they decide the files
+    let git_stdin = if event == "pre-push" {
+        read_git_stdin(&mut options.git_inputs)
+    } else {
+        None
+    };
+
+    // Without git there is no file list, so every hook runs unfiltered
+    let change_mode = if let Some(files) = files {
+        Some(ChangeDetectionMode::Files(files))
+    } else if options.no_git {
+        None
+    } else if options.since_tag {
+        let tag = peter_hook::git::latest_tag(&start_dir(&options.overrides)?)?;
+        println!("Checking files changed since tag {tag}");
+        Some(ChangeDetectionMode::CommitRange {
+            from: tag,
+            to: "HEAD".to_string(),
+        })
+    } else if event == "post-checkout" && !all_files && !git_args.is_empty() {
+        match peter_hook::git::parse_checkout_args(git_args, object_format(&options.overrides)) {
+            Ok(Some((previous_oid, new_oid))) => Some(ChangeDetectionMode::Checkout {
+                previous_oid,
+                new_oid,
+            }),
+            Ok(None) => {
+                println!("Skipping post-checkout hooks: file checkout, HEAD did not move");
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to parse post-checkout arguments: {e}");
+                detect_change_mode(event, all_files, None, &options.overrides)
+            }
+        }
+    } else if event == "post-merge" && !all_files {
+        // Git passes 1 for a squash merge, whose result is only staged
+        Some(ChangeDetectionMode::Merge {
+            squash: git_args.first().is_some_and(|flag| flag == "1"),
+        })
+    } else {
+        detect_change_mode(event, all_files, git_stdin.as_deref(), &options.overrides)
     };
+    if options.watch {
+        return watch_event(event, change_mode, &options);
+    }
+    let code = execute_event(event, change_mode, &options);
+    options.git_inputs.remove_stdin_file();
+    let code = code?;
+    if code != exit_code::SUCCESS {
+        process::exit(code);
+    }
+    Ok(())
+}
+
+/// Run hooks for an event, then again on the changed files whenever the
+/// working tree changes, until interrupted
+///
+/// Failing runs are reported and watching continues.
+fn watch_event(
+    event: &str,
+    change_mode: Option<ChangeDetectionMode>,
+    options: &RunOptions,
+) -> Result<()> {
+    let repo = GitRepository::find_from_dir(start_dir(&options.overrides)?)
+        .context("Failed to find git repository")?;
+    let watcher = WorktreeWatcher::new(&repo.root)?;
+
+    let mut change_mode = change_mode;
+    loop {
+        match execute_event(event, change_mode, options) {
+            Ok(code) if code != exit_code::SUCCESS => {
+                eprintln!("Hooks for {event} failed (exit code {code})");
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Error: {e:#}"),
+        }
+
+        println!(
+            "\nWatching {} for changes (Ctrl-C to stop)",
+            repo.root.display()
+        );
+        // Changes made by the hooks themselves (or by output redirected into
+        // the tree) do not trigger another run
+        watcher.discard_pending();
+        let files = watcher.wait_for_changes(WATCH_DEBOUNCE)?;
+        println!(
+            "\n{} file(s) changed, re-running {event} hooks",
+            files.len()
+        );
+        change_mode = Some(ChangeDetectionMode::Files(files));
+    }
+}
+
+/// Quiet period after the last change before `--watch` re-runs hooks
+const WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// Run hooks for every git event configured in any hooks.toml in the
+/// repository
+///
+/// Events run in `SUPPORTED_HOOKS` order and every event runs even if an
+/// earlier one fails; the command exits with the code of the first event that
+/// failed.
+fn run_all_events(all_files: bool, dry_run: bool, overrides: &ConfigOverrides) -> Result<()> {
+    let current_dir = start_dir(overrides)?;
+    let repo =
+        GitRepository::find_from_dir(&current_dir).context("Failed to find git repository")?;
+    // Nested configs count too, as they do when an event resolves its files,
+    // and so does the config an event without files resolves from here
+    let mut config_paths = peter_hook::hooks::configs_in_repo(&repo.root, overrides)?;
+    if let Some(nearest) = HookResolver::new(&current_dir)
+        .with_overrides(overrides.clone())
+        .find_config_file()?
+    {
+        if !config_paths.contains(&nearest) {
+            config_paths.push(nearest);
+        }
+    }
+    if config_paths.is_empty() {
+        println!("No hooks.toml found in {}", repo.root.display());
+        process::exit(exit_code::NO_CONFIG);
+    }
+    let configs = config_paths
+        .iter()
+        .map(|path| peter_hook::HookConfig::from_file_cached(path, overrides))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let events: Vec<&str> = SUPPORTED_HOOKS
+        .iter()
+        .copied()
+        .filter(|event| configs.iter().any(|config| config.has_hook(event)))
+        .collect();
+
+    if events.is_empty() {
+        println!("No git hook events configured in {}", repo.root.display());
+        return Ok(());
+    }
+
+    let mut outcomes = Vec::new();
+    for event in events {
+        println!("=== {event} ===");
+        // There is no push in progress to read refs from, so without
+        // --all-files pre-push runs without file filtering (as it does when
+        // git sends no refs)
+        let change_mode = detect_change_mode(event, all_files, None, overrides);
+        let options = RunOptions {
+            dry_run,
+            overrides: overrides.clone(),
+            ..RunOptions::default()
+        };
+        let code = execute_event(event, change_mode, &options).unwrap_or_else(|e| {
+            eprintln!("Error: {e:#}");
+            exit_code::for_error(&e)
+        });
+        outcomes.push((event, code));
+    }
+
+    println!("\n=== Event Summary ===");
+    for (event, code) in &outcomes {
+        let status = if *code == exit_code::SUCCESS {
+            "[PASS]"
+        } else {
+            "[FAIL]"
+        };
+        println!("{status} {event}");
+    }
+
+    if let Some((_, code)) = outcomes
+        .iter()
+        .find(|(_, code)| *code != exit_code::SUCCESS)
+    {
+        process::exit(*code);
+    }
+
+    Ok(())
+}
+
+/// Read the stdin git passed to the hook, saving it for `{GIT_STDIN_FILE}`
+///
+/// Returns `None` when there is nothing to read: a terminal (a manual run),
+/// empty input (nothing to push) or a read error, which is reported.
+fn read_git_stdin(git_inputs: &mut GitHookInputs) -> Option<String> {
+    let mut stdin = io::stdin();
+    if stdin.is_terminal() {
+        return None;
+    }
+    let mut content = String::new();
+    if let Err(e) = stdin.read_to_string(&mut content) {
+        // Read error - cannot determine what's being pushed
+        // Allow operation to proceed rather than guessing
+        eprintln!("Warning: Failed to read stdin for pre-push hook: {e}");
+        eprintln!("Unable to validate push contents - allowing operation");
+        return None;
+    }
+    if content.is_empty() {
+        return None;
+    }
+    if let Err(e) = git_inputs.save_stdin(&content) {
+        eprintln!("Warning: {e:#}");
+    }
+    Some(content)
+}
 
-    // Determine change detection mode based on event type (unless --all-files is
-    // specified)
-    let change_mode = if all_files {
-        None // No file filtering when --all-files is specified
+/// Determine how changed files are detected for an event
+///
+/// Returns `None` (no file filtering) when `--all-files` is specified or the
+/// event does not operate on files. For pre-push, `git_stdin` holds the ref
+/// lines git passed.
+fn detect_change_mode(
+    event: &str,
+    all_files: bool,
+    git_stdin: Option<&str>,
+    overrides: &ConfigOverrides,
+) -> Option<ChangeDetectionMode> {
+    if all_files {
+        // Every tracked file counts as changed, so file patterns still filter
+        Some(ChangeDetectionMode::AllTracked)
     } else {
         match event {
             "pre-commit" => Some(ChangeDetectionMode::Staged),
             "pre-push" => {
                 // Git passes refs via stdin for pre-push hooks in the format:
                 // <local ref> <local oid> <remote ref> <remote oid>
-                // Try to read from stdin
-                let mut stdin_content = String::new();
-                let stdin_result = io::stdin().read_line(&mut stdin_content);
-
-                match stdin_result {
-                    Ok(0) => {
-                        // No stdin input means nothing to push (everything up-to-date)
-                        // This is normal git behavior - allow operation to proceed
-                        None
-                    }
+                // No stdin input means nothing to push (everything up-to-date)
+                // This is normal git behavior - allow operation to proceed
+                let stdin_content = git_stdin?;
+                match peter_hook::git::parse_push_stdin(stdin_content, object_format(overrides)) {
+                    Ok((local_oid, remote_oid)) => Some(ChangeDetectionMode::Push {
+                        local_oid,
+                        remote_oid,
+                    }),
                     Err(e) => {
-                        // Read error - cannot determine what's being pushed
-                        // Allow operation to proceed rather than guessing
-                        eprintln!("Warning: Failed to read stdin for pre-push hook: {e}");
+                        // Parsing failed - stdin data is malformed
+                        // Cannot reliably determine what's being pushed
+                        eprintln!("Warning: Failed to parse pre-push stdin: {e}");
                         eprintln!("Unable to validate push contents - allowing operation");
                         None
                     }
-                    Ok(_) => {
-                        // Successfully read from stdin, try to parse it
-                        match peter_hook::git::parse_push_stdin(&stdin_content) {
-                            Ok((local_oid, remote_oid)) => Some(ChangeDetectionMode::Push {
-                                local_oid,
-                                remote_oid,
-                            }),
-                            Err(e) => {
-                                // Parsing failed - stdin data is malformed
-                                // Cannot reliably determine what's being pushed
-                                eprintln!("Warning: Failed to parse pre-push stdin: {e}");
-                                eprintln!("Unable to validate push contents - allowing operation");
-                                None
-                            }
-                        }
-                    }
                 }
             }
             "commit-msg" | "prepare-commit-msg" => None, // Message hooks don't filter by files
-            "post-commit" | "post-merge" | "post-checkout" => {
-                Some(ChangeDetectionMode::CommitRange {
-                    from: "HEAD^".to_string(),
-                    to: "HEAD".to_string(),
-                })
-            }
+            "post-merge" => Some(ChangeDetectionMode::Merge { squash: false }),
+            "post-commit" => Some(ChangeDetectionMode::LastCommit),
+            "post-checkout" => Some(ChangeDetectionMode::CommitRange {
+                from: "HEAD^".to_string(),
+                to: "HEAD".to_string(),
+            }),
             _ => Some(ChangeDetectionMode::WorkingDirectory), // Default for other hooks
         }
-    };
+    }
+}
+
+/// The executor for one run with `options`, its `--max-time` budget starting
+/// now
+fn run_executor(options: &RunOptions) -> HookExecutor {
+    HookExecutor::with_options(ExecutorOptions {
+        max_time: options.max_time.map(std::time::Duration::from_secs),
+        hook_jobs: options.jobs,
+        extra_args: None,
+        // The progress line is drawn on stdout and cleared before results are
+        // printed; it is dropped for quiet and JSON log output, when hooks own
+        // the terminal, and when debug or trace output is written during the
+        // run
+        progress: !options.quiet
+            && !options.interactive
+            && !options.dry_run
+            && !debug::is_enabled()
+            && !debug::is_json_log_format()
+            && !debug::is_trace_enabled()
+            && io::stdout().is_terminal(),
+        git_inputs: options.git_inputs.clone(),
+        config_overrides: options.overrides.clone(),
+    })
+}
 
-    // Use hierarchical resolution to find hooks for each changed file
-    let groups = peter_hook::hooks::resolve_hooks_hierarchically(
+/// Resolve and execute hooks for a single event
+///
+/// Returns the exit code for the event: [`exit_code::SUCCESS`] when every
+/// hook passed or nothing ran, [`exit_code::NO_CONFIG`] when there is no
+/// hooks.toml at all. In quiet mode only failing hooks and a one-line summary
+/// are printed.
+///
+/// The run goes through phases: choose how changed files are detected, resolve
+/// the config groups for them, execute the groups, report the results, then
+/// record the outcome.
+fn execute_event(
+    event: &str,
+    change_mode: Option<ChangeDetectionMode>,
+    options: &RunOptions,
+) -> Result<i32> {
+    if hooks_disabled_by_env() {
+        println!("PETER_HOOK_DISABLE is set: skipping all hooks for {event}");
+        return Ok(exit_code::SUCCESS);
+    }
+
+    // The budget covers the whole run, resolution included
+    let executor = run_executor(options);
+
+    let current_dir = start_dir(&options.overrides)?;
+    let (worktree_context, operation) = run_worktree_context(&current_dir, options)?;
+
+    let ChangeSelection {
+        mode,
+        staged,
+        last_run,
+        scope,
+        resolve_dir,
+    } = choose_change_mode(event, change_mode, options, &worktree_context, &current_dir)?;
+
+    let ResolvedRun {
+        mut groups,
+        mut skip_report,
+        env_skipped,
+    } = resolve_event_groups(
         event,
-        change_mode,
-        &repo.root,
-        &current_dir,
+        mode,
+        (scope.as_deref(), &resolve_dir),
+        options,
         &worktree_context,
+        operation,
+    )?;
+
+    if groups.is_empty() {
+        if env_skipped.is_empty()
+            && !has_any_config(
+                &current_dir,
+                &worktree_context.repo_root,
+                &options.overrides,
+            )?
+        {
+            println!("No hooks.toml found for event: {event}");
+            return Ok(exit_code::NO_CONFIG);
+        }
+        print_no_hooks(event);
+        if options.report_skips {
+            print_skip_report(&skip_report);
+        }
+        return Ok(exit_code::SUCCESS);
+    }
+
+    if !options.quiet || options.dry_run {
+        print_run_banner(event, &groups);
+    }
+
+    if options.dry_run {
+        print_dry_run(event, &groups);
+        if options.check_templates {
+            check_hook_templates(&executor, &groups)?;
+        }
+        if options.show_env {
+            print_hook_environments(&executor, &groups)?;
+        }
+        if options.report_skips {
+            print_skip_report(&skip_report);
+        }
+        return Ok(exit_code::SUCCESS);
+    }
+
+    if options.interactive {
+        let skipped =
+            confirm_modifying_hooks(&mut groups, &mut io::stdin().lock(), &mut io::stderr())?;
+        if !skipped.is_empty() {
+            println!("Skipped (declined): {}", skipped.join(", "));
+        }
+        skip_report.extend(skipped.into_iter().map(|name| SkippedHook {
+            name,
+            reason: SkipReason::Declined,
+        }));
+    }
+
+    let executed = execute_groups(
+        &executor,
+        &groups,
+        options,
+        &worktree_context.repo_root,
+        staged,
+    )?;
+
+    run_post_run_commands(
+        event,
+        &current_dir,
+        &options.overrides,
+        executed.outcome.as_ref().map_err(|e| format!("{e:#}")),
+    );
+    let results = executed.outcome?;
+
+    report_results(
+        event,
+        &results,
+        &groups,
+        &skip_report,
+        options,
+        &worktree_context.repo_root,
+        executed.wall_time,
+    )?;
+
+    finish_run(
+        &results,
+        executed.pending_changes.as_ref(),
+        last_run.as_ref(),
+    )
+}
+
+/// How an event's changed files are detected, and where resolution starts
+struct ChangeSelection {
+    /// Change detection for resolution (`None` runs every hook unfiltered)
+    mode: Option<ChangeDetectionMode>,
+    /// Whether the hooks check the staged files
+    staged: bool,
+    /// With --incremental, the state to record after a successful run
+    last_run: Option<(LastRun, String)>,
+    /// With --path, the repo-relative directory the run is limited to
+    scope: Option<PathBuf>,
+    /// Directory hierarchical resolution starts from
+    resolve_dir: PathBuf,
+}
+
+/// The config groups an event runs, after filtering and skips
+struct ResolvedRun {
+    /// Config groups with at least one hook left to run
+    groups: Vec<peter_hook::hooks::ConfigGroup>,
+    /// Hooks that will not run, and why
+    skip_report: Vec<SkippedHook>,
+    /// Hooks skipped through `PETER_HOOK_SKIP`
+    env_skipped: Vec<String>,
+}
+
+/// What executing an event's config groups produced
+struct ExecutedGroups {
+    /// The hooks' results, or why they could not be run
+    outcome: Result<ExecutionResults>,
+    /// With --check, what the hooks changed (already rolled back)
+    pending_changes: Option<SnapshotDiff>,
+    /// Wall-clock time of the execution
+    wall_time: std::time::Duration,
+}
+
+/// Build the worktree context for a run, from the git repository unless
+/// running without one, and the rebase or merge in progress, if any
+fn run_worktree_context(
+    current_dir: &Path,
+    options: &RunOptions,
+) -> Result<(
+    peter_hook::hooks::WorktreeContext,
+    Option<(&'static str, &'static str)>,
+)> {
+    if options.no_git {
+        let repo_root = options
+            .overrides
+            .repo_root()
+            .map_or_else(|| current_dir.to_path_buf(), Path::to_path_buf);
+        let worktree_context = peter_hook::hooks::WorktreeContext {
+            is_worktree: false,
+            worktree_name: None,
+            common_dir: repo_root.join(".git"),
+            repo_root,
+            working_dir: current_dir.to_path_buf(),
+        };
+        return Ok((worktree_context, None));
+    }
+
+    let repo =
+        GitRepository::find_from_dir(current_dir).context("Failed to find git repository")?;
+    let operation = repo.operation_in_progress();
+    let worktree_context = peter_hook::hooks::WorktreeContext {
+        is_worktree: repo.is_worktree,
+        worktree_name: repo.get_worktree_name().map(ToString::to_string),
+        repo_root: repo.root.clone(),
+        common_dir: repo.common_dir,
+        working_dir: current_dir.to_path_buf(),
+    };
+    Ok((worktree_context, operation))
+}
+
+/// Apply --incremental and --path to the event's change detection, and check
+/// config coverage of the changed files
+fn choose_change_mode(
+    event: &str,
+    change_mode: Option<ChangeDetectionMode>,
+    options: &RunOptions,
+    worktree_context: &peter_hook::hooks::WorktreeContext,
+    current_dir: &Path,
+) -> Result<ChangeSelection> {
+    let repo_root = &worktree_context.repo_root;
+    let staged = matches!(change_mode, Some(ChangeDetectionMode::Staged));
+
+    // The state recorded for the next incremental run is captured before
+    // hooks can modify anything
+    let mut change_mode = change_mode;
+    let last_run = match &change_mode {
+        Some(_) if options.incremental && !options.dry_run => {
+            let last_run = LastRun::for_event(repo_root, event)?;
+            let tree = last_run
+                .current_tree(staged)
+                .context("Failed to record state for --incremental")?;
+            if let Some(from) = last_run.load() {
+                println!("Incremental: checking files changed since the last successful run");
+                change_mode = Some(ChangeDetectionMode::TreeDiff {
+                    from,
+                    to: tree.clone(),
+                });
+            } else {
+                println!("Incremental: no previous successful run, checking all changed files");
+            }
+            Some((last_run, tree))
+        }
+        _ => None,
+    };
+
+    // With --path, resolution starts from that directory and only sees the
+    // changes within it
+    let scope = options
+        .path
+        .as_ref()
+        .map(|path| scope_path(&current_dir.join(path), repo_root))
+        .transpose()?;
+    let resolve_dir = scope
+        .as_ref()
+        .map_or_else(|| current_dir.to_path_buf(), |scope| repo_root.join(scope));
+    if options.dry_run || scope.is_some() {
+        change_mode = detect_changes_up_front(
+            change_mode,
+            scope.as_deref(),
+            options.dry_run,
+            repo_root,
+            &resolve_dir,
+            &options.overrides,
+        )?;
+    }
+
+    if options.require_config_coverage || options.warn_uncovered {
+        if let Some(mode) = &change_mode {
+            check_config_coverage(
+                mode,
+                repo_root,
+                options.require_config_coverage,
+                &options.overrides,
+            )?;
+        }
+    }
+
+    Ok(ChangeSelection {
+        mode: change_mode,
+        staged,
+        last_run,
+        scope,
+        resolve_dir,
+    })
+}
+
+/// Resolve the config groups for an event's changed files, then drop what
+/// --path, --hook and `PETER_HOOK_SKIP` leave out
+///
+/// `(scope, resolve_dir)` are the --path scope and the directory resolution
+/// starts from.
+///
+/// # Errors
+///
+/// Returns an error if resolution fails, --fail-on-skip is set and hooks were
+/// skipped, or modifying hooks would run during a rebase or merge
+fn resolve_event_groups(
+    event: &str,
+    change_mode: Option<ChangeDetectionMode>,
+    (scope, resolve_dir): (Option<&Path>, &Path),
+    options: &RunOptions,
+    worktree_context: &peter_hook::hooks::WorktreeContext,
+    operation: Option<(&'static str, &'static str)>,
+) -> Result<ResolvedRun> {
+    // Use hierarchical resolution to find hooks for each changed file. A
+    // single hook resolves like an event of the same name, with the real
+    // event's change detection.
+    let mut groups = peter_hook::hooks::resolve_hooks_hierarchically(
+        options.hook.as_deref().unwrap_or(event),
+        change_mode,
+        &worktree_context.repo_root,
+        resolve_dir,
+        worktree_context,
+        &options.overrides,
     )
     .context("Failed to resolve hooks hierarchically")?;
 
-    if groups.is_empty() {
-        // No config groups found
-        if io::stdout().is_terminal() {
-            println!("❌ \x1b[33mNo hooks configured for event:\x1b[0m \x1b[1m{event}\x1b[0m");
-            println!("💡 \x1b[36mTip:\x1b[0m Check your \x1b[33mhooks.toml\x1b[0m configuration");
-        } else {
-            println!("No hooks found for event: {event}");
+    if let Some(scope) = scope {
+        // Files under the path may be governed by a config above it
+        groups.retain(|group| {
+            options.overrides.config().is_some()
+                || group
+                    .config_path
+                    .strip_prefix(&worktree_context.repo_root)
+                    .is_ok_and(|config| config.starts_with(scope))
+        });
+    }
+
+    if let Some(hook) = &options.hook {
+        // A group of the same name would expand to other hooks; keep only
+        // the hook itself
+        for group in &mut groups {
+            group.resolved_hooks.hooks.retain(|name, _| name == hook);
+            group
+                .resolved_hooks
+                .skipped
+                .retain(|skip| skip.name == *hook);
         }
+    }
+
+    // Groups whose hooks were all skipped only carry the skip reasons
+    let mut skip_report: Vec<SkippedHook> = groups
+        .iter()
+        .flat_map(|group| group.resolved_hooks.skipped.iter().cloned())
+        .collect();
+    groups.retain(|group| !group.resolved_hooks.hooks.is_empty());
+    report_skipped_hooks(&skip_report, options.fail_on_skip)?;
+
+    let env_skipped = skip_hooks_from_env(&mut groups);
+    if !env_skipped.is_empty() {
+        println!(
+            "Skipping hooks (PETER_HOOK_SKIP): {}",
+            env_skipped.join(", ")
+        );
+    }
+    skip_report.extend(env_skipped.iter().map(|name| SkippedHook {
+        name: name.clone(),
+        reason: SkipReason::SkipEnv,
+    }));
+
+    if options.merge_identical_configs {
+        groups = peter_hook::hooks::merge_identical_groups(groups);
+    }
+    if let Some(operation) = operation.filter(|_| !options.dry_run) {
+        check_operation_in_progress(&groups, operation, options.force)?;
+    }
+
+    Ok(ResolvedRun {
+        groups,
+        skip_report,
+        env_skipped,
+    })
+}
+
+/// Tell the user the event has a config but no hooks to run
+fn print_no_hooks(event: &str) {
+    if output::stdout_colors() {
+        println!("❌ \x1b[33mNo hooks configured for event:\x1b[0m \x1b[1m{event}\x1b[0m");
+        println!("💡 \x1b[36mTip:\x1b[0m Check your \x1b[33mhooks.toml\x1b[0m configuration");
     } else {
-        // We have at least one config group with hooks
-        // Calculate totals across all groups
-        let total_hooks: usize = groups.iter().map(|g| g.resolved_hooks.hooks.len()).sum();
-        let total_files: usize = groups.iter().map(|g| g.files.len()).sum();
-        let unique_configs = groups.len();
+        println!("No hooks found for event: {event}");
+    }
+}
 
-        if debug::is_enabled() && io::stdout().is_terminal() {
+/// Print which configs, files and hooks are about to run
+fn print_run_banner(event: &str, groups: &[peter_hook::hooks::ConfigGroup]) {
+    let total_hooks: usize = groups.iter().map(|g| g.resolved_hooks.hooks.len()).sum();
+    let total_files: usize = groups.iter().map(|g| g.files.len()).sum();
+    let unique_configs = groups.len();
+
+    if debug::is_enabled() && output::stdout_colors() {
+        print_debug_banner(event, groups);
+    } else if output::stdout_colors() {
+        // Fun terminal output when writing to TTY
+        println!("\n🎯 \x1b[1m\x1b[36mHook Configuration Found\x1b[0m");
+
+        if unique_configs > 1 {
+            println!("📦 \x1b[33m{unique_configs}\x1b[0m configurations across the monorepo");
+            for (idx, group) in groups.iter().enumerate() {
+                println!(
+                    "   \x1b[90m{}.\x1b[0m \x1b[37m{}\x1b[0m ({} hooks, {} files)",
+                    idx + 1,
+                    group.config_path.display(),
+                    group.resolved_hooks.hooks.len(),
+                    group.files.len()
+                );
+            }
+        } else {
+            println!("📂 \x1b[33m{}\x1b[0m", groups[0].config_path.display());
+        }
+
+        if total_files == 0 {
             println!(
-                "\x1b[38;5;201m🎪 \x1b[1m\x1b[38;5;51mPETER-HOOK EXECUTION EXTRAVAGANZA!\x1b[0m"
+                "📋 \x1b[33mNo files changed\x1b[0m - some hooks may be \x1b[90mskipped\x1b[0m"
             );
+        } else {
+            println!("📁 \x1b[32m{total_files}\x1b[0m changed files detected");
+        }
 
-            if unique_configs > 1 {
+        let hook_emoji = match total_hooks {
+            1 => "🚀",
+            2..=3 => "⚡",
+            4..=6 => "🎪",
+            _ => "🌟",
+        };
+
+        println!(
+            "\n{hook_emoji} \x1b[1m\x1b[35mExecuting {total_hooks} total hooks\x1b[0m for event: \
+             \x1b[1m\x1b[33m{event}\x1b[0m"
+        );
+
+        // Collect all unique hook names across groups
+        let mut all_hook_names = std::collections::HashSet::new();
+        for group in groups {
+            for hook_name in group.resolved_hooks.hooks.keys() {
+                all_hook_names.insert(hook_name.as_str());
+            }
+        }
+        let hook_names: Vec<_> = all_hook_names.into_iter().collect();
+
+        if hook_names.len() <= 4 {
+            println!(
+                "🔧 Hooks: {}",
+                hook_names
+                    .iter()
+                    .map(|&name| format!("\x1b[36m{name}\x1b[0m"))
+                    .collect::<Vec<_>>()
+                    .join("\x1b[90m, \x1b[0m")
+            );
+        } else {
+            println!(
+                "🔧 Hooks: {} and {} others",
+                hook_names
+                    .iter()
+                    .take(3)
+                    .map(|&name| format!("\x1b[36m{name}\x1b[0m"))
+                    .collect::<Vec<_>>()
+                    .join("\x1b[90m, \x1b[0m"),
+                hook_names.len() - 3
+            );
+        }
+        println!();
+    } else {
+        // Plain output for non-TTY (pipes, redirects, etc.)
+        if unique_configs > 1 {
+            println!("Found {unique_configs} hook configurations:");
+            for (idx, group) in groups.iter().enumerate() {
                 println!(
-                    "\x1b[38;5;198m📦 \x1b[1mMulti-Config Setup:\x1b[0m \
-                     \x1b[38;5;87m{unique_configs} configurations found\x1b[0m"
+                    "  {}. {} ({} hooks, {} files)",
+                    idx + 1,
+                    group.config_path.display(),
+                    group.resolved_hooks.hooks.len(),
+                    group.files.len()
                 );
             }
-
+        } else {
             println!(
-                "\x1b[38;5;214m🎯 \x1b[1m\x1b[38;5;208mFile targeting activated!\x1b[0m \
-                 \x1b[38;5;118m{total_files} files detected across {unique_configs} \
-                 config(s)\x1b[0m"
+                "Found hooks configuration: {}",
+                groups[0].config_path.display()
             );
+        }
+
+        println!("Detected {total_files} changed files");
+        if total_files == 0 {
+            println!("No files changed - some hooks may be skipped");
+        }
+        println!("Running {total_hooks} hooks for event: {event}");
+    }
+}
+
+/// The `--debug` banner: every config group, then the hooks by file pattern
+fn print_debug_banner(event: &str, groups: &[peter_hook::hooks::ConfigGroup]) {
+    let total_hooks: usize = groups.iter().map(|g| g.resolved_hooks.hooks.len()).sum();
+    let total_files: usize = groups.iter().map(|g| g.files.len()).sum();
+    let unique_configs = groups.len();
+
+    println!("\x1b[38;5;201m🎪 \x1b[1m\x1b[38;5;51mPETER-HOOK EXECUTION EXTRAVAGANZA!\x1b[0m");
+
+    if unique_configs > 1 {
+        println!(
+            "\x1b[38;5;198m📦 \x1b[1mMulti-Config Setup:\x1b[0m \x1b[38;5;87m{unique_configs} \
+             configurations found\x1b[0m"
+        );
+    }
+
+    println!(
+        "\x1b[38;5;214m🎯 \x1b[1m\x1b[38;5;208mFile targeting activated!\x1b[0m \
+         \x1b[38;5;118m{total_files} files detected across {unique_configs} config(s)\x1b[0m"
+    );
+
+    println!(
+        "\x1b[38;5;46m🚀 \x1b[1m\x1b[38;5;82mLaunching {total_hooks} total hooks for \
+         event:\x1b[0m \x1b[38;5;226m{event}\x1b[0m"
+    );
 
+    // Show each config group
+    for (group_idx, group) in groups.iter().enumerate() {
+        if unique_configs > 1 {
+            println!(
+                "\n\x1b[38;5;198m📋 Config Group {} of {}:\x1b[0m \x1b[38;5;87m{}\x1b[0m",
+                group_idx + 1,
+                unique_configs,
+                group.config_path.display()
+            );
+            println!(
+                "\x1b[38;5;147m   📁 {} files, {} hooks\x1b[0m",
+                group.files.len(),
+                group.resolved_hooks.hooks.len()
+            );
+        } else {
             println!(
-                "\x1b[38;5;46m🚀 \x1b[1m\x1b[38;5;82mLaunching {total_hooks} total hooks for \
-                 event:\x1b[0m \x1b[38;5;226m{event}\x1b[0m"
+                "\x1b[38;5;198m📋 Config: \x1b[38;5;87m{}\x1b[0m",
+                group.config_path.display()
             );
+        }
+    }
 
-            // Show each config group
-            for (group_idx, group) in groups.iter().enumerate() {
-                if unique_configs > 1 {
-                    println!(
-                        "\n\x1b[38;5;198m📋 Config Group {} of {}:\x1b[0m \x1b[38;5;87m{}\x1b[0m",
-                        group_idx + 1,
-                        unique_configs,
-                        group.config_path.display()
-                    );
-                    println!(
-                        "\x1b[38;5;147m   📁 {} files, {} hooks\x1b[0m",
-                        group.files.len(),
-                        group.resolved_hooks.hooks.len()
-                    );
+    // Show hook configuration summary with crazy colors and emojis
+    println!("\n\x1b[38;5;198m🎭 \x1b[1m\x1b[38;5;207mHOOK CONFIGURATION EXTRAVAGANZA!\x1b[0m");
+
+    // Collect all hooks from all groups
+    let mut all_hooks = std::collections::HashMap::new();
+    for group in groups {
+        for (hook_name, hook) in &group.resolved_hooks.hooks {
+            all_hooks.insert((hook_name.clone(), group.config_path.clone()), hook.clone());
+        }
+    }
+
+    // Group hooks by file patterns for visual organization
+    let mut pattern_groups = std::collections::HashMap::new();
+    for ((hook_name, _config), hook) in &all_hooks {
+        let patterns = hook.definition.file_globs().map_or_else(
+            || {
+                if hook.definition.run_always {
+                    "🌍 ALL FILES (run_always)".to_string()
                 } else {
-                    println!(
-                        "\x1b[38;5;198m📋 Config: \x1b[38;5;87m{}\x1b[0m",
-                        group.config_path.display()
-                    );
+                    "🎯 NO PATTERNS".to_string()
                 }
+            },
+            |files| files.join(", "),
+        );
+        pattern_groups
+            .entry(patterns)
+            .or_insert_with(Vec::new)
+            .push(hook_name);
+    }
+
+    let colors = [196, 208, 226, 118, 51, 99, 201, 165, 129, 93];
+    for (i, (pattern, hooks)) in pattern_groups.iter().enumerate() {
+        let color = colors[i % colors.len()];
+        let emoji = match i % 8 {
+            0 => "🐍",
+            1 => "⚡",
+            2 => "🔧",
+            3 => "🎨",
+            4 => "🛡️",
+            5 => "📊",
+            6 => "🌐",
+            _ => "✨",
+        };
+        println!("\x1b[38;5;{color}{emoji} Pattern: \x1b[38;5;159m{pattern}\x1b[0m");
+        for hook in hooks {
+            println!("\x1b[38;5;147m      🎪 \x1b[38;5;183m{hook}\x1b[0m");
+        }
+    }
+
+    println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
+}
+
+/// Show what a `--dry-run` would execute, and on which files
+fn print_dry_run(event: &str, groups: &[peter_hook::hooks::ConfigGroup]) {
+    let total_hooks: usize = groups.iter().map(|g| g.resolved_hooks.hooks.len()).sum();
+    let total_files: usize = groups.iter().map(|g| g.files.len()).sum();
+    let unique_configs = groups.len();
+
+    if !output::stdout_colors() {
+        println!("DRY RUN: {total_hooks} hooks would run for event: {event}");
+        for (group_idx, group) in groups.iter().enumerate() {
+            if unique_configs > 1 {
+                println!(
+                    "\nConfig {}: {}",
+                    group_idx + 1,
+                    group.config_path.display()
+                );
+            }
+            if let Some(description) = &group.resolved_hooks.description {
+                println!("{description}");
+            }
+            for (name, hook) in &group.resolved_hooks.hooks {
+                let cmd_str = hook.definition.command.to_string();
+                println!("  {name} - {cmd_str}");
+                if let Some(description) = &hook.definition.description {
+                    println!("    {description}");
+                }
+                if let Some(image) = &hook.definition.container {
+                    println!("    container: {image}");
+                }
+            }
+        }
+        println!("Changed files: {total_files}");
+        return;
+    }
+
+    println!("🔍 \x1b[1m\x1b[36mDry Run Mode\x1b[0m - showing what would execute:");
+    println!(
+        "📋 \x1b[33m{total_hooks}\x1b[0m total hooks would run across {unique_configs} config(s):"
+    );
+
+    for (group_idx, group) in groups.iter().enumerate() {
+        if unique_configs > 1 {
+            println!(
+                "\n   \x1b[1m\x1b[33mConfig {} of {}:\x1b[0m {}",
+                group_idx + 1,
+                unique_configs,
+                group.config_path.display()
+            );
+        }
+        if let Some(description) = &group.resolved_hooks.description {
+            println!("   \x1b[1m{description}\x1b[0m");
+        }
+
+        for (name, hook) in &group.resolved_hooks.hooks {
+            let cmd_str = hook.definition.command.to_string();
+            println!("   🎯 \x1b[36m{name}\x1b[0m: \x1b[90m{cmd_str}\x1b[0m");
+            if let Some(description) = &hook.definition.description {
+                println!("      📝 {description}");
+            }
+            println!(
+                "      📂 Working dir: \x1b[90m{}\x1b[0m",
+                hook.working_directory.display()
+            );
+            if let Some(ref patterns) = hook.definition.files {
+                println!(
+                    "      📄 File patterns: \x1b[90m{}\x1b[0m",
+                    patterns.join(", ")
+                );
             }
+            if hook.definition.run_always {
+                println!("      ⚡ Always runs (ignores file changes)");
+            }
+            if let Some(image) = &hook.definition.container {
+                println!("      🐳 Container: \x1b[90m{image}\x1b[0m");
+            }
+        }
+    }
+
+    println!("\n📁 \x1b[32m{total_files}\x1b[0m changed files detected:");
+    let files = groups.iter().flat_map(|group| &group.files);
+    for file in files.take(10) {
+        println!("   \x1b[90m•\x1b[0m \x1b[37m{}\x1b[0m", file.display());
+    }
+    if total_files > 10 {
+        println!("   \x1b[90m... and {} more files\x1b[0m", total_files - 10);
+    }
+}
+
+/// The groups with every hook run one at a time, for --profile-hooks
+fn isolated_groups(
+    groups: &[peter_hook::hooks::ConfigGroup],
+) -> Vec<peter_hook::hooks::ConfigGroup> {
+    groups
+        .iter()
+        .cloned()
+        .map(|mut group| {
+            group.resolved_hooks.execution_strategy = ExecutionStrategy::Sequential;
+            group
+        })
+        .collect()
+}
+
+/// Execute the config groups, with unstaged changes set aside (--stash) and
+/// the working tree rolled back afterwards (--check)
+///
+/// # Errors
+///
+/// Returns an error if unstaged changes cannot be set aside or restored, or
+/// the --check snapshot cannot be taken or rolled back. A failure to run the
+/// hooks themselves is returned in [`ExecutedGroups::outcome`].
+fn execute_groups(
+    executor: &HookExecutor,
+    groups: &[peter_hook::hooks::ConfigGroup],
+    options: &RunOptions,
+    repo_root: &Path,
+    staged: bool,
+) -> Result<ExecutedGroups> {
+    // Only staged runs have a difference between what is committed and
+    // what is on disk
+    let unstaged = if options.stash && staged {
+        UnstagedStash::save(repo_root).context("Failed to set aside unstaged changes")?
+    } else {
+        None
+    };
+    if let Some(unstaged) = &unstaged {
+        println!(
+            "Unstaged changes set aside in {}",
+            unstaged.patch_path().display()
+        );
+    }
+
+    // If this fails, dropping `unstaged` puts the unstaged changes back
+    let snapshot = if options.check {
+        Some(WorktreeSnapshot::capture(repo_root).context("Failed to snapshot working tree")?)
+    } else {
+        None
+    };
+
+    // Execute all config groups hierarchically
+    let started = std::time::Instant::now();
+    // The profile run is the run, so hooks never execute twice
+    let outcome = if options.profile_hooks {
+        executor.execute_multiple(&isolated_groups(groups))
+    } else {
+        executor.execute_multiple_parallel(groups, options.jobs.unwrap_or(1))
+    }
+    .context("Failed to execute hooks");
+    let wall_time = started.elapsed();
+
+    // In check mode, roll back whatever the hooks changed before reporting
+    let pending_changes = snapshot
+        .map(|snapshot| {
+            let diff = snapshot.diff().context("Failed to compare working tree")?;
+            snapshot
+                .restore(&diff)
+                .context("Failed to restore working tree after check")?;
+            Ok::<_, anyhow::Error>(diff)
+        })
+        .transpose();
+
+    // Unstaged changes go back even if the check rollback failed
+    if let Some(unstaged) = unstaged {
+        if let Some(hook_changes) = unstaged.restore()? {
+            eprintln!(
+                "Warning: hook changes conflicted with unstaged changes; they were saved in {} \
+                 (apply them with `git apply --3way`)",
+                hook_changes.display()
+            );
+        }
+        println!("Restored unstaged changes");
+    }
+
+    Ok(ExecutedGroups {
+        outcome,
+        pending_changes: pending_changes?,
+        wall_time,
+    })
+}
 
-            // Show hook configuration summary with crazy colors and emojis
+/// Print the run's results, then whatever extra reports the options ask for
+/// (annotations, output files, timings, skips and profile)
+///
+/// # Errors
+///
+/// Returns an error if the output files cannot be written
+fn report_results(
+    event: &str,
+    results: &ExecutionResults,
+    groups: &[peter_hook::hooks::ConfigGroup],
+    skip_report: &[SkippedHook],
+    options: &RunOptions,
+    repo_root: &Path,
+    wall_time: std::time::Duration,
+) -> Result<()> {
+    if options.quiet {
+        results.print_failures_only();
+    } else {
+        let total_hooks = groups.iter().map(|g| g.resolved_hooks.hooks.len()).sum();
+        print_results_summary(results, total_hooks);
+    }
+
+    if options.github_annotations {
+        output::github::print_annotations(results, repo_root);
+    }
+
+    let cut_off = results.cut_off_hooks();
+    if !cut_off.is_empty() {
+        eprintln!(
+            "Run exceeded --max-time of {}s; cut off: {}",
+            options.max_time.unwrap_or_default(),
+            cut_off.join(", ")
+        );
+    }
+
+    if let Some(output_dir) = &options.output_dir {
+        results
+            .write_output_dir(output_dir, event, groups)
+            .context("Failed to write hook output files")?;
+    }
+
+    if options.timings {
+        results.print_timings(groups, wall_time);
+    }
+    if options.report_skips {
+        print_skip_report(skip_report);
+    }
+    if options.profile_hooks {
+        results.print_profile();
+    }
+
+    Ok(())
+}
+
+/// Print the outcome of a (non-quiet) run of `total_hooks` hooks
+fn print_results_summary(results: &ExecutionResults, total_hooks: usize) {
+    if debug::is_enabled() && output::stdout_colors() {
+        println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
+        if results.success {
+            println!(
+                "\x1b[38;5;46m🎊 \x1b[1m\x1b[38;5;82mALL HOOKS SUCCEEDED!\x1b[0m \
+                 \x1b[38;5;46m🎊\x1b[0m"
+            );
+            println!(
+                "\x1b[38;5;118m✨ Your code is \x1b[1m\x1b[38;5;159mPERFECT\x1b[0m\x1b[38;5;118m! \
+                 Ready to commit! ✨\x1b[0m"
+            );
+        } else {
+            println!(
+                "\x1b[38;5;196m💥 \x1b[1m\x1b[38;5;199mSOME HOOKS FAILED!\x1b[0m \
+                 \x1b[38;5;196m💥\x1b[0m"
+            );
+            let failed = results.get_failed_hooks();
+            println!(
+                "\x1b[38;5;197m🚨 Failed hooks: \x1b[38;5;167m{}\x1b[0m",
+                failed.join(", ")
+            );
+        }
+        println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
+        results.print_summary();
+    } else if !debug::is_enabled() && output::stdout_colors() {
+        // Fun completion message for successful runs (non-debug TTY output)
+        if results.success {
+            let success_messages = [
+                "🎉 All hooks passed! Your code is looking great!",
+                "✨ Perfect! All checks completed successfully!",
+                "🚀 Excellent work! All hooks are happy!",
+                "🎊 Fantastic! Everything looks good to go!",
+                "💫 Outstanding! All validation passed!",
+            ];
+            let message = success_messages[total_hooks % success_messages.len()];
+            println!("\n{message}");
+
+            // Show quick summary without hook output (happy path)
+            let allowed = results.get_allowed_failures();
+            let passed_count = results.results.len() - allowed.len();
             println!(
-                "\n\x1b[38;5;198m🎭 \x1b[1m\x1b[38;5;207mHOOK CONFIGURATION EXTRAVAGANZA!\x1b[0m"
+                "✅ \x1b[32m{}\x1b[0m hook{} completed successfully\n",
+                passed_count,
+                if passed_count == 1 { "" } else { "s" }
             );
 
-            // Collect all hooks from all groups
-            let mut all_hooks = std::collections::HashMap::new();
-            for group in &groups {
-                for (hook_name, hook) in &group.resolved_hooks.hooks {
-                    all_hooks.insert((hook_name.clone(), group.config_path.clone()), hook.clone());
-                }
+            if !allowed.is_empty() {
+                println!(
+                    "⚠️  \x1b[33mFailed (allowed, not blocking):\x1b[0m {}\n",
+                    allowed.join(", ")
+                );
+                results.print_summary();
+            }
+        } else {
+            println!("\n💥 \x1b[31mSome hooks failed!\x1b[0m");
+            let failed = results.get_failed_hooks();
+            println!("❌ Failed: \x1b[31m{}\x1b[0m\n", failed.join(", "));
+
+            // Print detailed summary for failures to show what went wrong
+            results.print_summary();
+        }
+    } else {
+        // Always print full summary for non-TTY or when piped/redirected
+        results.print_summary();
+    }
+}
+
+/// Work out the event's exit code, reporting --check changes, and record a
+/// successful --incremental run
+///
+/// # Errors
+///
+/// Returns an error if the --incremental state cannot be saved
+fn finish_run(
+    results: &ExecutionResults,
+    pending_changes: Option<&SnapshotDiff>,
+    last_run: Option<&(LastRun, String)>,
+) -> Result<i32> {
+    let code = pending_changes.map_or_else(
+        || results.exit_code(),
+        |diff| {
+            print_check_report(diff);
+            if results.success && !diff.is_empty() {
+                exit_code::FAILURE
+            } else {
+                results.exit_code()
+            }
+        },
+    );
+    if code == exit_code::SUCCESS {
+        if let Some((last_run, tree)) = last_run {
+            last_run
+                .save(tree)
+                .context("Failed to record state for --incremental")?;
+        }
+    }
+
+    Ok(code)
+}
+
+/// Fail, listing them, if any hook's templates cannot be expanded
+fn check_hook_templates(
+    executor: &HookExecutor,
+    groups: &[peter_hook::hooks::ConfigGroup],
+) -> Result<()> {
+    let mut problems = Vec::new();
+    for group in groups {
+        let resolved = &group.resolved_hooks;
+        let mut names: Vec<&String> = resolved.hooks.keys().collect();
+        names.sort();
+        for name in names {
+            if let Err(e) =
+                executor.check_templates(&resolved.hooks[name], &resolved.worktree_context)
+            {
+                problems.push(format!("  {name}: {e:#}"));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        println!("All hook templates expand");
+        return Ok(());
+    }
+    anyhow::bail!(
+        "{} hook(s) have templates that cannot be expanded:\n{}",
+        problems.len(),
+        problems.join("\n")
+    )
+}
+
+/// Print the environment each hook would receive and where each variable
+/// comes from, masking the hook's `mask_env` variables
+fn print_hook_environments(
+    executor: &HookExecutor,
+    groups: &[peter_hook::hooks::ConfigGroup],
+) -> Result<()> {
+    for group in groups {
+        let resolved = &group.resolved_hooks;
+        let mut names: Vec<&String> = resolved.hooks.keys().collect();
+        names.sort();
+        for name in names {
+            let hook = &resolved.hooks[name];
+            let environment = executor
+                .hook_environment(hook, &resolved.worktree_context)
+                .with_context(|| format!("Failed to resolve the environment of hook {name}"))?;
+            let masked = hook.definition.mask_env.as_deref().unwrap_or_default();
+            println!("Environment of {name}:");
+            for (key, (value, source)) in &environment {
+                let value = if masked.contains(key) { "****" } else { value };
+                println!("  {key}={value} ({source})");
             }
+        }
+    }
+    Ok(())
+}
 
-            // Group hooks by file patterns for visual organization
-            let mut pattern_groups = std::collections::HashMap::new();
-            for ((hook_name, _config), hook) in &all_hooks {
-                let patterns = hook.definition.files.as_ref().map_or_else(
-                    || {
-                        if hook.definition.run_always {
-                            "🌍 ALL FILES (run_always)".to_string()
-                        } else {
-                            "🎯 NO PATTERNS".to_string()
-                        }
-                    },
-                    |files| files.join(", "),
-                );
-                pattern_groups
-                    .entry(patterns)
-                    .or_insert_with(Vec::new)
-                    .push(hook_name);
-            }
+/// Whether any hooks.toml applies to the repository: a `--config` override,
+/// the nearest config above `start_dir`, or one anywhere in the repository
+fn has_any_config(start_dir: &Path, repo_root: &Path, overrides: &ConfigOverrides) -> Result<bool> {
+    if overrides.config().is_some()
+        || HookResolver::new(start_dir)
+            .with_overrides(overrides.clone())
+            .find_config_file()?
+            .is_some()
+    {
+        return Ok(true);
+    }
+    Ok(peter_hook::git::LintFileDiscovery::new(repo_root)
+        .discover_files()
+        .context("Failed to find config files")?
+        .iter()
+        .any(|path| path.file_name().is_some_and(|name| name == "hooks.toml")))
+}
 
-            let colors = [196, 208, 226, 118, 51, 99, 201, 165, 129, 93];
-            for (i, (pattern, hooks)) in pattern_groups.iter().enumerate() {
-                let color = colors[i % colors.len()];
-                let emoji = match i % 8 {
-                    0 => "🐍",
-                    1 => "⚡",
-                    2 => "🔧",
-                    3 => "🎨",
-                    4 => "🛡️",
-                    5 => "📊",
-                    6 => "🌐",
-                    _ => "✨",
-                };
-                println!("\x1b[38;5;{color}{emoji} Pattern: \x1b[38;5;159m{pattern}\x1b[0m");
-                for hook in hooks {
-                    println!("\x1b[38;5;147m      🎪 \x1b[38;5;183m{hook}\x1b[0m");
-                }
-            }
+/// Whether `PETER_HOOK_DISABLE` asks for every hook to be skipped
+fn hooks_disabled_by_env() -> bool {
+    env::var("PETER_HOOK_DISABLE").is_ok_and(|value| matches!(value.trim(), "1" | "true"))
+}
 
-            println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
-        } else if io::stdout().is_terminal() {
-            // Fun terminal output when writing to TTY
-            println!("\n🎯 \x1b[1m\x1b[36mHook Configuration Found\x1b[0m");
+/// Remove hooks named in `PETER_HOOK_SKIP` (comma-separated) from every group
+///
+/// Returns the names of the hooks that were removed, sorted and deduplicated.
+fn skip_hooks_from_env(groups: &mut [peter_hook::hooks::ConfigGroup]) -> Vec<String> {
+    let Ok(skip) = env::var("PETER_HOOK_SKIP") else {
+        return Vec::new();
+    };
 
-            if unique_configs > 1 {
-                println!("📦 \x1b[33m{unique_configs}\x1b[0m configurations across the monorepo");
-                for (idx, group) in groups.iter().enumerate() {
-                    println!(
-                        "   \x1b[90m{}.\x1b[0m \x1b[37m{}\x1b[0m ({} hooks, {} files)",
-                        idx + 1,
-                        group.config_path.display(),
-                        group.resolved_hooks.hooks.len(),
-                        group.files.len()
-                    );
-                }
-            } else {
-                println!("📂 \x1b[33m{}\x1b[0m", groups[0].config_path.display());
+    let mut skipped = std::collections::BTreeSet::new();
+    for name in skip
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        for group in groups.iter_mut() {
+            if group.resolved_hooks.hooks.remove(name).is_some() {
+                skipped.insert(name.to_string());
             }
+        }
+    }
 
-            if total_files == 0 {
-                println!(
-                    "📋 \x1b[33mNo files changed\x1b[0m - some hooks may be \x1b[90mskipped\x1b[0m"
-                );
-            } else {
-                println!("📁 \x1b[32m{total_files}\x1b[0m changed files detected");
-            }
+    skipped.into_iter().collect()
+}
 
-            let hook_emoji = match total_hooks {
-                1 => "🚀",
-                2..=3 => "⚡",
-                4..=6 => "🎪",
-                _ => "🌟",
-            };
+/// Report the files hooks would have modified in `--check` mode
+fn print_check_report(diff: &SnapshotDiff) {
+    if diff.is_empty() {
+        println!("Check passed: hooks would not modify any files");
+        return;
+    }
 
-            println!(
-                "\n{hook_emoji} \x1b[1m\x1b[35mExecuting {total_hooks} total hooks\x1b[0m for \
-                 event: \x1b[1m\x1b[33m{event}\x1b[0m"
-            );
+    println!(
+        "Check failed: hooks would modify {} file{}:",
+        diff.files.len(),
+        if diff.files.len() == 1 { "" } else { "s" }
+    );
+    for (path, change) in &diff.files {
+        let label = match change {
+            SnapshotChange::Added => "added",
+            SnapshotChange::Modified => "modified",
+            SnapshotChange::Deleted => "deleted",
+        };
+        println!("  {label}: {}", path.display());
+    }
+    if !diff.stat.trim().is_empty() {
+        println!("{}", diff.stat.trim_end());
+    }
+    println!("Working tree restored; no changes were kept.");
+}
 
-            // Collect all unique hook names across groups
-            let mut all_hook_names = std::collections::HashSet::new();
-            for group in &groups {
-                for hook_name in group.resolved_hooks.hooks.keys() {
-                    all_hook_names.insert(hook_name.as_str());
-                }
-            }
-            let hook_names: Vec<_> = all_hook_names.into_iter().collect();
+/// Run the `[on_failure]` and `[notify]` commands of the nearest config
+///
+/// `on_failure` runs first, and only if the run failed. Failures of either
+/// command are reported as warnings and never change the outcome of the run.
+fn run_post_run_commands(
+    event: &str,
+    current_dir: &Path,
+    overrides: &ConfigOverrides,
+    outcome: Result<&ExecutionResults, String>,
+) {
+    let config_and_dir = HookResolver::new(current_dir)
+        .with_overrides(overrides.clone())
+        .find_config_file()
+        .ok()
+        .flatten()
+        .and_then(|config_path| {
+            let config = peter_hook::HookConfig::from_file_cached(&config_path, overrides).ok()?;
+            let config_dir = config_path.parent()?.to_path_buf();
+            Some((config, config_dir))
+        });
+
+    let Some((config, config_dir)) = config_and_dir else {
+        return;
+    };
 
-            if hook_names.len() <= 4 {
-                println!(
-                    "🔧 Hooks: {}",
-                    hook_names
-                        .iter()
-                        .map(|&name| format!("\x1b[36m{name}\x1b[0m"))
-                        .collect::<Vec<_>>()
-                        .join("\x1b[90m, \x1b[0m")
-                );
-            } else {
-                println!(
-                    "🔧 Hooks: {} and {} others",
-                    hook_names
-                        .iter()
-                        .take(3)
-                        .map(|&name| format!("\x1b[36m{name}\x1b[0m"))
-                        .collect::<Vec<_>>()
-                        .join("\x1b[90m, \x1b[0m"),
-                    hook_names.len() - 3
-                );
+    if let Some(on_failure) = &config.on_failure {
+        let failed_hooks = match &outcome {
+            Ok(results) if results.success => None,
+            Ok(results) => {
+                let mut failed = results.get_failed_hooks();
+                failed.sort_unstable();
+                Some(failed)
             }
-            println!();
-        } else {
-            // Plain output for non-TTY (pipes, redirects, etc.)
-            if unique_configs > 1 {
-                println!("Found {unique_configs} hook configurations:");
-                for (idx, group) in groups.iter().enumerate() {
-                    println!(
-                        "  {}. {} ({} hooks, {} files)",
-                        idx + 1,
-                        group.config_path.display(),
-                        group.resolved_hooks.hooks.len(),
-                        group.files.len()
-                    );
-                }
-            } else {
-                println!(
-                    "Found hooks configuration: {}",
-                    groups[0].config_path.display()
-                );
+            Err(_) => Some(Vec::new()),
+        };
+        if let Some(failed_hooks) = failed_hooks {
+            if let Err(e) = run_on_failure(on_failure, &config_dir, event, &failed_hooks) {
+                eprintln!("Warning: on_failure command failed: {e:#}");
             }
+        }
+    }
 
-            println!("Detected {total_files} changed files");
-            if total_files == 0 {
-                println!("No files changed - some hooks may be skipped");
-            }
-            println!("Running {total_hooks} hooks for event: {event}");
+    if let Some(notify) = &config.notify {
+        let payload = NotifyPayload::new(event, outcome);
+        if let Err(e) = run_notify(notify, &config_dir, &payload) {
+            eprintln!("Warning: notify command failed: {e:#}");
         }
+    }
+}
 
-        // Handle dry-run mode
-        if dry_run {
-            if io::stdout().is_terminal() {
-                println!("🔍 \x1b[1m\x1b[36mDry Run Mode\x1b[0m - showing what would execute:");
-                println!(
-                    "📋 \x1b[33m{total_hooks}\x1b[0m total hooks would run across \
-                     {unique_configs} config(s):"
-                );
+/// Validate hook configuration
+fn validate_config(trace_imports: bool, json: bool, overrides: &ConfigOverrides) -> Result<()> {
+    let current_dir = start_dir(overrides)?;
 
-                for (group_idx, group) in groups.iter().enumerate() {
-                    if unique_configs > 1 {
-                        println!(
-                            "\n   \x1b[1m\x1b[33mConfig {} of {}:\x1b[0m {}",
-                            group_idx + 1,
-                            unique_configs,
-                            group.config_path.display()
-                        );
-                    }
+    let resolver = HookResolver::new(&current_dir).with_overrides(overrides.clone());
 
-                    for (name, hook) in &group.resolved_hooks.hooks {
-                        let cmd_str = match &hook.definition.command {
-                            HookCommand::Shell(cmd) => cmd.clone(),
-                            HookCommand::Args(args) => args.join(" "),
-                        };
-                        println!("   🎯 \x1b[36m{name}\x1b[0m: \x1b[90m{cmd_str}\x1b[0m");
-                        println!(
-                            "      📂 Working dir: \x1b[90m{}\x1b[0m",
-                            hook.working_directory.display()
-                        );
-                        if let Some(ref patterns) = hook.definition.files {
-                            println!(
-                                "      📄 File patterns: \x1b[90m{}\x1b[0m",
-                                patterns.join(", ")
-                            );
-                        }
-                        if hook.definition.run_always {
-                            println!("      ⚡ Always runs (ignores file changes)");
-                        }
+    let Some(config_path) = resolver.find_config_file()? else {
+        println!("No hooks.toml file found in current directory or parent directories");
+        process::exit(exit_code::NO_CONFIG);
+    };
+
+    println!("Validating config file: {}", config_path.display());
+
+    // Try to parse the configuration
+    if trace_imports {
+        match peter_hook::HookConfig::from_file_with_trace(&config_path, overrides) {
+            Ok((config, diag)) => {
+                exit_on_config_errors(&config);
+
+                println!("✓ Configuration is valid");
+
+                let hook_names = config.get_hook_names();
+                if hook_names.is_empty() {
+                    println!("  No hooks or groups defined");
+                } else {
+                    println!("  Found {} hooks/groups:", hook_names.len());
+                    for name in hook_names {
+                        println!("    - {name}");
                     }
                 }
 
-                println!("\n📁 \x1b[32m{total_files}\x1b[0m changed files detected:");
-                if total_files <= 10 {
-                    for group in &groups {
-                        for file in &group.files {
-                            println!("   \x1b[90m•\x1b[0m \x1b[37m{}\x1b[0m", file.display());
-                        }
+                if json {
+                    // Print diagnostics as JSON
+                    match serde_json::to_string_pretty(&diag) {
+                        Ok(s) => println!("{s}"),
+                        Err(e) => eprintln!("Failed to serialize diagnostics: {e:#}"),
                     }
                 } else {
-                    let mut shown = 0;
-                    for group in &groups {
-                        for file in &group.files {
-                            if shown >= 10 {
-                                break;
-                            }
-                            println!("   \x1b[90m•\x1b[0m \x1b[37m{}\x1b[0m", file.display());
-                            shown += 1;
-                        }
-                        if shown >= 10 {
-                            break;
+                    // Human-readable diagnostics
+                    if diag.imports.is_empty() {
+                        println!("(no imports)");
+                    } else {
+                        println!("Imports (order):");
+                        for r in &diag.imports {
+                            println!("  {} -> {}", r.from, r.resolved);
                         }
                     }
-                    if total_files > 10 {
-                        println!("   \x1b[90m... and {} more files\x1b[0m", total_files - 10);
+                    if !diag.overrides.is_empty() {
+                        println!("Overrides:");
+                        for o in &diag.overrides {
+                            println!("  {} {}: {} -> {}", o.kind, o.name, o.previous, o.new);
+                        }
                     }
-                }
-            } else {
-                println!("DRY RUN: {total_hooks} hooks would run for event: {event}");
-                for (group_idx, group) in groups.iter().enumerate() {
-                    if unique_configs > 1 {
-                        println!(
-                            "\nConfig {}: {}",
-                            group_idx + 1,
-                            group.config_path.display()
-                        );
+                    if !diag.cycles.is_empty() {
+                        println!("Cycles (skipped):");
+                        for c in &diag.cycles {
+                            println!("  {c}");
+                        }
                     }
-                    for (name, hook) in &group.resolved_hooks.hooks {
-                        let cmd_str = match &hook.definition.command {
-                            HookCommand::Shell(cmd) => cmd.clone(),
-                            HookCommand::Args(args) => args.join(" "),
-                        };
-                        println!("  {name} - {cmd_str}");
+                    if !diag.unused.is_empty() {
+                        println!("Unused imports (no contributions):");
+                        for u in &diag.unused {
+                            println!("  {u}");
+                        }
                     }
                 }
-                println!("Changed files: {total_files}");
-            }
-            return Ok(());
-        }
 
-        // Execute all config groups hierarchically
-        let results = HookExecutor::execute_multiple(&groups).context("Failed to execute hooks")?;
+                // Display detailed hook composition
+                print_hook_details(&config);
 
-        if debug::is_enabled() && io::stdout().is_terminal() {
-            println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
-            if results.success {
-                println!(
-                    "\x1b[38;5;46m🎊 \x1b[1m\x1b[38;5;82mALL HOOKS SUCCEEDED!\x1b[0m \
-                     \x1b[38;5;46m🎊\x1b[0m"
-                );
-                println!(
-                    "\x1b[38;5;118m✨ Your code is \
-                     \x1b[1m\x1b[38;5;159mPERFECT\x1b[0m\x1b[38;5;118m! Ready to commit! ✨\x1b[0m"
-                );
-            } else {
-                println!(
-                    "\x1b[38;5;196m💥 \x1b[1m\x1b[38;5;199mSOME HOOKS FAILED!\x1b[0m \
-                     \x1b[38;5;196m💥\x1b[0m"
-                );
-                let failed = results.get_failed_hooks();
-                println!(
-                    "\x1b[38;5;197m🚨 Failed hooks: \x1b[38;5;167m{}\x1b[0m",
-                    failed.join(", ")
-                );
-            }
-            println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
-            results.print_summary();
-        } else if !debug::is_enabled() && io::stdout().is_terminal() {
-            // Fun completion message for successful runs (non-debug TTY output)
-            if results.success {
-                let success_messages = [
-                    "🎉 All hooks passed! Your code is looking great!",
-                    "✨ Perfect! All checks completed successfully!",
-                    "🚀 Excellent work! All hooks are happy!",
-                    "🎊 Fantastic! Everything looks good to go!",
-                    "💫 Outstanding! All validation passed!",
-                ];
-                let message = success_messages[total_hooks % success_messages.len()];
-                println!("\n{message}");
-
-                // Show quick summary without hook output (happy path)
-                let passed_count = results.results.len();
-                println!(
-                    "✅ \x1b[32m{}\x1b[0m hook{} completed successfully\n",
-                    passed_count,
-                    if passed_count == 1 { "" } else { "s" }
-                );
-            } else {
-                println!("\n💥 \x1b[31mSome hooks failed!\x1b[0m");
-                let failed = results.get_failed_hooks();
-                println!("❌ Failed: \x1b[31m{}\x1b[0m\n", failed.join(", "));
+                // Validate requires_files compatibility
+                validate_requires_files_compatibility(&config);
 
-                // Print detailed summary for failures to show what went wrong
-                results.print_summary();
+                // Report hook names defined by more than one source
+                report_duplicate_hooks(&diag);
+            }
+            Err(e) => {
+                eprintln!("✗ Configuration is invalid: {e:#}");
+                process::exit(exit_code::CONFIG_ERROR);
             }
-        } else {
-            // Always print full summary for non-TTY or when piped/redirected
-            results.print_summary();
         }
+    } else {
+        match peter_hook::HookConfig::from_file_with_trace(&config_path, overrides) {
+            Ok((config, diag)) => {
+                exit_on_config_errors(&config);
 
-        if !results.success {
-            process::exit(1);
+                println!("✓ Configuration is valid");
+
+                let hook_names = config.get_hook_names();
+                if hook_names.is_empty() {
+                    println!("  No hooks or groups defined");
+                } else {
+                    println!("  Found {} hooks/groups:", hook_names.len());
+                    for name in hook_names {
+                        println!("    - {name}");
+                    }
+                }
+
+                // Display detailed hook composition
+                print_hook_details(&config);
+
+                // Validate requires_files compatibility
+                validate_requires_files_compatibility(&config);
+
+                // Report hook names defined by more than one source
+                report_duplicate_hooks(&diag);
+            }
+            Err(e) => {
+                eprintln!("✗ Configuration is invalid: {e:#}");
+                process::exit(exit_code::CONFIG_ERROR);
+            }
         }
     }
 
     Ok(())
 }
 
-/// Validate hook configuration
-fn validate_config(trace_imports: bool, json: bool) -> Result<()> {
-    let current_dir = env::current_dir().context("Failed to get current working directory")?;
-
-    let resolver = HookResolver::new(&current_dir);
-
-    match resolver.find_config_file()? {
-        Some(config_path) => {
-            println!("Validating config file: {}", config_path.display());
-
-            // Try to parse the configuration
-            if trace_imports {
-                match peter_hook::HookConfig::from_file_with_trace(&config_path) {
-                    Ok((config, diag)) => {
-                        println!("✓ Configuration is valid");
+/// A single validation finding, as emitted by `validate --format json`
+#[derive(serde::Serialize)]
+struct ValidationDiagnostic {
+    /// `error` or `warning`
+    severity: &'static str,
+    /// Human-readable description of the problem
+    message: String,
+    /// Hook the diagnostic refers to, if any
+    hook: Option<String>,
+    /// Config file the diagnostic was found in (`null` when none was found)
+    config_path: Option<String>,
+    /// Git event (group name) the diagnostic refers to, if any
+    event: Option<String>,
+}
 
-                        let hook_names = config.get_hook_names();
-                        if hook_names.is_empty() {
-                            println!("  No hooks or groups defined");
-                        } else {
-                            println!("  Found {} hooks/groups:", hook_names.len());
-                            for name in hook_names {
-                                println!("    - {name}");
-                            }
-                        }
+/// Print the merged configuration of the nearest hooks.toml, defaults
+/// included, as TOML or JSON
+///
+/// Keys are sorted and unset options left out, so the output is stable.
+fn print_config(format: &str, overrides: &ConfigOverrides) -> Result<()> {
+    /// Drop unset (null) values, which TOML cannot represent
+    fn strip_nulls(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.retain(|_, value| !value.is_null());
+                map.values_mut().for_each(strip_nulls);
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(strip_nulls),
+            _ => {}
+        }
+    }
 
-                        if json {
-                            // Print diagnostics as JSON
-                            match serde_json::to_string_pretty(&diag) {
-                                Ok(s) => println!("{s}"),
-                                Err(e) => eprintln!("Failed to serialize diagnostics: {e:#}"),
-                            }
-                        } else {
-                            // Human-readable diagnostics
-                            if diag.imports.is_empty() {
-                                println!("(no imports)");
-                            } else {
-                                println!("Imports (order):");
-                                for r in &diag.imports {
-                                    println!("  {} -> {}", r.from, r.resolved);
-                                }
-                            }
-                            if !diag.overrides.is_empty() {
-                                println!("Overrides:");
-                                for o in &diag.overrides {
-                                    println!(
-                                        "  {} {}: {} -> {}",
-                                        o.kind, o.name, o.previous, o.new
-                                    );
-                                }
-                            }
-                            if !diag.cycles.is_empty() {
-                                println!("Cycles (skipped):");
-                                for c in &diag.cycles {
-                                    println!("  {c}");
-                                }
-                            }
-                            if !diag.unused.is_empty() {
-                                println!("Unused imports (no contributions):");
-                                for u in &diag.unused {
-                                    println!("  {u}");
-                                }
-                            }
-                        }
+    let resolver = HookResolver::new(start_dir(overrides)?).with_overrides(overrides.clone());
+    let Some(config_path) = resolver.find_config_file()? else {
+        eprintln!("No hooks.toml found");
+        process::exit(exit_code::NO_CONFIG);
+    };
+    let config = peter_hook::HookConfig::from_file(&config_path, overrides)?;
 
-                        // Display detailed hook composition
-                        print_hook_details(&config);
+    let mut value = serde_json::to_value(&config).context("Failed to serialize configuration")?;
+    strip_nulls(&mut value);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!("# Effective configuration of {}", config_path.display());
+        print!(
+            "{}",
+            toml::to_string_pretty(&value).context("Failed to serialize configuration")?
+        );
+    }
+    Ok(())
+}
 
-                        // Validate requires_files compatibility
-                        validate_requires_files_compatibility(&config);
-                    }
-                    Err(e) => {
-                        eprintln!("✗ Configuration is invalid: {e:#}");
-                        process::exit(1);
-                    }
-                }
-            } else {
-                match peter_hook::HookConfig::from_file(&config_path) {
-                    Ok(config) => {
-                        println!("✓ Configuration is valid");
+/// Print a readable description of what `event` runs in the nearest
+/// hooks.toml: the group's description, and each hook's description,
+/// command, file patterns and timeout
+fn print_event_info(event: &str, overrides: &ConfigOverrides) -> Result<()> {
+    let resolver = HookResolver::new(start_dir(overrides)?).with_overrides(overrides.clone());
+    let Some(config_path) = resolver.find_config_file()? else {
+        eprintln!("No hooks.toml found");
+        process::exit(exit_code::NO_CONFIG);
+    };
+    let config = peter_hook::HookConfig::from_file(&config_path, overrides)?;
+    let group = config.groups.as_ref().and_then(|groups| groups.get(event));
+    if group.is_none() && !config.has_hook(event) {
+        anyhow::bail!("'{event}' is not defined in {}", config_path.display());
+    }
 
-                        let hook_names = config.get_hook_names();
-                        if hook_names.is_empty() {
-                            println!("  No hooks or groups defined");
-                        } else {
-                            println!("  Found {} hooks/groups:", hook_names.len());
-                            for name in hook_names {
-                                println!("    - {name}");
-                            }
-                        }
+    println!("{event}");
+    println!("  Config: {}", config_path.display());
+    if let Some(group) = group {
+        if let Some(description) = &group.description {
+            println!("  {description}");
+        }
+        if group.placeholder == Some(true) {
+            println!("  Placeholder: runs no hooks here, only those of nested configs");
+        }
+        if let Some(files) = &group.files {
+            println!("  Files: {}", files.join(", "));
+        }
+    }
 
-                        // Display detailed hook composition
-                        print_hook_details(&config);
+    let hooks = config.hooks.as_ref();
+    for name in config.hooks_named_by(event) {
+        println!();
+        let Some(hook) = hooks.and_then(|hooks| hooks.get(&name)) else {
+            println!("  {name} (not defined)");
+            continue;
+        };
+        match &hook.description {
+            Some(description) => println!("  {name}: {description}"),
+            None => println!("  {name}"),
+        }
+        println!("    Command: {}", hook.command);
+        if let Some(files) = &hook.files {
+            println!("    Files: {}", files.join(", "));
+        }
+        if let Some(exclude) = &hook.exclude_files {
+            println!("    Exclude: {}", exclude.join(", "));
+        }
+        println!("    Timeout: {}s", hook.timeout_seconds);
+    }
+    Ok(())
+}
 
-                        // Validate requires_files compatibility
-                        validate_requires_files_compatibility(&config);
-                    }
-                    Err(e) => {
-                        eprintln!("✗ Configuration is invalid: {e:#}");
-                        process::exit(1);
-                    }
-                }
+/// Validation diagnostics for the config at `config_path`
+fn config_diagnostics(
+    config_path: &Path,
+    overrides: &ConfigOverrides,
+) -> Vec<ValidationDiagnostic> {
+    let config_path_str = Some(config_path.display().to_string());
+    let mut diagnostics = Vec::new();
+    match peter_hook::HookConfig::from_file_with_trace(config_path, overrides) {
+        Ok((config, diag)) => {
+            for (group, include) in config.unresolved_includes() {
+                diagnostics.push(ValidationDiagnostic {
+                    severity: "error",
+                    message: format!(
+                        "Group '{group}' includes '{include}', which is not a defined hook or \
+                         group"
+                    ),
+                    hook: Some(include),
+                    config_path: config_path_str.clone(),
+                    event: Some(group),
+                });
+            }
+            for (group, step) in config.unresolved_group_steps() {
+                diagnostics.push(ValidationDiagnostic {
+                    severity: "error",
+                    message: unresolved_step_message(&group, &step),
+                    hook: Some(step),
+                    config_path: config_path_str.clone(),
+                    event: Some(group),
+                });
+            }
+            for (hook, message) in config.invalid_file_patterns() {
+                diagnostics.push(ValidationDiagnostic {
+                    severity: "error",
+                    message,
+                    hook: Some(hook),
+                    config_path: config_path_str.clone(),
+                    event: None,
+                });
+            }
+            if let Some(message) = config.dependency_cycle() {
+                diagnostics.push(ValidationDiagnostic {
+                    severity: "error",
+                    message,
+                    hook: None,
+                    config_path: config_path_str.clone(),
+                    event: None,
+                });
+            }
+            for (hook, sources) in diag.duplicate_hooks() {
+                diagnostics.push(ValidationDiagnostic {
+                    severity: "warning",
+                    message: duplicate_hook_message(&hook, &sources),
+                    hook: Some(hook),
+                    config_path: config_path_str.clone(),
+                    event: None,
+                });
+            }
+            for warning in collect_requires_files_warnings(&config) {
+                diagnostics.push(ValidationDiagnostic {
+                    severity: "warning",
+                    message: warning.message,
+                    hook: Some(warning.hook),
+                    config_path: config_path_str.clone(),
+                    event: Some(warning.event),
+                });
             }
         }
-        None => {
-            println!("No hooks.toml file found in current directory or parent directories");
+        Err(e) => {
+            diagnostics.push(ValidationDiagnostic {
+                severity: "error",
+                message: format!("{e:#}"),
+                hook: None,
+                config_path: config_path_str,
+                event: None,
+            });
         }
     }
+    diagnostics
+}
+
+/// Validate the nearest configuration and print diagnostics as a JSON array
+fn validate_config_json(overrides: &ConfigOverrides) -> Result<()> {
+    let current_dir = start_dir(overrides)?;
+
+    let resolver = HookResolver::new(&current_dir).with_overrides(overrides.clone());
+
+    let config_path = resolver.find_config_file()?;
+    let diagnostics = config_path.as_deref().map_or_else(
+        || {
+            vec![ValidationDiagnostic {
+                severity: "error",
+                message: "No hooks.toml file found in current directory or parent directories"
+                    .to_string(),
+                hook: None,
+                config_path: None,
+                event: None,
+            }]
+        },
+        |config_path| config_diagnostics(config_path, overrides),
+    );
+    let has_errors = diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == "error");
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&diagnostics).context("Failed to serialize diagnostics")?
+    );
+
+    if config_path.is_none() {
+        process::exit(exit_code::NO_CONFIG);
+    }
+    if has_errors {
+        process::exit(exit_code::CONFIG_ERROR);
+    }
 
     Ok(())
 }
@@ -846,6 +2592,7 @@ fn print_hook_details(config: &peter_hook::HookConfig) {
                 let cmd_str = match &hook.command {
                     HookCommand::Shell(cmd) => format!("shell: {cmd}"),
                     HookCommand::Args(args) => format!("args: [{}]", args.join(", ")),
+                    HookCommand::Steps { steps } => format!("steps: [{}]", steps.join(", ")),
                 };
                 println!("│  Command: {cmd_str}");
 
@@ -875,9 +2622,15 @@ fn print_hook_details(config: &peter_hook::HookConfig) {
                     println!("│  File Patterns: [{}]", files.join(", "));
                 } else if hook.run_always {
                     println!("│  File Patterns: ⚡ run_always=true (ignores file changes)");
-                } else {
+                } else if hook.content_patterns.is_none() && hook.extensions.is_none() {
                     println!("│  File Patterns: (none - runs on any file change)");
                 }
+                if let Some(extensions) = &hook.extensions {
+                    println!("│  Extensions: [{}]", extensions.join(", "));
+                }
+                if let Some(content_patterns) = &hook.content_patterns {
+                    println!("│  Content Patterns: [{}]", content_patterns.join(", "));
+                }
 
                 // Requires files flag
                 if hook.requires_files {
@@ -895,6 +2648,9 @@ fn print_hook_details(config: &peter_hook::HookConfig) {
                 if hook.run_at_root {
                     println!("│  Run Location: 📁 Repository root");
                 }
+                if let Some(image) = &hook.container {
+                    println!("│  Container: 🐳 {image} (repository at /work)");
+                }
 
                 // Dependencies
                 if let Some(deps) = &hook.depends_on {
@@ -957,6 +2713,12 @@ fn print_hook_details(config: &peter_hook::HookConfig) {
                 } else {
                     println!("│  Includes: [{}]", group.includes.join(", "));
                 }
+                if let Some(setup) = &group.setup {
+                    println!("│  Setup: {setup} (runs first; failure skips the group)");
+                }
+                if let Some(teardown) = &group.teardown {
+                    println!("│  Teardown: {teardown} (runs last, even after failures)");
+                }
 
                 println!("└─────────────────────────────────────────────────────────\n");
             }
@@ -966,8 +2728,96 @@ fn print_hook_details(config: &peter_hook::HookConfig) {
     println!("═══════════════════════════════════════════════════════════");
 }
 
+/// Fail validation if any group includes a name that is neither a hook nor a
+/// group or has a `setup`/`teardown` that is not a hook, a file pattern does
+/// not compile, or hooks depend on each other in a cycle
+fn exit_on_config_errors(config: &peter_hook::HookConfig) {
+    let unresolved = config.unresolved_includes();
+    let unresolved_steps = config.unresolved_group_steps();
+    let invalid_patterns = config.invalid_file_patterns();
+    let cycle = config.dependency_cycle();
+    if unresolved.is_empty()
+        && unresolved_steps.is_empty()
+        && invalid_patterns.is_empty()
+        && cycle.is_none()
+    {
+        return;
+    }
+
+    eprintln!("✗ Configuration is invalid:");
+    for (group, include) in &unresolved {
+        eprintln!("  Group '{group}' includes '{include}', which is not a defined hook or group");
+    }
+    for (group, step) in &unresolved_steps {
+        eprintln!("  {}", unresolved_step_message(group, step));
+    }
+    for (_, message) in &invalid_patterns {
+        eprintln!("  {message}");
+    }
+    if let Some(cycle) = &cycle {
+        eprintln!("  {cycle}");
+    }
+    process::exit(exit_code::CONFIG_ERROR);
+}
+
+/// Describe a group `setup`/`teardown` that does not name a hook
+fn unresolved_step_message(group: &str, step: &str) -> String {
+    format!("Group '{group}' uses '{step}' as setup or teardown, which is not a defined hook")
+}
+
+/// Describe a hook name defined by several sources and which one wins
+fn duplicate_hook_message(hook: &str, sources: &[String]) -> String {
+    let winner = sources.last().map_or("", String::as_str);
+    format!(
+        "Hook '{hook}' is defined in multiple sources ({}); the definition from {winner} is used",
+        sources.join(", ")
+    )
+}
+
+/// Warn about hook names defined by more than one source (imports or local)
+fn report_duplicate_hooks(diag: &peter_hook::config::ImportDiagnostics) {
+    let duplicates = diag.duplicate_hooks();
+    if duplicates.is_empty() {
+        return;
+    }
+
+    eprintln!("\n⚠️  DUPLICATE HOOK DEFINITIONS:\n");
+    for (hook, sources) in &duplicates {
+        eprintln!("  ⚠️  {}", duplicate_hook_message(hook, sources));
+    }
+    eprintln!("\n  Later imports override earlier ones; local definitions override all imports.\n");
+}
+
 /// Validate `requires_files` compatibility with hook event types
 fn validate_requires_files_compatibility(config: &peter_hook::HookConfig) {
+    let warnings = collect_requires_files_warnings(config);
+
+    if !warnings.is_empty() {
+        println!("\n⚠️  VALIDATION WARNINGS:\n");
+        for warning in warnings {
+            eprintln!("  ⚠️  {}", warning.message);
+        }
+        eprintln!("\n  Hooks with requires_files=true can only run in:");
+        eprintln!("    - pre-commit (staged files)");
+        eprintln!("    - pre-push (push changes)");
+        eprintln!("    - post-commit, post-merge, post-checkout (commit changes)");
+        eprintln!("    - Other file-based hooks\n");
+        eprintln!("  They CANNOT run in:");
+        eprintln!("    - commit-msg, prepare-commit-msg (message hooks)");
+        eprintln!("    - applypatch-msg (message hooks)\n");
+    }
+}
+
+/// A `requires_files` hook included in an event that cannot provide files
+struct RequiresFilesWarning {
+    hook: String,
+    event: String,
+    message: String,
+}
+
+/// Find hooks with `requires_files = true` included in groups for git events
+/// that cannot provide file lists
+fn collect_requires_files_warnings(config: &peter_hook::HookConfig) -> Vec<RequiresFilesWarning> {
     use peter_hook::git::can_provide_files;
 
     let mut warnings = Vec::new();
@@ -983,10 +2833,14 @@ fn validate_requires_files_compatibility(config: &peter_hook::HookConfig) {
                     for include in &group.includes {
                         if let Some(hook) = hooks.get(include) {
                             if hook.requires_files {
-                                warnings.push(format!(
-                                    "Hook '{include}' requires files but is included in group \
-                                     '{group_name}' which cannot provide file lists"
-                                ));
+                                warnings.push(RequiresFilesWarning {
+                                    hook: include.clone(),
+                                    event: group_name.clone(),
+                                    message: format!(
+                                        "Hook '{include}' requires files but is included in group \
+                                         '{group_name}' which cannot provide file lists"
+                                    ),
+                                });
                             }
                         }
                     }
@@ -995,31 +2849,56 @@ fn validate_requires_files_compatibility(config: &peter_hook::HookConfig) {
         }
     }
 
-    if !warnings.is_empty() {
-        println!("\n⚠️  VALIDATION WARNINGS:\n");
-        for warning in warnings {
-            eprintln!("  ⚠️  {warning}");
-        }
-        eprintln!("\n  Hooks with requires_files=true can only run in:");
-        eprintln!("    - pre-commit (staged files)");
-        eprintln!("    - pre-push (push changes)");
-        eprintln!("    - post-commit, post-merge, post-checkout (commit changes)");
-        eprintln!("    - Other file-based hooks\n");
-        eprintln!("  They CANNOT run in:");
-        eprintln!("    - commit-msg, prepare-commit-msg (message hooks)");
-        eprintln!("    - applypatch-msg (message hooks)\n");
+    warnings
+}
+
+/// The hook and arguments that the arguments after `lint <name> --` go to:
+/// the single hook `name` resolves to
+///
+/// # Errors
+///
+/// Returns an error if `name` resolves to several hooks or to a hook with
+/// `steps`
+fn lint_extra_args(
+    resolved_hooks: &peter_hook::hooks::ResolvedHooks,
+    name: &str,
+    args: Vec<String>,
+) -> Result<(String, Vec<String>)> {
+    let mut hooks = resolved_hooks.hooks.iter();
+    let (Some((hook_name, hook)), None) = (hooks.next(), hooks.next()) else {
+        anyhow::bail!(
+            "Extra arguments need a single hook, but '{name}' resolves to {} hooks",
+            resolved_hooks.hooks.len()
+        );
+    };
+    if matches!(
+        hook.definition.command,
+        peter_hook::config::HookCommand::Steps { .. }
+    ) {
+        anyhow::bail!("Extra arguments cannot be passed to hook '{hook_name}': it runs steps");
     }
+    Ok((hook_name.clone(), args))
 }
 
 /// Run hooks in lint mode
 #[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
-fn run_lint_mode(hook_name: &str, dry_run: bool) -> Result<()> {
-    let current_dir = env::current_dir().context("Failed to get current working directory")?;
+fn run_lint_mode(
+    hook_name: &str,
+    dry_run: bool,
+    args: Vec<String>,
+    overrides: &ConfigOverrides,
+) -> Result<()> {
+    let current_dir = start_dir(overrides)?;
 
-    let resolver = HookResolver::new(&current_dir);
+    let resolver = HookResolver::new(&current_dir).with_overrides(overrides.clone());
 
     if let Some(resolved_hooks) = resolver.resolve_hooks_for_lint(hook_name)? {
-        if debug::is_enabled() && io::stdout().is_terminal() {
+        let extra_args = if args.is_empty() {
+            None
+        } else {
+            Some(lint_extra_args(&resolved_hooks, hook_name, args)?)
+        };
+        if debug::is_enabled() && output::stdout_colors() {
             println!("\x1b[38;5;201m🎪 \x1b[1m\x1b[38;5;51mPETER-HOOK LINT MODE!\x1b[0m");
             println!(
                 "\x1b[38;5;198m📋 Config: \x1b[38;5;87m{}\x1b[0m",
@@ -1052,7 +2931,7 @@ fn run_lint_mode(hook_name: &str, dry_run: bool) -> Result<()> {
             }
 
             println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
-        } else if io::stdout().is_terminal() {
+        } else if output::stdout_colors() {
             println!("\n🎯 \x1b[1m\x1b[36mLint Mode:\x1b[0m \x1b[1m\x1b[33m{hook_name}\x1b[0m");
             println!("📂 \x1b[33m{}\x1b[0m", resolved_hooks.config_path.display());
 
@@ -1079,14 +2958,11 @@ fn run_lint_mode(hook_name: &str, dry_run: bool) -> Result<()> {
 
         // Handle dry-run mode
         if dry_run {
-            if io::stdout().is_terminal() {
+            if output::stdout_colors() {
                 println!("🔍 \x1b[1m\x1b[36mDry Run Mode\x1b[0m - showing what would execute:");
 
                 for (name, hook) in &resolved_hooks.hooks {
-                    let cmd_str = match &hook.definition.command {
-                        HookCommand::Shell(cmd) => cmd.clone(),
-                        HookCommand::Args(args) => args.join(" "),
-                    };
+                    let cmd_str = hook.definition.command.to_string();
                     println!("   🎯 \x1b[36m{name}\x1b[0m: \x1b[90m{cmd_str}\x1b[0m");
                     println!(
                         "      📂 Working dir: \x1b[90m{}\x1b[0m",
@@ -1105,20 +2981,23 @@ fn run_lint_mode(hook_name: &str, dry_run: bool) -> Result<()> {
                     resolved_hooks.hooks.len()
                 );
                 for (name, hook) in &resolved_hooks.hooks {
-                    let cmd_str = match &hook.definition.command {
-                        HookCommand::Shell(cmd) => cmd.clone(),
-                        HookCommand::Args(args) => args.join(" "),
-                    };
+                    let cmd_str = hook.definition.command.to_string();
                     println!("  {name} - {cmd_str}");
                 }
             }
             return Ok(());
         }
 
-        let results = HookExecutor::execute(&resolved_hooks)
+        let executor = HookExecutor::with_options(ExecutorOptions {
+            extra_args,
+            config_overrides: overrides.clone(),
+            ..ExecutorOptions::default()
+        });
+        let results = executor
+            .execute(&resolved_hooks)
             .context("Failed to execute hooks in lint mode")?;
 
-        if debug::is_enabled() && io::stdout().is_terminal() {
+        if debug::is_enabled() && output::stdout_colors() {
             println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
             if results.success {
                 println!(
@@ -1138,7 +3017,7 @@ fn run_lint_mode(hook_name: &str, dry_run: bool) -> Result<()> {
             }
             println!("\x1b[38;5;198m{}\x1b[0m", "═".repeat(60));
             results.print_summary();
-        } else if !debug::is_enabled() && io::stdout().is_terminal() {
+        } else if !debug::is_enabled() && output::stdout_colors() {
             if results.success {
                 println!("🎉 Lint passed! All checks completed successfully!");
                 println!(
@@ -1157,10 +3036,10 @@ fn run_lint_mode(hook_name: &str, dry_run: bool) -> Result<()> {
         }
 
         if !results.success {
-            process::exit(1);
+            process::exit(results.exit_code());
         }
     } else {
-        if io::stdout().is_terminal() {
+        if output::stdout_colors() {
             println!("❌ \x1b[31mHook not found:\x1b[0m \x1b[1m{hook_name}\x1b[0m");
             println!(
                 "💡 \x1b[36mTip:\x1b[0m Run \x1b[33mpeter-hook validate\x1b[0m to see available \
@@ -0,0 +1,157 @@
+//! GitHub Actions workflow commands for `run --format github`
+//!
+//! Every failing hook gets an `::error` (or `::warning` for an allowed
+//! failure) naming it. Lines of its output that look like
+//! `path:line[:col]: message`, where the path is a file in the repository,
+//! also become annotations on that file, so they show up inline in the diff.
+
+use crate::hooks::{ExecutionResult, ExecutionResults};
+use regex::Regex;
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+/// `path:line[:col]: message`, the location format most compilers and
+/// linters use
+static LOCATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<file>[^\s:][^:]*):(?P<line>\d+)(?::(?P<col>\d+))?:\s*(?P<message>\S.*)$")
+        .expect("location pattern is valid")
+});
+
+/// Print the annotations for every failing hook in `results`, in name order
+pub fn print_annotations(results: &ExecutionResults, repo_root: &Path) {
+    let mut failed: Vec<_> = results
+        .results
+        .iter()
+        .filter(|(_, result)| !result.success)
+        .collect();
+    failed.sort_by_key(|(name, _)| name.as_str());
+
+    for (name, result) in failed {
+        for annotation in annotations(name, result, repo_root) {
+            println!("{annotation}");
+        }
+    }
+}
+
+/// Workflow commands for one failing hook: one per located output line, then
+/// one for the hook itself
+#[must_use]
+pub fn annotations(name: &str, result: &ExecutionResult, repo_root: &Path) -> Vec<String> {
+    let command = if result.allowed_failure {
+        "warning"
+    } else {
+        "error"
+    };
+    let title = escape_property(name);
+    let config_dir = result.source_file.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut annotations: Vec<String> = result
+        .stdout
+        .lines()
+        .chain(result.stderr.lines())
+        .filter_map(|line| {
+            let captures = LOCATION.captures(line.trim_end())?;
+            let file = repo_file(&captures["file"], config_dir, repo_root)?;
+            let col = captures
+                .name("col")
+                .map(|col| format!(",col={}", col.as_str()))
+                .unwrap_or_default();
+            Some(format!(
+                "::{command} file={},line={}{col},title={title}::{}",
+                escape_property(&file.display().to_string()),
+                &captures["line"],
+                escape_data(&captures["message"])
+            ))
+        })
+        .collect();
+
+    annotations.push(format!(
+        "::{command} title={title}::{}",
+        escape_data(&format!("{name} failed (exit code {})", result.exit_code))
+    ));
+    annotations
+}
+
+/// The repo-relative path of a file named in hook output
+///
+/// Hooks usually run from their config's directory, so relative paths are
+/// tried against it first, then against the repository root.
+fn repo_file(file: &str, config_dir: &Path, repo_root: &Path) -> Option<PathBuf> {
+    let file = Path::new(file);
+    if file.is_absolute() {
+        let relative = file.strip_prefix(repo_root).ok()?;
+        return file.is_file().then(|| relative.to_path_buf());
+    }
+    [config_dir.join(file), file.to_path_buf()]
+        .into_iter()
+        .find(|candidate| repo_root.join(candidate).is_file())
+}
+
+/// Escape a workflow command message
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, time::Duration};
+    use tempfile::TempDir;
+
+    fn failed(stdout: &str, allowed_failure: bool) -> ExecutionResult {
+        ExecutionResult {
+            exit_code: 1,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            success: false,
+            allowed_failure,
+            source_file: PathBuf::from("pkg/hooks.toml"),
+            description: None,
+            duration: Duration::ZERO,
+            cached: false,
+            cut_off: false,
+        }
+    }
+
+    #[test]
+    fn test_annotations_locate_output_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("pkg/src")).unwrap();
+        fs::write(root.join("pkg/src/lib.rs"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+
+        let output = "pkg: checking\nsrc/lib.rs:3:5: unused variable: `x`\nREADME.md:10: line too \
+                      long, 100%\nmissing.rs:1:1: not a file here\n";
+        let annotations = annotations("lint", &failed(output, false), root);
+
+        assert_eq!(
+            annotations,
+            [
+                "::error file=pkg/src/lib.rs,line=3,col=5,title=lint::unused variable: `x`",
+                "::error file=README.md,line=10,title=lint::line too long, 100%25",
+                "::error title=lint::lint failed (exit code 1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_allowed_failures_are_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let annotations = annotations("a:b,c", &failed("", true), temp_dir.path());
+        assert_eq!(
+            annotations,
+            ["::warning title=a%3Ab%2Cc::a:b,c failed (exit code 1)"]
+        );
+    }
+}
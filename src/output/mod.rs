@@ -1,12 +1,83 @@
 //! Output formatting utilities
 
+pub mod github;
+
 use console::{Emoji, style};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::io::IsTerminal;
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Explicit `--color` choice: 0 = auto, 1 = always, 2 = never
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Set the `--color` choice (`auto`, `always` or `never`)
+///
+/// Call this before anything is printed; it also applies to text styled with
+/// the `console` crate.
+pub fn set_color_choice(choice: &str) {
+    let choice = match choice {
+        "always" => 1,
+        "never" => 2,
+        _ => 0,
+    };
+    COLOR_CHOICE.store(choice, Ordering::Relaxed);
+    console::set_colors_enabled(stdout_colors());
+    console::set_colors_enabled_stderr(stderr_colors());
+}
+
+/// Whether output to stdout should use colors and decorations
+#[must_use]
+pub fn stdout_colors() -> bool {
+    use_colors(std::io::stdout().is_terminal())
+}
+
+/// Whether output to stderr should use colors and decorations
+#[must_use]
+pub fn stderr_colors() -> bool {
+    use_colors(std::io::stderr().is_terminal())
+}
+
+/// Apply the color settings to a stream that is (or is not) a TTY
+fn use_colors(is_tty: bool) -> bool {
+    let flag = match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    };
+    colors_enabled(
+        flag,
+        std::env::var_os("NO_COLOR").is_some(),
+        std::env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0"),
+        is_tty,
+    )
+}
+
+/// Decide whether to color output
+///
+/// An explicit `--color` flag wins, then `NO_COLOR` (set to anything)
+/// disables color and `CLICOLOR_FORCE` (set to anything but `0`) forces it,
+/// and otherwise color follows TTY detection. `NO_COLOR` beats
+/// `CLICOLOR_FORCE` when both are set.
+#[must_use]
+pub const fn colors_enabled(
+    flag: Option<bool>,
+    no_color: bool,
+    clicolor_force: bool,
+    is_tty: bool,
+) -> bool {
+    match flag {
+        Some(enabled) => enabled,
+        None if no_color => false,
+        None => clicolor_force || is_tty,
+    }
+}
 
 /// Output formatter that strips colors and emojis for non-TTY output
 pub struct OutputFormatter {
-    /// Whether output is going to a TTY
+    /// Whether output is decorated: going to a TTY, unless color is turned
+    /// off (or forced on) by `--color`, `NO_COLOR` or `CLICOLOR_FORCE`
     is_tty: bool,
 }
 
@@ -15,7 +86,7 @@ impl OutputFormatter {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            is_tty: std::io::stdout().is_terminal(),
+            is_tty: stdout_colors(),
         }
     }
 
@@ -93,6 +164,20 @@ impl OutputFormatter {
         }
     }
 
+    /// Format the result of a failed hook with `allow_failure = true`
+    #[must_use]
+    pub fn allowed_failure_result(&self, name: &str, exit_code: i32) -> String {
+        if self.is_tty {
+            format!(
+                "{} {}: exit code {exit_code} (failed, allowed)",
+                style("!").yellow().bold(),
+                style(name).yellow()
+            )
+        } else {
+            format!("[FAIL (allowed)] {name}: exit code {exit_code}")
+        }
+    }
+
     /// Format section header
     #[must_use]
     pub fn section_header(&self, title: &str) -> String {
@@ -211,6 +296,10 @@ mod tests {
         assert_eq!(formatter.status(false), "[FAIL]");
         assert_eq!(formatter.managed_status(true), "[managed]");
         assert_eq!(formatter.managed_status(false), "[custom]");
+        assert_eq!(
+            formatter.allowed_failure_result("spell", 1),
+            "[FAIL (allowed)] spell: exit code 1"
+        );
     }
 
     #[test]
@@ -223,6 +312,20 @@ mod tests {
         assert_eq!(formatter.managed_status(false), "📄 custom");
     }
 
+    #[test]
+    fn test_color_precedence() {
+        // TTY detection when nothing is set
+        assert!(colors_enabled(None, false, false, true));
+        assert!(!colors_enabled(None, false, false, false));
+        // NO_COLOR turns color off even on a TTY, and beats CLICOLOR_FORCE
+        assert!(!colors_enabled(None, true, false, true));
+        assert!(!colors_enabled(None, true, true, true));
+        assert!(colors_enabled(None, false, true, false));
+        // --color beats both variables
+        assert!(colors_enabled(Some(true), true, false, false));
+        assert!(!colors_enabled(Some(false), false, true, true));
+    }
+
     #[test]
     fn test_divider_formatting() {
         let formatter_tty = OutputFormatter { is_tty: true };
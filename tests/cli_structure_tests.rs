@@ -2,7 +2,7 @@
 //! Tests for CLI structure and command definitions
 
 use clap::{CommandFactory, Parser};
-use peter_hook::cli::{Cli, Commands, ConfigCommand};
+use peter_hook::cli::{Cli, Commands, ConfigCommand, invocation_args};
 
 #[test]
 fn test_cli_has_all_subcommands() {
@@ -24,6 +24,10 @@ fn test_cli_has_all_subcommands() {
         "Missing 'uninstall' subcommand"
     );
     assert!(subcommands.contains(&"run"), "Missing 'run' subcommand");
+    assert!(
+        subcommands.contains(&"run-all"),
+        "Missing 'run-all' subcommand"
+    );
     assert!(
         subcommands.contains(&"validate"),
         "Missing 'validate' subcommand"
@@ -33,6 +37,10 @@ fn test_cli_has_all_subcommands() {
         subcommands.contains(&"list-worktrees"),
         "Missing 'list-worktrees' subcommand"
     );
+    assert!(
+        subcommands.contains(&"list-events"),
+        "Missing 'list-events' subcommand"
+    );
     assert!(
         subcommands.contains(&"config"),
         "Missing 'config' subcommand"
@@ -54,12 +62,25 @@ fn test_cli_has_all_subcommands() {
         subcommands.contains(&"doctor"),
         "Missing 'doctor' subcommand"
     );
+    assert!(
+        subcommands.contains(&"schema"),
+        "Missing 'schema' subcommand"
+    );
+    assert!(
+        subcommands.contains(&"print-config"),
+        "Missing 'print-config' subcommand"
+    );
+    assert!(subcommands.contains(&"info"), "Missing 'info' subcommand");
+    assert!(
+        subcommands.contains(&"targets"),
+        "Missing 'targets' subcommand"
+    );
 
-    // Should have exactly 12 visible subcommands
+    // Should have exactly 18 visible subcommands
     assert_eq!(
         subcommands.len(),
-        12,
-        "Expected 12 visible subcommands, got {}",
+        18,
+        "Expected 18 visible subcommands, got {}",
         subcommands.len()
     );
 }
@@ -252,6 +273,13 @@ fn test_lint_command_structure() {
         .get_arguments()
         .find(|arg| arg.get_long() == Some("dry-run"));
     assert!(dry_run_arg.is_some(), "Missing --dry-run flag in lint");
+
+    // Arguments after `--` are passed to the hook
+    let args = lint_cmd
+        .get_positionals()
+        .find(|arg| arg.get_id().as_str() == "args")
+        .expect("Missing trailing args in lint");
+    assert!(args.is_last_set());
 }
 
 #[test]
@@ -315,15 +343,19 @@ fn test_list_and_list_worktrees_have_no_args() {
 }
 
 #[test]
-fn test_doctor_command_has_no_args() {
+fn test_doctor_command_only_has_assert_managed() {
     let cmd = Cli::command();
     let doctor_cmd = cmd
         .find_subcommand("doctor")
         .expect("doctor subcommand not found");
+    let args: Vec<_> = doctor_cmd
+        .get_arguments()
+        .filter_map(|arg| arg.get_long())
+        .collect();
     assert_eq!(
-        doctor_cmd.get_arguments().count(),
-        0,
-        "doctor command should have no arguments"
+        args,
+        ["assert-managed"],
+        "doctor command should only have --assert-managed"
     );
 }
 
@@ -368,12 +400,64 @@ fn test_cli_parsing_run_with_args() {
         event,
         all_files,
         dry_run,
+        check_templates,
+        show_env,
+        quiet,
+        format,
+        timings,
+        profile_hooks,
+        check,
+        interactive,
+        output_dir,
+        no_git,
+        stash,
+        jobs,
+        hook,
+        path,
+        require_config_coverage,
+        warn_uncovered,
+        max_time,
+        incremental,
+        fail_on_skip,
+        report_skips,
+        merge_identical_configs,
+        force,
+        since_tag,
+        files_from,
+        files_from0,
+        watch,
         git_args,
     } = result.unwrap().command
     {
         assert_eq!(event, "pre-commit");
         assert!(all_files);
         assert!(dry_run);
+        assert!(!check_templates);
+        assert!(!show_env);
+        assert!(!quiet);
+        assert_eq!(format, "text");
+        assert!(!timings);
+        assert!(!profile_hooks);
+        assert!(!check);
+        assert!(!interactive);
+        assert!(output_dir.is_none());
+        assert!(!no_git);
+        assert!(!stash);
+        assert_eq!(jobs, None);
+        assert!(hook.is_none());
+        assert!(path.is_none());
+        assert!(!require_config_coverage);
+        assert!(!warn_uncovered);
+        assert!(max_time.is_none());
+        assert!(!incremental);
+        assert!(!fail_on_skip);
+        assert!(!report_skips);
+        assert!(!merge_identical_configs);
+        assert!(!force);
+        assert!(!since_tag);
+        assert!(files_from.is_none());
+        assert!(files_from0.is_none());
+        assert!(!watch);
         assert_eq!(git_args, vec!["extra", "args"]);
     } else {
         panic!("Expected Run command");
@@ -420,3 +504,45 @@ fn test_cli_invalid_worktree_strategy() {
     let result = Cli::try_parse_from(["peter-hook", "install", "--worktree-strategy", "invalid"]);
     assert!(result.is_err(), "Should reject invalid worktree strategy");
 }
+
+#[test]
+fn test_cli_since_tag_rejects_other_change_selections() {
+    for other in [
+        ["--all-files"].as_slice(),
+        &["--files-from", "files.txt"],
+        &["--files-from0", "files.txt"],
+        &["--incremental"],
+        &["--no-git"],
+    ] {
+        let mut args = vec!["peter-hook", "run", "pre-commit", "--since-tag"];
+        args.extend_from_slice(other);
+        let Err(error) = Cli::try_parse_from(&args) else {
+            panic!("--since-tag should conflict with {other:?}");
+        };
+        assert_eq!(
+            error.kind(),
+            clap::error::ErrorKind::ArgumentConflict,
+            "{other:?}: {error}"
+        );
+    }
+}
+
+#[test]
+fn test_invocation_as_hook_name_dispatches_to_run() {
+    let args = invocation_args(
+        ["/repo/.git/hooks/commit-msg", ".git/COMMIT_EDITMSG", "-x"].map(Into::into),
+    );
+    let cli = Cli::try_parse_from(args).expect("Hook invocation should parse");
+    let Commands::Run {
+        event, git_args, ..
+    } = cli.command
+    else {
+        panic!("Expected the run command");
+    };
+    assert_eq!(event, "commit-msg");
+    assert_eq!(git_args, vec![".git/COMMIT_EDITMSG", "-x"]);
+
+    // Any other program name leaves the arguments alone
+    let args = invocation_args(["/usr/bin/peter-hook", "version"].map(Into::into));
+    assert_eq!(args, ["/usr/bin/peter-hook", "version"]);
+}
@@ -0,0 +1,105 @@
+#![allow(clippy::all, clippy::pedantic, clippy::nursery)]
+//! Integration tests for the global --config override flag
+
+use git2::Repository as Git2Repository;
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn bin_path() -> std::path::PathBuf {
+    assert_cmd::cargo::cargo_bin("peter-hook")
+}
+
+#[test]
+fn test_config_outside_repo_is_used() {
+    let repo_dir = TempDir::new().unwrap();
+    Git2Repository::init(repo_dir.path()).unwrap();
+
+    // The repository's own config would fail if it were used
+    fs::write(
+        repo_dir.path().join("hooks.toml"),
+        r#"
+[hooks.pre-commit]
+command = "false"
+execution_type = "other"
+"#,
+    )
+    .unwrap();
+
+    let config_dir = TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("work")).unwrap();
+    fs::write(
+        config_dir.path().join("hooks.toml"),
+        r#"
+[hooks.marker]
+command = "touch ran-in-workdir && touch {HOOK_DIR}/ran-in-hook-dir"
+workdir = "work"
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["marker"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(repo_dir.path())
+        .arg("--config")
+        .arg(config_dir.path().join("hooks.toml"))
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // Relative workdir and {HOOK_DIR} resolve against the override's directory
+    assert!(config_dir.path().join("work/ran-in-workdir").exists());
+    assert!(config_dir.path().join("ran-in-hook-dir").exists());
+}
+
+#[test]
+fn test_config_override_applies_to_validate() {
+    let repo_dir = TempDir::new().unwrap();
+    Git2Repository::init(repo_dir.path()).unwrap();
+
+    let config_dir = TempDir::new().unwrap();
+    fs::write(
+        config_dir.path().join("hooks.toml"),
+        r#"
+[hooks.only-in-override]
+command = "true"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(repo_dir.path())
+        .arg("--config")
+        .arg(config_dir.path().join("hooks.toml"))
+        .arg("validate")
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {stdout}");
+    assert!(stdout.contains("only-in-override"));
+}
+
+#[test]
+fn test_missing_config_override_fails() {
+    let repo_dir = TempDir::new().unwrap();
+    Git2Repository::init(repo_dir.path()).unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(repo_dir.path())
+        .args(["--config", "does-not-exist.toml", "validate"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Config file not found"), "stderr: {stderr}");
+}
@@ -0,0 +1,180 @@
+#![allow(clippy::all, clippy::pedantic, clippy::nursery)]
+//! Integration tests for which config governs a file when several levels
+//! (--config, hooks.toml, .peter-hook.toml, the user config) are present
+
+use git2::Repository as Git2Repository;
+use std::{fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+fn bin_path() -> std::path::PathBuf {
+    assert_cmd::cargo::cargo_bin("peter-hook")
+}
+
+/// A pre-commit config whose hook creates `markers/<marker>`
+fn marker_config(markers: &Path, marker: &str) -> String {
+    format!(
+        r#"
+[hooks.{marker}]
+command = "touch '{}'"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["{marker}"]
+"#,
+        markers.join(marker).display()
+    )
+}
+
+/// Repository with staged files in `pkg/` and `other/`, `pkg/hooks.toml`,
+/// a repo-root `.peter-hook.toml` and a user config under `xdg/`
+struct Fixture {
+    repo: TempDir,
+    xdg: TempDir,
+    markers: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let fixture = Self {
+            repo: TempDir::new().unwrap(),
+            xdg: TempDir::new().unwrap(),
+            markers: TempDir::new().unwrap(),
+        };
+        let root = fixture.repo.path();
+        Git2Repository::init(root).unwrap();
+        for dir in ["pkg", "other"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("file.txt"), "content\n").unwrap();
+        }
+        fs::write(
+            root.join("pkg/hooks.toml"),
+            marker_config(fixture.markers.path(), "pkg"),
+        )
+        .unwrap();
+        fs::write(
+            root.join(".peter-hook.toml"),
+            marker_config(fixture.markers.path(), "repo-default"),
+        )
+        .unwrap();
+        let user_dir = fixture.xdg.path().join("peter-hook");
+        fs::create_dir_all(&user_dir).unwrap();
+        fs::write(
+            user_dir.join("hooks.toml"),
+            marker_config(fixture.markers.path(), "user"),
+        )
+        .unwrap();
+        fixture
+    }
+
+    fn run(&self, extra_args: &[&str]) {
+        let status = Command::new("git")
+            .args(["add", "."])
+            .current_dir(self.repo.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let output = Command::new(bin_path())
+            .current_dir(self.repo.path())
+            .env("XDG_CONFIG_HOME", self.xdg.path())
+            .args(extra_args)
+            .args(["run", "pre-commit"])
+            .output()
+            .expect("Failed to execute");
+        assert!(
+            output.status.success(),
+            "stdout: {}\nstderr: {}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn ran(&self, marker: &str) -> bool {
+        self.markers.path().join(marker).exists()
+    }
+}
+
+#[test]
+fn test_hooks_toml_wins_and_repo_default_covers_the_rest() {
+    let fixture = Fixture::new();
+    fixture.run(&[]);
+
+    assert!(
+        fixture.ran("pkg"),
+        "pkg/file.txt is governed by pkg/hooks.toml"
+    );
+    assert!(
+        fixture.ran("repo-default"),
+        "other/file.txt falls back to .peter-hook.toml"
+    );
+    assert!(
+        !fixture.ran("user"),
+        "the repo default shadows the user config"
+    );
+}
+
+#[test]
+fn test_user_config_is_the_last_resort() {
+    let fixture = Fixture::new();
+    fs::remove_file(fixture.repo.path().join(".peter-hook.toml")).unwrap();
+    fixture.run(&[]);
+
+    assert!(fixture.ran("pkg"));
+    assert!(
+        fixture.ran("user"),
+        "other/file.txt falls back to the user config"
+    );
+}
+
+#[test]
+fn test_root_hooks_toml_shadows_fallbacks() {
+    let fixture = Fixture::new();
+    fs::write(
+        fixture.repo.path().join("hooks.toml"),
+        marker_config(fixture.markers.path(), "root"),
+    )
+    .unwrap();
+    fixture.run(&[]);
+
+    assert!(fixture.ran("pkg"));
+    assert!(fixture.ran("root"));
+    assert!(!fixture.ran("repo-default"));
+    assert!(!fixture.ran("user"));
+}
+
+#[test]
+fn test_config_override_beats_every_level() {
+    let fixture = Fixture::new();
+    let override_dir = TempDir::new().unwrap();
+    let override_path = override_dir.path().join("hooks.toml");
+    fs::write(
+        &override_path,
+        marker_config(fixture.markers.path(), "override"),
+    )
+    .unwrap();
+    fixture.run(&["--config", override_path.to_str().unwrap()]);
+
+    assert!(fixture.ran("override"));
+    assert!(!fixture.ran("pkg"));
+    assert!(!fixture.ran("repo-default"));
+    assert!(!fixture.ran("user"));
+}
+
+#[test]
+fn test_lint_uses_repo_default_without_hooks_toml() {
+    let fixture = Fixture::new();
+    let output = Command::new(bin_path())
+        .current_dir(fixture.repo.path().join("other"))
+        .env("XDG_CONFIG_HOME", fixture.xdg.path())
+        .args(["lint", "repo-default"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(fixture.ran("repo-default"));
+}
@@ -36,7 +36,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("test", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -59,7 +59,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("failing", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
         // The hook may succeed or fail depending on the shell/environment
     }
@@ -95,11 +95,56 @@ execution = "sequential"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("test-group", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
 
+#[test]
+fn test_sequential_hooks_run_in_order() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.alpha]
+command = "echo alpha >> order.log"
+modifies_repository = false
+execution_type = "other"
+order = 3
+
+[hooks.beta]
+command = "echo beta >> order.log"
+modifies_repository = false
+execution_type = "other"
+order = 1
+
+[hooks.gamma]
+command = "echo gamma >> order.log"
+modifies_repository = false
+execution_type = "other"
+order = 2
+
+[groups.test-group]
+includes = ["alpha", "beta", "gamma"]
+execution = "sequential"
+"#,
+    )
+    .unwrap();
+
+    let resolver = HookResolver::new(temp_dir.path());
+    let resolved = resolver
+        .resolve_hook_by_name("test-group", None)
+        .unwrap()
+        .unwrap();
+    let results = HookExecutor::new().execute(&resolved).unwrap();
+    assert!(results.success);
+
+    let log = fs::read_to_string(temp_dir.path().join("order.log")).unwrap();
+    assert_eq!(log, "beta\ngamma\nalpha\n");
+}
+
 #[test]
 fn test_execute_multiple_hooks_parallel() {
     let temp_dir = TempDir::new().unwrap();
@@ -126,7 +171,7 @@ execution = "parallel"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("test-group", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -157,7 +202,7 @@ execution = "force-parallel"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("test-group", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -185,7 +230,7 @@ depends_on = ["first"]
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("second", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -209,7 +254,7 @@ env = { TEST_VAR = "test_value" }
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("with-env", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -237,7 +282,7 @@ run_at_root = true
     let resolver = HookResolver::new(&subdir);
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("root-hook", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -260,7 +305,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("array-cmd", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -285,7 +330,7 @@ files = ["*.txt"]
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("in-place", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -309,7 +354,7 @@ execution_type = "other"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("other-type", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -332,7 +377,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("output", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -355,7 +400,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("nonexistent", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         // Should complete (command not found handling varies by shell)
         assert!(result.is_ok());
     }
@@ -383,7 +428,7 @@ files = ["**/*.rs"]
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("rust-check", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -407,7 +452,7 @@ run_always = true
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("always", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -438,7 +483,7 @@ execution = "parallel"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("parallel-group", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -469,7 +514,7 @@ execution = "parallel"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("mixed", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -496,7 +541,7 @@ workdir = "custom"
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("custom-wd", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -532,7 +577,7 @@ execution = "{strategy}"
         let resolver = HookResolver::new(temp_dir.path());
 
         if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("test-group", None) {
-            let result = HookExecutor::execute(&resolved);
+            let result = HookExecutor::new().execute(&resolved);
             assert!(result.is_ok(), "Strategy {name} should work");
         }
     }
@@ -556,7 +601,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("template", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
     }
 }
@@ -579,7 +624,7 @@ modifies_repository = false
     let resolver = HookResolver::new(temp_dir.path());
 
     if let Ok(Some(resolved)) = resolver.resolve_hook_by_name("test", None) {
-        let result = HookExecutor::execute(&resolved);
+        let result = HookExecutor::new().execute(&resolved);
         assert!(result.is_ok());
         if let Ok(results) = result {
             assert!(!results.results.is_empty(), "Should have results");
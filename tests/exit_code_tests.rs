@@ -0,0 +1,197 @@
+#![allow(clippy::all, clippy::pedantic, clippy::nursery)]
+//! Integration tests for the documented exit codes
+//!
+//! 0 = success, 1 = hook failure, 2 = invalid config, 3 = no config found,
+//! 124 = hook timeout
+
+use git2::Repository as Git2Repository;
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+/// Get the peter-hook binary path
+fn bin_path() -> std::path::PathBuf {
+    assert_cmd::cargo::cargo_bin("peter-hook")
+}
+
+/// Create a git repository with a tracked file, and a hooks.toml if `config`
+/// is given
+fn setup_repo(config: Option<&str>) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    if let Some(config) = config {
+        fs::write(temp_dir.path().join("hooks.toml"), config).unwrap();
+    }
+    temp_dir
+}
+
+/// Run peter-hook in `dir` and return its exit code
+fn exit_code(dir: &std::path::Path, args: &[&str]) -> Option<i32> {
+    Command::new(bin_path())
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute command")
+        .status
+        .code()
+}
+
+#[test]
+fn test_run_success_exits_zero() {
+    let temp_dir = setup_repo(Some(
+        r#"
+[hooks.ok]
+command = "true"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["ok"]
+"#,
+    ));
+
+    assert_eq!(
+        exit_code(temp_dir.path(), &["run", "pre-commit", "--all-files"]),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_run_hook_failure_exits_one() {
+    let temp_dir = setup_repo(Some(
+        r#"
+[hooks.fail]
+command = "exit 7"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["fail"]
+"#,
+    ));
+
+    assert_eq!(
+        exit_code(temp_dir.path(), &["run", "pre-commit", "--all-files"]),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_run_timeout_exits_124() {
+    let temp_dir = setup_repo(Some(
+        r#"
+[hooks.slow]
+command = "sleep 10"
+modifies_repository = false
+timeout_seconds = 1
+
+[groups.pre-commit]
+includes = ["slow"]
+"#,
+    ));
+
+    assert_eq!(
+        exit_code(temp_dir.path(), &["run", "pre-commit", "--all-files"]),
+        Some(124)
+    );
+}
+
+#[test]
+fn test_run_max_time_cuts_off_hooks() {
+    let temp_dir = setup_repo(Some(
+        r#"
+[hooks.slow]
+command = "sleep 30"
+modifies_repository = true
+execution_type = "other"
+
+[hooks.zz-next]
+command = "true"
+modifies_repository = true
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["slow", "zz-next"]
+execution = "sequential"
+"#,
+    ));
+
+    let started = std::time::Instant::now();
+    let output = Command::new(bin_path())
+        .args(["run", "pre-commit", "--all-files", "--max-time", "1"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute command");
+    let elapsed = started.elapsed();
+
+    assert_eq!(output.status.code(), Some(124));
+    assert!(
+        elapsed < std::time::Duration::from_secs(10),
+        "Run should stop at roughly the budget, took {elapsed:?}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Run exceeded --max-time of 1s; cut off: slow, zz-next"),
+        "Should list the hook that was killed and the one never started.\nStderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_invalid_config_exits_two() {
+    let temp_dir = setup_repo(Some("[hooks.broken\ncommand = \"true\"\n"));
+
+    assert_eq!(
+        exit_code(temp_dir.path(), &["run", "pre-commit", "--all-files"]),
+        Some(2)
+    );
+    assert_eq!(exit_code(temp_dir.path(), &["validate"]), Some(2));
+    assert_eq!(
+        exit_code(temp_dir.path(), &["validate", "--format", "json"]),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_validate_unresolved_include_exits_two() {
+    let temp_dir = setup_repo(Some(
+        r#"
+[groups.pre-commit]
+includes = ["missing"]
+"#,
+    ));
+
+    assert_eq!(exit_code(temp_dir.path(), &["validate"]), Some(2));
+}
+
+#[test]
+fn test_no_config_exits_three() {
+    let temp_dir = setup_repo(None);
+
+    assert_eq!(
+        exit_code(temp_dir.path(), &["run", "pre-commit", "--all-files"]),
+        Some(3)
+    );
+    assert_eq!(exit_code(temp_dir.path(), &["run-all"]), Some(3));
+    assert_eq!(exit_code(temp_dir.path(), &["validate"]), Some(3));
+}
+
+#[test]
+fn test_event_without_hooks_still_exits_zero() {
+    let temp_dir = setup_repo(Some(
+        r#"
+[hooks.ok]
+command = "true"
+modifies_repository = false
+
+[groups.pre-push]
+includes = ["ok"]
+"#,
+    ));
+
+    // A config exists, it just has nothing for this event
+    assert_eq!(
+        exit_code(temp_dir.path(), &["run", "pre-commit", "--all-files"]),
+        Some(0)
+    );
+}
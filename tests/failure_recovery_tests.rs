@@ -307,6 +307,51 @@ execution_strategy = "sequential"
     assert!(!output.status.success(), "Command should fail on timeout");
 }
 
+#[test]
+fn test_zero_timeout_never_kills_hook() {
+    let temp_dir = setup_test_repo();
+    let repo_path = temp_dir.path();
+
+    // timeout_seconds = 0 disables the timeout entirely
+    let config = r#"
+[hooks.no-timeout]
+command = "sleep 1 && echo 'Finished without timeout'"
+modifies_repository = false
+timeout_seconds = 0
+
+[groups.pre-commit]
+includes = ["no-timeout"]
+description = "No timeout"
+"#;
+    fs::write(repo_path.join("hooks.toml"), config).unwrap();
+
+    fs::write(repo_path.join("test.txt"), "content").unwrap();
+    Command::new("git")
+        .args(["add", "test.txt"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = format!("{stdout}{stderr}");
+
+    assert!(
+        output.status.success(),
+        "Hook with no timeout should complete.\nOutput: {combined}"
+    );
+    assert!(
+        combined.contains("Finished without timeout"),
+        "Hook should run to completion.\nOutput: {combined}"
+    );
+}
+
 #[test]
 fn test_error_messages_include_hook_names() {
     let temp_dir = setup_test_repo();
@@ -666,3 +711,50 @@ description = "Would fail in real run"
         "Dry run should succeed regardless of hook failures"
     );
 }
+
+#[test]
+fn test_dry_run_check_templates_rejects_unknown_variable() {
+    let temp_dir = setup_test_repo();
+    let repo_path = temp_dir.path();
+
+    let config = r#"
+[hooks.bad-template]
+command = "touch ran.marker && echo {UNKNOWN}"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["bad-template"]
+"#;
+    fs::write(repo_path.join("hooks.toml"), config).unwrap();
+
+    fs::write(repo_path.join("test.txt"), "content").unwrap();
+    Command::new("git")
+        .args(["add", "test.txt"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    // A plain dry run neither expands nor executes the hook
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit", "--dry-run"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!repo_path.join("ran.marker").exists());
+
+    // Checking templates catches the unknown variable, still without running
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit", "--dry-run", "--check-templates"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("bad-template"), "stderr: {stderr}");
+    assert!(
+        stderr.contains("Unknown template variable: UNKNOWN"),
+        "stderr: {stderr}"
+    );
+    assert!(!repo_path.join("ran.marker").exists());
+}
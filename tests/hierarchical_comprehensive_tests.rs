@@ -3,6 +3,7 @@
 
 use git2::Repository as Git2Repository;
 use peter_hook::{
+    config::ConfigOverrides,
     git::ChangeDetectionMode,
     hooks::{WorktreeContext, resolve_hooks_hierarchically},
 };
@@ -38,6 +39,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -86,6 +88,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -110,6 +113,7 @@ fn test_hierarchical_no_config() {
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     // Should return Ok but empty groups
@@ -152,6 +156,7 @@ files = ["*.txt"]
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -186,6 +191,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -247,6 +253,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -288,6 +295,7 @@ includes = ["a", "b"]
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -322,6 +330,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     // Should return Ok with empty groups
@@ -357,6 +366,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -407,6 +417,7 @@ modifies_repository = false
         temp_dir.path(),
         temp_dir.path(),
         &worktree_context,
+        &ConfigOverrides::default(),
     );
 
     assert!(result.is_ok());
@@ -707,3 +707,50 @@ modifies_repository = false
     // Exit code should be 0 or 1 (depending on actual result)
     assert!(matches!(output.status.code(), Some(0 | 1)));
 }
+
+#[test]
+fn test_doctor_assert_managed_fails_until_hooks_are_installed() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "echo lint"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["lint"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["doctor", "--assert-managed"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "stdout: {stdout}");
+    assert!(
+        stdout.contains("pre-commit: not installed"),
+        "stdout: {stdout}"
+    );
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("install")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["doctor", "--assert-managed"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {stdout}");
+    assert!(stdout.contains("pre-commit"), "stdout: {stdout}");
+}
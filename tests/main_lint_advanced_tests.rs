@@ -271,3 +271,62 @@ files = ["*.txt"]
         );
     }
 }
+
+#[test]
+fn test_lint_passes_extra_args_to_the_hook() {
+    let temp_dir = TempDir::new().unwrap();
+    git2::Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.echo-args]
+command = "printf '[%s]' base > '{HOOK_DIR}/args.txt'"
+modifies_repository = false
+execution_type = "in-place"
+
+[hooks.other]
+command = "true"
+modifies_repository = false
+
+[groups.both]
+includes = ["echo-args", "other"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args([
+            "lint",
+            "echo-args",
+            "--",
+            "--fix",
+            "{NOT_A_TEMPLATE}",
+            "two words",
+        ])
+        .output()
+        .expect("Failed to execute");
+    assert!(
+        output.status.success(),
+        "stdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("args.txt")).unwrap(),
+        "[base][--fix][{NOT_A_TEMPLATE}][two words]"
+    );
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["lint", "both", "--", "--fix"])
+        .output()
+        .expect("Failed to execute");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("resolves to 2 hooks"),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
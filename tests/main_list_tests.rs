@@ -1,5 +1,5 @@
 #![allow(clippy::all, clippy::pedantic, clippy::nursery)]
-//! Comprehensive integration tests for list and list-worktrees commands
+//! Comprehensive integration tests for list, list-worktrees and info commands
 
 use git2::Repository as Git2Repository;
 use std::{fs, process::Command};
@@ -321,3 +321,123 @@ fn test_list_exit_code() {
 
     assert_eq!(output.status.code(), Some(0), "List should exit with 0");
 }
+
+#[test]
+fn test_list_events_annotates_file_and_message_events() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "true"
+modifies_repository = false
+
+[hooks.check-message]
+command = "true"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["lint"]
+
+[groups.commit-msg]
+includes = ["check-message"]
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(temp_dir.path().join("backend")).unwrap();
+    fs::write(
+        temp_dir.path().join("backend/hooks.toml"),
+        r#"
+[hooks.test]
+command = "true"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["test"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("list-events")
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("pre-commit [files]: backend/hooks.toml, hooks.toml"),
+        "Stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("commit-msg [message]: hooks.toml"),
+        "Stdout: {stdout}"
+    );
+    assert!(!stdout.contains("pre-push"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_info_describes_each_hook_of_an_event() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.format]
+command = "cargo fmt --check"
+description = "Check Rust formatting"
+files = ["**/*.rs"]
+modifies_repository = false
+
+[hooks.lint]
+command = ["cargo", "clippy"]
+description = "Lint Rust code"
+timeout_seconds = 600
+modifies_repository = false
+
+[groups.checks]
+includes = ["lint"]
+
+[groups.pre-commit]
+includes = ["format", "checks"]
+description = "Fast checks before each commit"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["info", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Fast checks before each commit"),
+        "Stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("format: Check Rust formatting"),
+        "Stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("Command: cargo fmt --check"),
+        "Stdout: {stdout}"
+    );
+    assert!(stdout.contains("Files: **/*.rs"), "Stdout: {stdout}");
+    assert!(stdout.contains("lint: Lint Rust code"), "Stdout: {stdout}");
+    assert!(stdout.contains("Command: cargo clippy"), "Stdout: {stdout}");
+    assert!(stdout.contains("Timeout: 600s"), "Stdout: {stdout}");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["info", "pre-push"])
+        .output()
+        .expect("Failed to execute");
+    assert!(!output.status.success());
+}
@@ -372,3 +372,39 @@ modifies_repository = false
     // Should show some execution output
     assert!(!combined.trim().is_empty());
 }
+
+#[test]
+fn test_parallel_run_shows_no_progress_when_not_a_tty() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    let mut config = String::new();
+    for i in 0..5 {
+        config.push_str(&format!(
+            "[hooks.hook{i}]\ncommand = \"echo output{i}\"\nmodifies_repository = \
+             false\nexecution_type = \"other\"\n\n"
+        ));
+    }
+    config.push_str(
+        "[groups.pre-commit]\nincludes = [\"hook0\", \"hook1\", \"hook2\", \"hook3\", \
+         \"hook4\"]\nexecution = \"parallel\"\n",
+    );
+    fs::write(temp_dir.path().join("hooks.toml"), config).unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "stdout: {stdout}\nstderr: {stderr}"
+    );
+    for stream in [&stdout, &stderr] {
+        assert!(!stream.contains(" done, "), "stream: {stream}");
+        assert!(!stream.contains('\r'), "stream: {stream}");
+    }
+}
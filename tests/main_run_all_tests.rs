@@ -0,0 +1,137 @@
+#![allow(clippy::all, clippy::pedantic, clippy::nursery)]
+//! Integration tests for the run-all command
+
+use git2::Repository as Git2Repository;
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn bin_path() -> std::path::PathBuf {
+    assert_cmd::cargo::cargo_bin("peter-hook")
+}
+
+fn write_config(dir: &std::path::Path, commit_msg_command: &str) {
+    fs::write(
+        dir.join("hooks.toml"),
+        format!(
+            r#"
+[hooks.check]
+command = "touch pre-commit.ran"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.message]
+command = "touch commit-msg.ran && {commit_msg_command}"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["check"]
+
+[groups.commit-msg]
+includes = ["message"]
+"#
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_run_all_runs_every_configured_event() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_config(temp_dir.path(), "true");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run-all", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {stdout}");
+    assert!(temp_dir.path().join("pre-commit.ran").exists());
+    assert!(temp_dir.path().join("commit-msg.ran").exists());
+    assert!(stdout.contains("[PASS] pre-commit"));
+    assert!(stdout.contains("[PASS] commit-msg"));
+    // pre-commit runs before commit-msg
+    assert!(stdout.find("=== pre-commit ===") < stdout.find("=== commit-msg ==="));
+}
+
+#[test]
+fn test_run_all_fails_if_any_event_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_config(temp_dir.path(), "false");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run-all", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "stdout: {stdout}");
+    assert!(temp_dir.path().join("pre-commit.ran").exists());
+    assert!(temp_dir.path().join("commit-msg.ran").exists());
+    assert!(stdout.contains("[PASS] pre-commit"));
+    assert!(stdout.contains("[FAIL] commit-msg"));
+}
+
+#[test]
+fn test_run_all_dry_run_executes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_config(temp_dir.path(), "false");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run-all", "--all-files", "--dry-run"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    assert!(!temp_dir.path().join("pre-commit.ran").exists());
+    assert!(!temp_dir.path().join("commit-msg.ran").exists());
+}
+
+#[test]
+fn test_run_all_includes_events_from_nested_configs() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let repo = Git2Repository::init(root).unwrap();
+    write_config(root, "true");
+
+    // Only the nested config defines pre-push
+    fs::create_dir_all(root.join("web")).unwrap();
+    fs::write(
+        root.join("web/hooks.toml"),
+        r#"
+[hooks.push-check]
+command = "touch pre-push.ran"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-push]
+includes = ["push-check"]
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("web/app.js"), "app\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run-all", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {stdout}");
+    assert!(stdout.contains("[PASS] pre-commit"), "stdout: {stdout}");
+    assert!(stdout.contains("[PASS] pre-push"), "stdout: {stdout}");
+    assert!(root.join("web/pre-push.ran").exists());
+}
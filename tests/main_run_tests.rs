@@ -687,3 +687,3346 @@ modifies_repository = false
     // May return non-zero on hook failure
     assert!(output.status.code().is_some());
 }
+
+#[test]
+fn test_run_quiet_shows_only_failing_hook_output() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.passing]
+command = "echo passing-hook-output"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.failing]
+command = "echo failing-hook-error >&2; exit 1"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["passing", "failing"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--quiet"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        !combined.contains("passing-hook-output"),
+        "Passing hook output should be suppressed.\nOutput: {combined}"
+    );
+    assert!(
+        combined.contains("failing-hook-error"),
+        "Failing hook stderr should be shown.\nOutput: {combined}"
+    );
+    assert!(
+        combined.contains("1 passed, 1 failed"),
+        "Should print a one-line summary.\nOutput: {combined}"
+    );
+}
+
+#[test]
+fn test_run_trace_with_json_log_format() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.quiet-hook]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["quiet-hook"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args([
+            "--trace",
+            "--log-format",
+            "json",
+            "run",
+            "pre-commit",
+            "--all-files",
+        ])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<_> = stderr.lines().filter(|l| !l.trim().is_empty()).collect();
+    assert!(!lines.is_empty(), "Trace should emit events");
+
+    for line in lines {
+        let event: serde_json::Value =
+            serde_json::from_str(line).unwrap_or_else(|e| panic!("Line is not JSON ({e}): {line}"));
+        assert_eq!(event["level"], "trace");
+        assert!(event["message"].is_string());
+        assert!(
+            event["target"]
+                .as_str()
+                .is_some_and(|t| t.starts_with("peter_hook"))
+        );
+    }
+}
+
+#[test]
+fn test_run_debug_with_json_log_format_emits_only_json() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.shell-hook]
+command = "echo {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.args-hook]
+command = ["echo", "args"]
+modifies_repository = false
+files = ["*.txt"]
+
+[groups.pre-commit]
+includes = ["shell-hook", "args-hook"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+    // Even with colors forced, debug output must not bypass the JSON format
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .env("CLICOLOR_FORCE", "1")
+        .args([
+            "--debug",
+            "--log-format",
+            "json",
+            "run",
+            "pre-commit",
+            "--all-files",
+        ])
+        .output()
+        .expect("Failed to execute");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {stderr}");
+    let lines: Vec<_> = stderr.lines().filter(|l| !l.trim().is_empty()).collect();
+    assert!(
+        lines.iter().any(|line| line.contains("Executing hook")),
+        "Debug should emit hook events: {stderr}"
+    );
+    for line in lines {
+        let event: serde_json::Value =
+            serde_json::from_str(line).unwrap_or_else(|e| panic!("Line is not JSON ({e}): {line}"));
+        assert!(event["message"].is_string());
+    }
+}
+
+#[test]
+fn test_run_verbosity_levels_gate_per_file_trace() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.check]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["check"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("tracked.txt"), "content").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("tracked.txt")).unwrap();
+    index.write().unwrap();
+
+    let run_with = |flag: &str| {
+        let output = Command::new(bin_path())
+            .current_dir(temp_dir.path())
+            .args([flag, "run", "pre-commit"])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+
+    let per_file_line = "tracked.txt -> ";
+
+    let phases = run_with("-v");
+    assert!(phases.contains("Hierarchical Resolution Started"));
+    assert!(
+        !phases.contains(per_file_line),
+        "-v should hide per-file lines"
+    );
+
+    let details = run_with("-vv");
+    assert!(details.contains("Resolving hooks for config"));
+    assert!(
+        !details.contains(per_file_line),
+        "-vv should hide per-file lines"
+    );
+
+    let files = run_with("-vvv");
+    assert!(
+        files.contains(per_file_line),
+        "-vvv should show per-file mapping.\nStderr: {files}"
+    );
+}
+
+#[test]
+fn test_run_timings_lists_slowest_hook_first() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.fast]
+command = "sleep 0.1"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.slow]
+command = "sleep 1"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.after-fast]
+command = "sleep 1.5"
+modifies_repository = false
+execution_type = "other"
+depends_on = ["fast"]
+
+[groups.pre-commit]
+includes = ["fast", "slow", "after-fast"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--timings"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report = &stdout[stdout.find("Hook Timings").expect("Missing timing report")..];
+
+    let after_fast = report.find("after-fast").unwrap();
+    let slow = report.find("slow").unwrap();
+    let fast = report.find("  fast ").unwrap();
+    assert!(
+        after_fast < slow && slow < fast,
+        "Hooks should be sorted slowest first.\nReport: {report}"
+    );
+    assert!(report.contains("Total: "));
+    assert!(
+        report.contains("Critical path: fast -> after-fast"),
+        "Longest depends_on chain should be the critical path.\nReport: {report}"
+    );
+}
+
+#[test]
+fn test_run_path_limits_hooks_to_one_subproject() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    for project in ["api", "web"] {
+        let dir = root.join("services").join(project);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("hooks.toml"),
+            format!(
+                r#"
+[hooks.check-{project}]
+command = "ls > ran-{project}.txt"
+modifies_repository = false
+execution_type = "other"
+files = ["**/*.rs"]
+
+[groups.pre-commit]
+includes = ["check-{project}"]
+"#
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    }
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--path", "services/api"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(root.join("services/api/ran-api.txt").exists());
+    assert!(
+        !root.join("services/web/ran-web.txt").exists(),
+        "Hooks outside --path must not run"
+    );
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--path", "../elsewhere"])
+        .output()
+        .expect("Failed to execute");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_dry_run_reports_change_detection() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "true"
+modifies_repository = false
+files = ["*.txt"]
+
+[groups.pre-commit]
+includes = ["lint"]
+"#,
+    )
+    .unwrap();
+    for index in 0..12 {
+        fs::write(temp_dir.path().join(format!("file{index:02}.txt")), "x\n").unwrap();
+    }
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(temp_dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--dry-run"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Change detection: staged changes"),
+        "Stdout: {stdout}"
+    );
+    // hooks.toml and the 12 text files, the first 10 listed
+    assert!(
+        stdout.contains("Detected 13 changed file(s)"),
+        "Stdout: {stdout}"
+    );
+    assert!(stdout.contains("\n  file00.txt\n"), "Stdout: {stdout}");
+    assert!(stdout.contains("  ... and 3 more"), "Stdout: {stdout}");
+    let detection = stdout.find("Change detection").unwrap();
+    let hooks = stdout.find("lint").unwrap();
+    assert!(detection < hooks, "Detection is reported before the hooks");
+}
+
+#[test]
+fn test_pre_push_stdin_is_available_to_hooks() {
+    use std::io::Write as _;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(root)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    fs::write(
+        root.join("hooks.toml"),
+        r#"
+[hooks.save-refs]
+command = "cp '{GIT_STDIN_FILE}' saved-refs.txt"
+modifies_repository = false
+execution_type = "other"
+files = ["a.txt"]
+
+[hooks.piped-refs]
+command = "cat > piped-refs.txt"
+modifies_repository = false
+execution_type = "other"
+files = ["a.txt"]
+stdin_from_git = true
+
+[groups.pre-push]
+includes = ["save-refs", "piped-refs"]
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "initial"]);
+    let head = git(&["rev-parse", "HEAD"]);
+    let zeros = "0".repeat(40);
+    let refs = format!(
+        "refs/heads/main {head} refs/heads/main {zeros}\nrefs/tags/v1 {head} refs/tags/v1 \
+         {zeros}\n",
+        head = head.trim()
+    );
+
+    let mut child = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-push", "origin", "https://example.com/repo.git"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(refs.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // The refs still drive change detection: a.txt is in the pushed range
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Failed to parse pre-push stdin"));
+    assert_eq!(
+        fs::read_to_string(root.join("saved-refs.txt")).unwrap(),
+        refs
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("piped-refs.txt")).unwrap(),
+        refs
+    );
+}
+
+#[test]
+fn test_max_warnings_fails_hooks_above_the_threshold() {
+    let run_with_max = |max_warnings: usize| {
+        let temp_dir = TempDir::new().unwrap();
+        Git2Repository::init(temp_dir.path()).unwrap();
+        fs::write(
+            temp_dir.path().join("hooks.toml"),
+            format!(
+                r#"
+[hooks.lint]
+command = "printf 'warning: a\nwarning: b\nok\n'; echo 'warning: c' >&2"
+modifies_repository = false
+execution_type = "other"
+max_warnings = {max_warnings}
+warning_pattern = "^warning:"
+
+[groups.pre-commit]
+includes = ["lint"]
+"#
+            ),
+        )
+        .unwrap();
+
+        Command::new(bin_path())
+            .current_dir(temp_dir.path())
+            .args(["run", "pre-commit", "--all-files"])
+            .output()
+            .expect("Failed to execute")
+    };
+
+    let output = run_with_max(2);
+    assert!(
+        !output.status.success(),
+        "3 warnings exceed max_warnings = 2"
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("3 warnings exceed max_warnings = 2"),
+        "Stdout: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let output = run_with_max(5);
+    assert!(
+        output.status.success(),
+        "3 warnings are within max_warnings = 5.\nStdout: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn test_autofix_stages_rewritten_files() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    // The formatter exits 1 whenever it runs, like a formatter reporting that
+    // it fixed something; the paired check decides the outcome
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.format]
+command = "printf 'formatted\n' > a.txt; exit 1"
+modifies_repository = true
+execution_type = "other"
+files = ["*.txt"]
+autofix = true
+autofix_check = "format-check"
+
+[hooks.format-check]
+command = "grep -qx formatted a.txt"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["format"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("a.txt"), "messy\n").unwrap();
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to run git")
+    };
+    assert!(git(&["add", "a.txt"]).status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "The check passes after the fix.\nStdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let staged = git(&["show", ":a.txt"]);
+    assert_eq!(String::from_utf8_lossy(&staged.stdout), "formatted\n");
+    let unstaged = git(&["diff", "--name-only"]);
+    assert!(
+        unstaged.stdout.is_empty(),
+        "The fix should not be left unstaged"
+    );
+}
+
+#[test]
+fn test_format_github_annotates_failing_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "echo 'src/lib.rs:3:5: unused variable'; exit 1"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.ok]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["lint", "ok"]
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "fn main() {}\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--format", "github"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("::error title=lint::lint failed (exit code 1)"),
+        "Should annotate the failing hook.\nStdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("::error file=src/lib.rs,line=3,col=5,title=lint::unused variable"),
+        "Should annotate the location from the hook's output.\nStdout: {stdout}"
+    );
+    assert!(!stdout.contains("title=ok"), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Hook Execution Summary"),
+        "The normal summary is still printed"
+    );
+
+    // Without --format github there are no workflow commands
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("::error"));
+}
+
+#[test]
+fn test_config_profile_adds_hook_to_group() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "echo linting"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["lint"]
+
+[profiles.ci.hooks.audit]
+command = "echo auditing"
+modifies_repository = false
+execution_type = "other"
+
+[profiles.ci.groups.pre-commit]
+includes = ["lint", "audit"]
+"#,
+    )
+    .unwrap();
+
+    let stdout = |args: &[&str], profile_env: Option<&str>| {
+        let mut command = Command::new(bin_path());
+        command
+            .current_dir(temp_dir.path())
+            .env_remove("PETER_HOOK_PROFILE")
+            .args(args)
+            .args(["run", "pre-commit", "--all-files"]);
+        if let Some(profile) = profile_env {
+            command.env("PETER_HOOK_PROFILE", profile);
+        }
+        let output = command.output().expect("Failed to execute");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let base = stdout(&[], None);
+    assert!(base.contains("linting"), "Stdout: {base}");
+    assert!(!base.contains("auditing"), "Stdout: {base}");
+
+    let ci = stdout(&["--profile", "ci"], None);
+    assert!(ci.contains("linting"), "Stdout: {ci}");
+    assert!(ci.contains("auditing"), "Stdout: {ci}");
+
+    let from_env = stdout(&[], Some("ci"));
+    assert!(from_env.contains("auditing"), "Stdout: {from_env}");
+}
+
+#[test]
+fn test_no_color_disables_ansi_codes() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.ok]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["ok"]
+"#,
+    )
+    .unwrap();
+
+    let stdout = |env: &[(&str, &str)], args: &[&str]| {
+        let mut command = Command::new(bin_path());
+        command
+            .current_dir(temp_dir.path())
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .args(["run", "pre-commit", "--all-files"])
+            .args(args);
+        for (key, value) in env {
+            command.env(key, value);
+        }
+        let output = command.output().expect("Failed to execute");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // CLICOLOR_FORCE takes the terminal path even though stdout is a pipe
+    assert!(stdout(&[("CLICOLOR_FORCE", "1")], &[]).contains("\x1b["));
+    assert!(!stdout(&[], &[]).contains("\x1b["));
+
+    // NO_COLOR wins over the forced terminal path
+    let plain = stdout(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")], &[]);
+    assert!(!plain.contains("\x1b["), "Stdout: {plain}");
+
+    // An explicit --color beats both variables
+    assert!(stdout(&[("NO_COLOR", "1")], &["--color", "always"]).contains("\x1b["));
+    let plain = stdout(&[("CLICOLOR_FORCE", "1")], &["--color", "never"]);
+    assert!(!plain.contains("\x1b["), "Stdout: {plain}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_invoked_as_hook_name_runs_that_event() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.marker]
+command = "echo ran > marker.txt"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["marker"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+
+    // Like a .git/hooks/pre-commit symlink to the binary
+    let bin_dir = TempDir::new().unwrap();
+    let hook = bin_dir.path().join("pre-commit");
+    std::os::unix::fs::symlink(bin_path(), &hook).unwrap();
+
+    let output = Command::new(&hook)
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("marker.txt")).unwrap(),
+        "ran\n",
+        "Invoking the binary as pre-commit should run the pre-commit group"
+    );
+}
+
+#[test]
+fn test_run_profile_reports_isolated_durations() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.first]
+command = "sleep 0.1"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.second]
+command = "sleep 0.2"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["first", "second"]
+"#,
+    )
+    .unwrap();
+
+    let profile = |args: &[&str]| {
+        let output = Command::new(bin_path())
+            .current_dir(temp_dir.path())
+            .args(["run", "pre-commit", "--all-files", "--profile-hooks"])
+            .args(args)
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let start = stdout.find("Hook Profile").expect("Missing profile report");
+        stdout[start..].to_string()
+    };
+
+    let report = profile(&[]);
+    for hook in ["first", "second"] {
+        let line = report
+            .lines()
+            .find(|line| line.trim_start().starts_with(hook))
+            .unwrap_or_else(|| panic!("{hook} missing from profile.\nReport: {report}"));
+        let seconds: f64 = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|duration| duration.trim_end_matches('s').parse().ok())
+            .unwrap_or_else(|| panic!("No duration for {hook}.\nReport: {report}"));
+        assert!(
+            seconds > 0.0,
+            "{hook} should take some time.\nReport: {report}"
+        );
+    }
+    assert!(
+        report.find("second").unwrap() < report.find("first").unwrap(),
+        "Hooks should be sorted slowest first.\nReport: {report}"
+    );
+    assert!(report.contains("Total isolated: "));
+}
+
+#[test]
+fn test_run_profile_with_timings_runs_hooks_once() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.count]
+command = "echo ran >> runs.log"
+modifies_repository = true
+execution_type = "other"
+
+[hooks.other]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["count", "other"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args([
+            "run",
+            "pre-commit",
+            "--all-files",
+            "--profile-hooks",
+            "--timings",
+        ])
+        .output()
+        .expect("Failed to execute");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Hook Profile"), "stdout: {stdout}");
+
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("runs.log")).unwrap(),
+        "ran\n",
+        "Profiling with --timings must not run the hooks a second time"
+    );
+}
+
+#[test]
+fn test_run_always_with_all_files_gets_empty_changed_files() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.list-changed]
+command = "echo \"files=[{CHANGED_FILES}]\"; echo \"lines=$(cat '{CHANGED_FILES_FILE}' | wc -l | tr -d ' ')\""
+modifies_repository = false
+execution_type = "other"
+run_always = true
+
+[groups.pre-commit]
+includes = ["list-changed"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "Hook should succeed with an empty changed-files list.\nStdout: {stdout}\nStderr: {stderr}"
+    );
+    assert!(stdout.contains("files=[]"), "Stdout: {stdout}");
+    assert!(stdout.contains("lines=0"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_check_reports_changes_and_restores_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.formatter]
+command = "printf 'formatted\n' > code.txt"
+modifies_repository = true
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["formatter"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("code.txt"), "unformatted\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("code.txt")).unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--check"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !output.status.success(),
+        "Check should fail when hooks would modify files.\nStdout: {stdout}"
+    );
+    assert!(stdout.contains("would modify 1 file"), "Stdout: {stdout}");
+    assert!(stdout.contains("modified: code.txt"), "Stdout: {stdout}");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("code.txt")).unwrap(),
+        "unformatted\n",
+        "Working tree should be left untouched"
+    );
+}
+
+#[test]
+fn test_run_check_restores_changes_hooks_stage() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.formatter]
+command = "printf 'formatted\n' > code.txt && git add code.txt"
+modifies_repository = true
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["formatter"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("code.txt"), "unformatted\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("code.txt")).unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--check"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("modified: code.txt"), "Stdout: {stdout}");
+
+    let mut index = repo.index().unwrap();
+    index.read(true).unwrap();
+    let entry = index.get_path(std::path::Path::new("code.txt"), 0).unwrap();
+    let blob = repo.find_blob(entry.id).unwrap();
+    assert_eq!(
+        blob.content(),
+        b"unformatted\n",
+        "The index should be left untouched"
+    );
+}
+
+#[test]
+fn test_run_matches_extensionless_script_by_shebang() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.shellcheck]
+command = "echo checking: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["*.sh"]
+content_patterns = ["^#!.*\\bbash\\b"]
+
+[groups.pre-commit]
+includes = ["shellcheck"]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("deploy"),
+        "#!/usr/bin/env bash\necho deploying\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("NOTES"), "plain text\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("deploy")).unwrap();
+    index.add_path(std::path::Path::new("NOTES")).unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("checking: deploy"),
+        "Extensionless bash script should match by shebang.\nStdout: {stdout}"
+    );
+    assert!(!stdout.contains("NOTES"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_output_dir_writes_per_hook_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.greet]
+command = "echo hello-out; echo hello-err >&2"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.fail]
+command = "echo broken; exit 3"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["greet", "fail"]
+"#,
+    )
+    .unwrap();
+
+    let output_dir = temp_dir.path().join("artifacts");
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--output-dir"])
+        .arg(&output_dir)
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Stdout: {stdout}");
+    // On-screen output is still produced
+    assert!(stdout.contains("broken"), "Stdout: {stdout}");
+
+    let group_dir = output_dir.join("pre-commit");
+    assert_eq!(
+        fs::read_to_string(group_dir.join("greet.stdout")).unwrap(),
+        "hello-out\n"
+    );
+    assert_eq!(
+        fs::read_to_string(group_dir.join("greet.stderr")).unwrap(),
+        "hello-err\n"
+    );
+    assert_eq!(
+        fs::read_to_string(group_dir.join("fail.stdout")).unwrap(),
+        "broken\n"
+    );
+    assert_eq!(
+        fs::read_to_string(group_dir.join("fail.stderr")).unwrap(),
+        ""
+    );
+
+    let meta: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(group_dir.join("greet.meta.json")).unwrap())
+            .unwrap();
+    assert_eq!(meta["hook"], "greet");
+    assert_eq!(meta["exit_code"], 0);
+    assert_eq!(meta["success"], true);
+    assert!(meta["duration_secs"].is_number());
+
+    let meta: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(group_dir.join("fail.meta.json")).unwrap())
+            .unwrap();
+    assert_eq!(meta["exit_code"], 3);
+    assert_eq!(meta["success"], false);
+}
+
+#[test]
+fn test_run_output_dir_keeps_colliding_hook_names_apart() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks."lint/a"]
+command = "echo slash"
+modifies_repository = false
+execution_type = "other"
+
+[hooks."lint:a"]
+command = "echo colon"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["lint/a", "lint:a"]
+"#,
+    )
+    .unwrap();
+
+    let output_dir = temp_dir.path().join("artifacts");
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--output-dir"])
+        .arg(&output_dir)
+        .output()
+        .expect("Failed to execute");
+    assert!(
+        output.status.success(),
+        "Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Both names sanitize to `lint_a`; the later one gets a suffix
+    let group_dir = output_dir.join("pre-commit");
+    assert_eq!(
+        fs::read_to_string(group_dir.join("lint_a.stdout")).unwrap(),
+        "slash\n"
+    );
+    assert_eq!(
+        fs::read_to_string(group_dir.join("lint_a-2.stdout")).unwrap(),
+        "colon\n"
+    );
+    let meta: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(group_dir.join("lint_a-2.meta.json")).unwrap())
+            .unwrap();
+    assert_eq!(meta["hook"], "lint:a");
+}
+
+#[test]
+fn test_run_output_dir_nests_by_config_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    let hook = |message: &str| {
+        format!(
+            r#"
+[hooks.lint]
+command = "echo {message}"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["lint"]
+"#
+        )
+    };
+    fs::write(temp_dir.path().join("hooks.toml"), hook("root")).unwrap();
+    fs::write(temp_dir.path().join("root.txt"), "root").unwrap();
+    let sub = temp_dir.path().join("crates/sub");
+    fs::create_dir_all(&sub).unwrap();
+    fs::write(sub.join("hooks.toml"), hook("nested")).unwrap();
+    fs::write(sub.join("lib.txt"), "lib").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+
+    let output_dir = temp_dir.path().join("artifacts");
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--output-dir"])
+        .arg(&output_dir)
+        .output()
+        .expect("Failed to execute");
+    assert!(
+        output.status.success(),
+        "Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let group_dir = output_dir.join("pre-commit");
+    assert_eq!(
+        fs::read_to_string(group_dir.join("lint.stdout")).unwrap(),
+        "root\n"
+    );
+    assert_eq!(
+        fs::read_to_string(group_dir.join("crates/sub/lint.stdout")).unwrap(),
+        "nested\n"
+    );
+}
+
+#[test]
+fn test_run_interactive_errors_without_tty() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.format]
+command = "touch formatted.marker"
+modifies_repository = true
+execution_type = "other"
+
+[hooks.lint]
+command = "touch linted.marker"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["format", "lint"]
+"#,
+    )
+    .unwrap();
+
+    // Piped stdin is not a terminal, so the prompt must not wait for input
+    let mut child = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--interactive"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute");
+    // The process may already have exited with the error, closing the pipe
+    let _ = std::io::Write::write_all(child.stdin.as_mut().unwrap(), b"n\n");
+    let output = child.wait_with_output().unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("--interactive requires a terminal"),
+        "Stderr: {stderr}"
+    );
+    assert!(!temp_dir.path().join("formatted.marker").exists());
+    assert!(!temp_dir.path().join("linted.marker").exists());
+}
+
+#[test]
+fn test_run_skips_group_when_group_files_do_not_match() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.eslint]
+command = "echo ran-eslint"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.prettier]
+command = "echo ran-prettier"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.cargo-check]
+command = "echo ran-cargo-check"
+modifies_repository = false
+execution_type = "other"
+
+[groups.frontend]
+includes = ["eslint", "prettier"]
+files = ["frontend/**"]
+
+[groups.pre-commit]
+includes = ["frontend", "cargo-check"]
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(temp_dir.path().join("backend")).unwrap();
+    fs::write(temp_dir.path().join("backend/main.rs"), "fn main() {}\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index
+        .add_path(std::path::Path::new("backend/main.rs"))
+        .unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("ran-cargo-check"), "Stdout: {stdout}");
+    assert!(
+        !stdout.contains("ran-eslint") && !stdout.contains("ran-prettier"),
+        "Frontend group should not run for backend-only changes.\nStdout: {stdout}"
+    );
+}
+
+fn write_lint_and_test_config(dir: &std::path::Path) {
+    fs::write(
+        dir.join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "echo ran-lint"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.test]
+command = "echo ran-test"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["lint", "test"]
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_run_peter_hook_skip_skips_named_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+    write_lint_and_test_config(temp_dir.path());
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .env("PETER_HOOK_SKIP", "lint")
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Skipping hooks (PETER_HOOK_SKIP): lint"),
+        "Skip should be reported.\nStdout: {stdout}"
+    );
+    assert!(!stdout.contains("ran-lint"), "Stdout: {stdout}");
+    assert!(stdout.contains("ran-test"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_peter_hook_disable_skips_everything() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+    write_lint_and_test_config(temp_dir.path());
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .env("PETER_HOOK_DISABLE", "1")
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("PETER_HOOK_DISABLE is set"),
+        "Stdout: {stdout}"
+    );
+    assert!(!stdout.contains("ran-lint") && !stdout.contains("ran-test"));
+}
+
+#[test]
+fn test_commit_msg_file_template_validates_message() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.conventional-commit]
+command = "grep -qE '^(feat|fix|chore)(\\([a-z-]+\\))?: .+' {COMMIT_MSG_FILE}"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.echo-message]
+command = ["echo", "message was: {COMMIT_MSG}"]
+modifies_repository = false
+execution_type = "other"
+
+[groups.commit-msg]
+includes = ["conventional-commit", "echo-message"]
+"#,
+    )
+    .unwrap();
+
+    let run_with_message = |message: &str| {
+        fs::write(temp_dir.path().join("COMMIT_EDITMSG"), message).unwrap();
+        Command::new(bin_path())
+            .current_dir(temp_dir.path())
+            .args(["run", "commit-msg", "COMMIT_EDITMSG"])
+            .output()
+            .expect("Failed to execute")
+    };
+
+    let output = run_with_message("fix(parser): handle {braces}\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "Good message should pass: {stdout}"
+    );
+    assert!(
+        stdout.contains("message was: fix(parser): handle {braces}"),
+        "Stdout: {stdout}"
+    );
+
+    let output = run_with_message("updated some stuff\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !output.status.success(),
+        "Bad message should fail: {stdout}"
+    );
+    assert!(stdout.contains("conventional-commit"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_files_relative_to_config_dir_scopes_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("other")).unwrap();
+    fs::write(
+        temp_dir.path().join("src/hooks.toml"),
+        r#"
+files_relative_to = "config_dir"
+
+[hooks.rustfmt]
+command = "echo checking: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["*.rs"]
+
+[groups.pre-commit]
+includes = ["rustfmt"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+    fs::write(temp_dir.path().join("other/b.rs"), "fn b() {}\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("src/a.rs")).unwrap();
+    index.add_path(std::path::Path::new("other/b.rs")).unwrap();
+    index.write().unwrap();
+
+    // --config sends every changed file to src/hooks.toml, including other/b.rs
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["--config", "src/hooks.toml", "run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("checking: a.rs"), "Stdout: {stdout}");
+    assert!(
+        !stdout.contains("b.rs"),
+        "Files outside the config directory must not match.\nStdout: {stdout}"
+    );
+}
+
+#[test]
+fn test_relative_paths_rewrites_changed_files_for_workdir() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("other")).unwrap();
+    fs::write(
+        temp_dir.path().join("src/hooks.toml"),
+        r#"
+[hooks.rustfmt]
+command = "echo checking: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["**/*.rs"]
+
+[groups.pre-commit]
+includes = ["rustfmt"]
+relative_paths = true
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+    fs::write(temp_dir.path().join("other/b.rs"), "fn b() {}\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("src/a.rs")).unwrap();
+    index.add_path(std::path::Path::new("other/b.rs")).unwrap();
+    index.write().unwrap();
+
+    // The hook runs in src/, so other/b.rs is reached through ..
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["--config", "src/hooks.toml", "run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("checking:"), "Stdout: {stdout}");
+    assert!(stdout.contains(" a.rs"), "Stdout: {stdout}");
+    assert!(stdout.contains("../other/b.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("src/a.rs"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_hook_runs_single_hook_with_staged_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.format]
+command = "echo formatting: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["*.rs"]
+
+[hooks.lint]
+command = "echo linting"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["format", "lint"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("staged.rs"), "fn a() {}\n").unwrap();
+    fs::write(temp_dir.path().join("unstaged.rs"), "fn b() {}\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("staged.rs")).unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--hook", "format"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("formatting: staged.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("unstaged.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("linting"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_allow_failure_hook_does_not_fail_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.spellcheck]
+command = "echo found typos && exit 1"
+modifies_repository = false
+run_always = true
+allow_failure = true
+
+[hooks.build]
+command = "echo building"
+modifies_repository = false
+run_always = true
+
+[groups.pre-commit]
+includes = ["spellcheck", "build"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("found typos"), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("[FAIL (allowed)] spellcheck (from hooks.toml): exit code 1"),
+        "Stdout: {stdout}"
+    );
+    assert!(stdout.contains("[PASS] build"), "Stdout: {stdout}");
+    assert!(stdout.contains("Overall: SUCCESS"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_no_git_with_repo_root_runs_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.task]
+command = "echo task ran in {REPO_ROOT}"
+modifies_repository = false
+run_always = true
+
+[groups.pre-commit]
+includes = ["task"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--no-git", "--repo-root", "."])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "Stdout: {stdout}\nStderr: {stderr}"
+    );
+    assert!(stdout.contains("task ran in"), "Stdout: {stdout}");
+    assert!(stdout.contains("[PASS] task"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_stash_hides_unstaged_changes_from_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "user.name", "Test"]);
+    git(&["config", "commit.gpgsign", "false"]);
+
+    fs::write(
+        root.join("hooks.toml"),
+        r#"
+[hooks.show]
+command = "cat notes.txt"
+modifies_repository = false
+execution_type = "in-place"
+files = ["notes.txt"]
+
+[groups.pre-commit]
+includes = ["show"]
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("notes.txt"), "committed\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+
+    // Partially staged: the second line is staged, the third is not
+    fs::write(root.join("notes.txt"), "committed\nstaged line\n").unwrap();
+    git(&["add", "notes.txt"]);
+    fs::write(
+        root.join("notes.txt"),
+        "committed\nstaged line\nunstaged line\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--stash"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("staged line"), "Stdout: {stdout}");
+    assert!(
+        !stdout.contains("unstaged line"),
+        "Hooks must only see staged content.\nStdout: {stdout}"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("notes.txt")).unwrap(),
+        "committed\nstaged line\nunstaged line\n"
+    );
+}
+
+#[test]
+fn test_failure_output_names_source_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    for (dir, command) in [("api", "true"), ("web", "exit 3")] {
+        fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        fs::write(
+            temp_dir.path().join(dir).join("hooks.toml"),
+            format!(
+                r#"
+[hooks.lint]
+command = "{command}"
+modifies_repository = false
+execution_type = "in-place"
+
+[groups.pre-commit]
+includes = ["lint"]
+"#
+            ),
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join(dir).join("main.txt"), "x\n").unwrap();
+    }
+
+    let mut index = repo.index().unwrap();
+    index
+        .add_path(std::path::Path::new("api/main.txt"))
+        .unwrap();
+    index
+        .add_path(std::path::Path::new("web/main.txt"))
+        .unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("(from web/hooks.toml)"), "Stdout: {stdout}");
+    assert!(
+        !stdout.contains("(from api/hooks.toml)"),
+        "Stdout: {stdout}"
+    );
+}
+
+#[test]
+fn test_hook_output_truncated_at_max_output_bytes() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.noisy]
+command = "head -c 10000 /dev/zero | tr '\\0' x"
+modifies_repository = false
+run_always = true
+max_output_bytes = 100
+
+[groups.pre-commit]
+includes = ["noisy"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("[... 9900 bytes truncated ...]"),
+        "Stdout: {stdout}"
+    );
+    assert!(!stdout.contains(&"x".repeat(51)), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_jobs_runs_config_groups_concurrently() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    for dir in ["api", "web"] {
+        fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        fs::write(
+            temp_dir.path().join(dir).join("hooks.toml"),
+            r#"
+[hooks.slow]
+command = "sleep 2"
+modifies_repository = false
+execution_type = "in-place"
+
+[groups.pre-commit]
+includes = ["slow"]
+"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join(dir).join("main.txt"), "x\n").unwrap();
+    }
+
+    let mut index = repo.index().unwrap();
+    index
+        .add_path(std::path::Path::new("api/main.txt"))
+        .unwrap();
+    index
+        .add_path(std::path::Path::new("web/main.txt"))
+        .unwrap();
+    index.write().unwrap();
+
+    let started = std::time::Instant::now();
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--jobs", "2"])
+        .output()
+        .expect("Failed to execute");
+    let elapsed = started.elapsed();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert_eq!(stdout.matches("[PASS]").count(), 2, "Stdout: {stdout}");
+    // Two 2-second hooks in parallel should take well under the 4-second sum
+    assert!(
+        elapsed < std::time::Duration::from_millis(3500),
+        "Took {elapsed:?}"
+    );
+}
+
+#[test]
+fn test_dry_run_and_summary_show_descriptions() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.fmt-check]
+command = "echo formatted"
+description = "Check that Rust code is formatted"
+modifies_repository = false
+run_always = true
+
+[groups.pre-commit]
+includes = ["fmt-check"]
+description = "Fast checks before every commit"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--dry-run"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    let group = stdout
+        .find("Fast checks before every commit")
+        .unwrap_or_else(|| panic!("Stdout: {stdout}"));
+    let hook = stdout
+        .find("Check that Rust code is formatted")
+        .unwrap_or_else(|| panic!("Stdout: {stdout}"));
+    assert!(
+        group < hook,
+        "Group description should come first: {stdout}"
+    );
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Fast checks before every commit"),
+        "Stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("Check that Rust code is formatted"),
+        "Stdout: {stdout}"
+    );
+}
+
+#[test]
+fn test_config_coverage_flags_report_uncovered_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("app")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+    fs::write(
+        temp_dir.path().join("app/hooks.toml"),
+        r#"
+[hooks.pre-commit]
+command = "echo app checks"
+modifies_repository = false
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("app/main.rs"), "fn main() {}\n").unwrap();
+    fs::write(temp_dir.path().join("docs/guide.md"), "# Guide\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("app/main.rs")).unwrap();
+    index
+        .add_path(std::path::Path::new("docs/guide.md"))
+        .unwrap();
+    index.write().unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--require-config-coverage"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "Stdout: {stdout}");
+    assert!(
+        stderr.contains("1 changed file(s) not covered by any hooks.toml"),
+        "Stderr: {stderr}"
+    );
+    assert!(stderr.contains("docs/guide.md"), "Stderr: {stderr}");
+    assert!(!stderr.contains("app/main.rs"), "Stderr: {stderr}");
+    assert!(!stdout.contains("app checks"), "Stdout: {stdout}");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--warn-uncovered"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stderr.contains("Warning:"), "Stderr: {stderr}");
+    assert!(stderr.contains("docs/guide.md"), "Stderr: {stderr}");
+    assert!(stdout.contains("app checks"), "Stdout: {stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_nice_hook_runs_at_lower_priority() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.docs]
+command = "echo niceness=$(nice)"
+modifies_repository = false
+run_always = true
+nice = 10
+
+[groups.pre-commit]
+includes = ["docs"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    let niceness: i32 = stdout
+        .split("niceness=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("Stdout: {stdout}"));
+    assert!(niceness >= 10, "Stdout: {stdout}");
+}
+
+#[test]
+fn test_all_files_filters_tracked_files_by_pattern() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.rust-check]
+command = "echo checking: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["**/*.rs"]
+
+[groups.pre-commit]
+includes = ["rust-check"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("notes.txt"), "notes\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("notes.txt")).unwrap();
+    index.write().unwrap();
+
+    // Only a tracked .txt file: the hook is filtered out
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(!stdout.contains("checking:"), "Stdout: {stdout}");
+
+    // An untracked .rs file does not count either
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "fn a() {}\n").unwrap();
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("checking:"), "Stdout: {stdout}");
+
+    // Once tracked, the hook runs and receives the file
+    index.add_path(std::path::Path::new("src/lib.rs")).unwrap();
+    index.write().unwrap();
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("checking: src/lib.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("notes.txt"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_steps_command_stops_at_failing_step() {
+    let temp_dir = TempDir::new().unwrap();
+    let _repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.check]
+command = { steps = ["echo first step in {HOOK_DIR}", "echo second step && exit 3", "echo third step"] }
+modifies_repository = false
+run_always = true
+execution_type = "in-place"
+
+[groups.pre-commit]
+includes = ["check"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("first step in /"), "Stdout: {stdout}");
+    assert!(stdout.contains("second step"), "Stdout: {stdout}");
+    assert!(!stdout.contains("third step"), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Step 2 of 3 failed: echo second step && exit 3"),
+        "Stdout: {stdout}"
+    );
+    assert!(stdout.contains("exit code 3"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_require_clean_worktree_refuses_unstaged_target_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "user.name", "Test"]);
+
+    fs::write(
+        root.join("hooks.toml"),
+        r#"
+[hooks.format]
+command = "echo formatting"
+modifies_repository = true
+execution_type = "in-place"
+files = ["*.rs"]
+require_clean_worktree = true
+
+[groups.pre-commit]
+includes = ["format"]
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("lib.rs"), "fn a() {}\n").unwrap();
+    fs::write(root.join("notes.txt"), "notes\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+
+    // Staged change with more unstaged work on top of it
+    fs::write(root.join("lib.rs"), "fn b() {}\n").unwrap();
+    git(&["add", "lib.rs"]);
+    fs::write(root.join("lib.rs"), "fn c() {}\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Stdout: {stdout}");
+    assert!(!stdout.contains("formatting"), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Refusing to run (require_clean_worktree): unstaged changes to lib.rs"),
+        "Stdout: {stdout}"
+    );
+
+    // Unstaged changes outside the hook's files do not block it
+    git(&["add", "lib.rs"]);
+    fs::write(root.join("notes.txt"), "more notes\n").unwrap();
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("formatting"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_incremental_run_only_sees_files_changed_since_last_success() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.check]
+command = "echo checking: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["*.rs"]
+
+[groups.pre-commit]
+includes = ["check"]
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+    fs::write(temp_dir.path().join("b.rs"), "fn b() {}\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("a.rs")).unwrap();
+    index.write().unwrap();
+
+    let run = || {
+        let output = Command::new(bin_path())
+            .current_dir(temp_dir.path())
+            .args(["run", "pre-commit", "--incremental"])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        assert!(output.status.success(), "Stdout: {stdout}");
+        stdout
+    };
+
+    // No recorded run yet: every staged file
+    let stdout = run();
+    assert!(
+        stdout.contains("no previous successful run"),
+        "Stdout: {stdout}"
+    );
+    assert!(stdout.contains("checking: a.rs"), "Stdout: {stdout}");
+
+    // a.rs is still staged, but only b.rs changed since the last run
+    index.add_path(std::path::Path::new("b.rs")).unwrap();
+    index.write().unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("changed since the last successful run"),
+        "Stdout: {stdout}"
+    );
+    assert!(stdout.contains("checking: b.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("a.rs"), "Stdout: {stdout}");
+
+    // Nothing new: the file-filtered hook does not run
+    let stdout = run();
+    assert!(!stdout.contains("checking:"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_files_from_stdin_replaces_change_detection() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.rust]
+command = "echo rust: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["*.rs"]
+
+[hooks.python]
+command = "echo python: {CHANGED_FILES}"
+modifies_repository = false
+execution_type = "other"
+files = ["*.py"]
+
+[groups.pre-commit]
+includes = ["rust", "python"]
+"#,
+    )
+    .unwrap();
+    for file in ["a.rs", "b.rs", "other.rs", "script.py"] {
+        fs::write(temp_dir.path().join(file), "content\n").unwrap();
+    }
+
+    // Nothing is staged; the piped list is the whole changed-file set
+    let mut child = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--files-from", "-"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute");
+    std::io::Write::write_all(child.stdin.as_mut().unwrap(), b"a.rs\nb.rs\n").unwrap();
+    drop(child.stdin.take());
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("rust: a.rs b.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("other.rs"), "Stdout: {stdout}");
+    assert!(!stdout.contains("python:"), "Stdout: {stdout}");
+
+    // NUL-separated lists work the same way
+    fs::write(temp_dir.path().join("files.list"), b"script.py\0").unwrap();
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--files-from0", "files.list"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("python: script.py"), "Stdout: {stdout}");
+    assert!(!stdout.contains("rust:"), "Stdout: {stdout}");
+}
+
+#[test]
+fn test_run_watch_reruns_hooks_on_change() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.log]
+command = "echo run: {CHANGED_FILES} >> runs.log"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["log"]
+"#,
+    )
+    .unwrap();
+    // The hook's own output is ignored, so it cannot retrigger the watch
+    fs::write(temp_dir.path().join(".gitignore"), "runs.log\n").unwrap();
+    fs::write(temp_dir.path().join("watched.txt"), "one\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("watched.txt")).unwrap();
+    index.write().unwrap();
+
+    let mut child = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files", "--watch"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("Failed to execute");
+
+    // Generous limits: file system events can be slow on loaded machines
+    let runs = |count: usize| {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+        loop {
+            let log = fs::read_to_string(temp_dir.path().join("runs.log")).unwrap_or_default();
+            if log.lines().count() >= count || std::time::Instant::now() > deadline {
+                return log;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    };
+
+    let first = runs(1);
+    // Let the watcher settle before changing anything
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    fs::write(temp_dir.path().join("watched.txt"), "two\n").unwrap();
+    let log = runs(2);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(first.lines().count(), 1, "Log: {first}");
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2, "Log: {log}");
+    assert_eq!(lines[1], "run: watched.txt");
+}
+
+#[test]
+fn test_dry_run_shows_container() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.test]
+command = "npm test"
+modifies_repository = false
+container = "node:20"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "test", "--all-files", "--dry-run"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("container: node:20"), "stdout: {stdout}");
+}
+
+#[test]
+fn test_run_in_container() {
+    let docker_available = Command::new("docker")
+        .arg("info")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !docker_available {
+        eprintln!("docker is not available, skipping");
+        return;
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.where]
+command = "pwd > {REPO_ROOT}/where.txt"
+modifies_repository = false
+execution_type = "other"
+container = "busybox"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "where", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let location = fs::read_to_string(temp_dir.path().join("where.txt")).unwrap();
+    assert_eq!(location.trim(), "/work");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_container_cli_keeps_runtime_environment() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    // A stand-in docker CLI that records the environment it was started with
+    let bin_dir = TempDir::new().unwrap();
+    let record = bin_dir.path().join("record.txt");
+    let docker = bin_dir.path().join("docker");
+    fs::write(
+        &docker,
+        format!(
+            "#!/bin/sh\necho \"DOCKER_HOST=$DOCKER_HOST HOOK_ONLY=$HOOK_ONLY\" > {}\n",
+            record.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&docker, fs::Permissions::from_mode(0o755)).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.boxed]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+container = "busybox"
+env = { HOOK_ONLY = "set" }
+"#,
+    )
+    .unwrap();
+
+    let path = format!(
+        "{}:{}",
+        bin_dir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .env("PATH", path)
+        .env("DOCKER_HOST", "tcp://runtime.example:2375")
+        .args(["run", "boxed", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // The hook's own env goes to the container with -e, not to the CLI
+    assert_eq!(
+        fs::read_to_string(&record).unwrap(),
+        "DOCKER_HOST=tcp://runtime.example:2375 HOOK_ONLY=\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_terminating_peter_hook_stops_running_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.slow]
+command = "echo $$ > hook.pid && exec sleep 30"
+modifies_repository = false
+execution_type = "other"
+"#,
+    )
+    .unwrap();
+
+    let mut peter_hook = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "slow", "--all-files"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("Failed to execute");
+
+    let pid_file = temp_dir.path().join("hook.pid");
+    let started = std::time::Instant::now();
+    let hook_pid = loop {
+        if let Some(pid) = fs::read_to_string(&pid_file)
+            .ok()
+            .filter(|pid| pid.ends_with('\n'))
+        {
+            break pid.trim().to_string();
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(10),
+            "Hook did not start"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    };
+
+    Command::new("kill")
+        .args(["-TERM", &peter_hook.id().to_string()])
+        .status()
+        .unwrap();
+    peter_hook.wait().unwrap();
+
+    // Gone, or a zombie waiting to be reaped by init
+    let hook_running = || {
+        let output = Command::new("ps")
+            .args(["-o", "stat=", "-p", &hook_pid])
+            .output()
+            .unwrap();
+        let stat = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        !stat.is_empty() && !stat.starts_with('Z')
+    };
+    let stopped = std::time::Instant::now();
+    while hook_running() {
+        assert!(
+            stopped.elapsed() < std::time::Duration::from_secs(5),
+            "Hook process {hook_pid} outlived peter-hook"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_cacheable_hook_skips_unchanged_inputs() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "one\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "echo run >> runs.log"
+modifies_repository = false
+execution_type = "other"
+files = ["**/*.txt"]
+cacheable = true
+"#,
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(bin_path())
+            .current_dir(temp_dir.path())
+            .args(["run", "pre-commit", "--all-files", "--hook", "lint"])
+            .output()
+            .expect("Failed to execute");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let runs = || {
+        fs::read_to_string(temp_dir.path().join("runs.log"))
+            .unwrap_or_default()
+            .lines()
+            .count()
+    };
+
+    run();
+    assert_eq!(runs(), 1);
+
+    let stdout = run();
+    assert_eq!(runs(), 1, "unchanged inputs should not re-run the hook");
+    assert!(stdout.contains("cached pass"), "stdout: {stdout}");
+
+    fs::write(temp_dir.path().join("file.txt"), "two\n").unwrap();
+    run();
+    assert_eq!(runs(), 2, "an edited file should re-run the hook");
+}
+
+#[test]
+fn test_group_setup_and_teardown_bracket_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content\n").unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.start]
+command = "echo ready > shared.txt && echo setup >> order.log"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.check-a]
+command = "grep -q ready shared.txt && echo check >> order.log"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.check-b]
+command = "grep -q ready shared.txt && echo check >> order.log"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.stop]
+command = "rm shared.txt && echo teardown >> order.log"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["check-a", "check-b"]
+execution = "parallel"
+setup = "start"
+teardown = "stop"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let order = fs::read_to_string(temp_dir.path().join("order.log")).unwrap();
+    assert_eq!(
+        order.lines().collect::<Vec<_>>(),
+        ["setup", "check", "check", "teardown"]
+    );
+    assert!(!temp_dir.path().join("shared.txt").exists());
+}
+
+#[test]
+fn test_group_setup_failure_aborts_group_but_runs_teardown() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content\n").unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.start]
+command = "exit 1"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.check]
+command = "echo check >> order.log"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.stop]
+command = "echo teardown >> order.log"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["check"]
+setup = "start"
+teardown = "stop"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert_eq!(output.status.code(), Some(1));
+    let order = fs::read_to_string(temp_dir.path().join("order.log")).unwrap();
+    assert_eq!(order.lines().collect::<Vec<_>>(), ["teardown"]);
+}
+
+#[test]
+fn test_if_exists_skips_hooks_without_the_required_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    for project in ["rust", "docs"] {
+        let dir = root.join(project);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("hooks.toml"),
+            r#"
+[hooks.cargo-check]
+command = "touch checked.txt"
+modifies_repository = false
+execution_type = "other"
+if_exists = ["Cargo.toml"]
+
+[groups.pre-commit]
+includes = ["cargo-check"]
+"#,
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "content\n").unwrap();
+    }
+    fs::write(root.join("rust/Cargo.toml"), "[package]\n").unwrap();
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(root.join("rust/checked.txt").exists());
+    assert!(
+        !root.join("docs/checked.txt").exists(),
+        "Hooks must be skipped where an if_exists file is missing"
+    );
+}
+
+#[test]
+fn test_group_depends_on_group_runs_its_hooks_first() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let log = root.join("order.log");
+    fs::write(
+        root.join("hooks.toml"),
+        format!(
+            r#"
+[hooks.compile]
+command = "sleep 0.3 && echo compile >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.bundle]
+command = "sleep 0.3 && echo bundle >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.unit]
+command = "echo unit >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.integration]
+command = "echo integration >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+
+[groups.build]
+includes = ["compile", "bundle"]
+
+[groups.test]
+includes = ["unit", "integration"]
+depends_on = ["build"]
+
+[groups.pre-commit]
+includes = ["test", "build"]
+execution = "parallel"
+"#,
+            log = log.display()
+        ),
+    )
+    .unwrap();
+    fs::write(root.join("file.txt"), "content\n").unwrap();
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let order = fs::read_to_string(&log).unwrap();
+    let order: Vec<&str> = order.lines().collect();
+    assert_eq!(order.len(), 4, "Every hook runs once: {order:?}");
+    let mut build: Vec<&str> = order[..2].to_vec();
+    build.sort_unstable();
+    assert_eq!(
+        build,
+        ["bundle", "compile"],
+        "Build hooks run first: {order:?}"
+    );
+}
+
+#[test]
+fn test_report_skips_lists_hooks_that_did_not_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    fs::write(
+        root.join("hooks.toml"),
+        r#"
+[hooks.rust-lint]
+command = "echo linting"
+modifies_repository = false
+files = ["**/*.rs"]
+
+[hooks.text-check]
+command = "echo checking"
+modifies_repository = false
+files = ["*.txt"]
+
+[hooks.slow]
+command = "echo slow"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["rust-lint", "text-check", "slow"]
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("notes.txt"), "content\n").unwrap();
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .env("PETER_HOOK_SKIP", "slow")
+        .args(["run", "pre-commit", "--report-skips"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "Stdout: {stdout}\nStderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report = stdout
+        .split("Skipped hooks:\n")
+        .nth(1)
+        .unwrap_or_else(|| panic!("No skip report: {stdout}"));
+    assert!(
+        report.contains("  rust-lint: no changed files match its patterns"),
+        "Report: {report}"
+    );
+    assert!(
+        report.contains("  slow: listed in PETER_HOOK_SKIP"),
+        "Report: {report}"
+    );
+    assert!(!report.contains("text-check"), "Report: {report}");
+}
+
+#[test]
+fn test_priority_orders_parallel_hooks_under_a_jobs_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let log = root.join("order.log");
+    fs::write(
+        root.join("hooks.toml"),
+        format!(
+            r#"
+[hooks.a-quick]
+command = "echo a-quick >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.b-long-pole]
+command = "echo b-long-pole >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+priority = 10
+
+[hooks.c-medium]
+command = "echo c-medium >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+priority = 5
+
+[hooks.d-last]
+command = "echo d-last >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+priority = -1
+
+[groups.pre-commit]
+includes = ["a-quick", "b-long-pole", "c-medium", "d-last"]
+execution = "parallel"
+"#,
+            log = log.display()
+        ),
+    )
+    .unwrap();
+    fs::write(root.join("file.txt"), "content\n").unwrap();
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--jobs", "1"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let order = fs::read_to_string(&log).unwrap();
+    assert_eq!(
+        order.lines().collect::<Vec<_>>(),
+        ["b-long-pole", "c-medium", "a-quick", "d-last"]
+    );
+}
+
+#[test]
+fn test_merge_identical_configs_runs_sibling_configs_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let log_dir = TempDir::new().unwrap();
+    let log = log_dir.path().join("runs.log");
+    for dir in ["a", "b"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(
+            root.join(dir).join("hooks.toml"),
+            format!(
+                r#"
+[hooks.check]
+command = "echo {{CHANGED_FILES}} >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+run_at_root = true
+
+[groups.pre-commit]
+includes = ["check"]
+"#,
+                log = log.display()
+            ),
+        )
+        .unwrap();
+        fs::write(root.join(dir).join("file.txt"), "content\n").unwrap();
+    }
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let run = |args: &[&str]| {
+        let _ = fs::remove_file(&log);
+        let output = Command::new(bin_path())
+            .current_dir(root)
+            .args(["run", "pre-commit"])
+            .args(args)
+            .output()
+            .expect("Failed to execute");
+        assert!(
+            output.status.success(),
+            "Stdout: {}\nStderr: {}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        fs::read_to_string(&log).unwrap()
+    };
+
+    let separate = run(&[]);
+    assert_eq!(separate.lines().count(), 2, "Runs: {separate}");
+
+    let merged = run(&["--merge-identical-configs"]);
+    let runs: Vec<&str> = merged.lines().collect();
+    assert_eq!(runs.len(), 1, "The hook runs once: {runs:?}");
+    assert!(
+        runs[0].contains("a/file.txt") && runs[0].contains("b/file.txt"),
+        "The run sees both configs' files: {runs:?}"
+    );
+}
+
+#[test]
+fn test_modifying_hooks_are_blocked_during_a_merge_unless_forced() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let log_dir = TempDir::new().unwrap();
+    let log = log_dir.path().join("runs.log");
+    fs::write(
+        root.join("hooks.toml"),
+        format!(
+            r#"
+[hooks.format]
+command = "echo format >> '{log}'"
+modifies_repository = true
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["format"]
+"#,
+            log = log.display()
+        ),
+    )
+    .unwrap();
+    fs::write(root.join("file.txt"), "content\n").unwrap();
+    let status = Command::new("git")
+        .args(["add", "."])
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::write(
+        root.join(".git/MERGE_HEAD"),
+        format!("{}\n", "0".repeat(40)),
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit"])
+        .output()
+        .expect("Failed to execute");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "Stderr: {stderr}");
+    assert!(
+        stderr.contains("merge is in progress (MERGE_HEAD exists)")
+            && stderr.contains("(format)")
+            && stderr.contains("--force"),
+        "Stderr: {stderr}"
+    );
+    assert!(!log.exists(), "The modifying hook must not run");
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--force"])
+        .output()
+        .expect("Failed to execute");
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(fs::read_to_string(&log).unwrap(), "format\n");
+}
+
+#[test]
+fn test_output_filters_trim_the_displayed_output() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "printf 'info: compiling\nerror: bad call\nnote: see docs\n'; printf 'error: known noise\nerror: bad type\n' >&2; exit 1"
+modifies_repository = false
+execution_type = "other"
+output_filter = "^error:"
+output_exclude = "known noise"
+
+[groups.pre-commit]
+includes = ["lint"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !output.status.success(),
+        "The exit code still fails the hook"
+    );
+    assert!(
+        stdout.contains("error: bad call") && stdout.contains("error: bad type"),
+        "Stdout: {stdout}"
+    );
+    for dropped in ["info: compiling", "note: see docs", "known noise"] {
+        assert!(!stdout.contains(dropped), "Stdout: {stdout}");
+    }
+}
+
+#[test]
+fn test_since_tag_checks_files_changed_after_the_latest_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "user.name", "Test"]);
+    git(&["config", "commit.gpgsign", "false"]);
+    git(&["config", "tag.gpgsign", "false"]);
+
+    let log_dir = TempDir::new().unwrap();
+    let log = log_dir.path().join("files.log");
+    fs::write(
+        root.join("hooks.toml"),
+        format!(
+            r#"
+[hooks.check]
+command = "echo {{CHANGED_FILES}} >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+run_at_root = true
+
+[groups.pre-commit]
+includes = ["check"]
+"#,
+            log = log.display()
+        ),
+    )
+    .unwrap();
+    let run = || {
+        Command::new(bin_path())
+            .current_dir(root)
+            .args(["run", "pre-commit", "--since-tag"])
+            .output()
+            .expect("Failed to execute")
+    };
+
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    let output = run();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("No tag is reachable from HEAD"),
+        "Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    for (file, tag) in [("first.txt", "v1.0.0"), ("second.txt", "v1.1.0")] {
+        fs::write(root.join(file), "content\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", file]);
+        git(&["tag", "-a", tag, "-m", tag]);
+    }
+    fs::write(root.join("third.txt"), "content\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "third"]);
+
+    let output = run();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "Stdout: {stdout}\nStderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("since tag v1.1.0"), "Stdout: {stdout}");
+    assert_eq!(fs::read_to_string(&log).unwrap().trim(), "third.txt");
+}
+
+#[test]
+fn test_show_env_lists_each_hooks_environment_by_precedence() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let log_dir = TempDir::new().unwrap();
+    let log = log_dir.path().join("env.log");
+    fs::write(
+        root.join("hooks.toml"),
+        format!(
+            r#"
+[hooks.deploy-check]
+command = "echo $SHARED $FILE_ONLY >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+env_file = "hook.env"
+env = {{ SHARED = "from-inline" }}
+mask_env = ["TOKEN"]
+
+[groups.pre-commit]
+includes = ["deploy-check"]
+"#,
+            log = log.display()
+        ),
+    )
+    .unwrap();
+    fs::write(
+        root.join("hook.env"),
+        "# shared settings\nSHARED=from-file\nFILE_ONLY=\"file value\"\nexport TOKEN='hunter2'\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args([
+            "run",
+            "pre-commit",
+            "--all-files",
+            "--dry-run",
+            "--show-env",
+        ])
+        .output()
+        .expect("Failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "Stdout: {stdout}\nStderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    for line in [
+        "Environment of deploy-check:",
+        "  SHARED=from-inline (env)",
+        "  FILE_ONLY=file value (env_file)",
+        "  TOKEN=**** (env_file)",
+        " (forwarded)",
+    ] {
+        assert!(stdout.contains(line), "Missing {line:?} in: {stdout}");
+    }
+    assert!(!stdout.contains("from-file"), "Stdout: {stdout}");
+    assert!(!stdout.contains("hunter2"), "Stdout: {stdout}");
+
+    // The hook itself receives the same environment
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "from-inline file value\n"
+    );
+}
+
+#[test]
+fn test_fragments_provide_hooks_to_the_main_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    Git2Repository::init(root).unwrap();
+    let log_dir = TempDir::new().unwrap();
+    let log = log_dir.path().join("runs.log");
+    let fragments = root.join(".peter-hook.d");
+    fs::create_dir_all(&fragments).unwrap();
+    for name in ["lint", "test"] {
+        fs::write(
+            fragments.join(format!("{name}.toml")),
+            format!(
+                r#"
+[hooks.{name}]
+command = "echo {name} >> '{log}'"
+modifies_repository = false
+execution_type = "other"
+"#,
+                log = log.display()
+            ),
+        )
+        .unwrap();
+    }
+    fs::write(
+        root.join("hooks.toml"),
+        r#"
+[groups.pre-commit]
+includes = ["lint", "test"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(root)
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+    assert!(
+        output.status.success(),
+        "Stdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut runs: Vec<String> = fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    runs.sort();
+    assert_eq!(runs, ["lint", "test"]);
+}
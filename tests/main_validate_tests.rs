@@ -316,3 +316,319 @@ modifies_repository = false
     // Should show hook information
     assert!(!stdout.is_empty());
 }
+
+#[test]
+fn test_validate_format_json_invalid_config() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        "[hooks.broken\nno closing bracket",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["validate", "--format", "json"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+    let diagnostics: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be JSON");
+    assert_eq!(diagnostics[0]["severity"], "error");
+    assert!(diagnostics[0]["hook"].is_null());
+}
+
+#[test]
+fn test_validate_format_json_without_config_emits_error() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["validate", "--format", "json"])
+        .env("HOME", temp_dir.path())
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .output()
+        .expect("Failed to execute");
+
+    assert_eq!(output.status.code(), Some(3));
+    let diagnostics: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be JSON");
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["severity"], "error");
+    assert!(diagnostics[0]["config_path"].is_null());
+    assert!(
+        diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("No hooks.toml")
+    );
+}
+
+#[test]
+fn test_validate_rejects_json_with_format() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["validate", "--json", "--format", "json"])
+        .output()
+        .expect("Failed to execute");
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "Stderr: {stderr}");
+}
+
+#[test]
+fn test_validate_rejects_unresolved_group_include() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.lint]
+command = "echo lint"
+modifies_repository = false
+
+[groups.pre-commit]
+includes = ["lint", "formatt"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("validate")
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Group 'pre-commit' includes 'formatt'"),
+        "Should name the group and missing include.\nStderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_validate_reports_duplicate_hook_from_import() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("shared.toml"),
+        r#"
+[hooks.lint]
+command = "echo shared-lint"
+modifies_repository = false
+"#,
+    )
+    .unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+imports = ["shared.toml"]
+
+[hooks.lint]
+command = "echo local-lint"
+modifies_repository = false
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("validate")
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Hook 'lint' is defined in multiple sources"),
+        "Should report the duplicate hook name.\nStderr: {stderr}"
+    );
+    assert!(
+        stderr.contains("shared.toml"),
+        "Should name the imported source"
+    );
+    assert!(
+        stderr.contains("hooks.toml"),
+        "Should name the local source"
+    );
+}
+
+#[test]
+fn test_validate_rejects_run_always_with_files() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[hooks.docs]
+command = "echo docs"
+modifies_repository = false
+files = ["**/*.md"]
+run_always = true
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("validate")
+        .output()
+        .expect("Failed to execute");
+
+    // files would have no effect, so the combination is a config error rather
+    // than something a run could silently ignore
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Hook 'docs' cannot have both 'files' patterns and 'run_always = true'"),
+        "Should name the hook and both fields.\nStderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_print_config_fills_in_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("lint.toml"),
+        r#"
+[hooks.lint]
+command = "cargo clippy"
+modifies_repository = false
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+imports = ["lint.toml"]
+
+[groups.pre-commit]
+includes = ["lint"]
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("print-config")
+        .output()
+        .expect("Failed to execute");
+    assert!(output.status.success());
+    let config: toml::Value = toml::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    assert_eq!(
+        config["hooks"]["lint"]["timeout_seconds"].as_integer(),
+        Some(300)
+    );
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["print-config", "--format", "json"])
+        .output()
+        .expect("Failed to execute");
+    assert!(output.status.success());
+    let config: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(config["hooks"]["lint"]["timeout_seconds"], 300);
+    assert_eq!(config["groups"]["pre-commit"]["includes"][0], "lint");
+}
+
+#[test]
+fn test_validate_file_outside_a_git_repository() {
+    let temp_dir = TempDir::new().unwrap();
+    let valid = temp_dir.path().join("valid.peter-hook.toml");
+    fs::write(
+        &valid,
+        r#"
+[hooks.lint]
+command = "echo lint"
+modifies_repository = false
+files = ["**/*.rs"]
+
+[groups.pre-commit]
+includes = ["lint"]
+"#,
+    )
+    .unwrap();
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["validate", "--file"])
+        .arg(&valid)
+        .output()
+        .expect("Failed to execute");
+    assert!(
+        output.status.success(),
+        "stdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Configuration is valid"));
+
+    let invalid_configs = [
+        (
+            "unresolved-include.toml",
+            r#"
+[groups.pre-commit]
+includes = ["missing"]
+"#,
+        ),
+        (
+            "bad-glob.toml",
+            r#"
+[hooks.lint]
+command = "echo lint"
+modifies_repository = false
+files = ["src/[*.rs"]
+"#,
+        ),
+        (
+            "cycle.toml",
+            r#"
+[hooks.a]
+command = "echo a"
+modifies_repository = false
+depends_on = ["b"]
+
+[hooks.b]
+command = "echo b"
+modifies_repository = false
+depends_on = ["a"]
+
+[groups.pre-commit]
+includes = ["a", "b"]
+"#,
+        ),
+    ];
+    for (name, content) in invalid_configs {
+        let invalid = temp_dir.path().join(name);
+        fs::write(&invalid, content).unwrap();
+        for format in ["human", "json"] {
+            let output = Command::new(bin_path())
+                .current_dir(temp_dir.path())
+                .args(["validate", "--format", format, "--file"])
+                .arg(&invalid)
+                .output()
+                .expect("Failed to execute");
+            assert_eq!(
+                output.status.code(),
+                Some(2),
+                "{name} ({format}) should be rejected\nstdout: {}\nstderr: {}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+}
@@ -0,0 +1,180 @@
+#![allow(clippy::all, clippy::pedantic, clippy::nursery)]
+//! Integration tests for the `[notify]` and `[on_failure]` post-run commands
+
+use git2::Repository as Git2Repository;
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn bin_path() -> std::path::PathBuf {
+    assert_cmd::cargo::cargo_bin("peter-hook")
+}
+
+fn write_config(dir: &std::path::Path, hook_command: &str) {
+    fs::write(
+        dir.join("hooks.toml"),
+        format!(
+            r#"
+[notify]
+command = "echo \"$PETER_HOOK_STATUS\" > status.txt; cat > payload.json"
+
+[hooks.check]
+command = "{hook_command}"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["check"]
+"#
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_notify_receives_failure_status() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_config(temp_dir.path(), "exit 1");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+    let status = fs::read_to_string(temp_dir.path().join("status.txt")).unwrap();
+    assert_eq!(status.trim(), "failure");
+
+    let payload: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("payload.json")).unwrap())
+            .unwrap();
+    assert_eq!(payload["event"], "pre-commit");
+    assert_eq!(payload["status"], "failure");
+    assert_eq!(payload["results"]["results"]["check"]["exit_code"], 1);
+}
+
+#[test]
+fn test_notify_receives_success_status() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_config(temp_dir.path(), "true");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    let status = fs::read_to_string(temp_dir.path().join("status.txt")).unwrap();
+    assert_eq!(status.trim(), "success");
+}
+
+fn write_on_failure_config(dir: &std::path::Path, lint_command: &str) {
+    fs::write(
+        dir.join("hooks.toml"),
+        format!(
+            r#"
+[on_failure]
+command = "echo \"$PETER_HOOK_FAILED_HOOKS\" > failed.txt; exit 7"
+
+[hooks.lint]
+command = "{lint_command}"
+modifies_repository = false
+execution_type = "other"
+
+[hooks.fmt]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["lint", "fmt"]
+"#
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_on_failure_receives_failed_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_on_failure_config(temp_dir.path(), "exit 1");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    // The hook failure decides the exit code, not the on_failure command
+    assert_eq!(output.status.code(), Some(1));
+    let failed = fs::read_to_string(temp_dir.path().join("failed.txt")).unwrap();
+    assert_eq!(failed.trim(), "lint");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("on_failure command failed"),
+        "on_failure's own failure should be reported.\nStderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_on_failure_skipped_when_run_succeeds() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    write_on_failure_config(temp_dir.path(), "true");
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(output.status.success());
+    assert!(!temp_dir.path().join("failed.txt").exists());
+}
+
+#[test]
+fn test_hung_notifier_is_killed_after_its_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+[notify]
+command = "sleep 60"
+timeout_seconds = 1
+
+[hooks.check]
+command = "true"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["check"]
+"#,
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    // The notifier's timeout does not change the outcome of the run
+    assert!(output.status.success());
+    assert!(
+        started.elapsed() < std::time::Duration::from_secs(30),
+        "A hung notifier must not block the run"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("exceeded timeout of 1 seconds"),
+        "The timeout should be reported.\nStderr: {stderr}"
+    );
+}
@@ -110,6 +110,44 @@ description = "Commit message hooks"
     );
 }
 
+#[test]
+fn test_fail_on_skip_reports_requires_files_skip() {
+    let config = r#"
+[hooks.test-hook]
+command = "echo 'Running test hook'"
+requires_files = true
+modifies_repository = false
+
+[groups.commit-msg]
+includes = ["test-hook"]
+description = "Commit message hooks"
+"#;
+
+    let temp_dir = setup_test_repo_with_config(config);
+    let repo_path = temp_dir.path();
+    fs::write(repo_path.join("COMMIT_MSG"), "Test commit").unwrap();
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "commit-msg", "--fail-on-skip", "COMMIT_MSG"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        !output.status.success(),
+        "--fail-on-skip should fail when a hook is skipped.\nStdout: {stdout}\nStderr: {stderr}"
+    );
+    assert!(
+        stderr.contains("1 hook(s) skipped (--fail-on-skip)")
+            && stderr.contains("test-hook: requires files but none are available"),
+        "Skip reason should be explained.\nStderr: {stderr}"
+    );
+    assert!(!stdout.contains("Running test hook"), "Stdout: {stdout}");
+}
+
 #[test]
 fn test_requires_files_runs_in_pre_commit_context() {
     let config = r#"
@@ -159,7 +197,7 @@ description = "Pre-commit hooks"
 fn test_requires_files_with_all_files_flag() {
     let config = r#"
 [hooks.test-hook]
-command = "echo 'Should not run'"
+command = "echo 'Ran on tracked files'"
 requires_files = true
 modifies_repository = false
 
@@ -179,7 +217,7 @@ description = "Pre-commit hooks"
         .output()
         .unwrap();
 
-    // Run with --all-files flag - should skip because no file list is provided
+    // Run with --all-files flag - every tracked file is in the file list
     let output = Command::new(peter_hook_bin())
         .args(["run", "pre-commit", "--all-files"])
         .current_dir(repo_path)
@@ -189,14 +227,63 @@ description = "Pre-commit hooks"
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
-    // Hook should be skipped when --all-files is used
+    // Hook should run against the tracked file when --all-files is used
     assert!(
-        !stdout.contains("Should not run") && !stderr.contains("Should not run"),
-        "Hook with requires_files=true should be skipped with --all-files flag.\nStdout: \
+        stdout.contains("Ran on tracked files"),
+        "Hook with requires_files=true should run with --all-files flag.\nStdout: \
          {stdout}\nStderr: {stderr}"
     );
 }
 
+#[test]
+fn test_requires_files_distinguishes_unavailable_from_empty() {
+    let config = r#"
+[hooks.test-hook]
+command = "echo 'Should not run'"
+requires_files = true
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["test-hook"]
+"#;
+
+    let temp_dir = setup_test_repo_with_config(config);
+    let repo_path = temp_dir.path();
+
+    // --no-git: no file list can be provided
+    let output = Command::new(peter_hook_bin())
+        .args(["--trace", "run", "pre-commit", "--no-git"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stdout.contains("Should not run"), "Stdout: {stdout}");
+    assert!(
+        stderr.contains("requires files but none are available"),
+        "--no-git should skip as unavailable.\nStderr: {stderr}"
+    );
+
+    // Nothing staged: a file list is provided, it is just empty
+    let output = Command::new(peter_hook_bin())
+        .args(["--trace", "run", "pre-commit"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stdout.contains("Should not run"), "Stdout: {stdout}");
+    assert!(
+        stderr.contains("requires files but no matching files changed"),
+        "Empty staged set should skip as no matching files.\nStderr: {stderr}"
+    );
+    assert!(
+        !stderr.contains("requires files but none are available"),
+        "Stderr: {stderr}"
+    );
+}
+
 #[test]
 fn test_validate_warns_about_incompatible_requires_files() {
     let config = r#"
@@ -231,6 +318,60 @@ description = "Commit message hooks"
     );
 }
 
+#[test]
+fn test_validate_json_reports_requires_files_warning() {
+    let config = r#"
+[hooks.test-hook]
+command = "echo 'test'"
+requires_files = true
+modifies_repository = false
+
+[groups.commit-msg]
+includes = ["test-hook"]
+description = "Commit message hooks"
+"#;
+
+    let temp_dir = setup_test_repo_with_config(config);
+    let repo_path = temp_dir.path();
+
+    let output = Command::new(peter_hook_bin())
+        .args(["validate", "--format", "json"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Warnings should not fail validation"
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let diagnostics: serde_json::Value =
+        serde_json::from_str(&stdout).expect("validate --format json should print only JSON");
+    let diagnostics = diagnostics
+        .as_array()
+        .expect("Diagnostics should be an array");
+
+    let warning = diagnostics
+        .iter()
+        .find(|d| d["hook"] == "test-hook")
+        .unwrap_or_else(|| panic!("Missing requires_files diagnostic.\nOutput: {stdout}"));
+    assert_eq!(warning["severity"], "warning");
+    assert_eq!(warning["event"], "commit-msg");
+    assert!(
+        warning["message"]
+            .as_str()
+            .unwrap()
+            .contains("cannot provide file lists")
+    );
+    assert!(
+        warning["config_path"]
+            .as_str()
+            .unwrap()
+            .ends_with("hooks.toml")
+    );
+}
+
 #[test]
 fn test_requires_files_hierarchical_override() {
     let temp_dir = TempDir::new().unwrap();
@@ -317,3 +458,65 @@ includes = ["test-hook"]
 
     assert!(output.status.success(), "Hook should succeed");
 }
+
+#[test]
+fn test_max_changed_files_skips_large_changesets() {
+    let config = r#"
+[hooks.format]
+command = "echo 'Formatter ran'"
+modifies_repository = false
+execution_type = "in-place"
+files = ["*.txt"]
+max_changed_files = 2
+
+[groups.pre-commit]
+includes = ["format"]
+description = "Pre-commit hooks"
+"#;
+
+    let run = |files: &[&str]| {
+        let temp_dir = setup_test_repo_with_config(config);
+        let repo_path = temp_dir.path();
+        for file in files {
+            fs::write(repo_path.join(file), "content").unwrap();
+        }
+        Command::new("git")
+            .arg("add")
+            .args(files)
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let output = Command::new(peter_hook_bin())
+            .args(["run", "pre-commit"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "Run should succeed");
+        format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    };
+
+    // Three matching files is more than max_changed_files allows
+    let combined = run(&["a.txt", "b.txt", "c.txt"]);
+    assert!(
+        !combined.contains("Formatter ran"),
+        "Hook should be skipped.\nOutput: {combined}"
+    );
+    assert!(
+        combined.contains(
+            "Skipping hook 'format': 3 matching files changed, more than max_changed_files = 2"
+        ),
+        "Skip reason should be reported.\nOutput: {combined}"
+    );
+
+    // A single matching file is within range
+    let combined = run(&["a.txt"]);
+    assert!(
+        combined.contains("Formatter ran"),
+        "Hook should run.\nOutput: {combined}"
+    );
+}
@@ -641,3 +641,90 @@ includes = ["cmd-sub"]
         "Command substitution treated as literal text"
     );
 }
+
+#[test]
+fn test_parent_environment_limited_to_pass_env() {
+    let temp_dir = setup_test_repo();
+    let repo_path = temp_dir.path();
+
+    let config = r#"
+[hooks.dump-env]
+command = "env > env.txt"
+modifies_repository = false
+execution_type = "other"
+pass_env = ["PETER_HOOK_TEST_PASSED"]
+
+[groups.pre-commit]
+includes = ["dump-env"]
+"#;
+    fs::write(repo_path.join("hooks.toml"), config).unwrap();
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit", "--all-files"])
+        .current_dir(repo_path)
+        .env("PETER_HOOK_TEST_PASSED", "forwarded")
+        .env("PETER_HOOK_TEST_BLOCKED", "leaked")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let env_output = fs::read_to_string(repo_path.join("env.txt")).unwrap();
+    assert!(env_output.contains("PETER_HOOK_TEST_PASSED=forwarded"));
+    assert!(
+        !env_output.contains("PETER_HOOK_TEST_BLOCKED"),
+        "Variables not in pass_env must not reach the hook"
+    );
+    // PATH and HOME are always forwarded
+    assert!(env_output.lines().any(|line| line.starts_with("PATH=")));
+    assert!(env_output.lines().any(|line| line.starts_with("HOME=")));
+}
+
+#[test]
+fn test_git_hook_variables_reach_hooks() {
+    let temp_dir = setup_test_repo();
+    let repo_path = temp_dir.path();
+
+    let config = r#"
+[hooks.list-index]
+command = "git ls-files --cached > index.txt"
+modifies_repository = false
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["list-index"]
+"#;
+    fs::write(repo_path.join("hooks.toml"), config).unwrap();
+
+    // Like `git commit -a`, stage a file in a separate index only
+    let alternate_index = repo_path.join(".git/alternate-index");
+    fs::write(repo_path.join("staged-elsewhere.txt"), "content").unwrap();
+    let staged = Command::new("git")
+        .args(["add", "staged-elsewhere.txt"])
+        .env("GIT_INDEX_FILE", &alternate_index)
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    assert!(staged.status.success());
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit", "--all-files"])
+        .current_dir(repo_path)
+        .env("GIT_INDEX_FILE", &alternate_index)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let index = fs::read_to_string(repo_path.join("index.txt")).unwrap();
+    assert!(
+        index.lines().any(|line| line == "staged-elsewhere.txt"),
+        "The hook must read the index git passed in GIT_INDEX_FILE: {index}"
+    );
+}
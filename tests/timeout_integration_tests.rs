@@ -329,3 +329,164 @@ description = "Hook with default timeout"
     );
     assert!(output.status.success(), "Command should succeed");
 }
+
+#[test]
+fn test_timeout_reports_stdout_and_stderr_separately() {
+    let config = r#"
+[hooks.chatty-hook]
+command = "echo 'result line'; echo 'progress line' >&2; exec sleep 10"
+modifies_repository = false
+execution_type = "other"
+timeout_seconds = 1
+
+[groups.pre-commit]
+includes = ["chatty-hook"]
+description = "Hook writing to both streams before hanging"
+"#;
+
+    let temp_dir = setup_test_repo_with_config(config);
+    let repo_path = temp_dir.path();
+
+    fs::write(repo_path.join("test.txt"), "content").unwrap();
+    Command::new("git")
+        .args(["add", "test.txt"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = format!("{stdout}{stderr}");
+
+    assert!(
+        combined.contains("--- partial stdout (12 bytes) ---"),
+        "Should label partial stdout with its size.\nOutput: {combined}"
+    );
+    assert!(
+        combined.contains("--- partial stderr (14 bytes) ---"),
+        "Should label partial stderr with its size.\nOutput: {combined}"
+    );
+    assert!(
+        combined.contains("killed after"),
+        "Should report elapsed time at kill.\nOutput: {combined}"
+    );
+
+    let stdout_section = combined.find("--- partial stdout").unwrap();
+    let stderr_section = combined.find("--- partial stderr").unwrap();
+    let result_line = combined[stdout_section..].find("result line").unwrap() + stdout_section;
+    let progress_line = combined[stderr_section..].find("progress line").unwrap() + stderr_section;
+    assert!(
+        result_line < stderr_section,
+        "stdout text should sit under the stdout label"
+    );
+    assert!(
+        progress_line > stderr_section,
+        "stderr text should sit under the stderr label"
+    );
+
+    assert!(!output.status.success(), "Command should fail on timeout");
+}
+
+#[test]
+fn test_timeout_sends_sigterm_before_sigkill() {
+    let config = r#"
+[hooks.cleanup-hook]
+command = "trap 'echo done > cleaned-up.marker; kill $! 2>/dev/null; exit 1' TERM; sleep 10 & wait"
+modifies_repository = false
+execution_type = "other"
+timeout_seconds = 1
+kill_grace_seconds = 5
+
+[groups.pre-commit]
+includes = ["cleanup-hook"]
+description = "Hook that cleans up on SIGTERM"
+"#;
+
+    let temp_dir = setup_test_repo_with_config(config);
+    let repo_path = temp_dir.path();
+
+    fs::write(repo_path.join("test.txt"), "content").unwrap();
+    Command::new("git")
+        .args(["add", "test.txt"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = format!("{stdout}{stderr}");
+
+    assert!(
+        combined.contains("exceeded timeout"),
+        "Hook should still be reported as timed out.\nOutput: {combined}"
+    );
+    assert!(
+        repo_path.join("cleaned-up.marker").exists(),
+        "Hook should have been able to clean up after SIGTERM.\nOutput: {combined}"
+    );
+    assert!(!output.status.success(), "Command should fail on timeout");
+}
+
+#[test]
+fn test_timeout_kills_processes_the_hook_started() {
+    let config = r#"
+[hooks.forking-hook]
+command = "(sleep 3; touch survived.marker) >/dev/null 2>&1 & sleep 30"
+modifies_repository = false
+execution_type = "other"
+timeout_seconds = 1
+kill_grace_seconds = 1
+
+[groups.pre-commit]
+includes = ["forking-hook"]
+"#;
+
+    let temp_dir = setup_test_repo_with_config(config);
+    let repo_path = temp_dir.path();
+
+    // Only what the hook and peter-hook need: no `pgrep` or `kill` binaries
+    let bin_dir = TempDir::new().unwrap();
+    for tool in ["sh", "sleep", "touch", "git"] {
+        let output = Command::new("sh")
+            .args(["-c", &format!("command -v {tool}")])
+            .output()
+            .unwrap();
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        std::os::unix::fs::symlink(path, bin_dir.path().join(tool)).unwrap();
+    }
+
+    let output = Command::new(peter_hook_bin())
+        .args(["run", "pre-commit", "--all-files"])
+        .current_dir(repo_path)
+        .env("PATH", bin_dir.path())
+        .output()
+        .unwrap();
+
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        combined.contains("exceeded timeout"),
+        "Hook should be reported as timed out.\nOutput: {combined}"
+    );
+
+    std::thread::sleep(std::time::Duration::from_secs(4));
+    assert!(
+        !repo_path.join("survived.marker").exists(),
+        "Processes started by a timed-out hook must be killed.\nOutput: {combined}"
+    );
+}
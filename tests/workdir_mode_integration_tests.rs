@@ -0,0 +1,75 @@
+#![allow(clippy::all, clippy::pedantic, clippy::nursery)]
+//! Integration tests for group and config-level `workdir_mode`
+
+use git2::Repository as Git2Repository;
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn bin_path() -> std::path::PathBuf {
+    assert_cmd::cargo::cargo_bin("peter-hook")
+}
+
+#[test]
+fn test_group_repo_root_mode_runs_hook_at_repo_root() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+    let package_dir = temp_dir.path().join("packages/app");
+    fs::create_dir_all(&package_dir).unwrap();
+
+    fs::write(
+        package_dir.join("hooks.toml"),
+        r#"
+[hooks.where]
+command = "pwd > cwd.txt"
+execution_type = "other"
+
+[groups.pre-commit]
+includes = ["where"]
+workdir_mode = "repo_root"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(&package_dir)
+        .args(["run", "pre-commit", "--all-files"])
+        .output()
+        .expect("Failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!package_dir.join("cwd.txt").exists());
+    let cwd = fs::read_to_string(temp_dir.path().join("cwd.txt")).unwrap();
+    assert_eq!(
+        std::path::Path::new(cwd.trim()).canonicalize().unwrap(),
+        temp_dir.path().canonicalize().unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_workdir_mode_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    Git2Repository::init(temp_dir.path()).unwrap();
+
+    fs::write(
+        temp_dir.path().join("hooks.toml"),
+        r#"
+workdir_mode = "somewhere"
+
+[hooks.test]
+command = "true"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .current_dir(temp_dir.path())
+        .arg("validate")
+        .output()
+        .expect("Failed to execute");
+
+    assert!(!output.status.success());
+}